@@ -0,0 +1,51 @@
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+use titan_core::omniarb::{predict_batch, run_tar_onnx, QuoteInfo, TokenEntry};
+
+const BRIDGES: [&str; 4] = ["STARGATE", "HOP", "LAYERZERO", "UNKNOWN"];
+const TOKENS: [&str; 4] = ["USDC", "WETH", "ARB", "PEPE"];
+
+fn matrix(rows: usize) -> (Vec<TokenEntry>, Vec<QuoteInfo>) {
+    (0..rows)
+        .map(|i| {
+            let entry = TokenEntry {
+                chain_origin: 1,
+                chain_dest: 137 + i as u64,
+                native_token: TOKENS[i % TOKENS.len()].to_string(),
+                bridge_protocol: BRIDGES[i % BRIDGES.len()].to_string(),
+                liquidity_score: (i % 100) as f64,
+                fee_tier: 0.3,
+                ..Default::default()
+            };
+            let quote = QuoteInfo {
+                spread_percentage: (i % 7) as f64 * 0.5,
+                slippage_estimate: (i % 5) as f64 * 0.1,
+                gas_cost_usd: (i % 20) as f64,
+                ..Default::default()
+            };
+            (entry, quote)
+        })
+        .unzip()
+}
+
+fn bench_predict(c: &mut Criterion) {
+    let mut group = c.benchmark_group("tar_predict");
+    for rows in [100, 1_000, 10_000] {
+        let (entries, quotes) = matrix(rows);
+        group.bench_with_input(BenchmarkId::new("per_row", rows), &rows, |b, _| {
+            b.iter(|| {
+                entries
+                    .iter()
+                    .zip(&quotes)
+                    .map(|(entry, quote)| run_tar_onnx(black_box(entry), black_box(quote)))
+                    .collect::<Vec<_>>()
+            })
+        });
+        group.bench_with_input(BenchmarkId::new("batch", rows), &rows, |b, _| {
+            b.iter(|| predict_batch(black_box(&entries), black_box(&quotes)))
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_predict);
+criterion_main!(benches);
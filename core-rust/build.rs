@@ -0,0 +1,32 @@
+use std::path::Path;
+use std::process::Command;
+
+/// Output of `git <args>`, trimmed; `None` outside a git checkout
+fn git(args: &[&str]) -> Option<String> {
+    Command::new("git")
+        .args(args)
+        .output()
+        .ok()
+        .filter(|output| output.status.success())
+        .and_then(|output| String::from_utf8(output.stdout).ok())
+        .map(|out| out.trim().to_string())
+}
+
+fn main() {
+    // Embed the git commit for the /api/version endpoint
+    let git_sha = git(&["rev-parse", "--short", "HEAD"]).unwrap_or_else(|| "unknown".to_string());
+    println!("cargo:rustc-env=TITAN_GIT_SHA={}", git_sha);
+
+    // Rerun when HEAD moves: on checkout (HEAD itself) and on commit (the
+    // branch ref it points to, loose or packed). Missing files would make
+    // cargo rerun every build, so only existing ones are watched.
+    let mut watched = vec![git(&["rev-parse", "--git-path", "HEAD"])];
+    if let Some(branch) = git(&["symbolic-ref", "-q", "HEAD"]) {
+        watched.push(git(&["rev-parse", "--git-path", &branch]));
+    }
+    watched.push(git(&["rev-parse", "--git-path", "packed-refs"]));
+    for path in watched.into_iter().flatten().filter(|path| Path::new(path).exists()) {
+        println!("cargo:rerun-if-changed={}", path);
+    }
+    println!("cargo:rerun-if-changed=build.rs");
+}
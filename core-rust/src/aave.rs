@@ -0,0 +1,158 @@
+use ethers::abi::{AbiDecode, AbiEncode};
+use ethers::prelude::*;
+use anyhow::{anyhow, Result};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use crate::chain_reader::ChainReader;
+
+// Aave V3 `ReserveData`; the one-word `ReserveConfigurationMap` struct is
+// flattened to its `uint256` since both encode the same
+abigen!(
+    AaveV3Pool,
+    r#"[
+        struct ReserveData { uint256 configuration; uint128 liquidityIndex; uint128 currentLiquidityRate; uint128 variableBorrowIndex; uint128 currentVariableBorrowRate; uint128 currentStableBorrowRate; uint40 lastUpdateTimestamp; uint16 id; address aTokenAddress; address stableDebtTokenAddress; address variableDebtTokenAddress; address interestRateStrategyAddress; uint128 accruedToTreasury; uint128 unbacked; uint128 isolationModeTotalDebt; }
+        function getReserveData(address asset) external view returns (ReserveData)
+    ]"#,
+);
+
+/// Token contracts backing one Aave reserve
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ReserveTokens {
+    /// Holds the reserve's unborrowed liquidity
+    pub a_token: Address,
+    pub stable_debt_token: Address,
+    pub variable_debt_token: Address,
+}
+
+/// aToken and debt token addresses of `token`'s reserve in an Aave V3 pool
+///
+/// Fails when `token` isn't listed, which the pool reports as a zero aToken.
+pub async fn get_aave_reserve_tokens<P: JsonRpcClient + 'static>(
+    token: Address,
+    aave_pool: Address,
+    provider: Arc<Provider<P>>,
+) -> Result<ReserveTokens> {
+    get_aave_reserve_tokens_from(provider.as_ref(), token, aave_pool).await
+}
+
+/// [`get_aave_reserve_tokens`] over any [`ChainReader`]
+pub async fn get_aave_reserve_tokens_from(reader: &dyn ChainReader, token: Address, aave_pool: Address) -> Result<ReserveTokens> {
+    let call = GetReserveDataCall { asset: token };
+    let data = GetReserveDataReturn::decode(reader.call(aave_pool, call.encode().into()).await?)?.0;
+    let (_, _, _, _, _, _, _, _, a_token, stable_debt_token, variable_debt_token, ..) = data;
+    if a_token.is_zero() {
+        return Err(anyhow!("Token {:?} has no reserve in Aave pool {:?}", token, aave_pool));
+    }
+    Ok(ReserveTokens {
+        a_token,
+        stable_debt_token,
+        variable_debt_token,
+    })
+}
+
+/// Reserve token addresses of one chain's Aave pool, read once per token
+///
+/// A reserve's token contracts only change on a governance upgrade, so
+/// entries are never refreshed; failed reads aren't cached.
+pub struct ReserveTokenCache {
+    aave_pool: Address,
+    reader: Arc<dyn ChainReader>,
+    tokens: Mutex<HashMap<Address, ReserveTokens>>,
+}
+
+impl ReserveTokenCache {
+    pub fn new(aave_pool: Address, reader: Arc<dyn ChainReader>) -> Self {
+        Self {
+            aave_pool,
+            reader,
+            tokens: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Reserve tokens of `token`, from the cache when already read
+    pub async fn get(&self, token: Address) -> Result<ReserveTokens> {
+        if let Some(tokens) = self.tokens.lock().unwrap().get(&token) {
+            return Ok(*tokens);
+        }
+        let tokens = get_aave_reserve_tokens_from(self.reader.as_ref(), token, self.aave_pool).await?;
+        self.tokens.lock().unwrap().insert(token, tokens);
+        Ok(tokens)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::abi_call::uint_words;
+    use crate::chain_reader::MockChainReader;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    /// `getReserveData` output with aToken/stableDebt/variableDebt at 0xa1/0xa2/0xa3
+    fn reserve_data(a_token: u64) -> Bytes {
+        let words: Vec<u64> = (0..15)
+            .map(|i| match i {
+                8 => a_token,
+                9 => 0xa2,
+                10 => 0xa3,
+                _ => i + 1,
+            })
+            .collect();
+        uint_words(&words)
+    }
+
+    #[tokio::test]
+    async fn test_decode_reserve_data_layout() {
+        let (provider, mock) = Provider::mocked();
+        mock.push::<Bytes, _>(reserve_data(0xa1)).unwrap();
+
+        let tokens = get_aave_reserve_tokens(Address::zero(), Address::zero(), Arc::new(provider)).await.unwrap();
+        assert_eq!(tokens.a_token, Address::from_low_u64_be(0xa1));
+        assert_eq!(tokens.stable_debt_token, Address::from_low_u64_be(0xa2));
+        assert_eq!(tokens.variable_debt_token, Address::from_low_u64_be(0xa3));
+
+        // Unlisted tokens come back as an all-zero struct
+        let (provider, mock) = Provider::mocked();
+        mock.push::<Bytes, _>(reserve_data(0)).unwrap();
+        assert!(get_aave_reserve_tokens(Address::zero(), Address::zero(), Arc::new(provider)).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_cache_reads_each_token_once() {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let counted = Arc::clone(&calls);
+        let pool = Address::from_low_u64_be(0x900);
+        let reader = MockChainReader::new().with_call_handler(move |to, data| {
+            assert_eq!(to, pool);
+            counted.fetch_add(1, Ordering::SeqCst);
+            let asset = GetReserveDataCall::decode(data)?.asset;
+            Ok(reserve_data(asset.to_low_u64_be()))
+        });
+        let cache = ReserveTokenCache::new(pool, Arc::new(reader));
+
+        let usdc = Address::from_low_u64_be(0xa1);
+        assert_eq!(cache.get(usdc).await.unwrap().a_token, usdc);
+        assert_eq!(cache.get(usdc).await.unwrap().a_token, usdc);
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+
+        // Failures are retried rather than cached
+        assert!(cache.get(Address::zero()).await.is_err());
+        assert!(cache.get(Address::zero()).await.is_err());
+        assert_eq!(calls.load(Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test]
+    #[ignore = "needs FORK_RPC_URL, AAVE_POOL, AAVE_TOKEN and AAVE_A_TOKEN"]
+    async fn test_reserve_tokens_fork() {
+        // Run with `--ignored` against an RPC (e.g. an anvil fork) with an
+        // Aave V3 pool and a listed token plus its expected aToken
+        let var = |name: &str| std::env::var(name).unwrap_or_else(|_| panic!("{} is not set", name));
+        let (rpc_url, pool, token, a_token) =
+            (var("FORK_RPC_URL"), var("AAVE_POOL"), var("AAVE_TOKEN"), var("AAVE_A_TOKEN"));
+
+        let provider = Arc::new(Provider::<Http>::try_from(rpc_url).unwrap());
+        let tokens = get_aave_reserve_tokens(token.parse().unwrap(), pool.parse().unwrap(), provider).await.unwrap();
+        assert_eq!(tokens.a_token, a_token.parse::<Address>().unwrap());
+        assert!(!tokens.variable_debt_token.is_zero());
+    }
+}
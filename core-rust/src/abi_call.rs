@@ -0,0 +1,158 @@
+use anyhow::{anyhow, Result};
+use ethers::abi::token::{LenientTokenizer, Tokenizer};
+use ethers::abi::{AbiParser, Function, ParamType, StateMutability, Token};
+use ethers::prelude::*;
+use ethers::types::transaction::eip2718::TypedTransaction;
+use ethers::utils::hex;
+use serde_json::Value;
+
+/// Parse a human-readable signature such as `balanceOf(address) view returns (uint256)`
+///
+/// Only `view` and `pure` functions are accepted; without a `returns`
+/// clause the call still runs but only the raw result is available.
+pub fn parse_signature(signature: &str) -> Result<Function> {
+    let function = AbiParser::default()
+        .parse_function(signature)
+        .map_err(|e| anyhow!("Invalid function signature '{}': {}", signature, e))?;
+    if !matches!(function.state_mutability, StateMutability::View | StateMutability::Pure) {
+        return Err(anyhow!("Function '{}' must be declared view or pure to be called read-only", function.name));
+    }
+    Ok(function)
+}
+
+/// Convert a JSON argument to an ABI token of type `kind`
+///
+/// Arrays take JSON arrays; scalars take strings, numbers or booleans.
+pub fn json_to_token(kind: &ParamType, value: &Value) -> Result<Token> {
+    match (kind, value) {
+        (ParamType::Array(inner), Value::Array(items)) => Ok(Token::Array(
+            items.iter().map(|item| json_to_token(inner, item)).collect::<Result<_>>()?,
+        )),
+        (ParamType::FixedArray(inner, len), Value::Array(items)) if items.len() == *len => Ok(Token::FixedArray(
+            items.iter().map(|item| json_to_token(inner, item)).collect::<Result<_>>()?,
+        )),
+        (ParamType::Tuple(kinds), Value::Array(items)) if items.len() == kinds.len() => Ok(Token::Tuple(
+            kinds.iter().zip(items).map(|(kind, item)| json_to_token(kind, item)).collect::<Result<_>>()?,
+        )),
+        (ParamType::Array(_) | ParamType::FixedArray(..) | ParamType::Tuple(_), _) => {
+            Err(anyhow!("Expected a JSON array of the right length for {}", kind))
+        }
+        (_, Value::String(text)) => Ok(LenientTokenizer::tokenize(kind, text)?),
+        (_, Value::Number(_) | Value::Bool(_)) => Ok(LenientTokenizer::tokenize(kind, &value.to_string())?),
+        _ => Err(anyhow!("Unsupported value {} for {}", value, kind)),
+    }
+}
+
+/// JSON form of a decoded token: integers as decimal strings, bytes as hex
+pub fn token_to_json(token: &Token) -> Value {
+    match token {
+        Token::Address(address) => Value::String(ethers::utils::to_checksum(address, None)),
+        Token::Uint(value) => Value::String(value.to_string()),
+        Token::Int(value) => Value::String(I256::from_raw(*value).to_string()),
+        Token::Bool(value) => Value::Bool(*value),
+        Token::String(value) => Value::String(value.clone()),
+        Token::Bytes(bytes) | Token::FixedBytes(bytes) => Value::String(format!("0x{}", hex::encode(bytes))),
+        Token::Array(items) | Token::FixedArray(items) | Token::Tuple(items) => {
+            Value::Array(items.iter().map(token_to_json).collect())
+        }
+    }
+}
+
+/// Encode `args` for `function`
+pub fn encode_call(function: &Function, args: &[Value]) -> Result<Bytes> {
+    if args.len() != function.inputs.len() {
+        return Err(anyhow!(
+            "{} expects {} arguments, got {}",
+            function.name,
+            function.inputs.len(),
+            args.len()
+        ));
+    }
+    let tokens = function
+        .inputs
+        .iter()
+        .zip(args)
+        .map(|(param, arg)| json_to_token(&param.kind, arg))
+        .collect::<Result<Vec<_>>>()?;
+    Ok(function.encode_input(&tokens)?.into())
+}
+
+/// `eth_call` `function` on `contract` with no value or sender, returning raw and decoded output
+pub async fn call_function<P: JsonRpcClient>(
+    provider: &Provider<P>,
+    contract: Address,
+    function: &Function,
+    args: &[Value],
+) -> Result<(Bytes, Vec<Value>)> {
+    let tx: TypedTransaction = TransactionRequest::new().to(contract).data(encode_call(function, args)?).into();
+    let raw = provider.call(&tx, None).await?;
+    let decoded = function.decode_output(&raw)?;
+    Ok((raw, decoded.iter().map(token_to_json).collect()))
+}
+
+/// ABI-encode `values` as consecutive uint256 words, as a contract returns them
+#[cfg(test)]
+pub(crate) fn uint_words(values: &[u64]) -> Bytes {
+    let tokens: Vec<Token> = values.iter().map(|value| Token::Uint(U256::from(*value))).collect();
+    ethers::abi::encode(&tokens).into()
+}
+
+/// ABI-encode an address return value
+#[cfg(test)]
+pub(crate) fn address_word(address: &str) -> Bytes {
+    ethers::abi::encode(&[Token::Address(address.parse().unwrap())]).into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_encode_balance_of() {
+        let function = parse_signature("balanceOf(address) view returns (uint256)").unwrap();
+        let data = encode_call(&function, &[serde_json::json!("0x0000000000000000000000000000000000000001")]).unwrap();
+        assert_eq!(
+            hex::encode(&data),
+            "70a082310000000000000000000000000000000000000000000000000000000000000001"
+        );
+
+        assert!(encode_call(&function, &[]).is_err());
+        assert!(parse_signature("function deposit() payable").is_err());
+        assert!(parse_signature("transfer(address,uint256) returns (bool)").is_err());
+        assert!(parse_signature("function add(uint256,uint256) pure returns (uint256)").is_ok());
+        assert!(parse_signature("not a signature").is_err());
+    }
+
+    #[test]
+    fn test_json_args_and_outputs() {
+        let function = parse_signature("getAmountsOut(uint256,address[]) view returns (uint256[])").unwrap();
+        let path = serde_json::json!(["0x0000000000000000000000000000000000000001", "0x0000000000000000000000000000000000000002"]);
+        assert!(encode_call(&function, &[serde_json::json!(1000), path]).is_ok());
+
+        let tokens = [
+            Token::Int(I256::from(-5).into_raw()),
+            Token::Bool(true),
+            Token::Array(vec![Token::Uint(U256::from(7))]),
+        ];
+        let json: Vec<Value> = tokens.iter().map(token_to_json).collect();
+        assert_eq!(json, vec![serde_json::json!("-5"), serde_json::json!(true), serde_json::json!(["7"])]);
+    }
+
+    #[tokio::test]
+    async fn test_call_decodes_result() {
+        let (provider, mock) = Provider::mocked();
+        mock.push::<Bytes, _>(format!("0x{:064x}", 1_500_000u64).parse::<Bytes>().unwrap()).unwrap();
+
+        let function = parse_signature("function balanceOf(address) view returns (uint256)").unwrap();
+        let (raw, outputs) = call_function(
+            &provider,
+            Address::zero(),
+            &function,
+            &[serde_json::json!("0x0000000000000000000000000000000000000001")],
+        )
+        .await
+        .unwrap();
+        assert_eq!(raw.len(), 32);
+        assert_eq!(outputs, vec![serde_json::json!("1500000")]);
+    }
+}
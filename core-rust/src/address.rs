@@ -0,0 +1,83 @@
+use ethers::types::Address;
+use ethers::utils::to_checksum;
+use thiserror::Error;
+
+/// Why an address string was rejected
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum AddressError {
+    #[error("Address must be 0x-prefixed: {0}")]
+    MissingPrefix(String),
+    #[error("Address must be 20-byte hex: {0}")]
+    InvalidHex(String),
+    #[error("Address {input} fails its EIP-55 checksum (did you mean {expected}?)")]
+    BadChecksum { input: String, expected: String },
+}
+
+/// Parse a `0x`-prefixed hex address, checking the EIP-55 checksum of
+/// mixed-case input
+///
+/// All-lowercase or all-uppercase hex carries no checksum and is accepted,
+/// so only a typo in an otherwise checksummed address is caught.
+pub fn validate_address(s: &str) -> Result<Address, AddressError> {
+    let hex = s
+        .strip_prefix("0x")
+        .ok_or_else(|| AddressError::MissingPrefix(s.to_string()))?;
+    if hex.len() != 40 || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        return Err(AddressError::InvalidHex(s.to_string()));
+    }
+    let address: Address = hex.parse().map_err(|_| AddressError::InvalidHex(s.to_string()))?;
+
+    let has_lower = hex.chars().any(|c| c.is_ascii_lowercase());
+    let has_upper = hex.chars().any(|c| c.is_ascii_uppercase());
+    if has_lower && has_upper {
+        let expected = checksummed(&address);
+        if expected[2..] != *hex {
+            return Err(AddressError::BadChecksum { input: s.to_string(), expected });
+        }
+    }
+    Ok(address)
+}
+
+/// EIP-55 form of `address`, as returned in responses
+pub fn checksummed(address: &Address) -> String {
+    to_checksum(address, None)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const USDC: &str = "0x2791Bca1f2de4661ED88A30C99A7a9449Aa84174";
+
+    #[test]
+    fn test_valid_checksum() {
+        let address = validate_address(USDC).unwrap();
+        assert_eq!(checksummed(&address), USDC);
+    }
+
+    #[test]
+    fn test_invalid_checksum() {
+        // One mistyped digit
+        let typo = "0x2791Bca1f2de4661ED88A30C99A7a9449Aa84175";
+        let err = validate_address(typo).unwrap_err();
+        assert!(matches!(err, AddressError::BadChecksum { .. }));
+        // Flipping the case of a single letter breaks it too
+        let err = validate_address("0x2791bca1f2de4661ED88A30C99A7a9449Aa84174").unwrap_err();
+        assert!(matches!(err, AddressError::BadChecksum { expected, .. } if expected == USDC));
+    }
+
+    #[test]
+    fn test_single_case_input_skips_checksum() {
+        let lower = validate_address(&USDC.to_lowercase()).unwrap();
+        let upper = validate_address(&format!("0x{}", USDC[2..].to_uppercase())).unwrap();
+        assert_eq!(lower, upper);
+        assert_eq!(checksummed(&lower), USDC);
+    }
+
+    #[test]
+    fn test_malformed_input() {
+        assert!(matches!(validate_address(&USDC[2..]), Err(AddressError::MissingPrefix(_))));
+        assert!(matches!(validate_address("0x1234"), Err(AddressError::InvalidHex(_))));
+        assert!(matches!(validate_address("0xnothex"), Err(AddressError::InvalidHex(_))));
+    }
+}
@@ -0,0 +1,348 @@
+use std::collections::hash_map::RandomState;
+use std::collections::HashMap;
+use std::hash::{BuildHasher, Hasher};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use log::{debug, warn};
+use reqwest::{RequestBuilder, Response, StatusCode};
+use serde::Serialize;
+
+/// Provider name used for LiFi requests
+pub const LIFI_PROVIDER: &str = "LIFI";
+
+/// Provider name used for Socket requests
+pub const SOCKET_PROVIDER: &str = "SOCKET";
+
+/// Retry schedule for transient API failures
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RetryPolicy {
+    /// Total attempts, including the first
+    pub max_attempts: u32,
+    /// Delay before the first retry, doubled for each one after
+    pub base_delay: Duration,
+    /// Upper bound for any single delay, including `Retry-After`
+    pub max_delay: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 3,
+            base_delay: Duration::from_millis(250),
+            max_delay: Duration::from_secs(10),
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Exponential backoff before retry number `retry` (1-based), with jitter
+    /// drawn from the upper half of the interval
+    pub fn backoff(&self, retry: u32) -> Duration {
+        let exponential = self
+            .base_delay
+            .saturating_mul(2u32.saturating_pow(retry.saturating_sub(1)))
+            .min(self.max_delay);
+        let half = exponential / 2;
+        half + half.mul_f64(random_fraction())
+    }
+}
+
+/// Uniform value in `0.0..1.0`, good enough for jitter
+fn random_fraction() -> f64 {
+    let mut hasher = RandomState::new().build_hasher();
+    hasher.write_u64(0);
+    (hasher.finish() >> 11) as f64 / (1u64 << 53) as f64
+}
+
+/// Whether a response status is worth retrying
+pub fn is_retryable_status(status: StatusCode) -> bool {
+    status == StatusCode::TOO_MANY_REQUESTS || status.is_server_error()
+}
+
+/// `Retry-After` in seconds, if present (HTTP dates aren't supported)
+fn retry_after(response: &Response) -> Option<Duration> {
+    response
+        .headers()
+        .get(reqwest::header::RETRY_AFTER)?
+        .to_str()
+        .ok()?
+        .trim()
+        .parse()
+        .ok()
+        .map(Duration::from_secs)
+}
+
+/// Token bucket allowing `rate_per_sec` requests with bursts of the same size
+#[derive(Debug)]
+pub struct TokenBucket {
+    rate_per_sec: f64,
+    capacity: f64,
+    state: Mutex<(f64, Instant)>,
+}
+
+impl TokenBucket {
+    pub fn new(rate_per_sec: f64) -> Self {
+        let capacity = rate_per_sec.max(1.0);
+        Self {
+            rate_per_sec,
+            capacity,
+            state: Mutex::new((capacity, Instant::now())),
+        }
+    }
+
+    /// Wait until a request may be sent
+    pub async fn acquire(&self) {
+        loop {
+            let wait = {
+                let mut state = self.state.lock().unwrap();
+                let (tokens, refilled_at) = &mut *state;
+                let now = Instant::now();
+                *tokens = (*tokens + now.duration_since(*refilled_at).as_secs_f64() * self.rate_per_sec)
+                    .min(self.capacity);
+                *refilled_at = now;
+                if *tokens >= 1.0 {
+                    *tokens -= 1.0;
+                    return;
+                }
+                Duration::from_secs_f64((1.0 - *tokens) / self.rate_per_sec)
+            };
+            tokio::time::sleep(wait).await;
+        }
+    }
+}
+
+/// Request counters for one provider
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct ProviderStats {
+    /// Requests sent, including retries
+    pub attempts: u64,
+    pub retries: u64,
+    /// Calls whose final outcome was a transport error or non-success status
+    pub failures: u64,
+}
+
+/// Retry and rate-limit policy shared by every client of one provider
+#[derive(Debug)]
+pub struct ProviderPolicy {
+    name: String,
+    retry: RetryPolicy,
+    rate_limit: Option<TokenBucket>,
+    attempts: AtomicU64,
+    retries: AtomicU64,
+    failures: AtomicU64,
+}
+
+impl ProviderPolicy {
+    pub fn new(name: &str, retry: RetryPolicy, rate_per_sec: Option<f64>) -> Self {
+        Self {
+            name: name.to_string(),
+            retry,
+            rate_limit: rate_per_sec.filter(|rps| *rps > 0.0).map(TokenBucket::new),
+            attempts: AtomicU64::new(0),
+            retries: AtomicU64::new(0),
+            failures: AtomicU64::new(0),
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn stats(&self) -> ProviderStats {
+        ProviderStats {
+            attempts: self.attempts.load(Ordering::Relaxed),
+            retries: self.retries.load(Ordering::Relaxed),
+            failures: self.failures.load(Ordering::Relaxed),
+        }
+    }
+
+    /// Send the request built by `build`, retrying 429s, 5xx and transport
+    /// errors
+    ///
+    /// Other statuses (400, 401, 404, ...) are returned at once for the
+    /// caller to interpret, as is the last response once attempts run out.
+    pub async fn send(
+        &self,
+        build: impl Fn() -> RequestBuilder,
+    ) -> Result<Response, reqwest::Error> {
+        let mut attempt = 1;
+        loop {
+            if let Some(bucket) = &self.rate_limit {
+                bucket.acquire().await;
+            }
+            self.attempts.fetch_add(1, Ordering::Relaxed);
+
+            let result = build().send().await;
+            let retry_delay = match &result {
+                Ok(response) if is_retryable_status(response.status()) => {
+                    Some(retry_after(response).unwrap_or_else(|| self.retry.backoff(attempt)))
+                }
+                Ok(_) => None,
+                Err(e) if e.is_timeout() || e.is_connect() || e.is_request() => {
+                    Some(self.retry.backoff(attempt))
+                }
+                Err(_) => None,
+            };
+
+            match retry_delay {
+                Some(delay) if attempt < self.retry.max_attempts => {
+                    let delay = delay.min(self.retry.max_delay);
+                    debug!("{} request attempt {} failed, retrying in {:?}", self.name, attempt, delay);
+                    self.retries.fetch_add(1, Ordering::Relaxed);
+                    tokio::time::sleep(delay).await;
+                    attempt += 1;
+                }
+                _ => {
+                    if !matches!(&result, Ok(response) if response.status().is_success()) {
+                        self.failures.fetch_add(1, Ordering::Relaxed);
+                        if retry_delay.is_some() {
+                            warn!("{} request failed after {} attempts", self.name, attempt);
+                        }
+                    }
+                    return result;
+                }
+            }
+        }
+    }
+}
+
+/// Requests per second allowed to each bridge API by default
+pub const DEFAULT_RATE_LIMIT_RPS: f64 = 10.0;
+
+/// Retry schedule and per-provider request rates for bridge API clients
+#[derive(Debug, Clone)]
+pub struct FetchOptions {
+    pub retry: RetryPolicy,
+    /// Requests per second by provider name; unlisted providers are unlimited
+    pub rate_limits: HashMap<String, f64>,
+}
+
+impl Default for FetchOptions {
+    fn default() -> Self {
+        Self {
+            retry: RetryPolicy::default(),
+            rate_limits: HashMap::from([
+                (LIFI_PROVIDER.to_string(), DEFAULT_RATE_LIMIT_RPS),
+                (SOCKET_PROVIDER.to_string(), DEFAULT_RATE_LIMIT_RPS),
+            ]),
+        }
+    }
+}
+
+impl FetchOptions {
+    /// Override one provider's rate limit, if given
+    pub fn with_rate_limit(mut self, provider: &str, rate_per_sec: Option<f64>) -> Self {
+        if let Some(rps) = rate_per_sec {
+            self.rate_limits.insert(provider.to_string(), rps);
+        }
+        self
+    }
+
+    /// Build the shared policy for `provider`
+    pub fn policy(&self, provider: &str) -> Arc<ProviderPolicy> {
+        Arc::new(ProviderPolicy::new(
+            provider,
+            self.retry,
+            self.rate_limits.get(provider).copied(),
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{extract::State, http::HeaderMap, routing::get, Router};
+    use std::sync::atomic::AtomicUsize;
+
+    /// Serve scripted `(status, Retry-After)` responses in order, repeating the last
+    async fn scripted(responses: Vec<(u16, Option<&'static str>)>) -> (String, Arc<AtomicUsize>) {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let app = Router::new()
+            .route(
+                "/",
+                get(move |State(calls): State<Arc<AtomicUsize>>| async move {
+                    let call = calls.fetch_add(1, Ordering::SeqCst);
+                    let (status, retry_after) = responses[call.min(responses.len() - 1)];
+                    let mut headers = HeaderMap::new();
+                    if let Some(value) = retry_after {
+                        headers.insert("retry-after", value.parse().unwrap());
+                    }
+                    (axum::http::StatusCode::from_u16(status).unwrap(), headers, "")
+                }),
+            )
+            .with_state(calls.clone());
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+        (format!("http://{}/", addr), calls)
+    }
+
+    fn fast_retry() -> RetryPolicy {
+        RetryPolicy {
+            max_attempts: 3,
+            base_delay: Duration::from_millis(5),
+            max_delay: Duration::from_secs(5),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_retry_after_honored() {
+        let (url, calls) = scripted(vec![(429, Some("1")), (200, None)]).await;
+        let policy = ProviderPolicy::new("TEST", fast_retry(), None);
+        let http = reqwest::Client::new();
+
+        let started = Instant::now();
+        let response = policy.send(|| http.get(&url)).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert!(started.elapsed() >= Duration::from_secs(1));
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+        assert_eq!(policy.stats(), ProviderStats { attempts: 2, retries: 1, failures: 0 });
+    }
+
+    #[tokio::test]
+    async fn test_bad_request_not_retried() {
+        let (url, calls) = scripted(vec![(400, None), (200, None)]).await;
+        let policy = ProviderPolicy::new("TEST", fast_retry(), None);
+        let http = reqwest::Client::new();
+
+        let response = policy.send(|| http.get(&url)).await.unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+        assert_eq!(policy.stats(), ProviderStats { attempts: 1, retries: 0, failures: 1 });
+    }
+
+    #[tokio::test]
+    async fn test_server_errors_exhaust_attempts() {
+        let (url, calls) = scripted(vec![(503, None)]).await;
+        let policy = ProviderPolicy::new("TEST", fast_retry(), None);
+        let http = reqwest::Client::new();
+
+        let response = policy.send(|| http.get(&url)).await.unwrap();
+        assert_eq!(response.status(), StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(calls.load(Ordering::SeqCst), 3);
+        assert_eq!(policy.stats(), ProviderStats { attempts: 3, retries: 2, failures: 1 });
+    }
+
+    #[tokio::test]
+    async fn test_token_bucket_limits_rate() {
+        let bucket = TokenBucket::new(20.0);
+        let started = Instant::now();
+        for _ in 0..25 {
+            bucket.acquire().await;
+        }
+        // 20 burst tokens, then 5 more at 20/s
+        assert!(started.elapsed() >= Duration::from_millis(200));
+    }
+
+    #[test]
+    fn test_backoff_bounds() {
+        let retry = RetryPolicy::default();
+        for n in 1..=4 {
+            let full = (retry.base_delay * 2u32.pow(n - 1)).min(retry.max_delay);
+            let delay = retry.backoff(n);
+            assert!(delay >= full / 2 && delay <= full, "{:?}", delay);
+        }
+    }
+}
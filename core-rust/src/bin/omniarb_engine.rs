@@ -1,18 +1,665 @@
 // Dual Turbo Rust Engine for OmniArb Token Matrix Module
 // Purpose: High-speed data fetch, matrix scoring & TAR model integration
 
+use std::collections::HashMap;
+use std::io::Write;
+use std::time::Duration;
+
+use titan_core::commander::{estimate_net_apr, meets_min_spread, meets_profit_gas_ratio, DEFAULT_MIN_SPREAD_PCT};
+use titan_core::config::{BridgeConfig, Config, DEFAULT_MATRIX_PATH};
 use titan_core::omniarb::{
-    load_token_matrix, calculate_tar_score, fetch_live_quotes,
-    run_tar_onnx, run_flanker
+    audit_routes, diff_matrices, invalid_row_count, load_token_matrix_auto, load_token_matrix_auto_with_options, simulated_quotes,
+    calculate_tar_breakdown_sized, calculate_tar_score_sized, ensemble_score, fetch_live_quotes_bounded, BatchModel, HeuristicModel,
+    model_bridge, parse_bridge_list, BridgePolicy, FeatureLogger, FeatureRecord, ModelBackend, ModelRegistry, save_token_matrix, AuditReport, DedupStrategy, EnsembleWeights, MatrixError, MatrixFormat, ParseOptions,
+    refresh_liquidity_scores, QuoteRouter, rank_routes, select_top, select_top_diversified, RouteId, DEFAULT_MAX_BATCH, DEFAULT_MAX_DISAGREEMENT, DEFAULT_MAX_PER_KEY, DEFAULT_SCORE_HALF_LIFE, ResumePolicy, ScoreHistory, ScoredRoute, ScoringCheckpoint, SelectionPolicy, TarWeights, TokenMatrix, CHECKPOINT_BATCH,
 };
 
+/// Default number of decimals for scores
+const DEFAULT_PRECISION: usize = 2;
+
+/// Default notional used to estimate route profit (USD)
+const DEFAULT_TRADE_SIZE_USD: f64 = 10_000.0;
+
+/// Seconds to execute a route's swaps, on top of bridge settlement
+const EXECUTION_TIME_SECS: f64 = 30.0;
+
+/// Default number of removed routes `diff` tolerates before failing
+const DEFAULT_MAX_REMOVED: usize = 0;
+
+/// Command-line options
+struct Args {
+    precision: usize,
+    matrix_path: String,
+    strict: bool,
+    export_filtered: Option<String>,
+    origin: Option<u64>,
+    dest: Option<u64>,
+    token: Option<String>,
+    bridge: Option<String>,
+    /// Replace the configured bridge allowlist / denylist
+    allow_bridges: Option<Vec<String>>,
+    deny_bridges: Option<Vec<String>>,
+    min_liquidity: Option<f64>,
+    dedupe: Option<DedupStrategy>,
+    /// Round matrix fee tiers to the nearest Uniswap tier
+    snap_fee_tiers: bool,
+    validate: Option<String>,
+    min_profit_gas_ratio: Option<f64>,
+    /// Spread floor (%) applied regardless of TAR score
+    min_spread_pct: f64,
+    trade_size_usd: f64,
+    /// Rank by the weighted model ensemble instead of the TAR score
+    ensemble: Option<EnsembleWeights>,
+    /// Skip routes whose quote is older than this
+    max_quote_age: Option<Duration>,
+    /// Recompute liquidity scores on-chain and rewrite the matrix file
+    refresh_liquidity: bool,
+    /// TAR component maxima overriding the config's
+    tar_weights: Option<TarWeights>,
+    /// Print the T/A/R components next to each top route
+    verbose: bool,
+    /// `.jsonl` file scored routes are appended to and resumed from
+    checkpoint: Option<String>,
+    /// Rescore every route, discarding the checkpoint
+    force: bool,
+    /// How the top routes are chosen; defaults to the config's enter threshold
+    select: Option<SelectionPolicy>,
+    /// JSON file of smoothed per-route scores carried between runs
+    score_history: Option<String>,
+    /// Cap on top routes sharing a token and bridge
+    max_per_key: Option<usize>,
+    /// Rows per model inference call
+    max_batch: usize,
+    /// Model spread above which a route is flagged
+    max_disagreement: f64,
+    /// Drop flagged routes from the top opportunities
+    exclude_disagreeing: bool,
+}
+
+/// Take a flag's value from `--flag=value` or the next argument
+fn flag_value(
+    flag: &str,
+    inline_value: Option<String>,
+    iter: &mut impl Iterator<Item = String>,
+) -> Result<String, String> {
+    inline_value
+        .or_else(|| iter.next())
+        .ok_or_else(|| format!("{} requires a value", flag))
+}
+
+/// Parse a flag's value into `T`
+fn parse_flag<T: std::str::FromStr>(flag: &str, value: &str) -> Result<T, String>
+where
+    T::Err: std::fmt::Display,
+{
+    value
+        .parse()
+        .map_err(|e| format!("Invalid {} '{}': {}", flag, value, e))
+}
+
+impl Args {
+    fn parse() -> Result<Self, String> {
+        let mut args = Args {
+            precision: DEFAULT_PRECISION,
+            matrix_path: DEFAULT_MATRIX_PATH.to_string(),
+            strict: false,
+            export_filtered: None,
+            origin: None,
+            dest: None,
+            token: None,
+            bridge: None,
+            allow_bridges: None,
+            deny_bridges: None,
+            min_liquidity: None,
+            dedupe: None,
+            snap_fee_tiers: false,
+            validate: None,
+            min_profit_gas_ratio: None,
+            min_spread_pct: DEFAULT_MIN_SPREAD_PCT,
+            trade_size_usd: DEFAULT_TRADE_SIZE_USD,
+            ensemble: None,
+            max_quote_age: None,
+            refresh_liquidity: false,
+            tar_weights: None,
+            verbose: false,
+            checkpoint: None,
+            force: false,
+            select: None,
+            score_history: None,
+            max_per_key: None,
+            max_batch: DEFAULT_MAX_BATCH,
+            max_disagreement: DEFAULT_MAX_DISAGREEMENT,
+            exclude_disagreeing: false,
+        };
+
+        let mut iter = std::env::args().skip(1);
+        while let Some(arg) = iter.next() {
+            let (flag, inline_value) = match arg.split_once('=') {
+                Some((flag, value)) => (flag.to_string(), Some(value.to_string())),
+                None => (arg.clone(), None),
+            };
+
+            match flag.as_str() {
+                "--precision" => {
+                    let value = flag_value(&flag, inline_value, &mut iter)?;
+                    args.precision = parse_flag(&flag, &value)?;
+                }
+                "--matrix" => args.matrix_path = flag_value(&flag, inline_value, &mut iter)?,
+                "--strict" => args.strict = true,
+                "--snap-fee-tiers" => args.snap_fee_tiers = true,
+                "--export-filtered" => {
+                    args.export_filtered = Some(flag_value(&flag, inline_value, &mut iter)?);
+                }
+                "--origin" => {
+                    let value = flag_value(&flag, inline_value, &mut iter)?;
+                    args.origin = Some(parse_flag(&flag, &value)?);
+                }
+                "--dest" => {
+                    let value = flag_value(&flag, inline_value, &mut iter)?;
+                    args.dest = Some(parse_flag(&flag, &value)?);
+                }
+                "--token" => args.token = Some(flag_value(&flag, inline_value, &mut iter)?),
+                "--bridge" => args.bridge = Some(flag_value(&flag, inline_value, &mut iter)?),
+                "--allow-bridges" => {
+                    args.allow_bridges = Some(parse_bridge_list(&flag_value(&flag, inline_value, &mut iter)?));
+                }
+                "--deny-bridges" => {
+                    args.deny_bridges = Some(parse_bridge_list(&flag_value(&flag, inline_value, &mut iter)?));
+                }
+                "--min-liquidity" => {
+                    let value = flag_value(&flag, inline_value, &mut iter)?;
+                    args.min_liquidity = Some(parse_flag(&flag, &value)?);
+                }
+                "--dedupe" => {
+                    let value = flag_value(&flag, inline_value, &mut iter)?;
+                    args.dedupe = Some(value.parse()?);
+                }
+                "--validate" => args.validate = Some(flag_value(&flag, inline_value, &mut iter)?),
+                "--min-profit-gas-ratio" => {
+                    let value = flag_value(&flag, inline_value, &mut iter)?;
+                    args.min_profit_gas_ratio = Some(parse_flag(&flag, &value)?);
+                }
+                "--min-spread-pct" => {
+                    let value = flag_value(&flag, inline_value, &mut iter)?;
+                    args.min_spread_pct = parse_flag(&flag, &value)?;
+                }
+                "--trade-size" => {
+                    let value = flag_value(&flag, inline_value, &mut iter)?;
+                    args.trade_size_usd = parse_flag(&flag, &value)?;
+                }
+                "--ensemble" => args.ensemble = Some(EnsembleWeights::equal()),
+                "--ensemble-weights" => {
+                    let value = flag_value(&flag, inline_value, &mut iter)?;
+                    args.ensemble = Some(value.parse()?);
+                }
+                "--max-quote-age" => {
+                    let value = flag_value(&flag, inline_value, &mut iter)?;
+                    args.max_quote_age = Some(Duration::from_secs(parse_flag(&flag, &value)?));
+                }
+                "--refresh-liquidity" => args.refresh_liquidity = true,
+                "--verbose" | "-v" => args.verbose = true,
+                "--checkpoint" => args.checkpoint = Some(flag_value(&flag, inline_value, &mut iter)?),
+                "--force" => args.force = true,
+                "--score-history" => args.score_history = Some(flag_value(&flag, inline_value, &mut iter)?),
+                "--max-batch" => {
+                    let value = flag_value(&flag, inline_value, &mut iter)?;
+                    args.max_batch = parse_flag(&flag, &value)?;
+                    if args.max_batch == 0 {
+                        return Err("--max-batch must be at least 1".to_string());
+                    }
+                }
+                "--max-disagreement" => {
+                    let value = flag_value(&flag, inline_value, &mut iter)?;
+                    args.max_disagreement = parse_flag(&flag, &value)?;
+                }
+                "--exclude-disagreeing" => args.exclude_disagreeing = true,
+                "--diversify" => args.max_per_key = Some(DEFAULT_MAX_PER_KEY),
+                "--diversify-max" => {
+                    let value = flag_value(&flag, inline_value, &mut iter)?;
+                    args.max_per_key = Some(parse_flag(&flag, &value)?);
+                }
+                "--select" => {
+                    let value = flag_value(&flag, inline_value, &mut iter)?;
+                    args.select = Some(value.parse()?);
+                }
+                "--tar-weights" => {
+                    let value = flag_value(&flag, inline_value, &mut iter)?;
+                    args.tar_weights = Some(value.parse()?);
+                }
+                _ => return Err(format!("Unknown argument: {}", arg)),
+            }
+        }
+
+        Ok(args)
+    }
+
+    /// `configured` with the lists given on the command line swapped in
+    fn bridge_policy(&self, configured: &BridgePolicy) -> BridgePolicy {
+        BridgePolicy {
+            allow: self.allow_bridges.clone().unwrap_or_else(|| configured.allow.clone()),
+            deny: self.deny_bridges.clone().unwrap_or_else(|| configured.deny.clone()),
+        }
+    }
+
+    /// Narrow the matrix to the routes selected on the command line
+    fn apply_filters(&self, mut matrix: TokenMatrix) -> TokenMatrix {
+        if let Some(origin) = self.origin {
+            matrix = matrix.filter_origin(origin);
+        }
+        if let Some(dest) = self.dest {
+            matrix = matrix.filter_dest(dest);
+        }
+        if let Some(token) = &self.token {
+            matrix = matrix.filter_token(token);
+        }
+        if let Some(bridge) = &self.bridge {
+            matrix = matrix.filter_bridge(bridge);
+        }
+        if let Some(score) = self.min_liquidity {
+            matrix = matrix.min_liquidity(score);
+        }
+        matrix
+    }
+}
+
+/// Lint a matrix file without scoring it
+///
+/// Writes one `ERROR`/`DUPLICATE` line per problem, a `WARN` line per
+/// normalized name, and a final `OK` or `FAIL` summary line. Returns
+/// whether the file is clean (warnings don't count).
+fn validate_matrix(path: &str, out: &mut impl Write) -> std::io::Result<bool> {
+    // Lenient load so every malformed row is reported alongside the row count
+    let options = ParseOptions {
+        strict: false,
+        ..ParseOptions::default()
+    };
+    let (matrix, diagnostics, warnings) = match load_token_matrix_auto_with_options(path, options) {
+        Ok(load) => (TokenMatrix::new(load.entries), load.diagnostics, load.warnings),
+        Err(MatrixError::Invalid(diagnostics)) => (TokenMatrix::default(), diagnostics, Vec::new()),
+        Err(e) => {
+            writeln!(out, "ERROR reason={:?}", e.to_string())?;
+            writeln!(out, "FAIL {} rows=0 errors=1 duplicates=0", path)?;
+            return Ok(false);
+        }
+    };
+
+    for d in &diagnostics {
+        writeln!(
+            out,
+            "ERROR line={} column={} value={:?} reason={:?}",
+            d.line, d.column, d.raw_value, d.reason
+        )?;
+    }
+    for w in &warnings {
+        writeln!(
+            out,
+            "WARN line={} column={} value={:?} reason={:?}",
+            w.line, w.column, w.raw_value, w.reason
+        )?;
+    }
+    let duplicates = matrix.duplicate_routes();
+    for (entry, count) in &duplicates {
+        writeln!(
+            out,
+            "DUPLICATE route=\"{}>{} {} {}>{} {}\" count={}",
+            entry.chain_origin,
+            entry.chain_dest,
+            entry.native_token,
+            entry.dex_origin,
+            entry.dex_dest,
+            entry.bridge_protocol,
+            count
+        )?;
+    }
+
+    let clean = diagnostics.is_empty() && duplicates.is_empty();
+    let invalid_rows = invalid_row_count(&diagnostics);
+    writeln!(
+        out,
+        "{} {} rows={} invalid_rows={} errors={} duplicates={}",
+        if clean { "OK" } else { "FAIL" },
+        path,
+        matrix.len() + invalid_rows,
+        invalid_rows,
+        diagnostics.len(),
+        duplicates.len()
+    )?;
+    Ok(clean)
+}
+
+/// Options for the `diff OLD NEW` subcommand
+struct DiffArgs {
+    old_path: String,
+    new_path: String,
+    max_removed: usize,
+    json: bool,
+}
+
+impl DiffArgs {
+    fn parse(argv: impl IntoIterator<Item = String>) -> Result<Self, String> {
+        let mut paths = Vec::new();
+        let mut max_removed = DEFAULT_MAX_REMOVED;
+        let mut json = false;
+
+        let mut iter = argv.into_iter();
+        while let Some(arg) = iter.next() {
+            let (flag, inline_value) = match arg.split_once('=') {
+                Some((flag, value)) => (flag.to_string(), Some(value.to_string())),
+                None => (arg.clone(), None),
+            };
+
+            match flag.as_str() {
+                "--max-removed" => {
+                    let value = flag_value(&flag, inline_value, &mut iter)?;
+                    max_removed = parse_flag(&flag, &value)?;
+                }
+                "--json" => json = true,
+                _ if !arg.starts_with("--") => paths.push(arg),
+                _ => return Err(format!("Unknown argument: {}", arg)),
+            }
+        }
+
+        match <[String; 2]>::try_from(paths) {
+            Ok([old_path, new_path]) => Ok(DiffArgs { old_path, new_path, max_removed, json }),
+            Err(_) => Err("diff requires exactly two matrix paths".to_string()),
+        }
+    }
+}
+
+/// Compare two matrix files and write the report (or JSON) to `out`
+///
+/// Returns false when a file can't be loaded or more than `max_removed`
+/// routes disappeared.
+fn diff_matrix_files(args: &DiffArgs, out: &mut impl Write) -> std::io::Result<bool> {
+    let (old, new) = match (
+        load_token_matrix_auto(&args.old_path),
+        load_token_matrix_auto(&args.new_path),
+    ) {
+        (Ok(old), Ok(new)) => (old, new),
+        (Err(e), _) | (_, Err(e)) => {
+            writeln!(out, "ERROR reason={:?}", e)?;
+            return Ok(false);
+        }
+    };
+
+    let diff = diff_matrices(&old, &new);
+    if args.json {
+        let json = serde_json::to_string_pretty(&diff).map_err(std::io::Error::other)?;
+        writeln!(out, "{}", json)?;
+    } else {
+        writeln!(out, "{}", diff)?;
+    }
+
+    if diff.removed.len() > args.max_removed {
+        if !args.json {
+            writeln!(
+                out,
+                "FAIL {} routes removed (max {})",
+                diff.removed.len(),
+                args.max_removed
+            )?;
+        }
+        return Ok(false);
+    }
+    Ok(true)
+}
+
+/// Options for the `audit --matrix PATH` subcommand
+struct AuditArgs {
+    matrix_path: String,
+    /// Fingerprint the run must reproduce
+    expect: Option<String>,
+    /// Write the audited routes here as a golden JSON file
+    write_golden: Option<String>,
+    tar_weights: TarWeights,
+}
+
+impl AuditArgs {
+    fn parse(argv: impl IntoIterator<Item = String>) -> Result<Self, String> {
+        let mut matrix_path = None;
+        let mut expect = None;
+        let mut write_golden = None;
+        let mut tar_weights = TarWeights::default();
+
+        let mut iter = argv.into_iter();
+        while let Some(arg) = iter.next() {
+            let (flag, inline_value) = match arg.split_once('=') {
+                Some((flag, value)) => (flag.to_string(), Some(value.to_string())),
+                None => (arg.clone(), None),
+            };
+
+            match flag.as_str() {
+                "--matrix" => matrix_path = Some(flag_value(&flag, inline_value, &mut iter)?),
+                "--expect" => expect = Some(flag_value(&flag, inline_value, &mut iter)?),
+                "--write-golden" => write_golden = Some(flag_value(&flag, inline_value, &mut iter)?),
+                "--tar-weights" => tar_weights = flag_value(&flag, inline_value, &mut iter)?.parse()?,
+                _ => return Err(format!("Unknown argument: {}", arg)),
+            }
+        }
+
+        let matrix_path = matrix_path.ok_or_else(|| "audit requires --matrix PATH".to_string())?;
+        Ok(AuditArgs { matrix_path, expect, write_golden, tar_weights })
+    }
+}
+
+/// Score a matrix against simulated quotes and check its fingerprint
+///
+/// Uses default TAR weights (or `--tar-weights`) rather than the config,
+/// so the result depends only on the matrix and the scoring code. Returns
+/// false when the matrix can't be loaded or the fingerprint differs from
+/// `--expect`.
+fn audit_matrix_file(args: &AuditArgs, out: &mut impl Write) -> std::io::Result<bool> {
+    let entries = match load_token_matrix_auto(&args.matrix_path) {
+        Ok(entries) => entries,
+        Err(e) => {
+            writeln!(out, "ERROR reason={:?}", e)?;
+            return Ok(false);
+        }
+    };
+
+    let quotes = simulated_quotes(&entries);
+    let report = AuditReport::new(audit_routes(&entries, &quotes, &args.tar_weights));
+    writeln!(out, "FINGERPRINT {} routes={}", report.fingerprint, report.routes.len())?;
+
+    if let Some(path) = &args.write_golden {
+        let json = serde_json::to_string_pretty(&report).map_err(std::io::Error::other)?;
+        std::fs::write(path, json + "\n")?;
+        writeln!(out, "WROTE {}", path)?;
+    }
+
+    match &args.expect {
+        Some(expected) if !expected.eq_ignore_ascii_case(&report.fingerprint) => {
+            writeln!(out, "FAIL expected {} got {}", expected, report.fingerprint)?;
+            Ok(false)
+        }
+        Some(_) => {
+            writeln!(out, "OK {}", args.matrix_path)?;
+            Ok(true)
+        }
+        None => Ok(true),
+    }
+}
+
+/// Seconds one round of a route ties up capital: execution plus the
+/// bridge's typical settlement time
+///
+/// `None` when the bridge has no configured time; guessing one would make
+/// the APR of such routes look arbitrarily good or bad.
+fn route_cycle_secs(bridges: &HashMap<String, BridgeConfig>, bridge_protocol: &str) -> Option<f64> {
+    bridges
+        .get(&bridge_protocol.to_lowercase())
+        .map(|bridge| EXECUTION_TIME_SECS + bridge.typical_time_seconds as f64)
+}
+
+/// Format a number with thousands separators, e.g. `1,000,000.00`
+fn format_thousands(value: f64, precision: usize) -> String {
+    let formatted = format!("{:.*}", precision, value.abs());
+    let (int_part, frac_part) = match formatted.split_once('.') {
+        Some((int_part, frac_part)) => (int_part, Some(frac_part)),
+        None => (formatted.as_str(), None),
+    };
+
+    let mut grouped = String::with_capacity(int_part.len() + int_part.len() / 3);
+    for (i, digit) in int_part.chars().enumerate() {
+        if i > 0 && (int_part.len() - i) % 3 == 0 {
+            grouped.push(',');
+        }
+        grouped.push(digit);
+    }
+
+    let sign = if value < 0.0 && formatted.chars().any(|c| c != '0' && c != '.') { "-" } else { "" };
+    match frac_part {
+        Some(frac) => format!("{}{}.{}", sign, grouped, frac),
+        None => format!("{}{}", sign, grouped),
+    }
+}
+
+/// Print rows as a table with columns sized to their widest cell
+fn print_table(headers: &[&str], rows: &[Vec<String>]) {
+    let widths: Vec<usize> = headers
+        .iter()
+        .enumerate()
+        .map(|(i, header)| {
+            rows.iter()
+                .map(|row| row[i].chars().count())
+                .chain(std::iter::once(header.chars().count()))
+                .max()
+                .unwrap_or(0)
+        })
+        .collect();
+    let total_width = widths.iter().sum::<usize>() + 2 * widths.len().saturating_sub(1);
+
+    let format_row = |cells: Vec<&str>| {
+        cells
+            .iter()
+            .zip(&widths)
+            .map(|(cell, width)| format!("{:<width$}", cell, width = width))
+            .collect::<Vec<_>>()
+            .join("  ")
+            .trim_end()
+            .to_string()
+    };
+
+    println!("{:-<width$}", "", width = total_width);
+    println!("{}", format_row(headers.to_vec()));
+    println!("{:-<width$}", "", width = total_width);
+    for row in rows {
+        println!("{}", format_row(row.iter().map(String::as_str).collect()));
+    }
+}
+
 fn main() {
+    if std::env::args().nth(1).as_deref() == Some("diff") {
+        let args = match DiffArgs::parse(std::env::args().skip(2)) {
+            Ok(args) => args,
+            Err(e) => {
+                eprintln!("❌ {}", e);
+                eprintln!("Usage: omniarb_engine diff OLD NEW [--max-removed N] [--json]");
+                std::process::exit(2);
+            }
+        };
+        let mut stdout = std::io::stdout().lock();
+        let ok = diff_matrix_files(&args, &mut stdout).unwrap_or(false);
+        std::process::exit(if ok { 0 } else { 1 });
+    }
+
+    if std::env::args().nth(1).as_deref() == Some("audit") {
+        let args = match AuditArgs::parse(std::env::args().skip(2)) {
+            Ok(args) => args,
+            Err(e) => {
+                eprintln!("❌ {}", e);
+                eprintln!(
+                    "Usage: omniarb_engine audit --matrix PATH [--expect HASH] [--write-golden PATH] \
+                     [--tar-weights TOKEN,ARBITRAGE,RISK]"
+                );
+                std::process::exit(2);
+            }
+        };
+        let mut stdout = std::io::stdout().lock();
+        let ok = audit_matrix_file(&args, &mut stdout).unwrap_or(false);
+        std::process::exit(if ok { 0 } else { 1 });
+    }
+
+    let args = match Args::parse() {
+        Ok(args) => args,
+        Err(e) => {
+            eprintln!("❌ {}", e);
+            eprintln!(
+                "Usage: omniarb_engine [diff OLD NEW | audit --matrix PATH] [--precision N] [--matrix PATH] [--strict] [--export-filtered PATH] \
+                 [--origin CHAIN] [--dest CHAIN] [--token SYMBOL] [--bridge NAME] [--allow-bridges A,B] [--deny-bridges A,B] \
+                 [--min-liquidity SCORE] \
+                 [--dedupe first|highest|average] [--snap-fee-tiers] [--validate PATH] [--min-profit-gas-ratio R] [--min-spread-pct PCT] \
+                 [--trade-size USD] [--ensemble] [--ensemble-weights ONNX,FLANKER,TAR] [--max-quote-age SECS] \
+                 [--refresh-liquidity] [--tar-weights TOKEN,ARBITRAGE,RISK] [--verbose] \
+                 [--checkpoint PATH] [--force] [--select percentile:P|score:S|top:N] \
+                 [--score-history PATH] [--diversify] [--diversify-max N] \
+                 [--max-batch N] [--max-disagreement POINTS] [--exclude-disagreeing]"
+            );
+            std::process::exit(2);
+        }
+    };
+    let precision = args.precision;
+
+    if let Some(path) = &args.validate {
+        let mut stdout = std::io::stdout().lock();
+        let clean = validate_matrix(path, &mut stdout).unwrap_or(false);
+        std::process::exit(if clean { 0 } else { 1 });
+    }
+
     println!("🚀 OmniArb Dual Turbo Rust Engine Starting...");
 
+    // TAR/Flanker model files (TAR_MODEL_PATH, FLANKER_MODEL_PATH)
+    let models = match ModelRegistry::from_env() {
+        Ok(models) => models,
+        Err(e) => {
+            eprintln!("❌ {} (TITAN_REQUIRE_MODELS is set)", e);
+            std::process::exit(1);
+        }
+    };
+    for status in model_bridge::status(&models) {
+        match (status.backend, &status.last_error) {
+            (ModelBackend::Onnx, _) => println!(
+                "🧠 {} model: ONNX {} ({})",
+                status.name,
+                status.version.as_deref().unwrap_or_default(),
+                status.path.as_ref().map(|p| p.display().to_string()).unwrap_or_default()
+            ),
+            (ModelBackend::Heuristic, None) => println!("🧠 {} model: heuristic (no model file configured)", status.name),
+            (ModelBackend::Heuristic, Some(error)) => eprintln!("⚠️  {} model: heuristic, {}", status.name, error),
+        }
+    }
+
     // Load the matrix
-    let matrix_path = "./data/omniarb_full_matrix_encoder_decoder_a_j_build_sheet.md";
-    let token_matrix = match load_token_matrix(matrix_path) {
-        Ok(matrix) => matrix,
+    let options = ParseOptions {
+        strict: args.strict,
+        dedupe: args.dedupe,
+        snap_fee_tiers: args.snap_fee_tiers,
+        ..ParseOptions::default()
+    };
+    let token_matrix = match load_token_matrix_auto_with_options(&args.matrix_path, options) {
+        Ok(load) => {
+            for diagnostic in &load.diagnostics {
+                eprintln!("⚠️  Skipped row: {}", diagnostic);
+            }
+            for warning in &load.warnings {
+                eprintln!("⚠️  {}", warning);
+            }
+            if load.merged > 0 {
+                println!("🔁 Merged {} duplicate rows", load.merged);
+            }
+            TokenMatrix::new(load.entries)
+        }
+        Err(MatrixError::Invalid(diagnostics)) => {
+            for diagnostic in &diagnostics {
+                eprintln!("❌ {}", diagnostic);
+            }
+            eprintln!(
+                "❌ Matrix load failed: {} invalid value(s) in {} row(s)",
+                diagnostics.len(),
+                invalid_row_count(&diagnostics)
+            );
+            std::process::exit(1);
+        }
         Err(e) => {
             eprintln!("❌ Matrix load failed: {}", e);
             std::process::exit(1);
@@ -20,61 +667,335 @@ fn main() {
     };
     println!("✅ Token matrix loaded: {} entries", token_matrix.len());
 
-    // Fetch bridge/live data
-    let live_quotes = fetch_live_quotes(&token_matrix);
-    println!("🌐 Bridge quotes fetched: {}", live_quotes.len());
+    let config = match Config::from_env() {
+        Ok(config) => config,
+        Err(e) => {
+            eprintln!("❌ Failed to load config: {}", e);
+            std::process::exit(1);
+        }
+    };
+    let runtime = match tokio::runtime::Builder::new_current_thread().enable_all().build() {
+        Ok(runtime) => runtime,
+        Err(e) => {
+            eprintln!("❌ Failed to start async runtime: {}", e);
+            std::process::exit(1);
+        }
+    };
 
-    // Calculate TAR Score for each path
-    let scored_routes: Vec<_> = token_matrix.iter().zip(live_quotes.iter())
-        .map(|(entry, quote)| {
-            let score = calculate_tar_score(entry, quote);
-            let model_pred_tar = run_tar_onnx(entry, quote);
-            let model_pred_flank = run_flanker(entry, quote);
+    let token_matrix = if args.refresh_liquidity {
+        let mut entries = token_matrix.into_entries();
+        let previous: Vec<f64> = entries.iter().map(|entry| entry.liquidity_score).collect();
+        let refreshed = runtime.block_on(refresh_liquidity_scores(&mut entries, &config));
+        let changed = entries
+            .iter()
+            .zip(&previous)
+            .filter(|(entry, previous)| entry.liquidity_score.to_bits() != previous.to_bits())
+            .count();
+        println!("💧 Refreshed liquidity scores: {}/{}, {} changed", refreshed, entries.len(), changed);
+        // Nothing to write back: leave the file (and its formatting) alone
+        if changed > 0 {
+            if let Err(e) = save_token_matrix(&args.matrix_path, &entries, MatrixFormat::from_path(&args.matrix_path)) {
+                eprintln!("❌ Failed to rewrite matrix: {}", e);
+                std::process::exit(1);
+            }
+        }
+        TokenMatrix::new(entries)
+    } else {
+        token_matrix
+    };
 
-            (entry.clone(), score, model_pred_tar, model_pred_flank)
-        })
-        .collect();
+    // Risk policy first: denied bridges are never scored
+    let bridge_policy = args.bridge_policy(&config.bridge_policy);
+    let before = token_matrix.len();
+    let token_matrix = token_matrix.filter_bridges(&bridge_policy);
+    if token_matrix.len() < before {
+        println!("🚫 Excluded {} routes by bridge policy", before - token_matrix.len());
+    }
+    let token_matrix = args.apply_filters(token_matrix);
+    if token_matrix.is_empty() {
+        println!("⚠️  No routes match the given filters");
+    }
 
-    // Filter top opportunities by TAR score >= 85.0
-    let mut top_opportunities: Vec<_> = scored_routes.into_iter()
-        .filter(|(_, score, _, _)| *score >= 85.0)
-        .collect();
+    let token_matrix = token_matrix.into_entries();
+
+    let tar_weights = args.tar_weights.clone().unwrap_or_else(|| config.tar_weights.clone());
+
+    // Resume from the checkpoint: routes scored by an earlier run with the
+    // same settings and quotes within --max-quote-age are not re-quoted
+    let resume_policy = ResumePolicy {
+        scoring_key: ScoringCheckpoint::scoring_key(&format!(
+            "{:?}|{:?}|{}",
+            tar_weights, args.ensemble, args.trade_size_usd
+        )),
+        max_quote_age: args.max_quote_age,
+    };
+    let mut checkpoint = match &args.checkpoint {
+        Some(path) => match ScoringCheckpoint::open(path, args.force, &resume_policy) {
+            Ok(checkpoint) => {
+                if checkpoint.discarded_len() > 0 {
+                    println!(
+                        "🗑️  Discarded {} checkpointed routes scored with other settings or stale quotes",
+                        checkpoint.discarded_len()
+                    );
+                }
+                Some(checkpoint)
+            }
+            Err(e) => {
+                eprintln!("❌ Failed to open checkpoint: {}", e);
+                std::process::exit(1);
+            }
+        },
+        None => None,
+    };
+    let mut scored_routes = Vec::new();
+    let mut pending = Vec::new();
+    for entry in &token_matrix {
+        match checkpoint.as_ref().and_then(|checkpoint| checkpoint.get(entry)) {
+            Some(route) => scored_routes.push(route.clone()),
+            None => pending.push(entry.clone()),
+        }
+    }
+    if !scored_routes.is_empty() {
+        println!("♻️  Resumed {} scored routes from checkpoint", scored_routes.len());
+    }
+
+    // Ranking key: TAR score, or the model ensemble when requested
+    let score_label = if args.ensemble.is_some() { "Ensemble" } else { "TAR Score" };
+
+    // Fetch bridge/live data and score each path, skipping stale quotes,
+    // with as many quotes in flight as the runtime profile allows.
+    // With a checkpoint, work in batches flushed to disk as they complete.
+    let router = QuoteRouter::from_config(&config);
+    let (tar_onnx, flanker_onnx) = (models.get("tar"), models.get("flanker"));
+    let (tar_heuristic, flanker_heuristic) = (
+        HeuristicModel::tar().with_max_batch(args.max_batch),
+        HeuristicModel::flanker().with_max_batch(args.max_batch),
+    );
+    let tar_model: &dyn BatchModel = tar_onnx.as_deref().map_or(&tar_heuristic, |model| model);
+    let flanker_model: &dyn BatchModel = flanker_onnx.as_deref().map_or(&flanker_heuristic, |model| model);
+    // Features and scores for offline training (FEATURE_LOG_PATH)
+    let feature_logger = match FeatureLogger::from_config(&config.feature_log) {
+        Ok(logger) => logger,
+        Err(e) => {
+            eprintln!("❌ Failed to open feature log: {}", e);
+            std::process::exit(1);
+        }
+    };
+    let batch_size = if checkpoint.is_some() { CHECKPOINT_BATCH } else { pending.len().max(1) };
+    let mut fetched = 0;
+    let mut stale = 0;
+    let mut fell_back = 0;
+    for batch in pending.chunks(batch_size) {
+        let live_quotes = runtime.block_on(fetch_live_quotes_bounded(
+            batch,
+            &router,
+            args.trade_size_usd,
+            config.runtime.max_concurrency,
+        ));
+        fetched += live_quotes.len();
+        let (entries, quotes): (Vec<_>, Vec<_>) = batch
+            .iter()
+            .cloned()
+            .zip(live_quotes)
+            .filter(|(_, quote)| {
+                let is_stale = args.max_quote_age.is_some_and(|max_age| quote.is_stale(max_age));
+                stale += usize::from(is_stale);
+                !is_stale
+            })
+            .unzip();
+        // One inference call per model for the whole batch
+        let tar_preds = tar_model.predict_batch(&entries, &quotes);
+        let flank_preds = flanker_model.predict_batch(&entries, &quotes);
+        fell_back += tar_preds.iter().chain(&flank_preds).filter(|p| p.fell_back).count();
+        let tar_preds = tar_preds.into_iter().map(|p| p.score);
+        let flank_preds = flank_preds.into_iter().map(|p| p.score);
+        for (((entry, quote), model_pred_tar), model_pred_flank) in entries.into_iter().zip(quotes).zip(tar_preds).zip(flank_preds) {
+            // Net of gas and capped by liquidity at the trade size the APR column assumes
+            let tar_score = calculate_tar_score_sized(&entry, &quote, &tar_weights, Some(args.trade_size_usd));
+            let weights = args.ensemble.unwrap_or_default();
+            let ensemble = ensemble_score(tar_score, Some(model_pred_tar), Some(model_pred_flank), &weights);
+            if let Some(logger) = &feature_logger {
+                let onnx_score = tar_onnx.is_some().then_some(model_pred_tar);
+                logger.log(FeatureRecord::new(&entry, &quote, tar_score, onnx_score, Some(model_pred_flank)));
+            }
+            let route = ScoredRoute {
+                model_pred_tar,
+                model_pred_flank,
+                entry,
+                quote,
+                score: if args.ensemble.is_some() { ensemble.score } else { tar_score },
+                percentile: 0.0,
+                z_score: 0.0,
+                disagreement: ensemble.disagreement,
+            };
+            if let Some(checkpoint) = checkpoint.as_mut() {
+                if let Err(e) = checkpoint.record(&route) {
+                    eprintln!("❌ Failed to write checkpoint: {}", e);
+                    std::process::exit(1);
+                }
+            }
+            scored_routes.push(route);
+        }
+        if let Some(Err(e)) = checkpoint.as_mut().map(ScoringCheckpoint::flush) {
+            eprintln!("❌ Failed to write checkpoint: {}", e);
+            std::process::exit(1);
+        }
+    }
+    println!("🌐 Bridge quotes fetched: {}", fetched);
 
-    top_opportunities.sort_by(|a, b| {
-        // Use total_cmp for safe NaN handling
-        b.1.total_cmp(&a.1)
+    // Blend this run's scores into the smoothed per-route history
+    let score_history = args.score_history.as_ref().map(|path| {
+        let mut history = match ScoreHistory::load(path, DEFAULT_SCORE_HALF_LIFE) {
+            Ok(history) => history,
+            Err(e) => {
+                eprintln!("❌ Failed to load score history: {}", e);
+                std::process::exit(1);
+            }
+        };
+        for route in &scored_routes {
+            history.update(&RouteId::from(&route.entry), route.score, route.quote.fetched_at);
+        }
+        if let Err(e) = history.save(path) {
+            eprintln!("❌ Failed to save score history: {}", e);
+            std::process::exit(1);
+        }
+        history
     });
 
-    println!("\n🔥 Top Arbitrage Routes (TAR Score >= 85):");
-    println!("{:-<120}", "");
-    println!("{:<15} {:<15} {:<10} {:<15} {:<15} {:<10} {:<10} {:<10}", 
-        "Origin Chain", "Dest Chain", "Token", "Bridge", "TAR Score", "ONNX", "Flanker", "Liquidity");
-    println!("{:-<120}", "");
-    
-    for (entry, score, tar_ml, flank_ml) in top_opportunities.iter().take(10) {
-        println!("{:<15} {:<15} {:<10} {:<15} {:<10.2} {:<10.2} {:<10.2} {:<10.0}",
-            format!("Chain-{}", entry.chain_origin),
-            format!("Chain-{}", entry.chain_dest),
-            entry.native_token,
-            entry.bridge_protocol,
-            score,
-            tar_ml,
-            flank_ml,
-            entry.liquidity_score
+    if stale > 0 {
+        println!("⏱️  Skipped {} routes with stale quotes", stale);
+    }
+    if let Some(logger) = feature_logger {
+        let dropped = logger.dropped();
+        // Waits for queued records to be written
+        drop(logger);
+        if dropped > 0 {
+            println!("⚠️  Feature log fell behind and dropped {} records", dropped);
+        }
+    }
+    if fell_back > 0 {
+        println!("⚠️  {} model predictions were NaN and fell back to the heuristic", fell_back);
+    }
+
+    // Hard spread floor: a high score can't rescue a route that barely moves
+    let before = scored_routes.len();
+    let mut scored_routes: Vec<_> = scored_routes
+        .into_iter()
+        .filter(|route| meets_min_spread(route.quote.spread_percentage, args.min_spread_pct))
+        .collect();
+    if before > scored_routes.len() {
+        println!(
+            "📉 Dropped {} routes below {}% spread",
+            before - scored_routes.len(),
+            args.min_spread_pct
+        );
+    }
+
+    // Select top opportunities (default: the configured enter threshold)
+    rank_routes(&mut scored_routes);
+    let selection = args
+        .select
+        .unwrap_or(SelectionPolicy::AbsoluteScore(config.opportunity.enter_threshold));
+    let mut top_opportunities = match args.max_per_key {
+        Some(max_per_key) => select_top_diversified(scored_routes, selection, max_per_key),
+        None => select_top(scored_routes, selection),
+    };
+
+    // Drop routes whose profit doesn't justify the gas risk
+    if let Some(ratio) = args.min_profit_gas_ratio {
+        let before = top_opportunities.len();
+        top_opportunities.retain(|route| {
+            let net_profit = route.quote.estimated_net_profit_usd(args.trade_size_usd);
+            meets_profit_gas_ratio(net_profit, route.quote.gas_cost_usd, ratio)
+        });
+        println!(
+            "⛽ Dropped {} routes below {}x profit-to-gas",
+            before - top_opportunities.len(),
+            ratio
+        );
+    }
+
+    // Drop routes the models can't agree on
+    if args.exclude_disagreeing {
+        let before = top_opportunities.len();
+        top_opportunities.retain(|route| route.disagreement <= args.max_disagreement);
+        println!(
+            "⚖️  Dropped {} routes whose models disagree by more than {}",
+            before - top_opportunities.len(),
+            args.max_disagreement
         );
     }
-    
+
+    println!("\n🔥 Top Arbitrage Routes ({} {}):", score_label, selection);
+    let rows: Vec<Vec<String>> = top_opportunities.iter().take(10)
+        .map(|ScoredRoute { entry, quote, score, model_pred_tar, model_pred_flank, disagreement, .. }| {
+            let mut row = vec![
+                format!("Chain-{}", entry.chain_origin),
+                format!("Chain-{}", entry.chain_dest),
+                entry.native_token.clone(),
+                entry.bridge_protocol.clone(),
+                format!("{:.*}", precision, score),
+            ];
+            if let Some(history) = &score_history {
+                let route = RouteId::from(entry);
+                row.extend([
+                    history.smoothed(&route).map_or_else(String::new, |s| format!("{:.*}", precision, s)),
+                    history.trend(&route).map_or_else(|| "-".to_string(), |trend| trend.to_string()),
+                ]);
+            }
+            if args.verbose {
+                let breakdown = calculate_tar_breakdown_sized(entry, quote, &tar_weights, Some(args.trade_size_usd));
+                row.extend([breakdown.token_quality, breakdown.arbitrage_efficiency, breakdown.risk]
+                    .iter()
+                    .map(|points| format!("{:.*}", precision, points)));
+            }
+            let flag = if *disagreement > args.max_disagreement { " ⚠" } else { "" };
+            let apr = route_cycle_secs(&config.intent_based_bridges, &entry.bridge_protocol).map(|cycle_secs| {
+                estimate_net_apr(quote.estimated_net_profit_usd(args.trade_size_usd), args.trade_size_usd, cycle_secs)
+            });
+            row.extend([
+                format!("{:.*}", precision, model_pred_tar),
+                format!("{:.*}", precision, model_pred_flank),
+                format!("{:.*}{}", precision, disagreement, flag),
+                format_thousands(quote.available_liquidity, precision),
+                apr.map_or_else(|| "-".to_string(), |apr| format_thousands(apr, precision)),
+            ]);
+            row
+        })
+        .collect();
+    let mut headers = vec!["Origin Chain", "Dest Chain", "Token", "Bridge", score_label];
+    if score_history.is_some() {
+        headers.extend(["Smoothed", "Trend"]);
+    }
+    if args.verbose {
+        headers.extend(["T", "A", "R"]);
+    }
+    headers.extend(["ONNX", "Flanker", "Disagree", "Liquidity (USD)", "Net APR %"]);
+    print_table(&headers, &rows);
+
+    if let Some(export_path) = &args.export_filtered {
+        let entries: Vec<_> = top_opportunities.iter().map(|route| route.entry.clone()).collect();
+        match save_token_matrix(export_path, &entries, MatrixFormat::from_path(export_path)) {
+            Ok(()) => println!("\n💾 Exported {} filtered routes to {}", entries.len(), export_path),
+            Err(e) => {
+                eprintln!("❌ Export failed: {}", e);
+                std::process::exit(1);
+            }
+        }
+    }
+
     println!("\n📊 Summary Statistics:");
     println!("   Total routes analyzed: {}", token_matrix.len());
-    println!("   High-quality routes (TAR >= 85): {}", 
-        top_opportunities.len());
-    println!("   Average TAR score (top routes): {:.2}", 
+    println!("   High-quality routes ({} {}): {}",
+        score_label, selection, top_opportunities.len());
+    println!("   Average {} (top routes): {:.*}",
+        score_label, precision,
         if !top_opportunities.is_empty() {
-            top_opportunities.iter().map(|(_, s, _, _)| s).sum::<f64>() / top_opportunities.len() as f64
+            top_opportunities.iter().map(|route| route.score).sum::<f64>() / top_opportunities.len() as f64
         } else {
             0.0
         });
-    
+
     println!("\n✨ OmniArb Dual Turbo Rust Engine Complete!");
 }
 
@@ -82,3 +1003,146 @@ fn main() {
 // Example: TokenEntry, QuoteInfo, and the `calculate_tar_score` logic using T/A/R weights
 // Uses Serde for CSV/JSON parsing
 // ONNX Runtime integration available via model_bridge module
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_thousands() {
+        assert_eq!(format_thousands(1_000_000.0, 0), "1,000,000");
+        assert_eq!(format_thousands(950_000.0, 2), "950,000.00");
+        assert_eq!(format_thousands(999.5, 1), "999.5");
+        assert_eq!(format_thousands(-1234.0, 0), "-1,234");
+        assert_eq!(format_thousands(0.004, 2), "0.00");
+    }
+
+    #[test]
+    fn test_route_cycle_secs_needs_a_configured_bridge() {
+        let bridges = Config::default().intent_based_bridges;
+        let across = bridges["across"].typical_time_seconds as f64;
+        assert_eq!(route_cycle_secs(&bridges, "ACROSS"), Some(EXECUTION_TIME_SECS + across));
+        assert_eq!(route_cycle_secs(&bridges, "WORMHOLE"), None);
+    }
+
+    fn validate_str(name: &str, content: &str) -> (bool, String) {
+        let path = std::env::temp_dir()
+            .join(format!("titan_validate_{}_{}", std::process::id(), name))
+            .to_string_lossy()
+            .into_owned();
+        std::fs::write(&path, content).unwrap();
+        let mut out = Vec::new();
+        let clean = validate_matrix(&path, &mut out).unwrap();
+        std::fs::remove_file(&path).ok();
+        (clean, String::from_utf8(out).unwrap())
+    }
+
+    const HEADER: &str =
+        "chain_origin,chain_dest,native_token,dex_origin,dex_dest,bridge_protocol,liquidity_score,fee_tier\n";
+
+    #[test]
+    fn test_validate_clean_matrix() {
+        let (clean, out) = validate_str(
+            "clean.csv",
+            &format!("{}1,137,USDC,UNISWAP_V3,QUICKSWAP,LIFI,95,0.3\n", HEADER),
+        );
+        assert!(clean);
+        assert!(out.starts_with("OK "), "{}", out);
+        assert!(out.contains("rows=1 invalid_rows=0 errors=0 duplicates=0"), "{}", out);
+    }
+
+    #[test]
+    fn test_validate_reports_problems() {
+        let (clean, out) = validate_str(
+            "broken.csv",
+            &format!(
+                "{}1,137,USDC,UNISWAP_V3,QUICKSWAP,LIFI,95,0.3\n\
+                 1,137,USDC,UNISWAP_V3,QUICKSWAP,LIFI,97,0.3\n\
+                 1,137,USDC,UNISWAP_V3,QUICKSWAP,LIFI,high,0.3\n\
+                 1,137,USDT,UNISWAP_V3,QUICKSWAP,LIFI,150,-1\n",
+                HEADER
+            ),
+        );
+        assert!(!clean);
+        let lines: Vec<_> = out.lines().collect();
+        assert!(lines[0].starts_with("ERROR line=4 column=liquidity_score value=\"high\""), "{}", out);
+        assert!(lines[1].starts_with("ERROR line=5 "), "{}", out);
+        assert!(lines[2].starts_with("ERROR line=5 "), "{}", out);
+        assert_eq!(lines[3], "DUPLICATE route=\"1>137 USDC UNISWAP_V3>QUICKSWAP LIFI\" count=2");
+        // The row with two bad values counts once
+        assert!(
+            lines[4].starts_with("FAIL ") && lines[4].ends_with("rows=4 invalid_rows=2 errors=3 duplicates=1"),
+            "{}",
+            out
+        );
+    }
+
+    #[test]
+    fn test_diff_fails_over_removal_threshold() {
+        let write = |name: &str, rows: &str| {
+            let path = std::env::temp_dir()
+                .join(format!("titan_diff_{}_{}", std::process::id(), name))
+                .to_string_lossy()
+                .into_owned();
+            std::fs::write(&path, format!("{}{}", HEADER, rows)).unwrap();
+            path
+        };
+        let old = write(
+            "old.csv",
+            "1,137,USDC,UNISWAP_V3,QUICKSWAP,LIFI,95,0.3\n1,10,WETH,UNISWAP_V3,VELODROME,ACROSS,97,0.05\n",
+        );
+        let new = write("new.csv", "1,137,USDC,UNISWAP_V3,QUICKSWAP,LIFI,90,0.3\n");
+
+        let run = |extra: &[&str]| {
+            let argv: Vec<String> = [old.as_str(), new.as_str()]
+                .iter()
+                .chain(extra)
+                .map(|s| s.to_string())
+                .collect();
+            let mut out = Vec::new();
+            let ok = diff_matrix_files(&DiffArgs::parse(argv).unwrap(), &mut out).unwrap();
+            (ok, String::from_utf8(out).unwrap())
+        };
+
+        let (ok, out) = run(&[]);
+        assert!(!ok);
+        assert!(out.contains("0 added, 1 removed, 1 changed"), "{}", out);
+        assert!(out.ends_with("FAIL 1 routes removed (max 0)\n"), "{}", out);
+        assert!(run(&["--max-removed", "1"]).0);
+
+        std::fs::remove_file(&old).ok();
+        std::fs::remove_file(&new).ok();
+    }
+
+    #[test]
+    fn test_audit_fails_on_fingerprint_mismatch() {
+        let matrix = concat!(env!("CARGO_MANIFEST_DIR"), "/../data/fixtures/omniarb_scoring_audit.md");
+        let run = |extra: &[&str]| {
+            let argv: Vec<String> = ["--matrix", matrix].iter().chain(extra).map(|s| s.to_string()).collect();
+            let mut out = Vec::new();
+            let ok = audit_matrix_file(&AuditArgs::parse(argv).unwrap(), &mut out).unwrap();
+            (ok, String::from_utf8(out).unwrap())
+        };
+
+        let (ok, out) = run(&[]);
+        assert!(ok);
+        let fingerprint = out.split_whitespace().nth(1).unwrap().to_string();
+        assert!(run(&["--expect", &fingerprint]).0);
+
+        let (ok, out) = run(&["--expect", "0000000000000000"]);
+        assert!(!ok);
+        assert!(out.ends_with(&format!("FAIL expected 0000000000000000 got {}\n", fingerprint)), "{}", out);
+
+        // Reweighting is a scoring change and moves the fingerprint
+        assert!(!run(&["--expect", &fingerprint, "--tar-weights", "20,20,60"]).0);
+        assert!(AuditArgs::parse(["--expect".to_string(), fingerprint]).is_err());
+    }
+
+    #[test]
+    fn test_diff_args_require_two_paths() {
+        assert!(DiffArgs::parse(["old.md".to_string()]).is_err());
+        let args = DiffArgs::parse(["a.md", "b.md", "--json"].map(String::from)).unwrap();
+        assert!(args.json);
+        assert_eq!(args.max_removed, DEFAULT_MAX_REMOVED);
+    }
+}
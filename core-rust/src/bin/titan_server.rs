@@ -1,6 +1,42 @@
-use titan_core::{Config, start_server};
+use titan_core::{Config, TokenRegistry, start_server};
+use titan_core::selfcheck::{run_selfcheck, CheckTimeouts, SelfCheckReport};
 use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
 use std::collections::HashMap;
+use std::time::Duration;
+
+/// Parse `--selfcheck` options; `None` (server mode) when the flag is absent
+fn selfcheck_args(argv: impl IntoIterator<Item = String>) -> Result<Option<CheckTimeouts>, String> {
+    let argv: Vec<String> = argv.into_iter().collect();
+    if !argv.iter().any(|arg| arg == "--selfcheck") {
+        return Ok(None);
+    }
+    let mut timeouts = CheckTimeouts::default();
+
+    let mut iter = argv.into_iter();
+    while let Some(arg) = iter.next() {
+        let (flag, inline_value) = match arg.split_once('=') {
+            Some((flag, value)) => (flag.to_string(), Some(value.to_string())),
+            None => (arg.clone(), None),
+        };
+        let slot = match flag.as_str() {
+            "--selfcheck" => continue,
+            "--rpc-timeout" => &mut timeouts.rpc,
+            "--token-timeout" => &mut timeouts.token,
+            "--quoter-timeout" => &mut timeouts.quoter,
+            _ => return Err(format!("Unknown argument: {}", arg)),
+        };
+        let value = inline_value
+            .or_else(|| iter.next())
+            .ok_or_else(|| format!("{} requires a value in seconds", flag))?;
+        *slot = value
+            .parse::<f64>()
+            .ok()
+            .filter(|secs| secs.is_finite() && *secs > 0.0)
+            .map(Duration::from_secs_f64)
+            .ok_or_else(|| format!("Invalid {} value: {}", flag, value))?;
+    }
+    Ok(Some(timeouts))
+}
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
@@ -24,10 +60,37 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                 dex_routers: HashMap::new(),
                 intent_based_bridges: HashMap::new(),
                 lifi_supported_chains: vec![1, 137, 42161],
+                token_registry: TokenRegistry::with_defaults(),
+                offline: titan_core::config::offline_from_env(),
+                quote_apis: Default::default(),
+                gas_policy: titan_core::config::GasPolicy::default(),
+                tar_weights: Default::default(),
+                opportunity: titan_core::config::OpportunityThresholds::from_env(),
+                feature_log: titan_core::omniarb::FeatureLogConfig::from_env(),
+                bridge_policy: titan_core::omniarb::BridgePolicy::from_env(),
+                matrix_path: titan_core::config::matrix_path_from_env(),
+                runtime: titan_core::config::RuntimeProfile::from_env(),
             }
         }
     };
 
+    let selfcheck = match selfcheck_args(std::env::args().skip(1)) {
+        Ok(selfcheck) => selfcheck,
+        Err(e) => {
+            eprintln!("{}", e);
+            eprintln!(
+                "Usage: titan_server [--selfcheck [--rpc-timeout SECS] [--token-timeout SECS] [--quoter-timeout SECS]]"
+            );
+            std::process::exit(2);
+        }
+    };
+    if let Some(timeouts) = selfcheck {
+        let checks = run_selfcheck(&config, timeouts).await;
+        println!("{}", SelfCheckReport(&checks));
+        let ok = !checks.is_empty() && checks.iter().all(|check| check.passed());
+        std::process::exit(if ok { 0 } else { 1 });
+    }
+
     // Get port from environment or use default
     let port = std::env::var("RUST_SERVER_PORT")
         .ok()
@@ -41,3 +104,29 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(argv: &[&str]) -> Result<Option<CheckTimeouts>, String> {
+        selfcheck_args(argv.iter().map(|s| s.to_string()))
+    }
+
+    #[test]
+    fn test_selfcheck_args() {
+        assert_eq!(args(&[]), Ok(None));
+        assert_eq!(args(&["--selfcheck"]), Ok(Some(CheckTimeouts::default())));
+
+        let timeouts = args(&["--selfcheck", "--rpc-timeout", "2", "--quoter-timeout=0.5"])
+            .unwrap()
+            .unwrap();
+        assert_eq!(timeouts.rpc, Duration::from_secs(2));
+        assert_eq!(timeouts.quoter, Duration::from_millis(500));
+        assert_eq!(timeouts.token, CheckTimeouts::default().token);
+
+        assert!(args(&["--selfcheck", "--rpc-timeout", "-1"]).is_err());
+        assert!(args(&["--selfcheck", "--bogus"]).is_err());
+        assert_eq!(args(&["--bogus"]), Ok(None));
+    }
+}
@@ -0,0 +1,197 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use ethers::abi::{AbiDecode, AbiEncode};
+use ethers::prelude::*;
+use ethers::types::transaction::eip2718::TypedTransaction;
+
+use crate::simulation_engine::{fetch_gas_fees, BalanceOfCall, GasFees};
+
+/// Read-only chain access used by the simulation and sizing code
+///
+/// Implemented for every ethers `Provider`, and by [`MockChainReader`] so
+/// the sizing flow can be tested without an RPC endpoint.
+#[async_trait]
+pub trait ChainReader: Send + Sync {
+    /// ERC-20 `balanceOf(owner)` on `token`
+    async fn balance_of(&self, token: Address, owner: Address) -> Result<U256>;
+
+    /// Native balance of `owner`
+    async fn native_balance(&self, owner: Address) -> Result<U256>;
+
+    async fn block_number(&self) -> Result<u64>;
+
+    /// `eth_call` `data` on `to` at the latest block
+    async fn call(&self, to: Address, data: Bytes) -> Result<Bytes>;
+
+    /// `eth_call` `data` on `to` against the state at `block`
+    async fn call_at(&self, to: Address, data: Bytes, block: u64) -> Result<Bytes>;
+
+    /// Latest base fee and tip, see [`fetch_gas_fees`]
+    async fn gas_fees(&self) -> Result<GasFees>;
+}
+
+#[async_trait]
+impl<P: JsonRpcClient + 'static> ChainReader for Provider<P> {
+    async fn balance_of(&self, token: Address, owner: Address) -> Result<U256> {
+        let raw = ChainReader::call(self, token, BalanceOfCall { owner }.encode().into()).await?;
+        Ok(U256::decode(raw)?)
+    }
+
+    async fn native_balance(&self, owner: Address) -> Result<U256> {
+        Ok(self.get_balance(owner, None).await?)
+    }
+
+    async fn block_number(&self) -> Result<u64> {
+        Ok(self.get_block_number().await?.as_u64())
+    }
+
+    async fn call(&self, to: Address, data: Bytes) -> Result<Bytes> {
+        let tx: TypedTransaction = TransactionRequest::new().to(to).data(data).into();
+        Ok(Middleware::call(self, &tx, None).await?)
+    }
+
+    async fn call_at(&self, to: Address, data: Bytes, block: u64) -> Result<Bytes> {
+        let tx: TypedTransaction = TransactionRequest::new().to(to).data(data).into();
+        Ok(Middleware::call(self, &tx, Some(block.into())).await?)
+    }
+
+    async fn gas_fees(&self) -> Result<GasFees> {
+        fetch_gas_fees(self).await
+    }
+}
+
+/// Handler answering `call` requests in a [`MockChainReader`]
+pub type MockCallHandler = Arc<dyn Fn(Address, &[u8]) -> Result<Bytes> + Send + Sync>;
+
+/// In-memory [`ChainReader`] with canned balances and call results
+///
+/// Unset balances read as zero; calls without a handler fail.
+#[derive(Clone, Default)]
+pub struct MockChainReader {
+    balances: HashMap<(Address, Address), U256>,
+    native_balances: HashMap<Address, U256>,
+    block_number: u64,
+    fees: GasFees,
+    call_handler: Option<MockCallHandler>,
+}
+
+impl MockChainReader {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// `token.balanceOf(owner)` result
+    pub fn with_balance(mut self, token: Address, owner: Address, balance: U256) -> Self {
+        self.balances.insert((token, owner), balance);
+        self
+    }
+
+    pub fn with_native_balance(mut self, owner: Address, balance: U256) -> Self {
+        self.native_balances.insert(owner, balance);
+        self
+    }
+
+    pub fn with_block_number(mut self, block_number: u64) -> Self {
+        self.block_number = block_number;
+        self
+    }
+
+    /// Fees returned by `gas_fees`
+    pub fn with_fees(mut self, fees: GasFees) -> Self {
+        self.fees = fees;
+        self
+    }
+
+    /// Answer `call` with `handler(to, calldata)`
+    pub fn with_call_handler(
+        mut self,
+        handler: impl Fn(Address, &[u8]) -> Result<Bytes> + Send + Sync + 'static,
+    ) -> Self {
+        self.call_handler = Some(Arc::new(handler));
+        self
+    }
+}
+
+#[async_trait]
+impl ChainReader for MockChainReader {
+    async fn balance_of(&self, token: Address, owner: Address) -> Result<U256> {
+        Ok(self.balances.get(&(token, owner)).copied().unwrap_or_default())
+    }
+
+    async fn native_balance(&self, owner: Address) -> Result<U256> {
+        Ok(self.native_balances.get(&owner).copied().unwrap_or_default())
+    }
+
+    async fn block_number(&self) -> Result<u64> {
+        Ok(self.block_number)
+    }
+
+    async fn call(&self, to: Address, data: Bytes) -> Result<Bytes> {
+        let handler = self
+            .call_handler
+            .as_ref()
+            .ok_or_else(|| anyhow!("No mocked call result for {:?}", to))?;
+        handler(to, &data)
+    }
+
+    /// Mocked calls answer the same at every block
+    async fn call_at(&self, to: Address, data: Bytes, _block: u64) -> Result<Bytes> {
+        ChainReader::call(self, to, data).await
+    }
+
+    async fn gas_fees(&self) -> Result<GasFees> {
+        Ok(self.fees)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_provider_reads_balance_via_call() {
+        let (provider, mock) = Provider::mocked();
+        mock.push::<Bytes, _>(format!("0x{:064x}", 42u64).parse::<Bytes>().unwrap()).unwrap();
+        mock.push(U64::from(7)).unwrap();
+
+        let reader: Arc<dyn ChainReader> = Arc::new(provider);
+        assert_eq!(reader.block_number().await.unwrap(), 7);
+        assert_eq!(reader.balance_of(Address::zero(), Address::zero()).await.unwrap(), U256::from(42));
+        assert!(reader.block_number().await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_provider_pins_call_to_block() {
+        use axum::{routing::post, Json, Router};
+
+        // Answers eth_call with the block tag it was sent
+        let rpc = Router::new().route(
+            "/",
+            post(|Json(request): Json<serde_json::Value>| async move {
+                let tag = request["params"][1].as_str().unwrap_or("").trim_start_matches("0x").to_string();
+                let word = format!("0x{:0>64}", tag);
+                Json(serde_json::json!({ "jsonrpc": "2.0", "id": request["id"], "result": word }))
+            }),
+        );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, rpc).await.unwrap() });
+
+        let reader = Provider::<Http>::try_from(url).unwrap();
+        let raw = reader.call_at(Address::zero(), Bytes::new(), 0x1234).await.unwrap();
+        assert_eq!(U256::from_big_endian(&raw), U256::from(0x1234));
+    }
+
+    #[tokio::test]
+    async fn test_mock_reader_defaults() {
+        let token = Address::repeat_byte(1);
+        let owner = Address::repeat_byte(2);
+        let reader = MockChainReader::new().with_balance(token, owner, U256::from(5));
+        assert_eq!(reader.balance_of(token, owner).await.unwrap(), U256::from(5));
+        assert_eq!(reader.balance_of(owner, token).await.unwrap(), U256::zero());
+        assert!(reader.call(token, Bytes::new()).await.is_err());
+    }
+}
@@ -0,0 +1,64 @@
+use ethers::abi::{AbiDecode, AbiEncode};
+use ethers::prelude::*;
+use anyhow::{anyhow, Result};
+use std::sync::Arc;
+
+use crate::chain_reader::ChainReader;
+
+abigen!(
+    AggregatorV3,
+    r#"[
+        function decimals() external view returns (uint8)
+        function latestRoundData() external view returns (uint80 roundId, int256 answer, uint256 startedAt, uint256 updatedAt, uint80 answeredInRound)
+    ]"#,
+);
+
+/// Read the latest answer of a Chainlink price feed, scaled to a float
+///
+/// Fails on non-positive answers, which feeds report when they're broken.
+pub async fn read_chainlink_price<P: JsonRpcClient + 'static>(
+    feed: Address,
+    provider: Arc<Provider<P>>,
+) -> Result<f64> {
+    read_chainlink_price_from(provider.as_ref(), feed).await
+}
+
+/// [`read_chainlink_price`] over any [`ChainReader`]
+pub async fn read_chainlink_price_from(reader: &dyn ChainReader, feed: Address) -> Result<f64> {
+    let decimals = DecimalsReturn::decode(reader.call(feed, DecimalsCall.encode().into()).await?)?.0;
+    let answer = LatestRoundDataReturn::decode(reader.call(feed, LatestRoundDataCall.encode().into()).await?)?.answer;
+
+    if answer <= I256::zero() {
+        return Err(anyhow!("Chainlink feed {:?} returned non-positive answer {}", feed, answer));
+    }
+    let answer: f64 = answer.to_string().parse()?;
+    Ok(answer / 10f64.powi(decimals as i32))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::abi_call::uint_words;
+
+    #[tokio::test]
+    async fn test_read_chainlink_price() {
+        let (provider, mock) = Provider::mocked();
+        // Mocked responses are served last-in, first-out
+        let round = uint_words(&[1, 200_012_345_678, 0, 0, 1]);
+        mock.push::<Bytes, _>(round).unwrap();
+        mock.push::<Bytes, _>(uint_words(&[8])).unwrap();
+
+        let price = read_chainlink_price(Address::zero(), Arc::new(provider)).await.unwrap();
+        assert!((price - 2000.12345678).abs() < 1e-9);
+    }
+
+    #[tokio::test]
+    async fn test_non_positive_answer_rejected() {
+        let (provider, mock) = Provider::mocked();
+        let round = uint_words(&[1, 0, 0, 0, 1]);
+        mock.push::<Bytes, _>(round).unwrap();
+        mock.push::<Bytes, _>(uint_words(&[8])).unwrap();
+
+        assert!(read_chainlink_price(Address::zero(), Arc::new(provider)).await.is_err());
+    }
+}
@@ -1,20 +1,158 @@
 use ethers::prelude::*;
+use std::collections::HashSet;
 use std::sync::Arc;
 use anyhow::Result;
 use log::{info, warn, debug};
 
+use crate::chain_reader::ChainReader;
+use crate::chainlink::read_chainlink_price_from;
 use crate::config::BALANCER_V3_VAULT;
-use crate::simulation_engine::get_provider_tvl;
+use crate::simulation_engine::{get_provider_tvl, is_likely_fee_on_transfer, simulated_tvl, TitanSimulationEngine};
+
+/// Endpoint given to offline commanders; never dialed
+const OFFLINE_PLACEHOLDER_RPC: &str = "http://offline.invalid";
+
+/// Resolution of the slippage floor in `min_amount_out`
+const SLIPPAGE_PPM: u64 = 1_000_000;
+
+/// Error unless `tolerance` is a usable share of output to keep, in `(0, 1]`
+pub fn check_slippage_tolerance(tolerance: f64) -> Result<()> {
+    if !(tolerance > 0.0 && tolerance <= 1.0) {
+        anyhow::bail!("Slippage tolerance must be in (0, 1], got {}", tolerance);
+    }
+    Ok(())
+}
+
+/// Divergence of a DEX-implied price from a reference price, in basis points
+///
+/// Saturates at `u32::MAX` when either price is non-finite or the reference
+/// is not positive, so such inputs always fail a deviation check.
+pub fn price_deviation_bps(dex_price: f64, chainlink_price: f64) -> u32 {
+    if !dex_price.is_finite() || !chainlink_price.is_finite() || chainlink_price <= 0.0 {
+        return u32::MAX;
+    }
+    let bps = ((dex_price - chainlink_price).abs() / chainlink_price * 10_000.0).round();
+    if bps >= u32::MAX as f64 { u32::MAX } else { bps as u32 }
+}
+
+/// Whether a route's net profit covers `min_ratio` times its gas cost
+///
+/// Unprofitable routes always fail; a ratio of 0 only requires profit > 0.
+pub fn meets_profit_gas_ratio(net_profit_usd: f64, gas_cost_usd: f64, min_ratio: f64) -> bool {
+    if !net_profit_usd.is_finite() || net_profit_usd <= 0.0 {
+        return false;
+    }
+    net_profit_usd >= gas_cost_usd.max(0.0) * min_ratio.max(0.0)
+}
+
+/// Seconds in a 365-day year
+const SECONDS_PER_YEAR: f64 = 365.0 * 24.0 * 3600.0;
+
+/// Simple (non-compounding) APR, in percent, of earning `profit_usd` net of
+/// gas on `capital_usd` once every `cycle_time_secs`
+///
+/// The cycle should cover execution plus the bridge's typical settlement
+/// time, since capital is tied up until it lands. Returns 0 when capital or
+/// cycle time isn't positive, or any input is non-finite.
+pub fn estimate_net_apr(profit_usd: f64, capital_usd: f64, cycle_time_secs: f64) -> f64 {
+    if !profit_usd.is_finite() || !capital_usd.is_finite() || !cycle_time_secs.is_finite() {
+        return 0.0;
+    }
+    if capital_usd <= 0.0 || cycle_time_secs <= 0.0 {
+        return 0.0;
+    }
+    profit_usd / capital_usd * (SECONDS_PER_YEAR / cycle_time_secs) * 100.0
+}
+
+/// Default spread floor (%) below which routes are dropped regardless of score
+pub const DEFAULT_MIN_SPREAD_PCT: f64 = 0.3;
+
+/// Whether a route's spread clears `min_spread_pct`
+///
+/// Non-finite spreads always fail.
+pub fn meets_min_spread(spread_pct: f64, min_spread_pct: f64) -> bool {
+    spread_pct.is_finite() && spread_pct >= min_spread_pct
+}
+
+/// Bisection steps used to refine a pool's active liquidity
+const ACTIVE_LIQUIDITY_PROBES: u32 = 12;
+
+/// Pool to probe for active liquidity via Uniswap V3 quotes
+#[derive(Debug, Clone, Copy)]
+pub struct PoolProbe {
+    pub token_in: Address,
+    pub token_out: Address,
+    pub fee: u32,
+    pub quoter: Address,
+}
+
+/// Largest amount up to `max_amount` whose price impact stays within `max_impact`
+///
+/// `quote` returns the output for an input amount. Impact is measured
+/// against the rate of a tiny reference trade (a millionth of `max_amount`).
+/// The amount is doubled until impact is exceeded, then bisected.
+pub async fn probe_active_liquidity<F, Fut>(quote: F, max_amount: U256, max_impact: f64) -> Result<U256>
+where
+    F: Fn(U256) -> Fut,
+    Fut: std::future::Future<Output = Result<U256>>,
+{
+    let reference_in = (max_amount / 1_000_000).max(U256::one());
+    let reference_out = quote(reference_in).await?;
+    if reference_out.is_zero() || max_amount <= reference_in {
+        return Ok(U256::zero());
+    }
+
+    // out / amount >= (1 - max_impact) * reference_out / reference_in
+    let keep_ppm = U256::from(((1.0 - max_impact.clamp(0.0, 1.0)) * 1_000_000.0) as u64);
+    let within_impact = |amount: U256, out: U256| {
+        out.saturating_mul(reference_in).saturating_mul(U256::from(1_000_000u64))
+            >= reference_out.saturating_mul(amount).saturating_mul(keep_ppm)
+    };
+
+    let (mut low, mut high) = (reference_in, reference_in);
+    loop {
+        high = high.saturating_mul(U256::from(2)).min(max_amount);
+        if !within_impact(high, quote(high).await?) {
+            break;
+        }
+        if high == max_amount {
+            return Ok(max_amount);
+        }
+        low = high;
+    }
+    for _ in 0..ACTIVE_LIQUIDITY_PROBES {
+        let mid = low + (high - low) / 2;
+        if within_impact(mid, quote(mid).await?) {
+            low = mid;
+        } else {
+            high = mid;
+        }
+    }
+    Ok(low)
+}
 
 /// Titan Commander - Loan optimization and risk management
 pub struct TitanCommander {
     chain_id: u64,
     provider: Arc<Provider<Http>>,
+    // Balance and price reads; the provider unless replaced with `with_reader`
+    reader: Arc<dyn ChainReader>,
     
     // Guardrails (Real Money Limits)
     pub min_loan_usd: u64,
     pub max_tvl_share: f64,
     pub slippage_tolerance: f64,
+    pub min_pool_liquidity: U256,
+    pub min_profit_gas_ratio: f64,
+    pub min_spread_pct: f64,
+    pub max_price_deviation_bps: u32,
+    pub max_loan_raw: Option<U256>,
+
+    // Tokens that lose value in transit; loans in these are refused
+    fee_on_transfer_tokens: HashSet<Address>,
+
+    // Size against simulated liquidity without any RPC calls
+    offline: bool,
 }
 
 impl TitanCommander {
@@ -22,13 +160,38 @@ impl TitanCommander {
     pub fn new(chain_id: u64, provider: Arc<Provider<Http>>) -> Self {
         Self {
             chain_id,
+            reader: provider.clone(),
             provider,
             min_loan_usd: 10000,      // Minimum trade size ($10k)
             max_tvl_share: 0.20,      // Max % of pool to borrow (20%)
             slippage_tolerance: 0.995, // 0.5% max slippage
+            min_pool_liquidity: U256::zero(), // Minimum pool depth (raw units, 0 = disabled)
+            min_profit_gas_ratio: 0.0, // Net profit / gas cost floor (0 = any profit)
+            min_spread_pct: DEFAULT_MIN_SPREAD_PCT, // Spread floor (0.3%)
+            max_price_deviation_bps: 100, // Max DEX vs Chainlink divergence (1%)
+            max_loan_raw: None,       // Absolute loan ceiling (raw units, None = TVL cap only)
+            fee_on_transfer_tokens: HashSet::new(),
+            offline: false,
         }
     }
 
+    /// Create a commander that never calls an RPC (see `set_offline`)
+    pub fn new_offline(chain_id: u64) -> Result<Self> {
+        let provider = Arc::new(Provider::<Http>::try_from(OFFLINE_PLACEHOLDER_RPC)?);
+        let mut commander = Self::new(chain_id, provider);
+        commander.set_offline(true);
+        Ok(commander)
+    }
+
+    /// Read balances and prices through `reader` instead of the provider
+    ///
+    /// Fee-on-transfer detection needs state overrides, so it still uses
+    /// the provider.
+    pub fn with_reader(mut self, reader: Arc<dyn ChainReader>) -> Self {
+        self.reader = reader;
+        self
+    }
+
     /// Optimize loan size using binary search based on real on-chain liquidity
     /// Returns: Safe amount or 0 (abort)
     pub async fn optimize_loan_size(
@@ -37,6 +200,17 @@ impl TitanCommander {
         target_amount_raw: U256,
         decimals: u8,
     ) -> Result<U256> {
+        // GUARD: Fee-on-transfer tokens break repayment math
+        if self.is_fee_on_transfer(token_address) {
+            info!("❌ Token {:?} is fee-on-transfer. Aborting.", token_address);
+            return Ok(U256::zero());
+        }
+
+        // OFFLINE: deterministic simulated liquidity
+        if self.offline {
+            return Ok(self.size_against_liquidity(simulated_tvl(), target_amount_raw, decimals));
+        }
+
         // Get lender address (Balancer V3 Vault)
         let lender_address: Address = BALANCER_V3_VAULT.parse()?;
 
@@ -44,7 +218,7 @@ impl TitanCommander {
         let pool_liquidity = match get_provider_tvl(
             token_address,
             lender_address,
-            Arc::clone(&self.provider),
+            Arc::clone(&self.reader),
         ).await {
             Ok(liquidity) => liquidity,
             Err(_) => {
@@ -58,6 +232,81 @@ impl TitanCommander {
             return self.validate_paper_mode_amount(target_amount_raw, decimals);
         }
 
+        Ok(self.size_against_liquidity(pool_liquidity, target_amount_raw, decimals))
+    }
+
+    /// Optimize loan size after checking the DEX price against a Chainlink feed
+    /// Returns: Safe amount or 0 (abort) if the pool price looks manipulated
+    pub async fn optimize_loan_size_with_reference(
+        &self,
+        token_address: Address,
+        target_amount_raw: U256,
+        decimals: u8,
+        dex_price: f64,
+        price_feed: Address,
+    ) -> Result<U256> {
+        // GUARD: DEX spot price must agree with the oracle (no oracle offline)
+        if !self.offline {
+            let reference = read_chainlink_price_from(self.reader.as_ref(), price_feed).await?;
+            if !self.passes_price_sanity(dex_price, reference) {
+                return Ok(U256::zero());
+            }
+        }
+
+        self.optimize_loan_size(token_address, target_amount_raw, decimals).await
+    }
+
+    /// Optimize loan size for a live trade sent from `executor`
+    /// Returns: Safe amount or 0 (abort) if the executor can't pay for gas
+    pub async fn optimize_live_loan_size(
+        &self,
+        token_address: Address,
+        target_amount_raw: U256,
+        decimals: u8,
+        executor: Address,
+        estimated_gas: U256,
+        gas_price: U256,
+    ) -> Result<U256> {
+        // GUARD: Executor must hold enough native token for gas
+        if !self.has_gas_for(executor, estimated_gas, gas_price).await? {
+            return Ok(U256::zero());
+        }
+
+        self.optimize_loan_size(token_address, target_amount_raw, decimals).await
+    }
+
+    /// Pre-flight check that `address` can pay `estimated_gas * gas_price`
+    pub async fn has_gas_for(&self, address: Address, estimated_gas: U256, gas_price: U256) -> Result<bool> {
+        if self.offline {
+            return Ok(true);
+        }
+
+        let engine = TitanSimulationEngine::new(self.chain_id, Arc::clone(&self.reader));
+        let balance = engine.get_native_balance(address).await?;
+        let gas_cost = estimated_gas.saturating_mul(gas_price);
+
+        if balance < gas_cost {
+            warn!(
+                "❌ Insufficient gas funds for {:?}: balance {}, need {}",
+                address, balance, gas_cost
+            );
+            return Ok(false);
+        }
+        Ok(true)
+    }
+
+    /// Apply liquidity guardrails to a requested amount
+    /// Returns: Safe amount or 0 (abort)
+    fn size_against_liquidity(&self, pool_liquidity: U256, target_amount_raw: U256, decimals: u8) -> U256 {
+        // GUARD 0: Dust pool check
+        if pool_liquidity < self.min_pool_liquidity {
+            info!(
+                "❌ Pool too shallow ({} < {}). Aborting.",
+                pool_liquidity, self.min_pool_liquidity
+            );
+            return U256::zero();
+        }
+
         // Calculate caps
         let max_cap = self.calculate_max_cap(pool_liquidity);
         let mut requested_amount = target_amount_raw;
@@ -71,25 +320,76 @@ impl TitanCommander {
             requested_amount = max_cap;
         }
 
-        // GUARD 2: Floor Check
+        // GUARD 2: Absolute Ceiling
+        if let Some(max_loan) = self.max_loan_raw.filter(|&max_loan| requested_amount > max_loan) {
+            warn!(
+                "⚠️ Loan Ceiling: Requested {}, Ceiling {}. Scaling down.",
+                requested_amount, max_loan
+            );
+            requested_amount = max_loan;
+        }
+
+        // GUARD 3: Floor Check
         let min_floor = self.calculate_min_floor(decimals);
         if requested_amount < min_floor {
             info!(
                 "❌ Trade too small for profitability ({} < {}). Aborting.",
                 requested_amount, min_floor
             );
-            return Ok(U256::zero());
+            return U256::zero();
         }
 
         info!(
             "✅ Loan Sizing Optimized: {} (Cap: {})",
             requested_amount, max_cap
         );
-        Ok(requested_amount)
+        requested_amount
+    }
+
+    /// Profit-to-gas guardrail for a route
+    /// Returns: false if net profit is below `min_profit_gas_ratio` x gas cost
+    pub fn passes_profit_gas_ratio(&self, net_profit_usd: f64, gas_cost_usd: f64) -> bool {
+        if !meets_profit_gas_ratio(net_profit_usd, gas_cost_usd, self.min_profit_gas_ratio) {
+            info!(
+                "❌ Profit ${:.2} below {}x gas cost ${:.2}. Dropping route.",
+                net_profit_usd, self.min_profit_gas_ratio, gas_cost_usd
+            );
+            return false;
+        }
+        true
+    }
+
+    /// Spread guardrail for a route, applied regardless of its TAR score
+    /// Returns: false if the spread is below `min_spread_pct`
+    pub fn passes_min_spread(&self, spread_pct: f64) -> bool {
+        if !meets_min_spread(spread_pct, self.min_spread_pct) {
+            info!(
+                "❌ Spread {:.3}% below {}% floor. Dropping route.",
+                spread_pct, self.min_spread_pct
+            );
+            return false;
+        }
+        true
+    }
+
+    /// Spot-price guardrail against a reference (Chainlink) price
+    /// Returns: false if the prices diverge by more than `max_price_deviation_bps`
+    pub fn passes_price_sanity(&self, dex_price: f64, chainlink_price: f64) -> bool {
+        let deviation = price_deviation_bps(dex_price, chainlink_price);
+        if deviation > self.max_price_deviation_bps {
+            warn!(
+                "❌ DEX price {} deviates {} bps from Chainlink {} (max {}). Aborting.",
+                dex_price, deviation, chainlink_price, self.max_price_deviation_bps
+            );
+            return false;
+        }
+        true
     }
 
     /// Validate amount in paper mode
     fn validate_paper_mode_amount(&self, requested_amount: U256, decimals: u8) -> Result<U256> {
+        // The ceiling holds even without a TVL reading
+        let requested_amount = self.max_loan_raw.map_or(requested_amount, |max_loan| requested_amount.min(max_loan));
         let min_floor = self.calculate_min_floor(decimals);
 
         if requested_amount < min_floor {
@@ -101,6 +401,17 @@ impl TitanCommander {
         Ok(requested_amount)
     }
 
+    /// Lowest output a swap expecting `expected_out` should accept
+    ///
+    /// Applies `slippage_tolerance` (the share of output kept) in integer
+    /// parts per million, rounding down. Quotient and remainder are scaled
+    /// separately so amounts near `U256::MAX` don't overflow.
+    pub fn min_amount_out(&self, expected_out: U256) -> U256 {
+        let keep_ppm = (self.slippage_tolerance.clamp(0.0, 1.0) * SLIPPAGE_PPM as f64).round() as u64;
+        let (scale, keep) = (U256::from(SLIPPAGE_PPM), U256::from(keep_ppm));
+        expected_out / scale * keep + expected_out % scale * keep / scale
+    }
+
     /// Calculate maximum cap based on TVL
     fn calculate_max_cap(&self, pool_liquidity: U256) -> U256 {
         // max_cap = pool_liquidity * MAX_TVL_SHARE
@@ -108,6 +419,28 @@ impl TitanCommander {
         pool_liquidity * U256::from(multiplier) / U256::from(1000000u128)
     }
 
+    /// Maximum cap based on the pool's active liquidity instead of raw TVL
+    ///
+    /// Concentrated-liquidity pools can hold far more TVL than is tradeable
+    /// near the current price; this caps against the largest amount that
+    /// stays within `slippage_tolerance`, probed through the quoter.
+    pub async fn calculate_max_cap_active(
+        &self,
+        engine: &TitanSimulationEngine,
+        pool: &PoolProbe,
+        pool_liquidity: U256,
+    ) -> Result<U256> {
+        let max_impact = 1.0 - self.slippage_tolerance;
+        let active = probe_active_liquidity(
+            |amount| engine.get_price_impact(pool.token_in, pool.token_out, amount, pool.fee, pool.quoter),
+            pool_liquidity,
+            max_impact,
+        )
+        .await?;
+        debug!("Active liquidity {} of TVL {}", active, pool_liquidity);
+        Ok(self.calculate_max_cap(active))
+    }
+
     /// Calculate minimum floor based on decimals
     fn calculate_min_floor(&self, decimals: u8) -> U256 {
         // 500 units of stablecoin/ETH
@@ -124,9 +457,73 @@ impl TitanCommander {
         self.max_tvl_share = share;
     }
 
-    /// Set slippage tolerance
-    pub fn set_slippage_tolerance(&mut self, tolerance: f64) {
+    /// Set minimum pool liquidity (raw token units)
+    pub fn set_min_pool_liquidity(&mut self, min_liquidity: U256) {
+        self.min_pool_liquidity = min_liquidity;
+    }
+
+    /// Set the absolute loan ceiling (raw token units), whatever the pool's TVL
+    pub fn set_max_loan(&mut self, max_loan_raw: Option<U256>) {
+        self.max_loan_raw = max_loan_raw;
+    }
+
+    /// Set minimum net-profit-to-gas-cost ratio
+    pub fn set_min_profit_gas_ratio(&mut self, ratio: f64) {
+        self.min_profit_gas_ratio = ratio;
+    }
+
+    /// Set minimum route spread (%)
+    pub fn set_min_spread_pct(&mut self, min_spread_pct: f64) {
+        self.min_spread_pct = min_spread_pct;
+    }
+
+    /// Set maximum DEX vs Chainlink price deviation (bps)
+    pub fn set_max_price_deviation_bps(&mut self, bps: u32) {
+        self.max_price_deviation_bps = bps;
+    }
+
+    /// Mark a token as fee-on-transfer so loans in it are refused
+    pub fn flag_fee_on_transfer(&mut self, token: Address) {
+        self.fee_on_transfer_tokens.insert(token);
+    }
+
+    /// Whether a token has been flagged as fee-on-transfer
+    pub fn is_fee_on_transfer(&self, token: Address) -> bool {
+        self.fee_on_transfer_tokens.contains(&token)
+    }
+
+    /// Simulate a transfer from `holder` and flag the token if it takes a fee
+    pub async fn check_fee_on_transfer(&mut self, token: Address, holder: Address) -> Result<bool> {
+        if self.offline {
+            return Ok(self.is_fee_on_transfer(token));
+        }
+
+        let flagged = is_likely_fee_on_transfer(token, holder, Arc::clone(&self.provider)).await?;
+        if flagged {
+            warn!("⚠️ Token {:?} charges a transfer fee", token);
+            self.flag_fee_on_transfer(token);
+        }
+        Ok(flagged)
+    }
+
+    /// Use simulated liquidity and skip every RPC-backed check
+    pub fn set_offline(&mut self, offline: bool) {
+        self.offline = offline;
+    }
+
+    /// Whether the commander runs without RPC access
+    pub fn is_offline(&self) -> bool {
+        self.offline
+    }
+
+    /// Set slippage tolerance, the share of output a swap keeps
+    ///
+    /// Errors unless `tolerance` is finite and in `(0, 1]`; anything else
+    /// would floor `min_amount_out` at 0 and disable slippage protection.
+    pub fn set_slippage_tolerance(&mut self, tolerance: f64) -> Result<()> {
+        check_slippage_tolerance(tolerance)?;
         self.slippage_tolerance = tolerance;
+        Ok(())
     }
 
     /// Get chain ID
@@ -151,6 +548,32 @@ mod tests {
         assert_eq!(min_floor_6, U256::from(500) * U256::exp10(6));
     }
 
+    #[test]
+    fn test_min_amount_out() {
+        let mut commander = TitanCommander::new_offline(137).unwrap();
+        // Default 0.5% tolerance
+        assert_eq!(commander.min_amount_out(U256::from(1_000_000)), U256::from(995_000));
+        assert_eq!(commander.min_amount_out(U256::from(999)), U256::from(994));
+
+        // No float drift on 18-decimal amounts, and no overflow at the top
+        let amount = U256::exp10(30) + U256::from(7);
+        assert_eq!(commander.min_amount_out(amount), U256::exp10(27) * U256::from(995) + U256::from(6));
+        assert!(commander.min_amount_out(U256::MAX) < U256::MAX);
+
+        commander.set_slippage_tolerance(1.0).unwrap();
+        assert_eq!(commander.min_amount_out(U256::MAX), U256::MAX);
+    }
+
+    #[test]
+    fn test_set_slippage_tolerance_rejects_unusable_values() {
+        let mut commander = TitanCommander::new_offline(137).unwrap();
+        for tolerance in [f64::NAN, f64::INFINITY, -0.5, 0.0, 1.5] {
+            assert!(commander.set_slippage_tolerance(tolerance).is_err(), "{}", tolerance);
+        }
+        // Rejected values leave the floor in place
+        assert_eq!(commander.min_amount_out(U256::from(1_000_000)), U256::from(995_000));
+    }
+
     #[test]
     fn test_max_cap_calculation() {
         let provider = Arc::new(Provider::<Http>::try_from("http://localhost:8545").unwrap());
@@ -162,4 +585,264 @@ mod tests {
         // Should be 20% of pool liquidity
         assert_eq!(max_cap, U256::from(200000));
     }
+
+    #[tokio::test]
+    async fn test_active_liquidity_cap_vs_tvl_cap() {
+        let commander = TitanCommander::new_offline(137).unwrap();
+        let tvl = U256::from(1_000_000) * U256::exp10(6);
+        let raw_cap = commander.calculate_max_cap(tvl);
+
+        // Concentrated pool: constant product over 50k of virtual reserves
+        let virtual_reserve = U256::from(50_000) * U256::exp10(6);
+        let concentrated = |amount: U256| async move { Ok(amount * virtual_reserve / (virtual_reserve + amount)) };
+        let active = probe_active_liquidity(concentrated, tvl, 0.005).await.unwrap();
+        let active_cap = commander.calculate_max_cap(active);
+
+        // ~0.5% impact at ~250 of 50k reserves
+        assert!(active > U256::from(240) * U256::exp10(6) && active < U256::from(260) * U256::exp10(6));
+        assert!(active_cap < raw_cap / 1000);
+
+        // Full-range pool with negligible impact: caps agree
+        let engine = TitanSimulationEngine::new(137, Arc::clone(&commander.reader)).with_offline(true);
+        let pool = PoolProbe {
+            token_in: Address::zero(),
+            token_out: Address::zero(),
+            fee: 500,
+            quoter: Address::zero(),
+        };
+        assert_eq!(commander.calculate_max_cap_active(&engine, &pool, tvl).await.unwrap(), raw_cap);
+    }
+
+    #[test]
+    fn test_min_pool_liquidity_guard() {
+        let provider = Arc::new(Provider::<Http>::try_from("http://localhost:8545").unwrap());
+        let mut commander = TitanCommander::new(137, provider);
+        
+        // 10k USDC pool, request 1k USDC (clears the 500 floor and the 20% cap)
+        let pool_liquidity = U256::from(10_000) * U256::exp10(6);
+        let requested = U256::from(1_000) * U256::exp10(6);
+        assert_eq!(commander.size_against_liquidity(pool_liquidity, requested, 6), requested);
+        
+        commander.set_min_pool_liquidity(U256::from(1_000_000) * U256::exp10(6));
+        assert_eq!(commander.size_against_liquidity(pool_liquidity, requested, 6), U256::zero());
+    }
+
+    #[test]
+    fn test_profit_gas_ratio_gate() {
+        let provider = Arc::new(Provider::<Http>::try_from("http://localhost:8545").unwrap());
+        let mut commander = TitanCommander::new(137, provider);
+
+        // Any positive profit passes while the gate is off
+        assert!(commander.passes_profit_gas_ratio(10.0, 5.0));
+        assert!(!commander.passes_profit_gas_ratio(-1.0, 5.0));
+
+        // Same $10 profit: 2x gas is rejected, 5x gas passes
+        commander.set_min_profit_gas_ratio(3.0);
+        assert!(!commander.passes_profit_gas_ratio(10.0, 5.0));
+        assert!(commander.passes_profit_gas_ratio(10.0, 2.0));
+    }
+
+    #[test]
+    fn test_min_spread_gate_rejects_high_score_route() {
+        use crate::omniarb::{calculate_tar_score, QuoteInfo, TokenEntry};
+
+        // Tier-1 token, tier-1 bridge, deep pool: scores well but barely moves
+        let entry = TokenEntry {
+            native_token: "USDC".to_string(),
+            bridge_protocol: "STARGATE".to_string(),
+            liquidity_score: 100.0,
+            fee_tier: 0.01,
+            ..Default::default()
+        };
+        let quote = QuoteInfo {
+            spread_percentage: 0.25,
+            slippage_estimate: 0.05,
+            gas_cost_usd: 1.0,
+            available_liquidity: 5_000_000.0,
+            ..Default::default()
+        };
+        assert!(calculate_tar_score(&entry, &quote) >= 85.0);
+
+        let mut commander = TitanCommander::new_offline(137).unwrap();
+        assert!(!commander.passes_min_spread(quote.spread_percentage));
+        assert!(commander.passes_min_spread(0.3));
+        assert!(!commander.passes_min_spread(f64::NAN));
+
+        commander.set_min_spread_pct(0.0);
+        assert!(commander.passes_min_spread(quote.spread_percentage));
+    }
+
+    #[tokio::test]
+    async fn test_offline_sizing_is_deterministic() {
+        let commander = TitanCommander::new_offline(137).unwrap();
+        let amount = U256::from(1_000) * U256::exp10(18);
+
+        assert!(commander.has_gas_for(Address::zero(), U256::from(300_000), U256::exp10(9)).await.unwrap());
+        assert_eq!(commander.optimize_loan_size(Address::zero(), amount, 18).await.unwrap(), amount);
+
+        // Capped at 20% of the simulated 1M-token pool
+        let huge = U256::from(10_000_000) * U256::exp10(18);
+        let capped = commander.optimize_loan_size(Address::zero(), huge, 18).await.unwrap();
+        assert_eq!(capped, U256::from(200_000) * U256::exp10(18));
+    }
+
+    #[tokio::test]
+    async fn test_max_loan_ceiling_clamps_large_tvl() {
+        let mut commander = TitanCommander::new_offline(137).unwrap();
+        let ceiling = U256::from(50_000) * U256::exp10(18);
+        commander.set_max_loan(Some(ceiling));
+
+        // The 200k TVL cap of the 1M-token pool is still above the ceiling
+        let huge = U256::from(10_000_000) * U256::exp10(18);
+        assert_eq!(commander.optimize_loan_size(Address::zero(), huge, 18).await.unwrap(), ceiling);
+        let small = U256::from(1_000) * U256::exp10(18);
+        assert_eq!(commander.optimize_loan_size(Address::zero(), small, 18).await.unwrap(), small);
+
+        // A ceiling below the floor aborts instead of lending dust
+        commander.set_max_loan(Some(U256::from(100) * U256::exp10(18)));
+        assert_eq!(commander.optimize_loan_size(Address::zero(), huge, 18).await.unwrap(), U256::zero());
+    }
+
+    #[test]
+    fn test_price_deviation_bps() {
+        assert_eq!(price_deviation_bps(2000.0, 2000.0), 0);
+        assert_eq!(price_deviation_bps(2100.0, 2000.0), 500);
+        assert_eq!(price_deviation_bps(1990.0, 2000.0), 50);
+        assert_eq!(price_deviation_bps(2000.0, 0.0), u32::MAX);
+        assert_eq!(price_deviation_bps(f64::NAN, 2000.0), u32::MAX);
+    }
+
+    #[test]
+    fn test_estimate_net_apr() {
+        // $10 on $10k every hour: 0.1% x 8760 cycles
+        assert!((estimate_net_apr(10.0, 10_000.0, 3600.0) - 876.0).abs() < 1e-9);
+        // Same profit behind a 2-hour bridge halves the yield
+        assert!((estimate_net_apr(10.0, 10_000.0, 7200.0) - 438.0).abs() < 1e-9);
+        assert!(estimate_net_apr(-5.0, 10_000.0, 3600.0) < 0.0);
+        assert_eq!(estimate_net_apr(10.0, 0.0, 3600.0), 0.0);
+        assert_eq!(estimate_net_apr(10.0, 10_000.0, 0.0), 0.0);
+        assert_eq!(estimate_net_apr(f64::NAN, 10_000.0, 3600.0), 0.0);
+    }
+
+    #[tokio::test]
+    async fn test_fee_on_transfer_token_refused() {
+        let provider = Arc::new(Provider::<Http>::try_from("http://localhost:8545").unwrap());
+        let mut commander = TitanCommander::new(137, provider);
+        let token = Address::repeat_byte(0x11);
+
+        commander.flag_fee_on_transfer(token);
+        assert!(commander.is_fee_on_transfer(token));
+        assert!(!commander.is_fee_on_transfer(Address::zero()));
+
+        let amount = U256::from(1_000) * U256::exp10(6);
+        assert_eq!(commander.optimize_loan_size(token, amount, 6).await.unwrap(), U256::zero());
+    }
+
+    /// Serve every JSON-RPC call with `result`
+    async fn mock_rpc(result: &'static str) -> Arc<Provider<Http>> {
+        use axum::{routing::post, Json, Router};
+
+        let app = Router::new().route(
+            "/",
+            post(move |Json(request): Json<serde_json::Value>| async move {
+                Json(serde_json::json!({ "jsonrpc": "2.0", "id": request["id"], "result": result }))
+            }),
+        );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+        Arc::new(Provider::<Http>::try_from(format!("http://{}", addr)).unwrap())
+    }
+
+    /// Serve JSON-RPC calls with `results` in order, repeating the last one
+    async fn mock_rpc_sequence(results: Vec<String>) -> Arc<Provider<Http>> {
+        use axum::{extract::State, routing::post, Json, Router};
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        let calls = Arc::new(AtomicUsize::new(0));
+        let app = Router::new()
+            .route(
+                "/",
+                post(
+                    move |State(calls): State<Arc<AtomicUsize>>, Json(request): Json<serde_json::Value>| async move {
+                        let call = calls.fetch_add(1, Ordering::SeqCst);
+                        let result = &results[call.min(results.len() - 1)];
+                        Json(serde_json::json!({ "jsonrpc": "2.0", "id": request["id"], "result": result }))
+                    },
+                ),
+            )
+            .with_state(calls);
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+        Arc::new(Provider::<Http>::try_from(format!("http://{}", addr)).unwrap())
+    }
+
+    #[tokio::test]
+    async fn test_price_divergence_aborts_sizing() {
+        // Feed with 8 decimals answering $2000
+        let word = |value: u64| format!("{:064x}", value);
+        let decimals = format!("0x{}", word(8));
+        let round = format!("0x{}{}{}{}{}", word(1), word(200_000_000_000), word(0), word(0), word(1));
+        let commander = TitanCommander::new(137, mock_rpc_sequence(vec![decimals, round]).await);
+
+        // Pool implies $2100: a 5% divergence
+        let amount = U256::from(1_000) * U256::exp10(18);
+        let sized = commander
+            .optimize_loan_size_with_reference(Address::zero(), amount, 18, 2100.0, Address::repeat_byte(0x33))
+            .await
+            .unwrap();
+        assert_eq!(sized, U256::zero());
+        assert!(commander.passes_price_sanity(2010.0, 2000.0));
+    }
+
+    #[tokio::test]
+    async fn test_zero_balance_fails_gas_check() {
+        let commander = TitanCommander::new(137, mock_rpc("0x0").await);
+        let executor = Address::repeat_byte(0x22);
+        let gas_price = U256::from(30) * U256::exp10(9);
+
+        assert!(!commander.has_gas_for(executor, U256::from(300_000), gas_price).await.unwrap());
+        assert!(commander.has_gas_for(executor, U256::zero(), gas_price).await.unwrap());
+
+        let amount = U256::from(1_000) * U256::exp10(6);
+        let sized = commander
+            .optimize_live_loan_size(Address::zero(), amount, 6, executor, U256::from(300_000), gas_price)
+            .await
+            .unwrap();
+        assert_eq!(sized, U256::zero());
+    }
+
+    #[tokio::test]
+    async fn test_funded_executor_passes_gas_check() {
+        // 1 MATIC
+        let commander = TitanCommander::new(137, mock_rpc("0xde0b6b3a7640000").await);
+        let gas_price = U256::from(30) * U256::exp10(9);
+        assert!(commander.has_gas_for(Address::zero(), U256::from(300_000), gas_price).await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_sizing_with_mock_reader() {
+        use crate::chain_reader::MockChainReader;
+
+        let usdc = Address::repeat_byte(0xaa);
+        let executor = Address::repeat_byte(0xee);
+        let vault: Address = BALANCER_V3_VAULT.parse().unwrap();
+        let reader = MockChainReader::new()
+            .with_balance(usdc, vault, U256::from(1_000_000u64) * U256::exp10(6))
+            .with_native_balance(executor, U256::exp10(18));
+        let mut commander = TitanCommander::new_offline(137).unwrap().with_reader(Arc::new(reader));
+        commander.set_offline(false);
+
+        // 20% of the 1M vault balance caps a 500k request
+        let sized = commander
+            .optimize_loan_size(usdc, U256::from(500_000u64) * U256::exp10(6), 6)
+            .await
+            .unwrap();
+        assert_eq!(sized, U256::from(200_000u64) * U256::exp10(6));
+
+        let gas_price = U256::from(100) * U256::exp10(9);
+        assert!(commander.has_gas_for(executor, U256::from(1_000_000u64), gas_price).await.unwrap());
+        assert!(!commander.has_gas_for(executor, U256::from(100_000_000u64), gas_price).await.unwrap());
+    }
 }
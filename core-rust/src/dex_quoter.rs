@@ -0,0 +1,394 @@
+use ethers::prelude::*;
+use std::sync::Arc;
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use futures::future::join_all;
+use log::{debug, warn};
+
+use crate::simulation_engine::{quote_params, UniswapV3QuoterV2};
+
+abigen!(
+    UniswapV2Router,
+    r#"[
+        function getAmountsOut(uint256 amountIn, address[] path) external view returns (uint256[] amounts)
+    ]"#,
+);
+
+abigen!(
+    CurveStableSwap,
+    r#"[
+        function get_dy(int128 i, int128 j, uint256 dx) external view returns (uint256)
+    ]"#,
+);
+
+/// Common interface for on-chain DEX quoters
+#[async_trait]
+pub trait DexQuoter: Send + Sync {
+    /// Human-readable DEX name
+    fn name(&self) -> &str;
+
+    /// Quote the output amount for swapping `amount_in` of `token_in` to `token_out`
+    async fn quote(&self, token_in: Address, token_out: Address, amount_in: U256) -> Result<U256>;
+}
+
+/// Best quote found across a set of DEXes
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DexQuote {
+    pub dex: String,
+    pub amount_out: U256,
+}
+
+/// Uniswap V3 QuoterV2 for a single fee tier
+pub struct UniV3Quoter {
+    quoter: UniswapV3QuoterV2<Provider<Http>>,
+    fee: u32,
+}
+
+impl UniV3Quoter {
+    pub fn new(quoter_address: Address, fee: u32, provider: Arc<Provider<Http>>) -> Self {
+        Self {
+            quoter: UniswapV3QuoterV2::new(quoter_address, provider),
+            fee,
+        }
+    }
+}
+
+#[async_trait]
+impl DexQuoter for UniV3Quoter {
+    fn name(&self) -> &str {
+        "UNISWAP_V3"
+    }
+
+    async fn quote(&self, token_in: Address, token_out: Address, amount_in: U256) -> Result<U256> {
+        let amount_out = self
+            .quoter
+            .quote_exact_input_single(quote_params(token_in, token_out, amount_in, self.fee))
+            .call()
+            .await?
+            .0;
+        Ok(amount_out)
+    }
+}
+
+/// Most candidate paths `build_paths` generates, however many intermediaries
+pub const MAX_V2_PATHS: usize = 64;
+
+/// Candidate swap paths from `token_in` to `token_out`, shortest first
+///
+/// A path of `n` hops routes through `n - 1` distinct intermediaries; paths
+/// longer than `max_hops` are never built, and at most `MAX_V2_PATHS` are
+/// returned. Intermediaries equal to either endpoint are ignored.
+pub fn build_paths(
+    token_in: Address,
+    token_out: Address,
+    intermediaries: &[Address],
+    max_hops: usize,
+) -> Vec<Vec<Address>> {
+    fn extend(
+        path: &mut Vec<Address>,
+        token_out: Address,
+        intermediaries: &[Address],
+        hops: usize,
+        paths: &mut Vec<Vec<Address>>,
+    ) {
+        if paths.len() >= MAX_V2_PATHS {
+            return;
+        }
+        if path.len() == hops {
+            let mut complete = path.clone();
+            complete.push(token_out);
+            paths.push(complete);
+            return;
+        }
+        for &token in intermediaries {
+            if !path.contains(&token) {
+                path.push(token);
+                extend(path, token_out, intermediaries, hops, paths);
+                path.pop();
+            }
+        }
+    }
+
+    let mut unique: Vec<Address> = Vec::new();
+    for &token in intermediaries {
+        if token != token_in && token != token_out && !unique.contains(&token) {
+            unique.push(token);
+        }
+    }
+
+    let mut paths = Vec::new();
+    for hops in 1..=max_hops.min(unique.len() + 1) {
+        extend(&mut vec![token_in], token_out, &unique, hops, &mut paths);
+    }
+    paths
+}
+
+/// Uniswap V2-style router (QuickSwap, Sushi, ...)
+///
+/// Quotes the direct path unless given intermediaries, in which case every
+/// path up to `max_hops` is quoted and the best output wins.
+pub struct UniV2Router {
+    router: UniswapV2Router<Provider<Http>>,
+    intermediaries: Vec<Address>,
+    max_hops: usize,
+}
+
+impl UniV2Router {
+    pub fn new(router_address: Address, provider: Arc<Provider<Http>>) -> Self {
+        Self {
+            router: UniswapV2Router::new(router_address, provider),
+            intermediaries: Vec::new(),
+            max_hops: 1,
+        }
+    }
+
+    /// Route through `intermediaries` on paths of at most `max_hops` swaps
+    pub fn with_routing(mut self, intermediaries: Vec<Address>, max_hops: usize) -> Self {
+        self.intermediaries = intermediaries;
+        self.max_hops = max_hops.max(1);
+        self
+    }
+
+    /// Best-output path and its amount out
+    pub async fn quote_best_path(
+        &self,
+        token_in: Address,
+        token_out: Address,
+        amount_in: U256,
+    ) -> Result<(Vec<Address>, U256)> {
+        let paths = build_paths(token_in, token_out, &self.intermediaries, self.max_hops);
+        let results = join_all(paths.iter().map(|path| async move {
+            let amounts = self.router.get_amounts_out(amount_in, path.clone()).call().await?;
+            amounts
+                .last()
+                .copied()
+                .ok_or_else(|| anyhow!("getAmountsOut returned no amounts"))
+        }))
+        .await;
+
+        let mut best: Option<(Vec<Address>, U256)> = None;
+        for (path, result) in paths.into_iter().zip(results) {
+            match result {
+                Ok(amount_out) => {
+                    if best.as_ref().is_none_or(|(_, b)| amount_out > *b) {
+                        best = Some((path, amount_out));
+                    }
+                }
+                Err(e) => debug!("V2 path of {} hops failed: {}", path.len() - 1, e),
+            }
+        }
+        best.ok_or_else(|| anyhow!("No V2 path from {:?} to {:?} returned a quote", token_in, token_out))
+    }
+}
+
+#[async_trait]
+impl DexQuoter for UniV2Router {
+    fn name(&self) -> &str {
+        "UNISWAP_V2"
+    }
+
+    async fn quote(&self, token_in: Address, token_out: Address, amount_in: U256) -> Result<U256> {
+        Ok(self.quote_best_path(token_in, token_out, amount_in).await?.1)
+    }
+}
+
+/// Curve StableSwap pool with a known coin ordering
+pub struct CurvePool {
+    pool: CurveStableSwap<Provider<Http>>,
+    coins: Vec<Address>,
+}
+
+impl CurvePool {
+    /// `coins` must match the pool's `coins(i)` ordering
+    pub fn new(pool_address: Address, coins: Vec<Address>, provider: Arc<Provider<Http>>) -> Self {
+        Self {
+            pool: CurveStableSwap::new(pool_address, provider),
+            coins,
+        }
+    }
+
+    fn coin_index(&self, token: Address) -> Result<i128> {
+        self.coins
+            .iter()
+            .position(|coin| *coin == token)
+            .map(|i| i as i128)
+            .ok_or_else(|| anyhow!("Token {:?} not in Curve pool {:?}", token, self.pool.address()))
+    }
+}
+
+#[async_trait]
+impl DexQuoter for CurvePool {
+    fn name(&self) -> &str {
+        "CURVE"
+    }
+
+    async fn quote(&self, token_in: Address, token_out: Address, amount_in: U256) -> Result<U256> {
+        let i = self.coin_index(token_in)?;
+        let j = self.coin_index(token_out)?;
+        let amount_out = self.pool.get_dy(i, j, amount_in).call().await?;
+        Ok(amount_out)
+    }
+}
+
+/// Uniswap V3 factory on Ethereum, Polygon, Arbitrum and Optimism
+pub const UNISWAP_V3_FACTORY: &str = "0x1F98431c8aD98523631AE4a59f267346ea31F984";
+
+/// Uniswap V3 QuoterV2 deployments by chain, all answering the
+/// `quoteExactInputSingle((address,address,uint256,uint24,uint160))` struct ABI
+const UNISWAP_V3_QUOTERS: &[(u64, &str)] = &[
+    (1, "0x61fFE014bA17989E743c5F6cB21bF9697530B21e"),
+    (137, "0x61fFE014bA17989E743c5F6cB21bF9697530B21e"),
+    (42161, "0x61fFE014bA17989E743c5F6cB21bF9697530B21e"),
+    (10, "0x61fFE014bA17989E743c5F6cB21bF9697530B21e"),
+    (8453, "0x3d4e44Eb1374240CE5F1B871ab261CD16335B76a"),
+    (56, "0x78D78E420Da98ad378D7799bE8f4AF69033EB077"),
+    (43114, "0xbe0F5544EC67e9B3b2D979aaA43f18Fd87E6257F"),
+];
+
+/// Uniswap V3 QuoterV2 address on a chain, if deployed
+pub fn uniswap_v3_quoter(chain_id: u64) -> Option<Address> {
+    UNISWAP_V3_QUOTERS
+        .iter()
+        .find(|(id, _)| *id == chain_id)
+        .and_then(|(_, address)| address.parse().ok())
+}
+
+/// keccak256 of the Uniswap V3 pool creation code
+pub const UNISWAP_V3_POOL_INIT_CODE_HASH: [u8; 32] = [
+    0xe3, 0x4f, 0x19, 0x9b, 0x19, 0xb2, 0xb4, 0xf4, 0x7f, 0x68, 0x44, 0x26, 0x19, 0xd5, 0x55, 0x52,
+    0x7d, 0x24, 0x4f, 0x78, 0xa3, 0x29, 0x7e, 0xa8, 0x93, 0x25, 0xf8, 0x43, 0xf8, 0x7b, 0x8b, 0x54,
+];
+
+/// Derive a Uniswap V3 pool address locally, without an RPC call
+///
+/// Tokens may be passed in either order; they're sorted as the factory does.
+pub fn compute_v3_pool_address(factory: Address, token0: Address, token1: Address, fee: u32) -> Address {
+    let (token0, token1) = if token0 < token1 { (token0, token1) } else { (token1, token0) };
+    let salt = ethers::utils::keccak256(ethers::abi::encode(&[
+        ethers::abi::Token::Address(token0),
+        ethers::abi::Token::Address(token1),
+        ethers::abi::Token::Uint(U256::from(fee)),
+    ]));
+    ethers::utils::get_create2_address_from_hash(factory, salt, UNISWAP_V3_POOL_INIT_CODE_HASH)
+}
+
+/// Query all quoters concurrently and return the highest output
+///
+/// Failing quoters are logged and skipped; errors only if none succeed.
+pub async fn best_quote_across(
+    quoters: &[Box<dyn DexQuoter>],
+    token_in: Address,
+    token_out: Address,
+    amount_in: U256,
+) -> Result<DexQuote> {
+    let results = join_all(
+        quoters
+            .iter()
+            .map(|quoter| quoter.quote(token_in, token_out, amount_in)),
+    )
+    .await;
+
+    let mut best: Option<DexQuote> = None;
+    for (quoter, result) in quoters.iter().zip(results) {
+        match result {
+            Ok(amount_out) => {
+                debug!("{} quote: {} in -> {} out", quoter.name(), amount_in, amount_out);
+                if best.as_ref().is_none_or(|b| amount_out > b.amount_out) {
+                    best = Some(DexQuote {
+                        dex: quoter.name().to_string(),
+                        amount_out,
+                    });
+                }
+            }
+            Err(e) => warn!("{} quote failed: {}", quoter.name(), e),
+        }
+    }
+
+    best.ok_or_else(|| anyhow!("No DEX returned a quote"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct FixedQuoter {
+        name: &'static str,
+        amount_out: Option<u64>,
+    }
+
+    #[async_trait]
+    impl DexQuoter for FixedQuoter {
+        fn name(&self) -> &str {
+            self.name
+        }
+
+        async fn quote(&self, _: Address, _: Address, _: U256) -> Result<U256> {
+            self.amount_out
+                .map(U256::from)
+                .ok_or_else(|| anyhow!("no liquidity"))
+        }
+    }
+
+    #[test]
+    fn test_v3_pool_address_matches_mainnet() {
+        let factory: Address = UNISWAP_V3_FACTORY.parse().unwrap();
+        let usdc: Address = "0xA0b86991c6218b36c1d19D4a2e9Eb0cE3606eB48".parse().unwrap();
+        let weth: Address = "0xC02aaA39b223FE8D0A0e5C4F27eAD9083C756Cc2".parse().unwrap();
+        let wbtc: Address = "0x2260FAC5E5542a773Aa44fBCfeDf7C193bc2C599".parse().unwrap();
+
+        let usdc_weth_005: Address = "0x88e6A0c2dDD26FEEb64F039a2c41296FcB3f5640".parse().unwrap();
+        let usdc_weth_03: Address = "0x8ad599c3A0ff1De082011EFDDc58f1908eb6e6D8".parse().unwrap();
+        let wbtc_weth_03: Address = "0xCBCdF9626bC03E24f779434178A73a0B4bad62eD".parse().unwrap();
+
+        assert_eq!(compute_v3_pool_address(factory, usdc, weth, 500), usdc_weth_005);
+        assert_eq!(compute_v3_pool_address(factory, usdc, weth, 3000), usdc_weth_03);
+        assert_eq!(compute_v3_pool_address(factory, wbtc, weth, 3000), wbtc_weth_03);
+        // Token order doesn't matter
+        assert_eq!(compute_v3_pool_address(factory, weth, usdc, 500), usdc_weth_005);
+    }
+
+    #[test]
+    fn test_build_paths_respects_max_hops() {
+        let [usdc, weth, wmatic, dai, wbtc] = [1u8, 2, 3, 4, 5].map(Address::repeat_byte);
+
+        let paths = build_paths(usdc, wbtc, &[weth, wmatic, usdc], 2);
+        assert_eq!(paths, vec![vec![usdc, wbtc], vec![usdc, weth, wbtc], vec![usdc, wmatic, wbtc]]);
+        // The 3-hop route through both intermediaries needs max_hops=3
+        let three_hop = vec![usdc, weth, wmatic, wbtc];
+        assert!(!paths.contains(&three_hop));
+        assert!(build_paths(usdc, wbtc, &[weth, wmatic], 3).contains(&three_hop));
+
+        assert_eq!(build_paths(usdc, wbtc, &[weth, wmatic, dai], 1), vec![vec![usdc, wbtc]]);
+
+        // Long intermediary lists are capped
+        let many: Vec<Address> = (10u8..30).map(Address::repeat_byte).collect();
+        let capped = build_paths(usdc, wbtc, &many, 4);
+        assert_eq!(capped.len(), MAX_V2_PATHS);
+        assert_eq!(capped[0], vec![usdc, wbtc]);
+    }
+
+    #[tokio::test]
+    async fn test_best_quote_wins() {
+        let quoters: Vec<Box<dyn DexQuoter>> = vec![
+            Box::new(FixedQuoter { name: "LOW", amount_out: Some(990) }),
+            Box::new(FixedQuoter { name: "HIGH", amount_out: Some(1_010) }),
+            Box::new(FixedQuoter { name: "BROKEN", amount_out: None }),
+        ];
+
+        let best = best_quote_across(&quoters, Address::zero(), Address::zero(), U256::from(1_000))
+            .await
+            .unwrap();
+        assert_eq!(best.dex, "HIGH");
+        assert_eq!(best.amount_out, U256::from(1_010));
+    }
+
+    #[tokio::test]
+    async fn test_no_quotes_is_error() {
+        let quoters: Vec<Box<dyn DexQuoter>> = vec![
+            Box::new(FixedQuoter { name: "BROKEN", amount_out: None }),
+        ];
+
+        let result = best_quote_across(&quoters, Address::zero(), Address::zero(), U256::one()).await;
+        assert!(result.is_err());
+    }
+}
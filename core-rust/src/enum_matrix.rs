@@ -1,7 +1,14 @@
 use ethers::prelude::*;
-use std::sync::Arc;
+use std::future::Future;
+use std::sync::{Arc, Mutex};
 use std::collections::HashMap;
-use anyhow::Result;
+use std::time::Duration;
+use anyhow::{anyhow, Result};
+use futures::future::join_all;
+use tokio::sync::OnceCell;
+
+/// Per-chain timeout for `ProviderManager::current_blocks`
+pub const DEFAULT_BLOCK_FETCH_TIMEOUT: Duration = Duration::from_secs(5);
 
 /// Chain ID enumeration
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
@@ -84,36 +91,302 @@ impl ChainId {
             ChainId::OpBnb,
         ]
     }
+
+    /// Canonical (Circle-issued where available) USDC, if deployed
+    pub fn usdc(&self) -> Option<Address> {
+        let address = match self {
+            ChainId::Ethereum => "0xA0b86991c6218b36c1d19D4a2e9Eb0cE3606eB48",
+            ChainId::Polygon => "0x3c499c542cEF5E3811e1192ce70d8cC03d5c3359",
+            ChainId::Arbitrum => "0xaf88d065e77c8cC2239327C5EDb3A432268e5831",
+            ChainId::Optimism => "0x0b2C639c533813f4Aa9D7837CAf62653d097Ff85",
+            ChainId::Base => "0x833589fCD6eDb6E08f4c7C32D4f71b54bdA02913",
+            ChainId::Bsc => "0x8AC76a51cc950d9822D68b83fE1Ad97B32Cd580d",
+            ChainId::Avalanche => "0xB97EF9Ef8734C71904D8002F8b6Bc66Dd9c48a6E",
+            ChainId::Linea => "0x176211869cA2b568f2A7D4EE941E073a821EE1ff",
+            ChainId::Scroll => "0x06eFdBFf2a14a7c8E15944D1F4A48F9F95F663A4",
+            ChainId::Mantle => "0x09Bc4E0D864854c6aFB6eB9A9cdF58aC190D0dF9",
+            ChainId::ZkSync => "0x1d17CBcF0D6D143135aE902365D2E5e2A16538D4",
+            ChainId::Celo => "0xcebA9300f2b948710d2653dD7B07f33A8B32118C",
+            ChainId::Fantom | ChainId::OpBnb => return None,
+        };
+        address.parse().ok()
+    }
+
+    /// Canonical WETH (bridged on non-ETH chains), if deployed
+    pub fn weth(&self) -> Option<Address> {
+        let address = match self {
+            ChainId::Ethereum => "0xC02aaA39b223FE8D0A0e5C4F27eAD9083C756Cc2",
+            ChainId::Polygon => "0x7ceB23fD6bC0adD59E62ac25578270cFf1b9f619",
+            ChainId::Arbitrum => "0x82aF49447D8a07e3bd95BD0d56f35241523fBab1",
+            ChainId::Optimism | ChainId::Base => "0x4200000000000000000000000000000000000006",
+            ChainId::Bsc => "0x2170Ed0880ac9A755fd29B2688956BD959F933F8",
+            ChainId::Avalanche => "0x49D5c2BdFfac6CE2BFdB6640F4F80f226bc10bAB",
+            ChainId::Linea => "0xe5D7C2a44FfDDf6b295A15c148167daaAf5Cf34f",
+            ChainId::Scroll => "0x5300000000000000000000000000000000000004",
+            ChainId::Mantle => "0xdEAddEaDdeadDEadDEADDEAddEADDEAddead1111",
+            ChainId::ZkSync => "0x5AEa5775959fBC2557Cc8789bC1bf90A239D9a91",
+            ChainId::Fantom | ChainId::Celo | ChainId::OpBnb => return None,
+        };
+        address.parse().ok()
+    }
+
+    /// Wrapped form of the chain's gas token (WETH on ETH-gas chains)
+    ///
+    /// On Celo the gas token is itself an ERC-20, so that is returned.
+    pub fn native_wrapped(&self) -> Option<Address> {
+        let address = match self {
+            ChainId::Ethereum
+            | ChainId::Arbitrum
+            | ChainId::Optimism
+            | ChainId::Base
+            | ChainId::Linea
+            | ChainId::Scroll
+            | ChainId::ZkSync => return self.weth(),
+            ChainId::Polygon => "0x0d500B1d8E8eF31E21C99d1Db9A6444d3ADf1270",
+            ChainId::Bsc => "0xbb4CdB9CBd36B01bD1cBaEBF2De08d9173bc095c",
+            ChainId::Avalanche => "0xB31f66AA3C1e785363F0875A1B74E27b85FD66c7",
+            ChainId::Fantom => "0x21be370D5312f44cB42ce377BC9b8a0cEF1A4C83",
+            ChainId::Mantle => "0x78c1b0C915c4FAA5FffA6CAbf0219DA63d7f4cb8",
+            ChainId::Celo => "0x471EcE3750Da237f93B8E339c536989b8978a438",
+            ChainId::OpBnb => "0x4200000000000000000000000000000000000006",
+        };
+        address.parse().ok()
+    }
+}
+
+/// Strip case and separators so "Star Gate", "star_gate" and "STARGATE" compare equal
+fn compact_name(name: &str) -> String {
+    name.chars()
+        .filter(|c| c.is_ascii_alphanumeric())
+        .map(|c| c.to_ascii_uppercase())
+        .collect()
+}
+
+/// Cross-chain bridge enumeration
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum BridgeKind {
+    Stargate,
+    Across,
+    Ccip,
+    Lifi,
+    Hop,
+    Synapse,
+    Socket,
+    LayerZero,
+    Celer,
+    Multichain,
+    Wormhole,
+    AvalancheBridge,
+    PolygonBridge,
+    OptimismBridge,
+    ArbitrumBridge,
+    BaseBridge,
+}
+
+impl BridgeKind {
+    /// Parse a bridge name, ignoring case and separators and accepting common aliases
+    pub fn from_name(name: &str) -> Option<Self> {
+        let compact = compact_name(name);
+        Self::all()
+            .into_iter()
+            .find(|bridge| compact_name(bridge.name()) == compact)
+            .or(match compact.as_str() {
+                "CHAINLINKCCIP" => Some(BridgeKind::Ccip),
+                "LZ" => Some(BridgeKind::LayerZero),
+                "CBRIDGE" => Some(BridgeKind::Celer),
+                "BUNGEE" => Some(BridgeKind::Socket),
+                "AVAXBRIDGE" => Some(BridgeKind::AvalancheBridge),
+                "POSBRIDGE" => Some(BridgeKind::PolygonBridge),
+                _ => None,
+            })
+    }
+
+    /// Canonical matrix name
+    pub fn name(&self) -> &'static str {
+        match self {
+            BridgeKind::Stargate => "STARGATE",
+            BridgeKind::Across => "ACROSS",
+            BridgeKind::Ccip => "CCIP",
+            BridgeKind::Lifi => "LIFI",
+            BridgeKind::Hop => "HOP",
+            BridgeKind::Synapse => "SYNAPSE",
+            BridgeKind::Socket => "SOCKET",
+            BridgeKind::LayerZero => "LAYERZERO",
+            BridgeKind::Celer => "CELER",
+            BridgeKind::Multichain => "MULTICHAIN",
+            BridgeKind::Wormhole => "WORMHOLE",
+            BridgeKind::AvalancheBridge => "AVALANCHE_BRIDGE",
+            BridgeKind::PolygonBridge => "POLYGON_BRIDGE",
+            BridgeKind::OptimismBridge => "OPTIMISM_BRIDGE",
+            BridgeKind::ArbitrumBridge => "ARBITRUM_BRIDGE",
+            BridgeKind::BaseBridge => "BASE_BRIDGE",
+        }
+    }
+
+    /// Get all known bridges
+    pub fn all() -> Vec<BridgeKind> {
+        vec![
+            BridgeKind::Stargate,
+            BridgeKind::Across,
+            BridgeKind::Ccip,
+            BridgeKind::Lifi,
+            BridgeKind::Hop,
+            BridgeKind::Synapse,
+            BridgeKind::Socket,
+            BridgeKind::LayerZero,
+            BridgeKind::Celer,
+            BridgeKind::Multichain,
+            BridgeKind::Wormhole,
+            BridgeKind::AvalancheBridge,
+            BridgeKind::PolygonBridge,
+            BridgeKind::OptimismBridge,
+            BridgeKind::ArbitrumBridge,
+            BridgeKind::BaseBridge,
+        ]
+    }
+}
+
+/// DEX enumeration
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum DexKind {
+    UniswapV3,
+    UniswapV2,
+    SushiSwap,
+    QuickSwap,
+    Camelot,
+    Velodrome,
+    Aerodrome,
+    PancakeSwap,
+    TraderJoe,
+    Curve,
+    Balancer,
+}
+
+impl DexKind {
+    /// Parse a DEX name, ignoring case and separators and accepting common aliases
+    pub fn from_name(name: &str) -> Option<Self> {
+        let compact = compact_name(name);
+        Self::all()
+            .into_iter()
+            .find(|dex| compact_name(dex.name()) == compact)
+            .or(match compact.as_str() {
+                "UNIV3" => Some(DexKind::UniswapV3),
+                "UNIV2" => Some(DexKind::UniswapV2),
+                "SUSHI" => Some(DexKind::SushiSwap),
+                "QUICK" => Some(DexKind::QuickSwap),
+                "PANCAKE" => Some(DexKind::PancakeSwap),
+                "JOE" => Some(DexKind::TraderJoe),
+                _ => None,
+            })
+    }
+
+    /// Canonical matrix name
+    pub fn name(&self) -> &'static str {
+        match self {
+            DexKind::UniswapV3 => "UNISWAP_V3",
+            DexKind::UniswapV2 => "UNISWAP_V2",
+            DexKind::SushiSwap => "SUSHISWAP",
+            DexKind::QuickSwap => "QUICKSWAP",
+            DexKind::Camelot => "CAMELOT",
+            DexKind::Velodrome => "VELODROME",
+            DexKind::Aerodrome => "AERODROME",
+            DexKind::PancakeSwap => "PANCAKESWAP",
+            DexKind::TraderJoe => "TRADERJOE",
+            DexKind::Curve => "CURVE",
+            DexKind::Balancer => "BALANCER",
+        }
+    }
+
+    /// Get all known DEXes
+    pub fn all() -> Vec<DexKind> {
+        vec![
+            DexKind::UniswapV3,
+            DexKind::UniswapV2,
+            DexKind::SushiSwap,
+            DexKind::QuickSwap,
+            DexKind::Camelot,
+            DexKind::Velodrome,
+            DexKind::Aerodrome,
+            DexKind::PancakeSwap,
+            DexKind::TraderJoe,
+            DexKind::Curve,
+            DexKind::Balancer,
+        ]
+    }
+}
+
+/// Parse with `from_name`; unknown DEXes are an error rather than a default
+impl std::str::FromStr for DexKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::from_name(s).ok_or_else(|| {
+            let known: Vec<_> = Self::all().iter().map(DexKind::name).collect();
+            format!("Unknown DEX '{}' (expected one of {})", s, known.join(", "))
+        })
+    }
+}
+
+impl std::fmt::Display for DexKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.name())
+    }
 }
 
+/// A chain's provider, filled by whichever caller builds it first
+type ProviderSlot = Arc<OnceCell<Arc<Provider<Http>>>>;
+
 /// Provider manager for managing Web3 connections
+///
+/// Each chain's provider is built at most once: concurrent callers for an
+/// uncached chain wait on the same build instead of racing to insert.
 pub struct ProviderManager {
-    providers: HashMap<u64, Arc<Provider<Http>>>,
+    providers: Mutex<HashMap<u64, ProviderSlot>>,
+    rpc_timeout: Option<Duration>,
 }
 
 impl ProviderManager {
     /// Create a new provider manager
     pub fn new() -> Self {
         Self {
-            providers: HashMap::new(),
+            providers: Mutex::new(HashMap::new()),
+            rpc_timeout: None,
         }
     }
 
-    /// Get provider for a specific chain
-    pub async fn get_provider(&mut self, chain_id: u64, rpc_url: &str) -> Result<Arc<Provider<Http>>> {
-        if let Some(provider) = self.providers.get(&chain_id) {
-            return Ok(Arc::clone(provider));
-        }
+    /// Fail each request of the providers built from now on after `timeout`
+    pub fn with_rpc_timeout(mut self, timeout: Duration) -> Self {
+        self.rpc_timeout = Some(timeout);
+        self
+    }
 
-        let provider = Provider::<Http>::try_from(rpc_url)?;
-        let provider = Arc::new(provider);
-        self.providers.insert(chain_id, Arc::clone(&provider));
+    /// Get provider for a specific chain
+    pub async fn get_provider(&self, chain_id: u64, rpc_url: &str) -> Result<Arc<Provider<Http>>> {
+        self.get_or_build(chain_id, || async {
+            let Some(timeout) = self.rpc_timeout else {
+                return Ok(Provider::<Http>::try_from(rpc_url)?);
+            };
+            let client = reqwest::Client::builder().timeout(timeout).build()?;
+            Ok(Provider::new(Http::new_with_client(reqwest::Url::parse(rpc_url)?, client)))
+        })
+        .await
+    }
 
-        Ok(provider)
+    /// Cached provider for `chain_id`, or the one `build` produces
+    ///
+    /// A failed build leaves the chain uncached so the next caller retries.
+    async fn get_or_build<F, Fut>(&self, chain_id: u64, build: F) -> Result<Arc<Provider<Http>>>
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = Result<Provider<Http>>>,
+    {
+        let cell = Arc::clone(self.providers.lock().unwrap().entry(chain_id).or_default());
+        let provider = cell.get_or_try_init(|| async { build().await.map(Arc::new) }).await?;
+        Ok(Arc::clone(provider))
     }
 
     /// Test connection to a specific chain
-    pub async fn test_connection(&mut self, chain_id: u64, rpc_url: &str) -> Result<bool> {
+    pub async fn test_connection(&self, chain_id: u64, rpc_url: &str) -> Result<bool> {
         let provider = self.get_provider(chain_id, rpc_url).await?;
         
         match provider.get_block_number().await {
@@ -128,14 +401,58 @@ impl ProviderManager {
         }
     }
 
-    /// Get all providers
-    pub fn get_all_providers(&self) -> &HashMap<u64, Arc<Provider<Http>>> {
-        &self.providers
+    /// Fetch the current block of every chain concurrently
+    pub async fn current_blocks(&self, chains: &[(u64, String)]) -> HashMap<u64, Result<u64>> {
+        self.current_blocks_with_timeout(chains, DEFAULT_BLOCK_FETCH_TIMEOUT).await
+    }
+
+    /// Fetch the current block of every chain concurrently
+    ///
+    /// Each chain gets its own `timeout`, so a stalled RPC only fails its
+    /// own entry.
+    pub async fn current_blocks_with_timeout(
+        &self,
+        chains: &[(u64, String)],
+        timeout: Duration,
+    ) -> HashMap<u64, Result<u64>> {
+        let mut results = HashMap::new();
+        let mut pending = Vec::new();
+        for (chain_id, rpc_url) in chains {
+            match self.get_provider(*chain_id, rpc_url).await {
+                Ok(provider) => pending.push((*chain_id, provider)),
+                Err(e) => {
+                    results.insert(*chain_id, Err(e));
+                }
+            }
+        }
+
+        let fetched = join_all(pending.into_iter().map(|(chain_id, provider)| async move {
+            let block = match tokio::time::timeout(timeout, provider.get_block_number()).await {
+                Ok(Ok(block)) => Ok(block.as_u64()),
+                Ok(Err(e)) => Err(anyhow!("Chain {}: {}", chain_id, e)),
+                Err(_) => Err(anyhow!("Chain {}: timed out after {:?}", chain_id, timeout)),
+            };
+            (chain_id, block)
+        }))
+        .await;
+
+        results.extend(fetched);
+        results
+    }
+
+    /// Get all providers built so far
+    pub fn get_all_providers(&self) -> HashMap<u64, Arc<Provider<Http>>> {
+        self.providers
+            .lock()
+            .unwrap()
+            .iter()
+            .filter_map(|(chain_id, cell)| cell.get().map(|provider| (*chain_id, Arc::clone(provider))))
+            .collect()
     }
 
     /// Close all connections
-    pub fn close_all(&mut self) {
-        self.providers.clear();
+    pub fn close_all(&self) {
+        self.providers.lock().unwrap().clear();
     }
 }
 
@@ -149,6 +466,21 @@ impl Default for ProviderManager {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_chain_default_tokens() {
+        let mainnet_usdc: Address = "0xA0b86991c6218b36c1d19D4a2e9Eb0cE3606eB48".parse().unwrap();
+        assert_eq!(ChainId::Ethereum.usdc(), Some(mainnet_usdc));
+        assert_eq!(ChainId::Base.weth(), ChainId::Optimism.weth());
+        assert_eq!(ChainId::Arbitrum.native_wrapped(), ChainId::Arbitrum.weth());
+        assert_ne!(ChainId::Polygon.native_wrapped(), ChainId::Polygon.weth());
+        assert_eq!(ChainId::Fantom.usdc(), None);
+
+        // Every listed address parses
+        for chain in ChainId::all() {
+            assert!(chain.native_wrapped().is_some(), "{}", chain.name());
+        }
+    }
+
     #[test]
     fn test_chain_id_conversion() {
         assert_eq!(ChainId::from_u64(1), Some(ChainId::Ethereum));
@@ -170,4 +502,109 @@ mod tests {
         assert!(chains.contains(&ChainId::Ethereum));
         assert!(chains.contains(&ChainId::Polygon));
     }
+
+    #[test]
+    fn test_bridge_name_normalization() {
+        assert_eq!(BridgeKind::from_name("STAR GATE"), Some(BridgeKind::Stargate));
+        assert_eq!(BridgeKind::from_name("polygon-bridge"), Some(BridgeKind::PolygonBridge));
+        assert_eq!(BridgeKind::from_name("Li.Fi"), Some(BridgeKind::Lifi));
+        assert_eq!(BridgeKind::from_name("Chainlink CCIP"), Some(BridgeKind::Ccip));
+        assert_eq!(BridgeKind::from_name("TELEPORT"), None);
+    }
+
+    #[test]
+    fn test_dex_name_normalization() {
+        assert_eq!(DexKind::from_name("Uniswap V3"), Some(DexKind::UniswapV3));
+        assert_eq!(DexKind::from_name("univ2"), Some(DexKind::UniswapV2));
+        assert_eq!(DexKind::from_name("Trader Joe"), Some(DexKind::TraderJoe));
+        assert_eq!(DexKind::from_name("UNKNOWNSWAP"), None);
+    }
+
+    #[test]
+    fn test_dex_kind_from_str() {
+        for name in ["UNISWAP_V2", "uniswap_v2", "UniswapV2", "Uniswap V2", "univ2", "UNIV2"] {
+            assert_eq!(name.parse::<DexKind>(), Ok(DexKind::UniswapV2), "{}", name);
+        }
+        assert_eq!(DexKind::SushiSwap.to_string(), "SUSHISWAP");
+        let err = "UNKNOWNSWAP".parse::<DexKind>().unwrap_err();
+        assert!(err.starts_with("Unknown DEX 'UNKNOWNSWAP'"), "{}", err);
+    }
+
+    /// JSON-RPC endpoint answering `eth_blockNumber` with `block` after `delay`
+    async fn mock_rpc(block: &'static str, delay: Duration) -> String {
+        use axum::{routing::post, Json, Router};
+
+        let app = Router::new().route(
+            "/",
+            post(move |Json(request): Json<serde_json::Value>| async move {
+                tokio::time::sleep(delay).await;
+                Json(serde_json::json!({ "jsonrpc": "2.0", "id": request["id"], "result": block }))
+            }),
+        );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+        format!("http://{}", addr)
+    }
+
+    #[tokio::test]
+    async fn test_current_blocks_isolates_stalled_chain() {
+        let fast = mock_rpc("0x1234", Duration::ZERO).await;
+        let stalled = mock_rpc("0x1", Duration::from_secs(30)).await;
+        let chains = vec![(1, fast), (137, stalled), (10, "not a url".to_string())];
+
+        let manager = ProviderManager::new();
+        let started = std::time::Instant::now();
+        let blocks = manager
+            .current_blocks_with_timeout(&chains, Duration::from_millis(200))
+            .await;
+
+        assert!(started.elapsed() < Duration::from_secs(5));
+        assert_eq!(blocks.len(), 3);
+        assert_eq!(blocks[&1].as_ref().unwrap(), &0x1234);
+        assert!(blocks[&137].as_ref().unwrap_err().to_string().contains("timed out"));
+        assert!(blocks[&10].is_err());
+    }
+
+    #[tokio::test]
+    async fn test_rpc_timeout_bounds_every_request() {
+        let stalled = mock_rpc("0x1", Duration::from_secs(30)).await;
+        let manager = ProviderManager::new().with_rpc_timeout(Duration::from_millis(200));
+        let provider = manager.get_provider(137, &stalled).await.unwrap();
+
+        let started = std::time::Instant::now();
+        assert!(provider.get_block_number().await.is_err());
+        assert!(started.elapsed() < Duration::from_secs(5));
+        assert!(manager.get_provider(10, "not a url").await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_concurrent_callers_share_one_build() {
+        let manager = Arc::new(ProviderManager::new());
+        let builds = Arc::new(std::sync::atomic::AtomicUsize::new(0));
+
+        let callers = (0..16).map(|_| {
+            let (manager, builds) = (Arc::clone(&manager), Arc::clone(&builds));
+            tokio::spawn(async move {
+                manager
+                    .get_or_build(137, || async move {
+                        builds.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+                        // Hold the build open so every caller arrives while it runs
+                        tokio::time::sleep(Duration::from_millis(50)).await;
+                        Ok(Provider::<Http>::try_from("http://localhost:8545")?)
+                    })
+                    .await
+                    .unwrap()
+            })
+        });
+        let providers: Vec<_> = join_all(callers).await.into_iter().map(Result::unwrap).collect();
+
+        assert_eq!(builds.load(std::sync::atomic::Ordering::SeqCst), 1);
+        assert!(providers.iter().all(|provider| Arc::ptr_eq(provider, &providers[0])));
+        assert!(Arc::ptr_eq(&manager.get_provider(137, "http://ignored").await.unwrap(), &providers[0]));
+
+        // A failed build isn't cached
+        assert!(manager.get_provider(10, "not a url").await.is_err());
+        assert!(manager.get_provider(10, "http://localhost:8545").await.is_ok());
+    }
 }
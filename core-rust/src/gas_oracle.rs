@@ -0,0 +1,147 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use anyhow::{anyhow, Result};
+use ethers::prelude::*;
+use log::{debug, warn};
+
+use crate::chainlink::read_chainlink_price;
+use crate::config::GasPolicy;
+use crate::simulation_engine::{fetch_gas_fees, gas_cost_native, gas_cost_usd};
+
+/// How long a chain's swap gas cost is reused
+pub const DEFAULT_GAS_CACHE_TTL: Duration = Duration::from_secs(15);
+
+/// Live USD cost of a swap per chain, from current fees and native price
+pub struct GasOracle<P: JsonRpcClient = Http> {
+    policy: GasPolicy,
+    providers: HashMap<u64, Arc<Provider<P>>>,
+    ttl: Duration,
+    cache: Mutex<HashMap<u64, (Instant, f64)>>,
+}
+
+impl<P: JsonRpcClient + 'static> GasOracle<P> {
+    pub fn new(policy: GasPolicy) -> Self {
+        Self {
+            policy,
+            providers: HashMap::new(),
+            ttl: DEFAULT_GAS_CACHE_TTL,
+            cache: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Provider used for a chain's fees and price feed
+    pub fn with_provider(mut self, chain_id: u64, provider: Arc<Provider<P>>) -> Self {
+        self.providers.insert(chain_id, provider);
+        self
+    }
+
+    /// Set how long estimates are cached
+    pub fn with_ttl(mut self, ttl: Duration) -> Self {
+        self.ttl = ttl;
+        self
+    }
+
+    /// Expected USD cost of one swap on `chain_id`, at base fee plus tip
+    ///
+    /// Uses the configured Chainlink feed for the native price, or the
+    /// static fallback price when there's no feed or it can't be read.
+    pub async fn swap_cost_usd(&self, chain_id: u64) -> Result<f64> {
+        if let Some((fetched_at, cost)) = self.cache.lock().unwrap().get(&chain_id) {
+            if fetched_at.elapsed() < self.ttl {
+                return Ok(*cost);
+            }
+        }
+
+        let provider = self
+            .providers
+            .get(&chain_id)
+            .ok_or_else(|| anyhow!("No provider for chain {}", chain_id))?;
+        let fees = fetch_gas_fees(provider).await?;
+        let cost_wei = gas_cost_native(U256::from(self.policy.gas_per_swap(chain_id)), fees.expected());
+        let native_usd = self.native_usd(chain_id, provider).await?;
+        let cost = gas_cost_usd(cost_wei, native_usd)?;
+
+        debug!("Swap gas cost on chain {}: ${:.4}", chain_id, cost);
+        self.cache.lock().unwrap().insert(chain_id, (Instant::now(), cost));
+        Ok(cost)
+    }
+
+    async fn native_usd(&self, chain_id: u64, provider: &Arc<Provider<P>>) -> Result<f64> {
+        if let Some(feed) = self.policy.native_usd_feed(chain_id) {
+            match read_chainlink_price(feed, Arc::clone(provider)).await {
+                Ok(price) => return Ok(price),
+                Err(e) => warn!("Native price feed failed on chain {}: {}", chain_id, e),
+            }
+        }
+        self.policy
+            .native_usd_fallback(chain_id)
+            .ok_or_else(|| anyhow!("No native price for chain {}", chain_id))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::abi_call::uint_words;
+
+    fn gwei(n: u64) -> U256 {
+        U256::from(n) * U256::exp10(9)
+    }
+
+    /// Queue fee history and a base fee of 20 gwei with a 2 gwei tip
+    fn push_fees(mock: &MockProvider) {
+        mock.push(serde_json::json!({
+            "oldestBlock": "0x1",
+            "baseFeePerGas": [],
+            "gasUsedRatio": [],
+            "reward": [[gwei(2)]],
+        }))
+        .unwrap();
+        mock.push(serde_json::json!({ "number": "0x2", "baseFeePerGas": gwei(20) })).unwrap();
+    }
+
+    fn policy(feed: Option<&str>) -> GasPolicy {
+        GasPolicy {
+            est_gas_per_swap: HashMap::from([(1, 200_000)]),
+            native_usd_feeds: feed.map(|f| HashMap::from([(1, f.to_string())])).unwrap_or_default(),
+            native_usd_fallback: HashMap::from([(1, 3000.0)]),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_swap_cost_from_fees_and_feed() {
+        let (provider, mock) = Provider::mocked();
+        // Served last-in first-out: block, fee history, decimals, round data
+        let round = uint_words(&[1, 200_000_000_000, 0, 0, 1]);
+        mock.push::<Bytes, _>(round).unwrap();
+        mock.push::<Bytes, _>(uint_words(&[8])).unwrap();
+        push_fees(&mock);
+
+        let feed = "0x5f4eC3Df9cbd43714FE2740f5E3616155c5b8419";
+        let oracle = GasOracle::new(policy(Some(feed))).with_provider(1, Arc::new(provider));
+
+        // 200k gas * (20 + 2) gwei = 0.0044 ETH at $2000
+        let cost = oracle.swap_cost_usd(1).await.unwrap();
+        assert!((cost - 8.8).abs() < 1e-9, "{}", cost);
+
+        // Cached: the mock has nothing left to serve
+        assert_eq!(oracle.swap_cost_usd(1).await.unwrap(), cost);
+    }
+
+    #[tokio::test]
+    async fn test_static_price_fallback() {
+        let (provider, mock) = Provider::mocked();
+        push_fees(&mock);
+
+        // The feed call fails once the mock runs dry
+        let feed = "0x5f4eC3Df9cbd43714FE2740f5E3616155c5b8419";
+        let oracle = GasOracle::new(policy(Some(feed))).with_provider(1, Arc::new(provider));
+        let cost = oracle.swap_cost_usd(1).await.unwrap();
+        assert!((cost - 13.2).abs() < 1e-9, "{}", cost);
+
+        let no_provider: GasOracle<MockProvider> = GasOracle::new(policy(None));
+        assert!(no_provider.swap_cost_usd(1).await.is_err());
+    }
+}
@@ -1,27 +1,447 @@
 use axum::{
-    extract::{State, Query},
-    http::StatusCode,
-    response::{IntoResponse, Json},
+    async_trait,
+    extract::{DefaultBodyLimit, FromRequest, FromRequestParts, Path, Request, State, Query},
+    http::{header::{HeaderName, HeaderValue}, request::Parts, StatusCode},
+    middleware::{self, Next},
+    response::{IntoResponse, Json, Response},
     routing::{get, post},
     Router,
 };
-use serde::{Deserialize, Serialize};
-use std::sync::Arc;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
 use tokio::sync::RwLock;
+use tower_http::compression::CompressionLayer;
 use tower_http::cors::CorsLayer;
-use tracing::{info, error};
+use tower_http::request_id::{MakeRequestUuid, PropagateRequestIdLayer, SetRequestIdLayer};
+use tower_http::trace::TraceLayer;
+use tracing::{info, error, warn};
 use ethers::prelude::*;
 
-use crate::config::{Config, BALANCER_V3_VAULT};
-use crate::enum_matrix::ProviderManager;
-use crate::simulation_engine::get_provider_tvl;
-use crate::commander::TitanCommander;
+use crate::abi_call::{call_function, encode_call, parse_signature};
+use crate::address::checksummed;
+use crate::chain_reader::ChainReader;
+use crate::config::{Config, TokenResolver, BALANCER_V3_VAULT};
+use crate::enum_matrix::{ChainId, DexKind, ProviderManager};
+use crate::simulation_engine::{fetch_tvl_batch, get_provider_tvl, simulated_tvl, TitanSimulationEngine};
+use crate::commander::{check_slippage_tolerance, TitanCommander};
+use crate::dex_quoter::uniswap_v3_quoter;
+use crate::metrics_snapshot::{MetricsSnapshot, DEFAULT_SNAPSHOT_INTERVAL};
+use crate::omniarb::model_bridge;
+use crate::omniarb::{
+    sanitization_counts, score_matrix_file, LoadedModel, ModelError, ModelRegistry, ModelStatus, QuoteCache, QuoteRouter,
+    RunHistory,
+};
+
+/// API versions mounted under `/api/<version>/...`
+pub const SUPPORTED_API_VERSIONS: &[&str] = &["v1"];
+
+/// Version served by the deprecated unprefixed `/api/...` aliases
+pub const LEGACY_API_VERSION: &str = "v1";
+
+/// Default maximum request body size (64 KiB)
+pub const DEFAULT_BODY_LIMIT: usize = 64 * 1024;
+
+/// Maximum token decimals accepted by the API
+const MAX_DECIMALS: u8 = 36;
+
+/// Maximum tokens in one `/tvl_batch` request
+pub const MAX_TVL_BATCH_SIZE: usize = 500;
+
+/// How long `POST /api/v1/run` waits for the run before answering with its id
+pub const RUN_RESPONSE_WAIT: Duration = Duration::from_secs(2);
+
+/// Emit every high-volume info log unless sampling is configured
+pub const DEFAULT_LOG_SAMPLE_EVERY: u64 = 1;
+
+/// Counts every call to an endpoint and lets 1-in-N of its info logs through
+///
+/// The first call always logs. Errors are logged unsampled by the handlers.
+#[derive(Debug)]
+pub struct LogSampler {
+    every: u64,
+    count: AtomicU64,
+}
+
+impl LogSampler {
+    pub fn new(every: u64) -> Self {
+        Self {
+            every: every.max(1),
+            count: AtomicU64::new(0),
+        }
+    }
+
+    /// Count one call; true when its info log should be emitted
+    pub fn record(&self) -> bool {
+        self.count.fetch_add(1, Ordering::Relaxed).is_multiple_of(self.every)
+    }
+
+    /// Calls recorded so far, sampled or not
+    pub fn count(&self) -> u64 {
+        self.count.load(Ordering::Relaxed)
+    }
+}
+
+/// API requests served, split by outcome
+#[derive(Debug, Default)]
+pub struct RequestCounters {
+    total: AtomicU64,
+    success: AtomicU64,
+    failed: AtomicU64,
+}
+
+impl RequestCounters {
+    /// Count one response; anything but 2xx is a failure
+    pub fn record(&self, status: StatusCode) {
+        self.total.fetch_add(1, Ordering::Relaxed);
+        let outcome = if status.is_success() { &self.success } else { &self.failed };
+        outcome.fetch_add(1, Ordering::Relaxed);
+    }
+}
 
 /// Server state shared across handlers
 #[derive(Clone)]
 pub struct AppState {
     pub config: Arc<Config>,
     pub provider_manager: Arc<RwLock<ProviderManager>>,
+    pub body_limit: usize,
+    pub quote_cache: Arc<QuoteCache>,
+    pub token_resolver: Arc<TokenResolver>,
+    pub quote_router: Arc<QuoteRouter>,
+    /// Concurrent `balanceOf` calls per `/tvl_batch` request
+    pub tvl_batch_concurrency: usize,
+    /// Serve the arbitrary-read `/call` endpoint (`RUST_SERVER_ENABLE_CALL=1`)
+    pub call_enabled: bool,
+    /// Serve `POST /api/v1/admin/models/reload` (`RUST_SERVER_ENABLE_ADMIN=1`)
+    pub admin_enabled: bool,
+    /// Serve `POST /api/v1/run` (`RUST_SERVER_ENABLE_RUN=1`)
+    pub run_enabled: bool,
+    /// gzip/brotli responses when the client accepts them (`RUST_SERVER_COMPRESSION=0` disables)
+    pub compression: bool,
+    /// `/tvl` calls and their sampled info logs
+    pub tvl_log: Arc<LogSampler>,
+    /// `/optimize_loan` calls and their sampled info logs
+    pub optimize_loan_log: Arc<LogSampler>,
+    /// Models reloadable through `POST /api/v1/admin/models/reload`
+    pub models: Arc<ModelRegistry>,
+    /// API requests served by this process
+    pub requests: Arc<RequestCounters>,
+    /// Counters restored from the previous process's snapshot
+    pub metrics_baseline: MetricsSnapshot,
+    /// Scoring runs started through `POST /api/v1/run`
+    pub runs: Arc<RunHistory>,
+    /// Per-chain engines whose quote cache `/simulate` requests share
+    pub simulation_engines: Arc<Mutex<HashMap<u64, Arc<TitanSimulationEngine>>>>,
+}
+
+impl AppState {
+    /// Create server state sized by the config's runtime profile
+    pub fn new(config: Config) -> Self {
+        Self {
+            token_resolver: Arc::new(TokenResolver::new(&config.token_registry)),
+            quote_router: Arc::new(QuoteRouter::from_config(&config)),
+            quote_cache: Arc::new(QuoteCache::new(config.runtime.quote_cache_ttl)),
+            tvl_batch_concurrency: config.runtime.max_concurrency.max(1),
+            provider_manager: Arc::new(RwLock::new(ProviderManager::new().with_rpc_timeout(config.runtime.rpc_timeout))),
+            config: Arc::new(config),
+            body_limit: DEFAULT_BODY_LIMIT,
+            call_enabled: false,
+            admin_enabled: false,
+            run_enabled: false,
+            compression: true,
+            tvl_log: Arc::new(LogSampler::new(DEFAULT_LOG_SAMPLE_EVERY)),
+            optimize_loan_log: Arc::new(LogSampler::new(DEFAULT_LOG_SAMPLE_EVERY)),
+            models: Arc::new(ModelRegistry::new()),
+            requests: Arc::new(RequestCounters::default()),
+            metrics_baseline: MetricsSnapshot::default(),
+            runs: Arc::new(RunHistory::default()),
+            simulation_engines: Arc::default(),
+        }
+    }
+
+    /// Set maximum request body size in bytes
+    pub fn with_body_limit(mut self, body_limit: usize) -> Self {
+        self.body_limit = body_limit;
+        self
+    }
+
+    /// Set how many RPC calls a TVL batch may have in flight
+    pub fn with_tvl_batch_concurrency(mut self, concurrency: usize) -> Self {
+        self.tvl_batch_concurrency = concurrency.max(1);
+        self
+    }
+
+    /// Enable the generic contract call endpoint
+    pub fn with_call_enabled(mut self, enabled: bool) -> Self {
+        self.call_enabled = enabled;
+        self
+    }
+
+    /// Enable the model reload endpoint
+    pub fn with_admin_enabled(mut self, enabled: bool) -> Self {
+        self.admin_enabled = enabled;
+        self
+    }
+
+    /// Enable starting scoring runs
+    pub fn with_run_enabled(mut self, enabled: bool) -> Self {
+        self.run_enabled = enabled;
+        self
+    }
+
+    /// Compress responses according to `Accept-Encoding`
+    pub fn with_compression(mut self, enabled: bool) -> Self {
+        self.compression = enabled;
+        self
+    }
+
+    /// Serve and reload these models
+    pub fn with_models(mut self, models: ModelRegistry) -> Self {
+        self.models = Arc::new(models);
+        self
+    }
+
+    /// Log 1-in-`every` `/tvl` and `/optimize_loan` requests at info level
+    pub fn with_log_sample_every(mut self, every: u64) -> Self {
+        self.tvl_log = Arc::new(LogSampler::new(every));
+        self.optimize_loan_log = Arc::new(LogSampler::new(every));
+        self
+    }
+
+    /// Continue counting from a snapshot saved by an earlier process
+    pub fn with_restored_metrics(mut self, snapshot: MetricsSnapshot) -> Self {
+        self.metrics_baseline = snapshot;
+        self
+    }
+
+    /// Counters since the first snapshot, restored baseline included
+    pub fn metrics_snapshot(&self) -> MetricsSnapshot {
+        let cache = self.quote_cache.stats();
+        self.metrics_baseline.plus(&MetricsSnapshot {
+            queries_total: self.requests.total.load(Ordering::Relaxed),
+            queries_success: self.requests.success.load(Ordering::Relaxed),
+            queries_failed: self.requests.failed.load(Ordering::Relaxed),
+            quote_cache_hits: cache.hits,
+            quote_cache_misses: cache.misses,
+            tvl_requests: self.tvl_log.count(),
+            optimize_loan_requests: self.optimize_loan_log.count(),
+        })
+    }
+}
+
+/// Where and how often the server saves its counters
+#[derive(Debug, Clone)]
+pub struct SnapshotSettings {
+    pub path: PathBuf,
+    pub interval: Duration,
+}
+
+/// Save `state`'s counters to `path`, logging a failed write
+fn save_metrics_snapshot(state: &AppState, path: &std::path::Path) {
+    if let Err(e) = state.metrics_snapshot().save(path) {
+        error!("Failed to write metrics snapshot {}: {}", path.display(), e);
+    }
+}
+
+/// Save `state`'s counters to `path` every `interval`
+///
+/// Failed writes are logged and retried on the next tick.
+pub fn spawn_metrics_snapshots(state: AppState, path: PathBuf, interval: Duration) -> tokio::task::JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(interval);
+        ticker.tick().await;
+        loop {
+            ticker.tick().await;
+            save_metrics_snapshot(&state, &path);
+        }
+    })
+}
+
+/// Error body returned by every failing endpoint
+///
+/// `code` is stable and machine-readable; `message` is for humans.
+#[derive(Debug, Serialize)]
+pub struct ApiError {
+    pub code: String,
+    pub message: String,
+    pub details: Option<serde_json::Value>,
+}
+
+impl ApiError {
+    pub const CHAIN_UNSUPPORTED: &'static str = "CHAIN_UNSUPPORTED";
+    pub const INVALID_ADDRESS: &'static str = "INVALID_ADDRESS";
+    pub const INVALID_REQUEST: &'static str = "INVALID_REQUEST";
+    pub const INVALID_SIGNATURE: &'static str = "INVALID_SIGNATURE";
+    pub const PAYLOAD_TOO_LARGE: &'static str = "PAYLOAD_TOO_LARGE";
+    pub const NOT_IMPLEMENTED: &'static str = "NOT_IMPLEMENTED";
+    pub const PROVIDER_ERROR: &'static str = "PROVIDER_ERROR";
+    pub const UNKNOWN_TOKEN: &'static str = "UNKNOWN_TOKEN";
+    pub const RPC_ERROR: &'static str = "RPC_ERROR";
+    pub const REVERTED: &'static str = "REVERTED";
+    pub const ENDPOINT_DISABLED: &'static str = "ENDPOINT_DISABLED";
+    pub const UNKNOWN_DEX: &'static str = "UNKNOWN_DEX";
+    pub const UNKNOWN_MODEL: &'static str = "UNKNOWN_MODEL";
+    pub const MODEL_LOAD_FAILED: &'static str = "MODEL_LOAD_FAILED";
+    pub const UNKNOWN_RUN: &'static str = "UNKNOWN_RUN";
+    pub const RUN_IN_PROGRESS: &'static str = "RUN_IN_PROGRESS";
+
+    pub fn new(code: &str, message: impl Into<String>) -> Self {
+        Self {
+            code: code.to_string(),
+            message: message.into(),
+            details: None,
+        }
+    }
+
+    /// Attach structured context, e.g. the offending field
+    pub fn with_details(mut self, details: serde_json::Value) -> Self {
+        self.details = Some(details);
+        self
+    }
+
+    /// Error for a failed on-chain call, distinguishing reverts from transport errors
+    pub fn from_rpc(context: &str, error: &anyhow::Error) -> Self {
+        let message = format!("{}: {}", context, error);
+        let code = if message.to_lowercase().contains("revert") {
+            Self::REVERTED
+        } else {
+            Self::RPC_ERROR
+        };
+        Self::new(code, message)
+    }
+
+    fn with_status(self, status: StatusCode) -> (StatusCode, Json<ApiError>) {
+        (status, Json(self))
+    }
+}
+
+/// Handler result: JSON body on success, status plus `ApiError` on failure
+pub type ApiResult<T> = Result<Json<T>, (StatusCode, Json<ApiError>)>;
+
+/// Field-level request validation failure
+#[derive(Debug)]
+pub struct ValidationError {
+    pub code: &'static str,
+    pub field: &'static str,
+    pub message: String,
+}
+
+impl ValidationError {
+    fn new(field: &'static str, message: impl Into<String>) -> Self {
+        Self::with_code(ApiError::INVALID_REQUEST, field, message)
+    }
+
+    fn with_code(code: &'static str, field: &'static str, message: impl Into<String>) -> Self {
+        Self {
+            code,
+            field,
+            message: message.into(),
+        }
+    }
+}
+
+impl IntoResponse for ValidationError {
+    fn into_response(self) -> Response {
+        ApiError::new(self.code, self.message)
+            .with_details(serde_json::json!({ "field": self.field }))
+            .with_status(StatusCode::BAD_REQUEST)
+            .into_response()
+    }
+}
+
+/// Semantic validation for request payloads
+pub trait Validate {
+    fn validate(&self) -> Result<(), ValidationError>;
+}
+
+fn validate_chain_id(chain_id: u64) -> Result<(), ValidationError> {
+    ChainId::from_u64(chain_id)
+        .map(|_| ())
+        .ok_or_else(|| {
+            ValidationError::with_code(
+                ApiError::CHAIN_UNSUPPORTED,
+                "chain_id",
+                format!("Unknown chain ID: {}", chain_id),
+            )
+        })
+}
+
+fn validate_address(field: &'static str, value: &str) -> Result<(), ValidationError> {
+    crate::address::validate_address(value)
+        .map(|_| ())
+        .map_err(|e| ValidationError::with_code(ApiError::INVALID_ADDRESS, field, e.to_string()))
+}
+
+/// Require exactly one of `token_address` and `token_symbol`
+fn validate_token(address: &Option<String>, symbol: &Option<String>) -> Result<(), ValidationError> {
+    match (address, symbol) {
+        (Some(address), None) => validate_address("token_address", address),
+        (None, Some(symbol)) if symbol.trim().is_empty() => {
+            Err(ValidationError::new("token_symbol", "Token symbol must not be empty"))
+        }
+        (None, Some(_)) => Ok(()),
+        (Some(_), Some(_)) => Err(ValidationError::new(
+            "token_symbol",
+            "Provide either token_address or token_symbol, not both",
+        )),
+        (None, None) => Err(ValidationError::new(
+            "token_address",
+            "Either token_address or token_symbol is required",
+        )),
+    }
+}
+
+/// Map JSON/query extractor rejections into the error envelope
+fn rejection_response(status: StatusCode, message: String) -> Response {
+    let error = if status == StatusCode::PAYLOAD_TOO_LARGE {
+        ApiError::new(ApiError::PAYLOAD_TOO_LARGE, message).with_status(status)
+    } else {
+        ApiError::new(ApiError::INVALID_REQUEST, message).with_status(StatusCode::BAD_REQUEST)
+    };
+    error.into_response()
+}
+
+/// JSON body extractor that runs semantic validation
+pub struct ValidJson<T>(pub T);
+
+#[async_trait]
+impl<S, T> FromRequest<S> for ValidJson<T>
+where
+    T: DeserializeOwned + Validate,
+    S: Send + Sync,
+{
+    type Rejection = Response;
+
+    async fn from_request(req: Request, state: &S) -> Result<Self, Self::Rejection> {
+        let Json(value) = Json::<T>::from_request(req, state)
+            .await
+            .map_err(|e| rejection_response(e.status(), e.body_text()))?;
+        value.validate().map_err(IntoResponse::into_response)?;
+        Ok(Self(value))
+    }
+}
+
+/// Query string extractor that runs semantic validation
+pub struct ValidQuery<T>(pub T);
+
+#[async_trait]
+impl<S, T> FromRequestParts<S> for ValidQuery<T>
+where
+    T: DeserializeOwned + Validate,
+    S: Send + Sync,
+{
+    type Rejection = Response;
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        let Query(value) = Query::<T>::from_request_parts(parts, state)
+            .await
+            .map_err(|e| rejection_response(e.status(), e.body_text()))?;
+        value.validate().map_err(IntoResponse::into_response)?;
+        Ok(Self(value))
+    }
 }
 
 /// Health check response
@@ -31,22 +451,43 @@ pub struct HealthResponse {
     pub version: String,
     pub uptime_seconds: u64,
     pub rust_engine: bool,
+    /// Backend serving each model, so heuristics can't pass for ONNX
+    pub models: Vec<ModelStatus>,
+}
+
+/// API version response
+#[derive(Serialize)]
+pub struct VersionResponse {
+    pub version: String,
+    pub api_versions: Vec<String>,
+    pub git_sha: String,
 }
 
 /// Pool query request
 #[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
 pub struct PoolQueryRequest {
     pub chain_id: u64,
     pub pool_address: String,
     pub dex_type: String,
 }
 
+impl Validate for PoolQueryRequest {
+    fn validate(&self) -> Result<(), ValidationError> {
+        validate_chain_id(self.chain_id)?;
+        validate_address("pool_address", &self.pool_address)?;
+        self.dex_type
+            .parse::<DexKind>()
+            .map(|_| ())
+            .map_err(|e| ValidationError::with_code(ApiError::UNKNOWN_DEX, "dex_type", e))
+    }
+}
+
 /// Pool query response
 #[derive(Serialize)]
 pub struct PoolQueryResponse {
     pub pool_address: String,
     pub reserves: Option<Reserves>,
-    pub error: Option<String>,
 }
 
 #[derive(Serialize)]
@@ -65,16 +506,40 @@ pub struct MetricsResponse {
     pub queries_failed: u64,
     pub avg_response_time_ms: f64,
     pub uptime_seconds: u64,
+    pub quote_cache_hits: u64,
+    pub quote_cache_misses: u64,
+    /// Exact call counts, whatever the log sampling
+    pub tvl_requests: u64,
+    pub optimize_loan_requests: u64,
+    /// Model outputs clamped into 0-100 since startup
+    pub predictions_clamped: u64,
+    /// NaN model outputs replaced by the heuristic since startup
+    pub predictions_nan: u64,
+    pub model_versions: BTreeMap<String, String>,
 }
 
 /// TVL query request
 #[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
 pub struct TvlQueryRequest {
     pub chain_id: u64,
-    pub token_address: String,
+    pub token_address: Option<String>,
+    /// Registry symbol such as "USDC", instead of `token_address`
+    pub token_symbol: Option<String>,
     pub lender_address: Option<String>,
 }
 
+impl Validate for TvlQueryRequest {
+    fn validate(&self) -> Result<(), ValidationError> {
+        validate_chain_id(self.chain_id)?;
+        validate_token(&self.token_address, &self.token_symbol)?;
+        if let Some(lender) = &self.lender_address {
+            validate_address("lender_address", lender)?;
+        }
+        Ok(())
+    }
+}
+
 /// TVL query response
 #[derive(Serialize)]
 pub struct TvlQueryResponse {
@@ -83,16 +548,199 @@ pub struct TvlQueryResponse {
     pub token_address: String,
     pub lender_address: String,
     pub success: bool,
+}
+
+/// Batch TVL query request
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct TvlBatchRequest {
+    pub chain_id: u64,
+    pub token_addresses: Vec<String>,
+    pub lender_address: Option<String>,
+}
+
+impl Validate for TvlBatchRequest {
+    fn validate(&self) -> Result<(), ValidationError> {
+        validate_chain_id(self.chain_id)?;
+        if self.token_addresses.is_empty() || self.token_addresses.len() > MAX_TVL_BATCH_SIZE {
+            return Err(ValidationError::new(
+                "token_addresses",
+                format!("Between 1 and {} token addresses are required", MAX_TVL_BATCH_SIZE),
+            ));
+        }
+        for token in &self.token_addresses {
+            validate_address("token_addresses", token)?;
+        }
+        if let Some(lender) = &self.lender_address {
+            validate_address("lender_address", lender)?;
+        }
+        Ok(())
+    }
+}
+
+/// TVL of one token in a batch; `tvl` is absent when its call failed
+#[derive(Serialize)]
+pub struct TvlBatchItem {
+    pub token_address: String,
+    pub tvl: Option<String>,
     pub error: Option<String>,
 }
 
+/// Batch TVL query response, in request order
+#[derive(Serialize)]
+pub struct TvlBatchResponse {
+    pub chain_id: u64,
+    pub lender_address: String,
+    pub results: Vec<TvlBatchItem>,
+    pub success: bool,
+}
+
+/// Generic read-only contract call request
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ContractCallRequest {
+    pub chain_id: u64,
+    pub contract: String,
+    /// Human-readable `view` or `pure` signature, e.g.
+    /// `balanceOf(address) view returns (uint256)`
+    pub signature: String,
+    #[serde(default)]
+    pub args: Vec<serde_json::Value>,
+}
+
+impl Validate for ContractCallRequest {
+    fn validate(&self) -> Result<(), ValidationError> {
+        validate_chain_id(self.chain_id)?;
+        validate_address("contract", &self.contract)?;
+        parse_signature(&self.signature)
+            .map(|_| ())
+            .map_err(|e| ValidationError::with_code(ApiError::INVALID_SIGNATURE, "signature", e.to_string()))
+    }
+}
+
+/// Decoded outputs (empty without a `returns` clause) and the raw result
+#[derive(Serialize)]
+pub struct ContractCallResponse {
+    pub chain_id: u64,
+    pub contract: String,
+    pub function: String,
+    pub outputs: Vec<serde_json::Value>,
+    pub raw: String,
+    pub success: bool,
+}
+
+/// Unit of a typed amount value
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AmountUnit {
+    /// Integer in the token's smallest unit
+    Raw,
+    /// Decimal number of whole tokens, e.g. "1.5"
+    Human,
+}
+
+/// Amount with an explicit unit
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct TypedAmount {
+    pub value: String,
+    pub unit: AmountUnit,
+    pub decimals: u8,
+}
+
+/// Token amount: a bare raw-unit string or `{ value, unit, decimals }`
+#[derive(Debug, Clone, Deserialize)]
+#[serde(untagged)]
+pub enum AmountInput {
+    Raw(String),
+    Typed(TypedAmount),
+}
+
+/// Convert a human decimal string to raw units
+///
+/// Rejects values with more fractional digits than `decimals` rather than
+/// silently truncating them.
+fn parse_human_amount(value: &str, decimals: u8) -> Result<U256, String> {
+    let (whole, fraction) = value.split_once('.').unwrap_or((value, ""));
+    let digits = |part: &str| part.chars().all(|c| c.is_ascii_digit());
+    if (whole.is_empty() && fraction.is_empty()) || !digits(whole) || !digits(fraction) {
+        return Err(format!("Invalid decimal amount '{}'", value));
+    }
+    if fraction.len() > decimals as usize {
+        return Err(format!(
+            "Amount '{}' has more than {} fractional digits",
+            value, decimals
+        ));
+    }
+    let raw = format!("{}{:0<width$}", whole, fraction, width = decimals as usize);
+    U256::from_dec_str(&raw).map_err(|e| format!("Invalid amount '{}': {}", value, e))
+}
+
 /// Loan optimization request
 #[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
 pub struct LoanOptimizeRequest {
     pub chain_id: u64,
-    pub token_address: String,
-    pub target_amount: String,
-    pub decimals: u8,
+    pub token_address: Option<String>,
+    /// Registry symbol such as "USDC", instead of `token_address`
+    pub token_symbol: Option<String>,
+    pub target_amount: AmountInput,
+    /// Required for bare amounts; must match a typed amount's decimals if given
+    pub decimals: Option<u8>,
+}
+
+impl LoanOptimizeRequest {
+    /// Resolve the target amount to raw units and token decimals
+    pub fn resolve_amount(&self) -> Result<(U256, u8), ValidationError> {
+        let (amount, decimals) = match &self.target_amount {
+            AmountInput::Raw(value) => {
+                let decimals = self.decimals.ok_or_else(|| {
+                    ValidationError::new("decimals", "Decimals are required for a bare target amount")
+                })?;
+                let amount = U256::from_dec_str(value).map_err(|e| {
+                    ValidationError::new("target_amount", format!("Invalid target amount: {}", e))
+                })?;
+                (amount, decimals)
+            }
+            AmountInput::Typed(typed) => {
+                if let Some(decimals) = self.decimals.filter(|d| *d != typed.decimals) {
+                    return Err(ValidationError::new(
+                        "decimals",
+                        format!(
+                            "Decimals {} conflict with target_amount decimals {}",
+                            decimals, typed.decimals
+                        ),
+                    ));
+                }
+                let amount = match typed.unit {
+                    AmountUnit::Raw => U256::from_dec_str(&typed.value)
+                        .map_err(|e| format!("Invalid target amount: {}", e)),
+                    AmountUnit::Human => parse_human_amount(&typed.value, typed.decimals),
+                }
+                .map_err(|e| ValidationError::new("target_amount", e))?;
+                (amount, typed.decimals)
+            }
+        };
+
+        if decimals > MAX_DECIMALS {
+            return Err(ValidationError::new(
+                "decimals",
+                format!("Decimals must be at most {}, got {}", MAX_DECIMALS, decimals),
+            ));
+        }
+        if amount.is_zero() {
+            return Err(ValidationError::new("target_amount", "Target amount must be non-zero"));
+        }
+        Ok((amount, decimals))
+    }
+}
+
+impl Validate for LoanOptimizeRequest {
+    fn validate(&self) -> Result<(), ValidationError> {
+        validate_chain_id(self.chain_id)?;
+        validate_token(&self.token_address, &self.token_symbol)?;
+        self.resolve_amount().map(|_| ())
+    }
 }
 
 /// Loan optimization response
@@ -101,282 +749,748 @@ pub struct LoanOptimizeResponse {
     pub optimized_amount: String,
     pub chain_id: u64,
     pub success: bool,
-    pub error: Option<String>,
+}
+
+/// Swap simulation request, quoted through the chain's Uniswap V3 quoter
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SimulateRequest {
+    pub chain_id: u64,
+    pub token_in: String,
+    pub token_out: String,
+    /// Input amount in `token_in`'s raw units
+    pub amount_in: String,
+    /// Pool fee tier in hundredths of a bip
+    #[serde(default = "default_simulate_fee")]
+    pub fee: u32,
+    /// Share of the quoted output to keep in `min_amount_out`; the
+    /// commander default when omitted
+    #[serde(default)]
+    pub slippage_tolerance: Option<f64>,
+}
+
+fn default_simulate_fee() -> u32 {
+    3000
+}
+
+impl Validate for SimulateRequest {
+    fn validate(&self) -> Result<(), ValidationError> {
+        validate_chain_id(self.chain_id)?;
+        validate_address("token_in", &self.token_in)?;
+        validate_address("token_out", &self.token_out)?;
+        match U256::from_dec_str(&self.amount_in) {
+            Ok(amount) if !amount.is_zero() => {}
+            Ok(_) => return Err(ValidationError::new("amount_in", "Input amount must be non-zero")),
+            Err(e) => return Err(ValidationError::new("amount_in", format!("Invalid input amount: {}", e))),
+        }
+        if self.fee == 0 || self.fee >= 1_000_000 {
+            return Err(ValidationError::new("fee", format!("Fee tier must be between 1 and 999999, got {}", self.fee)));
+        }
+        if let Some(tolerance) = self.slippage_tolerance {
+            check_slippage_tolerance(tolerance)
+                .map_err(|e| ValidationError::new("slippage_tolerance", e.to_string()))?;
+        }
+        Ok(())
+    }
+}
+
+/// Swap simulation response
+#[derive(Serialize)]
+pub struct SimulateResponse {
+    pub chain_id: u64,
+    pub amount_in: String,
+    /// Quoted output of the swap
+    pub expected_amount_out: String,
+    /// Slippage floor on `expected_amount_out` the swap should enforce
+    pub min_amount_out: String,
+    pub success: bool,
 }
 
 /// Health check endpoint
-async fn health_check() -> impl IntoResponse {
+async fn health_check(State(state): State<AppState>) -> impl IntoResponse {
     let response = HealthResponse {
         status: "healthy".to_string(),
         version: env!("CARGO_PKG_VERSION").to_string(),
         uptime_seconds: 0, // TODO: Track actual uptime
         rust_engine: true,
+        models: model_bridge::status(&state.models),
     };
     
     Json(response)
 }
 
+/// Loaded configuration with secrets redacted
+async fn config_info(State(state): State<AppState>) -> impl IntoResponse {
+    Json(state.config.redacted())
+}
+
+/// API version endpoint
+async fn api_version() -> impl IntoResponse {
+    let response = VersionResponse {
+        version: env!("CARGO_PKG_VERSION").to_string(),
+        api_versions: SUPPORTED_API_VERSIONS.iter().map(|v| v.to_string()).collect(),
+        git_sha: env!("TITAN_GIT_SHA").to_string(),
+    };
+
+    Json(response)
+}
+
 /// Pool data query endpoint
 async fn query_pool(
     State(_state): State<AppState>,
-    Json(request): Json<PoolQueryRequest>,
-) -> impl IntoResponse {
+    ValidJson(request): ValidJson<PoolQueryRequest>,
+) -> ApiResult<PoolQueryResponse> {
+    // Checked by `validate`
+    let dex: DexKind = request
+        .dex_type
+        .parse()
+        .map_err(|e: String| ApiError::new(ApiError::UNKNOWN_DEX, e).with_status(StatusCode::BAD_REQUEST))?;
     info!(
         "Querying pool {} on chain {} ({})",
-        request.pool_address, request.chain_id, request.dex_type
+        request.pool_address, request.chain_id, dex
     );
     
     // TODO: Implement actual pool querying logic
-    // For now, return a placeholder response
-    
-    let response = PoolQueryResponse {
-        pool_address: request.pool_address.clone(),
-        reserves: None,
-        error: Some(format!(
-            "Pool querying for DEX '{}' on chain {} is not implemented yet; this endpoint currently returns a placeholder response.",
-            request.dex_type, request.chain_id
-        )),
-    };
-    
-    (StatusCode::NOT_IMPLEMENTED, Json(response))
+    Err(ApiError::new(
+        ApiError::NOT_IMPLEMENTED,
+        format!(
+            "Pool querying for DEX '{}' on chain {} is not implemented yet",
+            dex, request.chain_id
+        ),
+    )
+    .with_details(serde_json::json!({ "pool_address": request.pool_address }))
+    .with_status(StatusCode::NOT_IMPLEMENTED))
 }
 
 /// Metrics endpoint
-async fn metrics() -> impl IntoResponse {
+async fn metrics(State(state): State<AppState>) -> impl IntoResponse {
+    let counters = state.metrics_snapshot();
+    let sanitized = sanitization_counts();
     let response = MetricsResponse {
-        queries_total: 0,
-        queries_success: 0,
-        queries_failed: 0,
+        queries_total: counters.queries_total,
+        queries_success: counters.queries_success,
+        queries_failed: counters.queries_failed,
         avg_response_time_ms: 0.0,
         uptime_seconds: 0,
+        quote_cache_hits: counters.quote_cache_hits,
+        quote_cache_misses: counters.quote_cache_misses,
+        tvl_requests: counters.tvl_requests,
+        optimize_loan_requests: counters.optimize_loan_requests,
+        predictions_clamped: sanitized.clamped,
+        predictions_nan: sanitized.nan,
+        model_versions: state.models.versions(),
     };
     
     Json(response)
 }
 
+/// Error for a chain that passed validation but has no configuration
+fn chain_unsupported(chain_id: u64) -> (StatusCode, Json<ApiError>) {
+    ApiError::new(ApiError::CHAIN_UNSUPPORTED, format!("Chain {} not supported", chain_id))
+        .with_details(serde_json::json!({ "chain_id": chain_id }))
+        .with_status(StatusCode::BAD_REQUEST)
+}
+
+/// Parse an already-validated address field
+fn parse_address(field: &str, value: &str) -> Result<Address, (StatusCode, Json<ApiError>)> {
+    crate::address::validate_address(value).map_err(|e| {
+        ApiError::new(ApiError::INVALID_ADDRESS, format!("Invalid {}: {}", field, e))
+            .with_details(serde_json::json!({ "field": field }))
+            .with_status(StatusCode::BAD_REQUEST)
+    })
+}
+
+/// Resolve an already-validated token address or registry symbol
+fn resolve_token(
+    resolver: &TokenResolver,
+    chain_id: u64,
+    address: &Option<String>,
+    symbol: &Option<String>,
+) -> Result<Address, (StatusCode, Json<ApiError>)> {
+    match (address, symbol) {
+        (Some(address), _) => parse_address("token_address", address),
+        (None, Some(symbol)) => resolver.resolve(chain_id, symbol).ok_or_else(|| {
+            ApiError::new(
+                ApiError::UNKNOWN_TOKEN,
+                format!("Unknown token symbol '{}' on chain {}", symbol, chain_id),
+            )
+            .with_details(serde_json::json!({ "field": "token_symbol", "chain_id": chain_id }))
+            .with_status(StatusCode::BAD_REQUEST)
+        }),
+        (None, None) => Err(ApiError::new(
+            ApiError::INVALID_REQUEST,
+            "Either token_address or token_symbol is required",
+        )
+        .with_details(serde_json::json!({ "field": "token_address" }))
+        .with_status(StatusCode::BAD_REQUEST)),
+    }
+}
+
+/// Address or symbol a request names its token by, for logging
+fn token_label<'a>(address: &'a Option<String>, symbol: &'a Option<String>) -> &'a str {
+    address.as_deref().or(symbol.as_deref()).unwrap_or("-")
+}
+
+/// HTTP provider for a configured chain, shared through the provider manager
+///
+/// Providers warmed at startup or built by an earlier request are reused, so
+/// requests don't pay for a fresh client and connection pool each time.
+async fn chain_provider(
+    state: &AppState,
+    chain_id: u64,
+    rpc_url: &str,
+) -> Result<Arc<Provider<Http>>, (StatusCode, Json<ApiError>)> {
+    state.provider_manager.read().await.get_provider(chain_id, rpc_url).await.map_err(|e| {
+        ApiError::new(ApiError::PROVIDER_ERROR, format!("Failed to create provider: {}", e))
+            .with_status(StatusCode::INTERNAL_SERVER_ERROR)
+    })
+}
+
+/// Simulation engine for a configured chain, shared across requests
+///
+/// Sharing it lets requests in the same block reuse each other's quotes,
+/// see [`TitanSimulationEngine::advance_cycle`].
+async fn simulation_engine(
+    state: &AppState,
+    chain_id: u64,
+    rpc_url: &str,
+) -> Result<Arc<TitanSimulationEngine>, (StatusCode, Json<ApiError>)> {
+    if let Some(engine) = state.simulation_engines.lock().unwrap().get(&chain_id) {
+        return Ok(Arc::clone(engine));
+    }
+    let provider = chain_provider(state, chain_id, rpc_url).await?;
+    let engine = TitanSimulationEngine::new(chain_id, provider).with_offline(state.config.offline);
+    let mut engines = state.simulation_engines.lock().unwrap();
+    Ok(Arc::clone(engines.entry(chain_id).or_insert_with(|| Arc::new(engine))))
+}
+
 /// TVL query endpoint - Get Total Value Locked for a token
 async fn query_tvl(
     State(state): State<AppState>,
-    Query(request): Query<TvlQueryRequest>,
-) -> impl IntoResponse {
-    info!(
-        "Querying TVL for token {} on chain {}",
-        request.token_address, request.chain_id
-    );
+    ValidQuery(request): ValidQuery<TvlQueryRequest>,
+) -> ApiResult<TvlQueryResponse> {
+    if state.tvl_log.record() {
+        info!(
+            "Querying TVL for token {} on chain {}",
+            token_label(&request.token_address, &request.token_symbol),
+            request.chain_id
+        );
+    }
     
     // Get chain config
-    let chain_config = match state.config.get_chain(request.chain_id) {
-        Some(config) => config,
-        None => {
-            let response = TvlQueryResponse {
-                tvl: "0".to_string(),
-                chain_id: request.chain_id,
-                token_address: request.token_address.clone(),
-                lender_address: "".to_string(),
-                success: false,
-                error: Some(format!("Chain {} not supported", request.chain_id)),
-            };
-            return (StatusCode::BAD_REQUEST, Json(response));
-        }
-    };
+    let chain_config = state
+        .config
+        .get_chain(request.chain_id)
+        .ok_or_else(|| chain_unsupported(request.chain_id))?;
     
     // Use provided lender address or default to Balancer V3 Vault
     let lender_address = request.lender_address.unwrap_or_else(|| BALANCER_V3_VAULT.to_string());
     
     // Parse addresses
-    let token_addr = match request.token_address.parse::<Address>() {
-        Ok(addr) => addr,
-        Err(e) => {
-            let response = TvlQueryResponse {
-                tvl: "0".to_string(),
-                chain_id: request.chain_id,
-                token_address: request.token_address.clone(),
-                lender_address: lender_address.clone(),
-                success: false,
-                error: Some(format!("Invalid token address: {}", e)),
-            };
-            return (StatusCode::BAD_REQUEST, Json(response));
-        }
-    };
+    let token_addr = resolve_token(
+        &state.token_resolver,
+        request.chain_id,
+        &request.token_address,
+        &request.token_symbol,
+    )?;
+    let lender_addr = parse_address("lender_address", &lender_address)?;
+    let (token_address, lender_address) = (checksummed(&token_addr), checksummed(&lender_addr));
     
-    let lender_addr = match lender_address.parse::<Address>() {
-        Ok(addr) => addr,
-        Err(e) => {
-            let response = TvlQueryResponse {
-                tvl: "0".to_string(),
-                chain_id: request.chain_id,
-                token_address: request.token_address.clone(),
-                lender_address: lender_address.clone(),
-                success: false,
-                error: Some(format!("Invalid lender address: {}", e)),
-            };
-            return (StatusCode::BAD_REQUEST, Json(response));
-        }
-    };
+    // OFFLINE: deterministic simulated TVL, no RPC
+    if state.config.offline {
+        return Ok(Json(TvlQueryResponse {
+            tvl: simulated_tvl().to_string(),
+            chain_id: request.chain_id,
+            token_address,
+            lender_address,
+            success: true,
+        }));
+    }
     
     // Create provider
-    let provider = match Provider::<Http>::try_from(&chain_config.rpc) {
-        Ok(p) => Arc::new(p),
-        Err(e) => {
-            let response = TvlQueryResponse {
-                tvl: "0".to_string(),
-                chain_id: request.chain_id,
-                token_address: request.token_address.clone(),
-                lender_address: lender_address.clone(),
-                success: false,
-                error: Some(format!("Failed to create provider: {}", e)),
-            };
-            return (StatusCode::INTERNAL_SERVER_ERROR, Json(response));
-        }
-    };
+    let provider = chain_provider(&state, request.chain_id, &chain_config.rpc).await?;
     
     // Query TVL
     match get_provider_tvl(token_addr, lender_addr, provider).await {
-        Ok(tvl) => {
-            let response = TvlQueryResponse {
-                tvl: tvl.to_string(),
-                chain_id: request.chain_id,
-                token_address: request.token_address.clone(),
-                lender_address: lender_address.clone(),
-                success: true,
-                error: None,
-            };
-            (StatusCode::OK, Json(response))
-        }
+        Ok(tvl) => Ok(Json(TvlQueryResponse {
+            tvl: tvl.to_string(),
+            chain_id: request.chain_id,
+            token_address,
+            lender_address,
+            success: true,
+        })),
         Err(e) => {
             error!("TVL query failed: {}", e);
-            let response = TvlQueryResponse {
-                tvl: "0".to_string(),
-                chain_id: request.chain_id,
-                token_address: request.token_address.clone(),
-                lender_address: lender_address.clone(),
-                success: false,
-                error: Some(format!("TVL query failed: {}", e)),
-            };
-            (StatusCode::INTERNAL_SERVER_ERROR, Json(response))
+            Err(ApiError::from_rpc("TVL query failed", &e).with_status(StatusCode::INTERNAL_SERVER_ERROR))
         }
     }
 }
 
-/// Loan optimization endpoint - Optimize loan size based on liquidity
-async fn optimize_loan(
+/// Batch TVL endpoint - TVL for many tokens at one lender
+///
+/// `balanceOf` calls run with at most `tvl_batch_concurrency` in flight.
+async fn query_tvl_batch(
     State(state): State<AppState>,
-    Json(request): Json<LoanOptimizeRequest>,
-) -> impl IntoResponse {
+    ValidJson(request): ValidJson<TvlBatchRequest>,
+) -> ApiResult<TvlBatchResponse> {
     info!(
-        "Optimizing loan for token {} on chain {}, target: {}",
-        request.token_address, request.chain_id, request.target_amount
+        "Querying TVL for {} tokens on chain {}",
+        request.token_addresses.len(),
+        request.chain_id
     );
-    
-    // Get chain config
-    let chain_config = match state.config.get_chain(request.chain_id) {
-        Some(config) => config,
-        None => {
-            let response = LoanOptimizeResponse {
-                optimized_amount: "0".to_string(),
-                chain_id: request.chain_id,
-                success: false,
-                error: Some(format!("Chain {} not supported", request.chain_id)),
-            };
-            return (StatusCode::BAD_REQUEST, Json(response));
-        }
-    };
-    
-    // Parse token address
-    let token_addr = match request.token_address.parse::<Address>() {
-        Ok(addr) => addr,
-        Err(e) => {
-            let response = LoanOptimizeResponse {
-                optimized_amount: "0".to_string(),
-                chain_id: request.chain_id,
-                success: false,
-                error: Some(format!("Invalid token address: {}", e)),
-            };
-            return (StatusCode::BAD_REQUEST, Json(response));
-        }
+
+    let chain_config = state
+        .config
+        .get_chain(request.chain_id)
+        .ok_or_else(|| chain_unsupported(request.chain_id))?;
+
+    let lender_address = request.lender_address.unwrap_or_else(|| BALANCER_V3_VAULT.to_string());
+    let lender_addr = parse_address("lender_address", &lender_address)?;
+    let tokens = request
+        .token_addresses
+        .iter()
+        .map(|token| parse_address("token_addresses", token))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let results = if state.config.offline {
+        fetch_tvl_batch(&tokens, state.tvl_batch_concurrency, |_| async { Ok(simulated_tvl()) }).await
+    } else {
+        let reader: Arc<dyn ChainReader> = chain_provider(&state, request.chain_id, &chain_config.rpc).await?;
+        fetch_tvl_batch(&tokens, state.tvl_batch_concurrency, |token| {
+            get_provider_tvl(token, lender_addr, Arc::clone(&reader))
+        })
+        .await
     };
-    
-    // Parse target amount
-    let target_amount = match request.target_amount.parse::<U256>() {
-        Ok(amount) => amount,
+
+    let results: Vec<TvlBatchItem> = tokens
+        .iter()
+        .map(checksummed)
+        .zip(results)
+        .map(|(token_address, result)| match result {
+            Ok(tvl) => TvlBatchItem { token_address, tvl: Some(tvl.to_string()), error: None },
+            Err(e) => TvlBatchItem { token_address, tvl: None, error: Some(e.to_string()) },
+        })
+        .collect();
+    Ok(Json(TvlBatchResponse {
+        chain_id: request.chain_id,
+        lender_address: checksummed(&lender_addr),
+        success: results.iter().all(|item| item.error.is_none()),
+        results,
+    }))
+}
+
+/// Generic contract call endpoint - ABI-encode, `eth_call` and decode
+///
+/// An arbitrary-read escape hatch for debugging, off unless enabled. Calls
+/// carry no value or sender and `eth_call` never commits state.
+async fn contract_call(
+    State(state): State<AppState>,
+    ValidJson(request): ValidJson<ContractCallRequest>,
+) -> ApiResult<ContractCallResponse> {
+    if !state.call_enabled {
+        return Err(ApiError::new(
+            ApiError::ENDPOINT_DISABLED,
+            "Contract calls are disabled; set RUST_SERVER_ENABLE_CALL=1 to enable",
+        )
+        .with_status(StatusCode::FORBIDDEN));
+    }
+    info!("Calling {} on {} (chain {})", request.signature, request.contract, request.chain_id);
+
+    let chain_config = state
+        .config
+        .get_chain(request.chain_id)
+        .ok_or_else(|| chain_unsupported(request.chain_id))?;
+    let contract = parse_address("contract", &request.contract)?;
+    let invalid = |code: &str, field: &str, e: anyhow::Error| {
+        ApiError::new(code, e.to_string())
+            .with_details(serde_json::json!({ "field": field }))
+            .with_status(StatusCode::BAD_REQUEST)
+    };
+    let function = parse_signature(&request.signature)
+        .map_err(|e| invalid(ApiError::INVALID_SIGNATURE, "signature", e))?;
+    encode_call(&function, &request.args).map_err(|e| invalid(ApiError::INVALID_REQUEST, "args", e))?;
+
+    let provider = chain_provider(&state, request.chain_id, &chain_config.rpc).await?;
+    match call_function(&provider, contract, &function, &request.args).await {
+        Ok((raw, outputs)) => Ok(Json(ContractCallResponse {
+            chain_id: request.chain_id,
+            contract: checksummed(&contract),
+            function: function.signature(),
+            outputs,
+            raw: raw.to_string(),
+            success: true,
+        })),
         Err(e) => {
-            let response = LoanOptimizeResponse {
-                optimized_amount: "0".to_string(),
-                chain_id: request.chain_id,
-                success: false,
-                error: Some(format!("Invalid target amount: {}", e)),
-            };
-            return (StatusCode::BAD_REQUEST, Json(response));
+            error!("Contract call failed: {}", e);
+            Err(ApiError::from_rpc("Contract call failed", &e).with_status(StatusCode::BAD_GATEWAY))
         }
+    }
+}
+
+/// Loan optimization endpoint - Optimize loan size based on liquidity
+async fn optimize_loan(
+    State(state): State<AppState>,
+    ValidJson(request): ValidJson<LoanOptimizeRequest>,
+) -> ApiResult<LoanOptimizeResponse> {
+    if state.optimize_loan_log.record() {
+        info!(
+            "Optimizing loan for token {} on chain {}, target: {:?}",
+            token_label(&request.token_address, &request.token_symbol),
+            request.chain_id,
+            request.target_amount
+        );
+    }
+    
+    // Get chain config
+    let chain_config = state
+        .config
+        .get_chain(request.chain_id)
+        .ok_or_else(|| chain_unsupported(request.chain_id))?;
+    
+    // Parse or resolve token address
+    let token_addr = resolve_token(
+        &state.token_resolver,
+        request.chain_id,
+        &request.token_address,
+        &request.token_symbol,
+    )?;
+    
+    // Resolve target amount to raw units
+    let (target_amount, decimals) = request.resolve_amount().map_err(|e| {
+        ApiError::new(e.code, e.message)
+            .with_details(serde_json::json!({ "field": e.field }))
+            .with_status(StatusCode::BAD_REQUEST)
+    })?;
+    
+    // Create commander (offline commanders never dial the RPC)
+    let commander = if state.config.offline {
+        TitanCommander::new_offline(request.chain_id).map_err(|e| {
+            ApiError::new(ApiError::PROVIDER_ERROR, e.to_string())
+                .with_status(StatusCode::INTERNAL_SERVER_ERROR)
+        })?
+    } else {
+        TitanCommander::new(request.chain_id, chain_provider(&state, request.chain_id, &chain_config.rpc).await?)
     };
     
-    // Create provider
-    let provider = match Provider::<Http>::try_from(&chain_config.rpc) {
-        Ok(p) => Arc::new(p),
+    // Optimize
+    
+    match commander.optimize_loan_size(token_addr, target_amount, decimals).await {
+        Ok(optimized) => Ok(Json(LoanOptimizeResponse {
+            optimized_amount: optimized.to_string(),
+            chain_id: request.chain_id,
+            success: true,
+        })),
         Err(e) => {
-            let response = LoanOptimizeResponse {
-                optimized_amount: "0".to_string(),
-                chain_id: request.chain_id,
-                success: false,
-                error: Some(format!("Failed to create provider: {}", e)),
-            };
-            return (StatusCode::INTERNAL_SERVER_ERROR, Json(response));
+            error!("Loan optimization failed: {}", e);
+            Err(ApiError::from_rpc("Loan optimization failed", &e)
+                .with_status(StatusCode::INTERNAL_SERVER_ERROR))
+        }
+    }
+}
+
+/// Swap simulation endpoint - quote a swap and its slippage-protected floor
+async fn simulate(
+    State(state): State<AppState>,
+    ValidJson(request): ValidJson<SimulateRequest>,
+) -> ApiResult<SimulateResponse> {
+    info!(
+        "Simulating swap {} -> {} on chain {}, amount: {}",
+        request.token_in, request.token_out, request.chain_id, request.amount_in
+    );
+
+    let chain_config = state
+        .config
+        .get_chain(request.chain_id)
+        .ok_or_else(|| chain_unsupported(request.chain_id))?;
+    let quoter = uniswap_v3_quoter(request.chain_id).ok_or_else(|| {
+        ApiError::new(
+            ApiError::CHAIN_UNSUPPORTED,
+            format!("No Uniswap V3 quoter on chain {}", request.chain_id),
+        )
+        .with_details(serde_json::json!({ "chain_id": request.chain_id }))
+        .with_status(StatusCode::BAD_REQUEST)
+    })?;
+    let token_in = parse_address("token_in", &request.token_in)?;
+    let token_out = parse_address("token_out", &request.token_out)?;
+    // Checked by `validate`
+    let amount_in = U256::from_dec_str(&request.amount_in).unwrap_or_default();
+
+    // Offline engines answer from the simulated pool and never dial the RPC
+    let provider = chain_provider(&state, request.chain_id, &chain_config.rpc).await?;
+    let engine = simulation_engine(&state, request.chain_id, &chain_config.rpc).await?;
+    if !state.config.offline {
+        // Quote at the current block, reusing quotes other requests made in it
+        match engine.get_block_number().await {
+            Ok(block) => engine.advance_cycle(block),
+            Err(e) => {
+                warn!("Chain {} block unavailable, quoting uncached: {}", request.chain_id, e);
+                engine.clear_quote_cache();
+            }
+        }
+    }
+    let mut commander = TitanCommander::new(request.chain_id, provider);
+    if let Some(tolerance) = request.slippage_tolerance {
+        // Checked by `validate`
+        commander.set_slippage_tolerance(tolerance).ok();
+    }
+
+    match engine
+        .try_get_price_impact(token_in, token_out, amount_in, request.fee, quoter)
+        .await
+    {
+        Ok(expected_out) => Ok(Json(SimulateResponse {
+            chain_id: request.chain_id,
+            amount_in: amount_in.to_string(),
+            expected_amount_out: expected_out.to_string(),
+            min_amount_out: commander.min_amount_out(expected_out).to_string(),
+            success: true,
+        })),
+        Err(e) => {
+            error!("Swap simulation failed: {}", e);
+            Err(ApiError::from_rpc("Swap simulation failed", &e).with_status(StatusCode::BAD_GATEWAY))
         }
+    }
+}
+
+/// Model reload request; no name reloads every model
+#[derive(Deserialize, Default)]
+#[serde(deny_unknown_fields)]
+pub struct ModelReloadRequest {
+    pub name: Option<String>,
+}
+
+/// Models as loaded after a reload
+#[derive(Serialize)]
+pub struct ModelReloadResponse {
+    pub models: Vec<LoadedModel>,
+}
+
+/// Re-read model files from disk without restarting the server
+///
+/// The endpoint is unauthenticated, so it is off unless enabled.
+async fn reload_models(
+    State(state): State<AppState>,
+    request: Option<Json<ModelReloadRequest>>,
+) -> ApiResult<ModelReloadResponse> {
+    if !state.admin_enabled {
+        return Err(ApiError::new(
+            ApiError::ENDPOINT_DISABLED,
+            "Model reloads are disabled; set RUST_SERVER_ENABLE_ADMIN=1 to enable",
+        )
+        .with_status(StatusCode::FORBIDDEN));
+    }
+    let request = request.map(|Json(request)| request).unwrap_or_default();
+    let reloaded = match &request.name {
+        Some(name) => state.models.reload(name).map(|model| vec![model]),
+        None => state.models.reload_all(),
     };
-    
-    // Create commander and optimize
-    let commander = TitanCommander::new(request.chain_id, provider);
-    
-    match commander.optimize_loan_size(token_addr, target_amount, request.decimals).await {
-        Ok(optimized) => {
-            let response = LoanOptimizeResponse {
-                optimized_amount: optimized.to_string(),
-                chain_id: request.chain_id,
-                success: true,
-                error: None,
-            };
-            (StatusCode::OK, Json(response))
+    match reloaded {
+        Ok(models) => Ok(Json(ModelReloadResponse {
+            models: models.iter().map(|model| LoadedModel::clone(model)).collect(),
+        })),
+        Err(e @ ModelError::Unknown(_)) => {
+            Err(ApiError::new(ApiError::UNKNOWN_MODEL, e.to_string()).with_status(StatusCode::NOT_FOUND))
         }
         Err(e) => {
-            error!("Loan optimization failed: {}", e);
-            let response = LoanOptimizeResponse {
-                optimized_amount: "0".to_string(),
-                chain_id: request.chain_id,
-                success: false,
-                error: Some(format!("Loan optimization failed: {}", e)),
-            };
-            (StatusCode::INTERNAL_SERVER_ERROR, Json(response))
+            error!("Model reload failed: {}", e);
+            Err(ApiError::new(ApiError::MODEL_LOAD_FAILED, e.to_string())
+                .with_status(StatusCode::INTERNAL_SERVER_ERROR))
+        }
+    }
+}
+
+/// Score the configured matrix on a spawned task
+///
+/// Answers with the finished run when it completes within
+/// [`RUN_RESPONSE_WAIT`]; otherwise with `202 Accepted` and the running
+/// run, whose results `GET /api/v1/run/{id}` serves once done. Off unless
+/// enabled, and `409 Conflict` while another run is still going.
+async fn start_run(State(state): State<AppState>) -> Result<impl IntoResponse, (StatusCode, Json<ApiError>)> {
+    if !state.run_enabled {
+        return Err(ApiError::new(
+            ApiError::ENDPOINT_DISABLED,
+            "Scoring runs are disabled; set RUST_SERVER_ENABLE_RUN=1 to enable",
+        )
+        .with_status(StatusCode::FORBIDDEN));
+    }
+    let matrix_path = state.config.matrix_path.clone();
+    let id = state.runs.start(&matrix_path).map_err(|running| {
+        ApiError::new(ApiError::RUN_IN_PROGRESS, format!("Scoring run {} is still running", running))
+            .with_details(serde_json::json!({ "id": running }))
+            .with_status(StatusCode::CONFLICT)
+    })?;
+    info!("Scoring run {} started on {}", id, matrix_path);
+
+    let runs = Arc::clone(&state.runs);
+    let scoring = state.clone();
+    let mut task = tokio::spawn(async move {
+        // Scored on its own task so a panic still finishes the run
+        let outcome = tokio::spawn(async move {
+            score_matrix_file(&scoring.config, &scoring.quote_router, &scoring.models, &matrix_path)
+                .await
+                .map_err(|e| e.to_string())
+        })
+        .await
+        .unwrap_or_else(|e| Err(format!("Scoring run aborted: {}", e)));
+        match &outcome {
+            Ok(outcome) => info!(
+                "Scoring run {} completed: {} routes, {} above threshold",
+                id, outcome.summary.routes_analyzed, outcome.summary.above_threshold
+            ),
+            Err(e) => error!("Scoring run {} failed: {}", id, e),
         }
+        runs.finish(id, outcome);
+    });
+
+    let status = match tokio::time::timeout(RUN_RESPONSE_WAIT, &mut task).await {
+        Ok(_) => StatusCode::OK,
+        Err(_) => StatusCode::ACCEPTED,
+    };
+    Ok((status, Json(state.runs.get(id))))
+}
+
+/// Status and, once finished, results of a scoring run
+async fn get_run(
+    State(state): State<AppState>,
+    Path(id): Path<u64>,
+) -> Result<impl IntoResponse, (StatusCode, Json<ApiError>)> {
+    state.runs.get(id).map(Json).ok_or_else(|| {
+        ApiError::new(ApiError::UNKNOWN_RUN, format!("No scoring run {}", id)).with_status(StatusCode::NOT_FOUND)
+    })
+}
+
+/// Routes served by a given API version, relative to `/api/<version>`
+fn versioned_routes(version: &str) -> Router<AppState> {
+    match version {
+        "v1" => Router::new()
+            .route("/pool", post(query_pool))
+            .route("/metrics", get(metrics))
+            .route("/config", get(config_info))
+            .route("/tvl", get(query_tvl))
+            .route("/tvl_batch", post(query_tvl_batch))
+            .route("/call", post(contract_call))
+            .route("/optimize_loan", post(optimize_loan))
+            .route("/simulate", post(simulate))
+            .route("/admin/models/reload", post(reload_models))
+            .route("/run", post(start_run))
+            .route("/run/:id", get(get_run)),
+        _ => Router::new(),
     }
 }
 
+/// Count every matched API request towards `queries_*`
+async fn count_query(State(state): State<AppState>, request: Request, next: Next) -> Response {
+    let response = next.run(request).await;
+    state.requests.record(response.status());
+    response
+}
+
+/// Mark responses from unprefixed `/api/...` aliases as deprecated
+async fn mark_deprecated(mut response: Response) -> Response {
+    response
+        .headers_mut()
+        .insert("deprecation", HeaderValue::from_static("true"));
+    response
+}
+
 /// Build and configure the HTTP server router
 pub fn create_router(state: AppState) -> Router {
-    Router::new()
+    let mut router = Router::new()
         .route("/health", get(health_check))
-        .route("/api/pool", post(query_pool))
-        .route("/api/metrics", get(metrics))
-        .route("/api/tvl", get(query_tvl))
-        .route("/api/optimize_loan", post(optimize_loan))
+        .route("/api/version", get(api_version));
+
+    let count_queries = middleware::from_fn_with_state(state.clone(), count_query);
+    for version in SUPPORTED_API_VERSIONS {
+        let routes = versioned_routes(version).route_layer(count_queries.clone());
+        router = router.nest(&format!("/api/{}", version), routes);
+    }
+
+    // Legacy unprefixed aliases
+    let legacy = versioned_routes(LEGACY_API_VERSION)
+        .route_layer(count_queries)
+        .layer(middleware::map_response(mark_deprecated));
+
+    let body_limit = state.body_limit;
+    let compression = state.compression;
+    // Correlation id: kept from the client's `X-Request-Id` or generated,
+    // recorded on the request's span and echoed in the response
+    let request_id = HeaderName::from_static(REQUEST_ID_HEADER);
+    let router = router
+        .nest("/api", legacy)
+        .layer(DefaultBodyLimit::max(body_limit))
         .layer(CorsLayer::permissive())
-        .with_state(state)
+        .layer(PropagateRequestIdLayer::new(request_id.clone()))
+        .layer(TraceLayer::new_for_http().make_span_with(request_span))
+        .layer(SetRequestIdLayer::new(request_id, MakeRequestUuid))
+        .with_state(state);
+    if compression {
+        router.layer(CompressionLayer::new())
+    } else {
+        router
+    }
+}
+
+/// Chains whose providers answered during [`warm_up_providers`], and those that didn't
+#[derive(Debug, Default)]
+pub struct WarmupReport {
+    /// Chain id and the block it reported
+    pub warmed: Vec<(u64, u64)>,
+    /// Chain id and why it failed
+    pub failed: Vec<(u64, String)>,
+}
+
+/// Create and probe every configured chain's provider ahead of the first request
+///
+/// Each chain gets `timeout` to report its block number; a dead chain is
+/// logged and reported, never an error.
+pub async fn warm_up_providers(state: &AppState, timeout: Duration) -> WarmupReport {
+    let chains: Vec<(u64, String)> = state
+        .config
+        .chains
+        .iter()
+        .map(|(chain_id, chain)| (*chain_id, chain.rpc.clone()))
+        .collect();
+    let blocks = state
+        .provider_manager
+        .read()
+        .await
+        .current_blocks_with_timeout(&chains, timeout)
+        .await;
+
+    let mut report = WarmupReport::default();
+    for (chain_id, block) in blocks {
+        match block {
+            Ok(block) => report.warmed.push((chain_id, block)),
+            Err(e) => {
+                warn!("Chain {} failed to warm up: {}", chain_id, e);
+                report.failed.push((chain_id, e.to_string()));
+            }
+        }
+    }
+    report.warmed.sort_unstable();
+    report.failed.sort_unstable();
+    report
+}
+
+/// Header carrying the request's correlation id
+pub const REQUEST_ID_HEADER: &str = "x-request-id";
+
+/// Span every request in, tagged with its correlation id
+fn request_span(request: &Request) -> tracing::Span {
+    let request_id = request
+        .headers()
+        .get(REQUEST_ID_HEADER)
+        .and_then(|id| id.to_str().ok())
+        .unwrap_or_default();
+    tracing::info_span!("request", request_id, method = %request.method(), uri = %request.uri())
 }
 
 /// Start the HTTP server
+///
+/// Runs until Ctrl-C or SIGTERM, then finishes in-flight requests.
 pub async fn start_server(config: Config, port: u16) -> Result<(), Box<dyn std::error::Error>> {
     info!("🚀 Starting Titan Rust HTTP Server on port {}", port);
-    
-    // Initialize provider manager
-    let provider_manager = ProviderManager::new();
-    
-    // Create shared state
-    let state = AppState {
-        config: Arc::new(config),
-        provider_manager: Arc::new(RwLock::new(provider_manager)),
-    };
-    
-    // Build router
-    let app = create_router(state);
+    let (state, snapshots) = prepare_state(config).await?;
     
     // Bind to address
     let addr = format!("0.0.0.0:{}", port);
@@ -384,15 +1498,343 @@ pub async fn start_server(config: Config, port: u16) -> Result<(), Box<dyn std::
     
     info!("✅ Rust HTTP Server listening on {}", addr);
     
-    // Start server
-    axum::serve(listener, app).await?;
+    serve(listener, state, snapshots, shutdown_signal()).await?;
+    info!("👋 Rust HTTP Server stopped");
     
     Ok(())
 }
 
+/// Resolves on Ctrl-C or, on Unix, SIGTERM
+async fn shutdown_signal() {
+    let ctrl_c = async {
+        if let Err(e) = tokio::signal::ctrl_c().await {
+            error!("Failed to listen for Ctrl-C: {}", e);
+            std::future::pending::<()>().await;
+        }
+    };
+    #[cfg(unix)]
+    let terminate = async {
+        match tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate()) {
+            Ok(mut signal) => {
+                signal.recv().await;
+            }
+            Err(e) => {
+                error!("Failed to listen for SIGTERM: {}", e);
+                std::future::pending::<()>().await;
+            }
+        }
+    };
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        _ = ctrl_c => {},
+        _ = terminate => {},
+    }
+    info!("🛑 Shutting down, finishing in-flight requests");
+}
+
+/// Serve `state` on `listener` until `shutdown` resolves
+///
+/// With snapshots configured, counters are saved every interval and once
+/// more after the last in-flight request, so a graceful stop loses none
+/// counted since the previous tick. HTTP/1.1 and cleartext HTTP/2 are both
+/// accepted.
+async fn serve(
+    listener: tokio::net::TcpListener,
+    state: AppState,
+    snapshots: Option<SnapshotSettings>,
+    shutdown: impl std::future::Future<Output = ()> + Send + 'static,
+) -> std::io::Result<()> {
+    let periodic = snapshots
+        .as_ref()
+        .map(|settings| spawn_metrics_snapshots(state.clone(), settings.path.clone(), settings.interval));
+    let result = axum::serve(listener, create_router(state.clone()))
+        .with_graceful_shutdown(shutdown)
+        .await;
+    if let Some(periodic) = periodic {
+        periodic.abort();
+    }
+    if let Some(settings) = &snapshots {
+        save_metrics_snapshot(&state, &settings.path);
+    }
+    result
+}
+
+/// Server state configured from the `RUST_SERVER_*` and model variables,
+/// with providers warmed up, and where to snapshot its metrics
+async fn prepare_state(config: Config) -> Result<(AppState, Option<SnapshotSettings>), Box<dyn std::error::Error>> {
+    // Request body limit (bytes)
+    let body_limit = std::env::var("RUST_SERVER_BODY_LIMIT")
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(DEFAULT_BODY_LIMIT);
+    
+    // Concurrent RPC calls per TVL batch, overriding the runtime profile
+    info!("📐 Runtime profile: {:?}", config.runtime);
+    let tvl_batch_concurrency = std::env::var("RUST_SERVER_TVL_BATCH_CONCURRENCY")
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(config.runtime.max_concurrency);
+    
+    // Arbitrary-read contract calls are opt-in
+    let call_enabled = std::env::var("RUST_SERVER_ENABLE_CALL")
+        .map(|v| matches!(v.trim().to_ascii_lowercase().as_str(), "1" | "true" | "yes"))
+        .unwrap_or(false);
+    
+    // Unauthenticated model reloads are opt-in
+    let admin_enabled = std::env::var("RUST_SERVER_ENABLE_ADMIN")
+        .map(|v| matches!(v.trim().to_ascii_lowercase().as_str(), "1" | "true" | "yes"))
+        .unwrap_or(false);
+    
+    // Scoring runs over the configured matrix are opt-in
+    let run_enabled = std::env::var("RUST_SERVER_ENABLE_RUN")
+        .map(|v| matches!(v.trim().to_ascii_lowercase().as_str(), "1" | "true" | "yes"))
+        .unwrap_or(false);
+    
+    // Response compression is on unless disabled
+    let compression = std::env::var("RUST_SERVER_COMPRESSION")
+        .map(|v| !matches!(v.trim().to_ascii_lowercase().as_str(), "0" | "false" | "no"))
+        .unwrap_or(true);
+    
+    // Only 1-in-N `/tvl` and `/optimize_loan` info logs
+    let log_sample_every = std::env::var("RUST_SERVER_LOG_SAMPLE_EVERY")
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(DEFAULT_LOG_SAMPLE_EVERY);
+    
+    // TAR/Flanker model files (TAR_MODEL_PATH, FLANKER_MODEL_PATH);
+    // TITAN_REQUIRE_MODELS makes a failed load fatal
+    let models = ModelRegistry::from_env()?;
+    for status in model_bridge::status(&models) {
+        info!("🧠 {} model: {:?} {}", status.name, status.backend, status.version.as_deref().unwrap_or(""));
+    }
+    
+    // Counters persisted across restarts when a snapshot path is set
+    let metrics_snapshot = std::env::var("RUST_SERVER_METRICS_SNAPSHOT").ok().map(PathBuf::from);
+    let snapshot_interval = std::env::var("RUST_SERVER_METRICS_SNAPSHOT_INTERVAL_SECS")
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .filter(|secs| *secs > 0)
+        .map(Duration::from_secs)
+        .unwrap_or(DEFAULT_SNAPSHOT_INTERVAL);
+    let restored = match &metrics_snapshot {
+        Some(path) => MetricsSnapshot::load(path).unwrap_or_else(|e| {
+            warn!("Ignoring metrics snapshot {}: {}", path.display(), e);
+            MetricsSnapshot::default()
+        }),
+        None => MetricsSnapshot::default(),
+    };
+    
+    // Create shared state
+    let state = AppState::new(config)
+        .with_models(models)
+        .with_body_limit(body_limit)
+        .with_tvl_batch_concurrency(tvl_batch_concurrency)
+        .with_call_enabled(call_enabled)
+        .with_admin_enabled(admin_enabled)
+        .with_run_enabled(run_enabled)
+        .with_compression(compression)
+        .with_log_sample_every(log_sample_every)
+        .with_restored_metrics(restored);
+    
+    let snapshots = metrics_snapshot.map(|path| {
+        info!("📈 Saving metrics to {} every {:?} and on shutdown", path.display(), snapshot_interval);
+        SnapshotSettings { path, interval: snapshot_interval }
+    });
+    
+    // Provider warm-up trades startup time for fast first requests (WARMUP=1)
+    let warmup = std::env::var("WARMUP")
+        .map(|v| matches!(v.trim().to_ascii_lowercase().as_str(), "1" | "true" | "yes"))
+        .unwrap_or(false);
+    if warmup && !state.config.offline {
+        let report = warm_up_providers(&state, state.config.runtime.rpc_timeout).await;
+        let warmed: Vec<_> = report.warmed.iter().map(|(chain_id, _)| chain_id).collect();
+        let failed: Vec<_> = report.failed.iter().map(|(chain_id, _)| chain_id).collect();
+        info!("🔥 Warmed up chains {:?}; failed {:?}", warmed, failed);
+    }
+    
+    Ok((state, snapshots))
+}
+
+/// Why a background server couldn't start or stop
+#[derive(Debug, thiserror::Error)]
+pub enum ServerError {
+    #[error("Could not bind port {port}: {source}")]
+    Bind { port: u16, source: std::io::Error },
+    #[error("Server failed to start: {0}")]
+    Startup(String),
+    #[error("Server failed: {0}")]
+    Serve(String),
+    #[error("Server did not stop within {0:?}")]
+    StopTimeout(Duration),
+}
+
+/// HTTP server running on its own thread and tokio runtime
+///
+/// Dropping the handle asks the server to shut down without waiting for it.
+pub struct ServerHandle {
+    port: u16,
+    shutdown: Option<tokio::sync::oneshot::Sender<()>>,
+    finished: std::sync::mpsc::Receiver<Result<(), String>>,
+    thread: Option<std::thread::JoinHandle<()>>,
+}
+
+impl ServerHandle {
+    /// Bind `port` (0 picks a free one) and serve `config` in the background
+    ///
+    /// Returns once the server accepts connections, so binding and startup
+    /// failures are reported here rather than lost on the server thread.
+    pub fn spawn(config: Config, port: u16) -> Result<Self, ServerError> {
+        let bind_error = |source| ServerError::Bind { port, source };
+        let listener = std::net::TcpListener::bind(("0.0.0.0", port)).map_err(bind_error)?;
+        listener.set_nonblocking(true).map_err(bind_error)?;
+        let port = listener.local_addr().map_err(bind_error)?.port();
+
+        let (ready_tx, ready) = std::sync::mpsc::channel();
+        let (finished_tx, finished) = std::sync::mpsc::channel();
+        let (shutdown, shutdown_rx) = tokio::sync::oneshot::channel::<()>();
+        let thread = std::thread::Builder::new()
+            .name("titan-http-server".to_string())
+            .spawn(move || {
+                let runtime = match tokio::runtime::Runtime::new() {
+                    Ok(runtime) => runtime,
+                    Err(e) => {
+                        let _ = ready_tx.send(Err(e.to_string()));
+                        return;
+                    }
+                };
+                let result = runtime.block_on(async move {
+                    let started = async {
+                        let (state, snapshots) = prepare_state(config).await.map_err(|e| e.to_string())?;
+                        let listener = tokio::net::TcpListener::from_std(listener).map_err(|e| e.to_string())?;
+                        Ok::<_, String>((state, snapshots, listener))
+                    };
+                    let (state, snapshots, listener) = match started.await {
+                        Ok(started) => started,
+                        Err(e) => {
+                            let _ = ready_tx.send(Err(e));
+                            return Ok(());
+                        }
+                    };
+                    info!("✅ Rust HTTP Server listening on 0.0.0.0:{}", port);
+                    let _ = ready_tx.send(Ok(()));
+                    let shutdown = async {
+                        let _ = shutdown_rx.await;
+                    };
+                    serve(listener, state, snapshots, shutdown).await.map_err(|e| e.to_string())
+                });
+                let _ = finished_tx.send(result);
+            })
+            .map_err(|e| ServerError::Startup(e.to_string()))?;
+
+        let started = ready
+            .recv()
+            .unwrap_or_else(|_| Err("server thread exited during startup".to_string()));
+        if let Err(e) = started {
+            let _ = thread.join();
+            return Err(ServerError::Startup(e));
+        }
+        Ok(Self {
+            port,
+            shutdown: Some(shutdown),
+            finished,
+            thread: Some(thread),
+        })
+    }
+
+    /// The port actually bound
+    pub fn port(&self) -> u16 {
+        self.port
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.thread.as_ref().is_some_and(|thread| !thread.is_finished())
+    }
+
+    /// Stop accepting connections and wait up to `timeout` for in-flight
+    /// requests to finish and the final metrics snapshot to be saved
+    ///
+    /// Stopping a stopped server does nothing.
+    pub fn stop(&mut self, timeout: Duration) -> Result<(), ServerError> {
+        if let Some(shutdown) = self.shutdown.take() {
+            let _ = shutdown.send(());
+        }
+        let Some(thread) = self.thread.take() else {
+            return Ok(());
+        };
+        match self.finished.recv_timeout(timeout) {
+            Ok(result) => {
+                let _ = thread.join();
+                result.map_err(ServerError::Serve)
+            }
+            Err(std::sync::mpsc::RecvTimeoutError::Timeout) => {
+                self.thread = Some(thread);
+                Err(ServerError::StopTimeout(timeout))
+            }
+            Err(std::sync::mpsc::RecvTimeoutError::Disconnected) => {
+                let _ = thread.join();
+                Ok(())
+            }
+        }
+    }
+}
+
+impl Drop for ServerHandle {
+    fn drop(&mut self) {
+        if let Some(shutdown) = self.shutdown.take() {
+            let _ = shutdown.send(());
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use axum::body::{to_bytes, Body};
+    use axum::http::Request;
+    use tower::ServiceExt;
+
+    fn test_state() -> AppState {
+        AppState::new(Config::default())
+    }
+
+    async fn post_json(uri: &str, body: String) -> Response {
+        create_router(test_state())
+            .oneshot(
+                Request::post(uri)
+                    .header("content-type", "application/json")
+                    .body(Body::from(body))
+                    .unwrap(),
+            )
+            .await
+            .unwrap()
+    }
+
+    async fn error_field(response: Response) -> Option<String> {
+        let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        json["details"]["field"].as_str().map(str::to_string)
+    }
+
+    fn loan_body(chain_id: u64, token: &str, amount: &str, decimals: u32) -> String {
+        serde_json::json!({
+            "chain_id": chain_id,
+            "token_address": token,
+            "target_amount": amount,
+            "decimals": decimals,
+        })
+        .to_string()
+    }
+
+    const USDC: &str = "0x2791Bca1f2de4661ED88A30C99A7a9449Aa84174";
+
+    async fn get_response(uri: &str) -> Response {
+        create_router(test_state())
+            .oneshot(Request::get(uri).body(Body::empty()).unwrap())
+            .await
+            .unwrap()
+    }
     
     #[test]
     fn test_config_default() {
@@ -414,9 +1856,879 @@ mod tests {
         let state = AppState {
             config: Arc::new(config),
             provider_manager: Arc::new(RwLock::new(provider_manager)),
+            body_limit: DEFAULT_BODY_LIMIT,
+            quote_cache: Arc::new(QuoteCache::default()),
+            token_resolver: Arc::new(TokenResolver::default()),
+            quote_router: Arc::new(QuoteRouter::default()),
+            tvl_batch_concurrency: crate::simulation_engine::DEFAULT_TVL_BATCH_CONCURRENCY,
+            call_enabled: false,
+            admin_enabled: false,
+            run_enabled: false,
+            compression: true,
+            tvl_log: Arc::new(LogSampler::new(DEFAULT_LOG_SAMPLE_EVERY)),
+            optimize_loan_log: Arc::new(LogSampler::new(DEFAULT_LOG_SAMPLE_EVERY)),
+            models: Arc::new(ModelRegistry::new()),
+            requests: Arc::new(RequestCounters::default()),
+            metrics_baseline: MetricsSnapshot::default(),
+            runs: Arc::new(RunHistory::default()),
+            simulation_engines: Arc::default(),
         };
         
         let _app = create_router(state);
         // Just verify router can be created
     }
+
+    #[tokio::test]
+    async fn test_legacy_alias_matches_v1() {
+        let v1 = get_response("/api/v1/metrics").await;
+        let legacy = get_response("/api/metrics").await;
+
+        assert_eq!(v1.status(), StatusCode::OK);
+        assert_eq!(legacy.status(), StatusCode::OK);
+        assert!(v1.headers().get("deprecation").is_none());
+        assert_eq!(legacy.headers().get("deprecation").unwrap(), "true");
+
+        let v1_body = to_bytes(v1.into_body(), usize::MAX).await.unwrap();
+        let legacy_body = to_bytes(legacy.into_body(), usize::MAX).await.unwrap();
+        assert_eq!(v1_body, legacy_body);
+    }
+
+    #[tokio::test]
+    async fn test_metrics_report_quote_cache() {
+        let state = test_state();
+        let route = crate::omniarb::TokenEntry::default();
+        state.quote_cache.get(&route, U256::from(1000u64));
+
+        let response = create_router(state)
+            .oneshot(Request::get("/api/v1/metrics").body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(json["quote_cache_hits"], 0);
+        assert_eq!(json["quote_cache_misses"], 1);
+    }
+
+    #[tokio::test]
+    async fn test_request_id_echoed() {
+        let response = create_router(test_state())
+            .oneshot(
+                Request::get("/health")
+                    .header("X-Request-Id", "client-req-42")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.headers().get("x-request-id").unwrap(), "client-req-42");
+
+        // Generated when the client sends none
+        let response = get_response("/health").await;
+        let generated = response.headers().get("x-request-id").unwrap().to_str().unwrap();
+        assert_eq!(generated.len(), 36);
+    }
+
+    #[tokio::test]
+    async fn test_version_endpoint() {
+        let response = get_response("/api/version").await;
+        assert_eq!(response.status(), StatusCode::OK);
+        assert!(response.headers().get("deprecation").is_none());
+
+        let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(json["version"], env!("CARGO_PKG_VERSION"));
+        assert_eq!(json["api_versions"][0], "v1");
+        assert!(json["git_sha"].is_string());
+    }
+
+    #[tokio::test]
+    async fn test_oversized_body_rejected() {
+        let body = format!("{{\"padding\":\"{}\"}}", "x".repeat(DEFAULT_BODY_LIMIT + 1));
+        let response = post_json("/api/v1/optimize_loan", body).await;
+        assert_eq!(response.status(), StatusCode::PAYLOAD_TOO_LARGE);
+    }
+
+    #[tokio::test]
+    async fn test_unknown_field_rejected() {
+        let body = serde_json::json!({
+            "chain_id": 137,
+            "token_address": USDC,
+            "target_amount": "1000",
+            "decimals": 6,
+            "bogus": true,
+        })
+        .to_string();
+        let response = post_json("/api/v1/optimize_loan", body).await;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+        let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(json["code"], "INVALID_REQUEST");
+        assert!(json["message"].as_str().unwrap().contains("bogus"));
+    }
+
+    #[tokio::test]
+    async fn test_semantic_validation() {
+        let cases = [
+            (loan_body(137, USDC, "1000", 37), "decimals"),
+            (loan_body(137, USDC, "0", 6), "target_amount"),
+            (loan_body(137, USDC, "12abc", 6), "target_amount"),
+            (loan_body(137, "0x1234", "1000", 6), "token_address"),
+            (loan_body(137, "2791Bca1f2de4661ED88A30C99A7a9449Aa84174", "1000", 6), "token_address"),
+            (loan_body(999999, USDC, "1000", 6), "chain_id"),
+        ];
+
+        for (body, field) in cases {
+            let response = post_json("/api/v1/optimize_loan", body).await;
+            assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+            assert_eq!(error_field(response).await.as_deref(), Some(field));
+        }
+    }
+
+    fn loan_request(target_amount: serde_json::Value, decimals: Option<u8>) -> LoanOptimizeRequest {
+        serde_json::from_value(serde_json::json!({
+            "chain_id": 137,
+            "token_address": USDC,
+            "target_amount": target_amount,
+            "decimals": decimals,
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_raw_and_human_amounts_match() {
+        let expected = U256::from(1_500_000u64);
+        let forms = [
+            loan_request(serde_json::json!("1500000"), Some(6)),
+            loan_request(serde_json::json!({ "value": "1500000", "unit": "raw", "decimals": 6 }), None),
+            loan_request(serde_json::json!({ "value": "1.5", "unit": "human", "decimals": 6 }), None),
+            loan_request(serde_json::json!({ "value": "1.500000", "unit": "human", "decimals": 6 }), Some(6)),
+        ];
+        for request in forms {
+            assert_eq!(request.resolve_amount().unwrap(), (expected, 6));
+        }
+    }
+
+    #[test]
+    fn test_ambiguous_amounts_rejected() {
+        let cases = [
+            (loan_request(serde_json::json!("1500000"), None), "decimals"),
+            (loan_request(serde_json::json!({ "value": "1.5", "unit": "human", "decimals": 6 }), Some(18)), "decimals"),
+            (loan_request(serde_json::json!({ "value": "1.5", "unit": "raw", "decimals": 6 }), None), "target_amount"),
+            (loan_request(serde_json::json!({ "value": "1.0000001", "unit": "human", "decimals": 6 }), None), "target_amount"),
+            (loan_request(serde_json::json!({ "value": ".", "unit": "human", "decimals": 6 }), None), "target_amount"),
+            (loan_request(serde_json::json!({ "value": "1.5", "unit": "human", "decimals": 40 }), None), "decimals"),
+        ];
+        for (request, field) in cases {
+            assert_eq!(request.resolve_amount().unwrap_err().field, field);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_unknown_amount_unit_rejected() {
+        let body = serde_json::json!({
+            "chain_id": 137,
+            "token_address": USDC,
+            "target_amount": { "value": "1.5", "unit": "ether", "decimals": 18 },
+        })
+        .to_string();
+        let response = post_json("/api/v1/optimize_loan", body).await;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+
+    async fn error_code(response: Response) -> String {
+        let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        json["code"].as_str().unwrap_or_default().to_string()
+    }
+
+    #[tokio::test]
+    async fn test_unsupported_chain_error_code() {
+        // Unknown chain ID, rejected during validation
+        let response = post_json("/api/v1/optimize_loan", loan_body(999999, USDC, "1000", 6)).await;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        assert_eq!(error_code(response).await, "CHAIN_UNSUPPORTED");
+
+        // Known chain with no configuration
+        let mut config = Config::default();
+        config.chains.remove(&137);
+        let response = create_router(AppState::new(config))
+            .oneshot(
+                Request::get(format!("/api/v1/tvl?chain_id=137&token_address={}", USDC))
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        assert_eq!(error_code(response).await, "CHAIN_UNSUPPORTED");
+    }
+
+    #[tokio::test]
+    async fn test_invalid_address_error_code() {
+        let response = post_json("/api/v1/optimize_loan", loan_body(137, "0x1234", "1000", 6)).await;
+        assert_eq!(error_code(response).await, "INVALID_ADDRESS");
+    }
+
+    #[tokio::test]
+    async fn test_addresses_checked_and_checksummed() {
+        // One letter's case flipped: a mixed-case address with a bad checksum
+        let typo = USDC.replacen("Bca", "bca", 1);
+        let response = post_json("/api/v1/optimize_loan", loan_body(137, &typo, "1000", 6)).await;
+        assert_eq!(error_code(response).await, "INVALID_ADDRESS");
+
+        let config = Config { offline: true, ..Config::default() };
+        let uri = format!(
+            "/api/v1/tvl?chain_id=137&token_address={}&lender_address={}",
+            USDC.to_lowercase(),
+            BALANCER_V3_VAULT.to_lowercase()
+        );
+        let response = create_router(AppState::new(config))
+            .oneshot(Request::get(uri.as_str()).body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(json["token_address"], USDC);
+        assert_eq!(json["lender_address"], BALANCER_V3_VAULT);
+    }
+
+    #[tokio::test]
+    async fn test_unknown_dex_error_code() {
+        let pool = |dex: &str| serde_json::json!({ "chain_id": 137, "pool_address": USDC, "dex_type": dex }).to_string();
+        let response = post_json("/api/v1/pool", pool("UnknownSwap")).await;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        assert_eq!(error_code(response).await, "UNKNOWN_DEX");
+
+        // Any casing of a known DEX gets past validation
+        let response = post_json("/api/v1/pool", pool("quick_swap")).await;
+        assert_eq!(error_code(response).await, "NOT_IMPLEMENTED");
+    }
+
+    #[test]
+    fn test_rpc_error_classification() {
+        let reverted = anyhow::anyhow!("(code: 3, message: execution reverted, data: None)");
+        assert_eq!(ApiError::from_rpc("call", &reverted).code, "REVERTED");
+        let transport = anyhow::anyhow!("error sending request: connection refused");
+        assert_eq!(ApiError::from_rpc("call", &transport).code, "RPC_ERROR");
+    }
+
+    #[tokio::test]
+    async fn test_offline_tvl_is_deterministic() {
+        let mut config = Config {
+            offline: true,
+            ..Config::default()
+        };
+        for chain in config.chains.values_mut() {
+            chain.rpc.clear();
+        }
+        let state = AppState::new(config);
+
+        let uri = format!("/api/v1/tvl?chain_id=137&token_address={}", USDC);
+        let mut bodies = Vec::new();
+        for _ in 0..2 {
+            let response = create_router(state.clone())
+                .oneshot(Request::get(uri.as_str()).body(Body::empty()).unwrap())
+                .await
+                .unwrap();
+            assert_eq!(response.status(), StatusCode::OK);
+            let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+            bodies.push(serde_json::from_slice::<serde_json::Value>(&body).unwrap());
+        }
+        assert_eq!(bodies[0]["tvl"], simulated_tvl().to_string());
+        assert_eq!(bodies[0], bodies[1]);
+    }
+
+    /// Writer appending formatted log lines to a shared buffer
+    #[derive(Clone, Default)]
+    struct CapturedLogs(Arc<std::sync::Mutex<Vec<u8>>>);
+
+    impl std::io::Write for CapturedLogs {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.lock().unwrap().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_tvl_info_logs_are_sampled() {
+        let logs = CapturedLogs::default();
+        let writer = logs.clone();
+        let subscriber = tracing_subscriber::fmt()
+            .with_max_level(tracing::Level::INFO)
+            .with_ansi(false)
+            .with_writer(move || writer.clone())
+            .finish();
+        let _guard = tracing::subscriber::set_default(subscriber);
+
+        let config = Config {
+            offline: true,
+            ..Config::default()
+        };
+        let state = AppState::new(config).with_log_sample_every(10);
+        let uri = format!("/api/v1/tvl?chain_id=137&token_address={}", USDC);
+        for _ in 0..10 {
+            let response = create_router(state.clone())
+                .oneshot(Request::get(uri.as_str()).body(Body::empty()).unwrap())
+                .await
+                .unwrap();
+            assert_eq!(response.status(), StatusCode::OK);
+        }
+
+        let output = String::from_utf8(logs.0.lock().unwrap().clone()).unwrap();
+        assert_eq!(output.matches("Querying TVL for token").count(), 1, "{}", output);
+
+        // Counters stay exact
+        let response = create_router(state)
+            .oneshot(Request::get("/api/v1/metrics").body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(json["tvl_requests"], 10);
+        assert_eq!(json["optimize_loan_requests"], 0);
+    }
+
+    #[tokio::test]
+    async fn test_admin_model_reload() {
+        use crate::omniarb::model_registry::tests::onnx_model;
+        use crate::omniarb::TAR_FEATURE_WEIGHTS;
+
+        let path = std::env::temp_dir().join(format!("titan_http_model_{}.onnx", std::process::id()));
+        std::fs::write(&path, onnx_model(1, &[("version", "tar-v1")])).unwrap();
+        let mut models = ModelRegistry::new();
+        models.register("tar", &path, TAR_FEATURE_WEIGHTS).unwrap();
+        let state = AppState::new(Config::default()).with_models(models);
+
+        let send = |state: AppState, request: Request<Body>| async move {
+            let response = create_router(state).oneshot(request).await.unwrap();
+            let status = response.status();
+            let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+            (status, serde_json::from_slice::<serde_json::Value>(&body).unwrap())
+        };
+        let reload = |body: &'static str| {
+            Request::post("/api/v1/admin/models/reload")
+                .header("content-type", "application/json")
+                .body(Body::from(body))
+                .unwrap()
+        };
+
+        // Off unless enabled
+        let (status, json) = send(state.clone(), reload(r#"{"name":"tar"}"#)).await;
+        assert_eq!(status, StatusCode::FORBIDDEN);
+        assert_eq!(json["code"], ApiError::ENDPOINT_DISABLED);
+        let state = state.with_admin_enabled(true);
+        let send = |request: Request<Body>| send(state.clone(), request);
+
+        let (_, health) = send(Request::get("/health").body(Body::empty()).unwrap()).await;
+        assert_eq!(health["models"][1]["name"], "tar");
+        assert_eq!(health["models"][1]["backend"], "onnx");
+        assert_eq!(health["models"][1]["version"], "tar-v1");
+        assert_eq!(health["models"][0]["backend"], "heuristic");
+
+        std::fs::write(&path, onnx_model(2, &[("version", "tar-v2")])).unwrap();
+        let (status, json) = send(reload(r#"{"name":"tar"}"#)).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(json["models"][0]["version"], "tar-v2");
+
+        let (_, metrics) = send(Request::get("/api/v1/metrics").body(Body::empty()).unwrap()).await;
+        assert_eq!(metrics["model_versions"]["tar"], "tar-v2");
+
+        let (status, json) = send(reload(r#"{"name":"flanker"}"#)).await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+        assert_eq!(json["code"], "UNKNOWN_MODEL");
+
+        // Also served, deprecated, without the version prefix
+        std::fs::remove_file(&path).ok();
+        let (status, json) = send(Request::post("/api/admin/models/reload").body(Body::empty()).unwrap()).await;
+        assert_eq!(status, StatusCode::INTERNAL_SERVER_ERROR);
+        assert_eq!(json["code"], "MODEL_LOAD_FAILED");
+    }
+
+    #[test]
+    fn test_log_sampler_counts_every_call() {
+        let sampler = LogSampler::new(3);
+        let sampled: Vec<bool> = (0..7).map(|_| sampler.record()).collect();
+        assert_eq!(sampled, [true, false, false, true, false, false, true]);
+        assert_eq!(sampler.count(), 7);
+
+        // 0 means log everything rather than dividing by zero
+        let every = LogSampler::new(0);
+        assert!(every.record() && every.record());
+    }
+
+    #[tokio::test]
+    async fn test_warm_up_reports_dead_chains() {
+        use crate::config::ChainConfig;
+
+        let rpc = Router::new().route(
+            "/",
+            post(|Json(request): Json<serde_json::Value>| async move {
+                Json(serde_json::json!({ "jsonrpc": "2.0", "id": request["id"], "result": "0x10" }))
+            }),
+        );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let live = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, rpc).await.unwrap() });
+
+        let chain = |rpc: String| ChainConfig {
+            name: "test".to_string(),
+            rpc,
+            wss: None,
+            aave_pool: String::new(),
+            uniswap_router: String::new(),
+            curve_router: String::new(),
+            native: String::new(),
+        };
+        let config = Config {
+            chains: [(137, chain(live)), (10, chain("http://127.0.0.1:1".to_string()))].into(),
+            ..Config::default()
+        };
+        let state = AppState::new(config);
+
+        let report = warm_up_providers(&state, Duration::from_secs(2)).await;
+        assert_eq!(report.warmed, [(137, 16)]);
+        assert_eq!(report.failed.iter().map(|(chain_id, _)| *chain_id).collect::<Vec<_>>(), [10]);
+
+        // Later requests reuse the providers created during warm-up
+        let warmed = state.provider_manager.read().await.get_all_providers()[&137].clone();
+        let served = chain_provider(&state, 137, &state.config.chains[&137].rpc).await.unwrap();
+        assert!(Arc::ptr_eq(&warmed, &served));
+    }
+
+    #[tokio::test]
+    async fn test_restored_metrics_continue_counting() {
+        let path = std::env::temp_dir().join(format!("titan_metrics_{}_restore.json", std::process::id()));
+        let previous = MetricsSnapshot {
+            queries_total: 40,
+            queries_success: 38,
+            queries_failed: 2,
+            optimize_loan_requests: 9,
+            ..Default::default()
+        };
+        previous.save(&path).unwrap();
+
+        // A restarted server picks up where the snapshot left off
+        let config = Config { offline: true, ..Config::default() };
+        let state = AppState::new(config).with_restored_metrics(MetricsSnapshot::load(&path).unwrap());
+        let app = create_router(state.clone());
+        for body in [loan_body(137, USDC, "20000000000", 6), "{".to_string()] {
+            let request = Request::post("/api/v1/optimize_loan")
+                .header("content-type", "application/json")
+                .body(Body::from(body))
+                .unwrap();
+            app.clone().oneshot(request).await.unwrap();
+        }
+        // Unversioned routes aren't queries
+        let health = Request::get("/health").body(Body::empty()).unwrap();
+        app.clone().oneshot(health).await.unwrap();
+
+        let response = app
+            .oneshot(Request::get("/api/v1/metrics").body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(json["queries_total"], 42);
+        assert_eq!(json["queries_success"], 39);
+        assert_eq!(json["queries_failed"], 3);
+
+        // The next snapshot carries the continued counters forward
+        state.metrics_snapshot().save(&path).unwrap();
+        let saved = MetricsSnapshot::load(&path).unwrap();
+        assert_eq!(saved.queries_total, 43);
+        assert_eq!(saved.optimize_loan_requests, 10);
+        std::fs::remove_file(&path).ok();
+    }
+
+    #[tokio::test]
+    async fn test_simulate_reports_min_amount_out() {
+        let mut config = Config {
+            offline: true,
+            ..Config::default()
+        };
+        // Never dialed offline, but the handler still builds a provider
+        config.chains.get_mut(&137).unwrap().rpc = "http://127.0.0.1:8545".to_string();
+        let state = AppState::new(config);
+        let app = create_router(state.clone());
+        let simulate = |body: serde_json::Value| {
+            app.clone().oneshot(
+                Request::post("/api/v1/simulate")
+                    .header("content-type", "application/json")
+                    .body(Body::from(body.to_string()))
+                    .unwrap(),
+            )
+        };
+
+        let response = simulate(serde_json::json!({
+            "chain_id": 137,
+            "token_in": USDC,
+            "token_out": "0x7ceB23fD6bC0adD59E62ac25578270cFf1b9f619",
+            "amount_in": "1000000",
+        }))
+        .await
+        .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        // Simulated pool keeps 99.7% at the 0.3% tier, then 0.5% slippage
+        assert_eq!(json["expected_amount_out"], "997000");
+        assert_eq!(json["min_amount_out"], "992015");
+        // The provider and engine are kept for the next request on the chain
+        assert!(state.provider_manager.read().await.get_all_providers().contains_key(&137));
+        assert!(state.simulation_engines.lock().unwrap().contains_key(&137));
+
+        let response = simulate(serde_json::json!({
+            "chain_id": 137,
+            "token_in": USDC,
+            "token_out": "0x7ceB23fD6bC0adD59E62ac25578270cFf1b9f619",
+            "amount_in": "1000000",
+            "slippage_tolerance": 0.99,
+        }))
+        .await
+        .unwrap();
+        let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(json["min_amount_out"], "987030");
+
+        for tolerance in [-0.5, 0.0, 1.5] {
+            let response = simulate(serde_json::json!({
+                "chain_id": 137,
+                "token_in": USDC,
+                "token_out": "0x7ceB23fD6bC0adD59E62ac25578270cFf1b9f619",
+                "amount_in": "1000000",
+                "slippage_tolerance": tolerance,
+            }))
+            .await
+            .unwrap();
+            assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+            let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+            let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+            assert_eq!(json["details"]["field"], "slippage_tolerance");
+        }
+
+        let response = simulate(serde_json::json!({
+            "chain_id": 137,
+            "token_in": USDC,
+            "token_out": USDC,
+            "amount_in": "0",
+        }))
+        .await
+        .unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn test_tvl_by_token_symbol() {
+        let state = AppState::new(Config {
+            offline: true,
+            ..Config::default()
+        });
+        let get = |uri: &'static str| {
+            create_router(state.clone()).oneshot(Request::get(uri).body(Body::empty()).unwrap())
+        };
+
+        let response = get("/api/v1/tvl?chain_id=1&token_symbol=usdc").await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(json["token_address"], "0xA0b86991c6218b36c1d19D4a2e9Eb0cE3606eB48");
+
+        let response = get("/api/v1/tvl?chain_id=1&token_symbol=NOPE").await.unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(json["code"], ApiError::UNKNOWN_TOKEN);
+        assert_eq!(json["details"]["field"], "token_symbol");
+
+        let both = format!("/api/v1/tvl?chain_id=1&token_symbol=USDC&token_address={}", USDC);
+        let response = create_router(state.clone())
+            .oneshot(Request::get(both.as_str()).body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn test_offline_tvl_batch_keeps_order() {
+        let state = AppState::new(Config {
+            offline: true,
+            ..Config::default()
+        });
+        let tokens = [USDC, "0xc2132D05D31c914a87C6611C10748AEb04B58e8F"];
+        let body = serde_json::json!({ "chain_id": 137, "token_addresses": tokens }).to_string();
+        let response = create_router(state)
+            .oneshot(
+                Request::post("/api/v1/tvl_batch")
+                    .header("content-type", "application/json")
+                    .body(Body::from(body))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(json["results"][0]["token_address"], tokens[0]);
+        assert_eq!(json["results"][1]["token_address"], tokens[1]);
+        assert_eq!(json["results"][1]["tvl"], simulated_tvl().to_string());
+
+        let empty = serde_json::json!({ "chain_id": 137, "token_addresses": [] }).to_string();
+        let response = post_json("/api/v1/tvl_batch", empty).await;
+        assert_eq!(error_field(response).await.as_deref(), Some("token_addresses"));
+    }
+
+    #[tokio::test]
+    async fn test_large_response_gzip_encoded() {
+        let tokens = vec![USDC; 200];
+        let body = serde_json::json!({ "chain_id": 137, "token_addresses": tokens }).to_string();
+        let request = |accept: Option<&str>| {
+            let builder = Request::post("/api/v1/tvl_batch").header("content-type", "application/json");
+            let builder = match accept {
+                Some(encoding) => builder.header("accept-encoding", encoding),
+                None => builder,
+            };
+            builder.body(Body::from(body.clone())).unwrap()
+        };
+        let offline = || {
+            AppState::new(Config {
+                offline: true,
+                ..Config::default()
+            })
+        };
+
+        let plain = create_router(offline()).oneshot(request(None)).await.unwrap();
+        assert!(plain.headers().get("content-encoding").is_none());
+        let plain = to_bytes(plain.into_body(), usize::MAX).await.unwrap();
+
+        let gzipped = create_router(offline()).oneshot(request(Some("gzip"))).await.unwrap();
+        assert_eq!(gzipped.status(), StatusCode::OK);
+        assert_eq!(gzipped.headers().get("content-encoding").unwrap(), "gzip");
+        let gzipped = to_bytes(gzipped.into_body(), usize::MAX).await.unwrap();
+        assert_eq!(&gzipped[..2], &[0x1f, 0x8b]);
+        assert!(gzipped.len() * 10 < plain.len(), "{} vs {}", gzipped.len(), plain.len());
+
+        let disabled = create_router(offline().with_compression(false))
+            .oneshot(request(Some("gzip")))
+            .await
+            .unwrap();
+        assert!(disabled.headers().get("content-encoding").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_serves_cleartext_http2() {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, create_router(test_state())).await.unwrap() });
+
+        let client = reqwest::Client::builder().http2_prior_knowledge().build().unwrap();
+        let response = client.get(format!("http://{}/health", addr)).send().await.unwrap();
+        assert_eq!(response.status(), reqwest::StatusCode::OK);
+        assert_eq!(response.version(), reqwest::Version::HTTP_2);
+    }
+
+    #[tokio::test]
+    async fn test_server_handle_serves_and_stops() {
+        let config = Config { offline: true, ..Config::default() };
+        let mut server = ServerHandle::spawn(config.clone(), 0).unwrap();
+        assert_ne!(server.port(), 0);
+        assert!(server.is_running());
+
+        let url = format!("http://127.0.0.1:{}/health", server.port());
+        let response = reqwest::get(&url).await.unwrap();
+        assert_eq!(response.status(), reqwest::StatusCode::OK);
+
+        // The port is taken, so a second server fails up front
+        let err = ServerHandle::spawn(config, server.port()).err().unwrap();
+        assert!(matches!(err, ServerError::Bind { .. }), "{}", err);
+
+        server.stop(Duration::from_secs(5)).unwrap();
+        assert!(!server.is_running());
+        assert!(reqwest::get(&url).await.is_err());
+        server.stop(Duration::from_secs(5)).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_graceful_shutdown_saves_metrics_snapshot() {
+        let path = std::env::temp_dir().join(format!("titan_http_metrics_{}_shutdown.json", std::process::id()));
+        std::fs::remove_file(&path).ok();
+        let restored = MetricsSnapshot { queries_total: 5, queries_success: 5, ..Default::default() };
+        let state = AppState::new(Config { offline: true, ..Config::default() }).with_restored_metrics(restored);
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/api/v1/metrics", listener.local_addr().unwrap());
+
+        // Stopped long before the first periodic snapshot is due
+        let snapshots = SnapshotSettings { path: path.clone(), interval: Duration::from_secs(3600) };
+        let (shutdown, shutdown_rx) = tokio::sync::oneshot::channel::<()>();
+        let server = tokio::spawn(serve(listener, state, Some(snapshots), async {
+            let _ = shutdown_rx.await;
+        }));
+        for _ in 0..2 {
+            assert_eq!(reqwest::get(&url).await.unwrap().status(), reqwest::StatusCode::OK);
+        }
+        assert!(!path.exists());
+
+        shutdown.send(()).unwrap();
+        server.await.unwrap().unwrap();
+        let saved = MetricsSnapshot::load(&path).unwrap();
+        assert_eq!(saved.queries_total, 7);
+        assert_eq!(saved.queries_success, 7);
+        std::fs::remove_file(&path).ok();
+    }
+
+    #[tokio::test]
+    async fn test_contract_call_guarded_and_validated() {
+        let body = |signature: &str| {
+            serde_json::json!({
+                "chain_id": 137,
+                "contract": USDC,
+                "signature": signature,
+                "args": ["0x0000000000000000000000000000000000000001"],
+            })
+            .to_string()
+        };
+        let response = post_json("/api/v1/call", body("balanceOf(address) view returns (uint256)")).await;
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+        assert_eq!(error_code(response).await, ApiError::ENDPOINT_DISABLED);
+
+        let call = |body: String| {
+            create_router(test_state().with_call_enabled(true)).oneshot(
+                Request::post("/api/v1/call")
+                    .header("content-type", "application/json")
+                    .body(Body::from(body))
+                    .unwrap(),
+            )
+        };
+        for signature in ["function deposit(address) payable", "approve(address,uint256) returns (bool)"] {
+            let response = call(body(signature)).await.unwrap();
+            assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+            assert_eq!(error_code(response).await, ApiError::INVALID_SIGNATURE);
+        }
+        let response = call(body("allowance(address,address) view returns (uint256)")).await.unwrap();
+        assert_eq!(error_field(response).await.as_deref(), Some("args"));
+    }
+
+    #[tokio::test]
+    async fn test_tvl_query_validation() {
+        let response = get_response("/api/v1/tvl?chain_id=137&token_address=0xnothex").await;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        assert_eq!(error_field(response).await.as_deref(), Some("token_address"));
+    }
+
+    #[tokio::test]
+    async fn test_config_endpoint_redacts_rpc() {
+        let rpc = "https://polygon-mainnet.g.alchemy.com/v2/super-secret-key";
+        let wss = "wss://polygon-mainnet.g.alchemy.com/v2/super-secret-key";
+        let mut config = Config::default();
+        config.chains.insert(
+            137,
+            crate::config::ChainConfig {
+                name: "polygon".to_string(),
+                rpc: rpc.to_string(),
+                wss: Some(wss.to_string()),
+                aave_pool: "0x794a61358D6845594F94dc1DB02A252b5b4814aD".to_string(),
+                uniswap_router: "0xE592427A0AEce92De3Edee1F18E0157C05861564".to_string(),
+                curve_router: "0x445FE580eF8d70FF569aB36e80c647af338db351".to_string(),
+                native: "MATIC".to_string(),
+            },
+        );
+
+        let response = create_router(AppState::new(config))
+            .oneshot(Request::get("/api/v1/config").body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let text = String::from_utf8(body.to_vec()).unwrap();
+        assert!(!text.contains(rpc));
+        assert!(!text.contains(wss));
+        assert!(!text.contains("super-secret-key"));
+        assert!(text.contains("polygon-mainnet.g.alchemy.com"));
+    }
+
+    async fn json_body(response: Response) -> serde_json::Value {
+        let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        serde_json::from_slice(&body).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_scoring_run_completes_and_is_queryable() {
+        let matrix_path = concat!(env!("CARGO_MANIFEST_DIR"), "/../data/fixtures/omniarb_matrix_schema_v2.md");
+        let config = Config { offline: true, matrix_path: matrix_path.to_string(), ..Config::default() };
+        let state = AppState::new(config);
+
+        // Off unless enabled
+        let response = create_router(state.clone())
+            .oneshot(Request::post("/api/v1/run").body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+        assert_eq!(json_body(response).await["code"], ApiError::ENDPOINT_DISABLED);
+
+        let state = state.with_run_enabled(true);
+        let app = create_router(state.clone());
+        let response = app.clone().oneshot(Request::post("/api/v1/run").body(Body::empty()).unwrap()).await.unwrap();
+        assert!(matches!(response.status(), StatusCode::OK | StatusCode::ACCEPTED));
+        let id = json_body(response).await["id"].as_u64().unwrap();
+
+        // Poll until the spawned task has stored its results
+        let uri = format!("/api/v1/run/{}", id);
+        let mut run = serde_json::Value::Null;
+        for _ in 0..100 {
+            let response = app.clone().oneshot(Request::get(uri.as_str()).body(Body::empty()).unwrap()).await.unwrap();
+            assert_eq!(response.status(), StatusCode::OK);
+            run = json_body(response).await;
+            if run["status"] != "running" {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(50)).await;
+        }
+        assert_eq!(run["status"], "completed");
+        assert_eq!(run["id"], id);
+        assert_eq!(run["summary"]["routes_analyzed"], 3);
+        let above = run["summary"]["above_threshold"].as_u64().unwrap();
+        assert_eq!(run["top_routes"].as_array().unwrap().len() as u64, above);
+
+        // One run at a time
+        let running = state.runs.start("elsewhere.md").unwrap();
+        let response = app.clone().oneshot(Request::post("/api/v1/run").body(Body::empty()).unwrap()).await.unwrap();
+        assert_eq!(response.status(), StatusCode::CONFLICT);
+        let json = json_body(response).await;
+        assert_eq!(json["code"], ApiError::RUN_IN_PROGRESS);
+        assert_eq!(json["details"]["id"], running);
+    }
+
+    #[tokio::test]
+    async fn test_scoring_run_failures() {
+        let config = Config { offline: true, matrix_path: "/nonexistent/matrix.md".to_string(), ..Config::default() };
+        let app = create_router(AppState::new(config).with_run_enabled(true));
+
+        let response = app.clone().oneshot(Request::post("/api/v1/run").body(Body::empty()).unwrap()).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let run = json_body(response).await;
+        assert_eq!(run["status"], "failed");
+        assert!(run["error"].as_str().is_some());
+
+        let response = app.clone().oneshot(Request::get("/api/v1/run/999").body(Body::empty()).unwrap()).await.unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+        assert_eq!(json_body(response).await["code"], ApiError::UNKNOWN_RUN);
+
+        // The unprefixed alias still answers, marked deprecated
+        let response = app.clone().oneshot(Request::get("/api/run/999").body(Body::empty()).unwrap()).await.unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+        assert_eq!(response.headers()["deprecation"], "true");
+
+        // Run requests count towards the query metrics
+        let response = app.oneshot(Request::get("/api/v1/metrics").body(Body::empty()).unwrap()).await.unwrap();
+        let metrics = json_body(response).await;
+        assert_eq!(metrics["queries_total"], 3);
+        assert_eq!(metrics["queries_failed"], 2);
+    }
 }
@@ -1,21 +1,85 @@
+// pyo3 0.20's `#[pymethods]` expansion trips this lint on newer toolchains
+#![allow(non_local_definitions)]
+
+pub mod address;
 pub mod config;
 pub mod enum_matrix;
 pub mod simulation_engine;
+pub mod dex_quoter;
 pub mod commander;
 pub mod http_server;
+pub mod lifi;
+pub mod api_policy;
+pub mod gas_oracle;
+pub mod selfcheck;
+pub mod abi_call;
+pub mod chainlink;
+pub mod aave;
+pub mod chain_reader;
+pub mod metrics_snapshot;
 pub mod omniarb;
+mod py_commander;
+mod py_errors;
+mod py_matrix;
+mod py_scoring;
+mod py_server;
+mod py_simulation;
 
 // Re-export main types
-pub use config::{Config, ChainConfig, BALANCER_V3_VAULT};
+pub use address::{checksummed, validate_address, AddressError};
+pub use config::{ApiEndpoint, Config, ChainConfig, QuoteApiConfig, TokenRegistry, TokenResolver, GasPolicy, BALANCER_V3_VAULT};
 pub use enum_matrix::{ChainId, ProviderManager};
-pub use simulation_engine::{TitanSimulationEngine, get_provider_tvl};
+pub use simulation_engine::{TitanSimulationEngine, get_provider_tvl, fetch_tvl_batch, get_recent_swap_volume, is_likely_fee_on_transfer, estimate_eip1559_fees, fetch_gas_fees, gas_cost_native, gas_cost_usd, GasFees};
+pub use dex_quoter::{DexQuoter, DexQuote, UniV3Quoter, UniV2Router, CurvePool, best_quote_across, build_paths, compute_v3_pool_address, MAX_V2_PATHS, UNISWAP_V3_FACTORY};
 pub use commander::TitanCommander;
-pub use http_server::{start_server, create_router, AppState};
-pub use omniarb::{load_token_matrix, calculate_tar_score, fetch_live_quotes, run_tar_onnx, run_flanker, TokenEntry, QuoteInfo};
+pub use http_server::{start_server, create_router, AppState, ServerError, ServerHandle};
+pub use lifi::{BridgeStatus, LifiClient, poll_lifi_status, wait_for_bridge_completion};
+pub use chainlink::{read_chainlink_price, read_chainlink_price_from};
+pub use aave::{get_aave_reserve_tokens, get_aave_reserve_tokens_from, ReserveTokenCache, ReserveTokens};
+pub use chain_reader::{ChainReader, MockChainReader};
+pub use gas_oracle::GasOracle;
+pub use api_policy::{FetchOptions, ProviderPolicy, ProviderStats, RetryPolicy};
+pub use omniarb::{load_token_matrix, load_token_matrix_auto, calculate_tar_score, fetch_live_quotes, run_tar_onnx, run_flanker, TokenEntry, TokenMatrix, QuoteInfo};
 
 // Python bindings
 use pyo3::prelude::*;
-use pyo3::exceptions::PyValueError;
+use pyo3::types::{PyDict, PyList};
+use std::collections::HashMap;
+
+use py_errors::{supported_chain, ConfigError};
+
+/// Plain Python value (dict/list/str/int/float/bool/None) for a JSON value
+fn json_to_py(py: Python, value: &serde_json::Value) -> PyResult<PyObject> {
+    use serde_json::Value;
+    Ok(match value {
+        Value::Null => py.None(),
+        Value::Bool(b) => b.into_py(py),
+        Value::Number(n) => match (n.as_u64(), n.as_i64()) {
+            (Some(u), _) => u.into_py(py),
+            (None, Some(i)) => i.into_py(py),
+            _ => n.as_f64().unwrap_or(f64::NAN).into_py(py),
+        },
+        Value::String(s) => s.into_py(py),
+        Value::Array(items) => {
+            let items = items.iter().map(|item| json_to_py(py, item)).collect::<PyResult<Vec<_>>>()?;
+            PyList::new(py, items).into()
+        }
+        Value::Object(map) => {
+            let dict = PyDict::new(py);
+            for (key, item) in map {
+                dict.set_item(key, json_to_py(py, item)?)?;
+            }
+            dict.into()
+        }
+    })
+}
+
+/// Any serializable config section as plain Python values
+fn serialize_to_py<T: serde::Serialize>(py: Python, value: &T) -> PyResult<PyObject> {
+    let value = serde_json::to_value(value)
+        .map_err(|e| ConfigError::new_err(format!("Failed to serialize config: {}", e)))?;
+    json_to_py(py, &value)
+}
 
 /// Python wrapper for Config
 #[pyclass]
@@ -25,10 +89,12 @@ struct PyConfig {
 
 #[pymethods]
 impl PyConfig {
+    /// Reads `.env` and the environment with the GIL released
     #[new]
-    fn new() -> PyResult<Self> {
-        let config = Config::from_env()
-            .map_err(|e| PyValueError::new_err(format!("Failed to load config: {}", e)))?;
+    fn new(py: Python) -> PyResult<Self> {
+        let config = py
+            .allow_threads(Config::from_env)
+            .map_err(|e| ConfigError::new_err(format!("Failed to load config: {}", e)))?;
         Ok(PyConfig { inner: config })
     }
 
@@ -43,6 +109,50 @@ impl PyConfig {
     fn get_balancer_vault(&self) -> String {
         BALANCER_V3_VAULT.to_string()
     }
+
+    /// Every `ChainConfig` field for the chain, RPC URLs included
+    fn get_chain(&self, py: Python, chain_id: u64) -> PyResult<Option<PyObject>> {
+        self.inner.get_chain(chain_id).map(|chain| serialize_to_py(py, chain)).transpose()
+    }
+
+    /// DEX name ⇒ router address; empty for chains without routers
+    fn get_dex_routers(&self, chain_id: u64) -> HashMap<String, String> {
+        self.inner
+            .dex_routers
+            .get(&chain_id)
+            .map(|dex| dex.routers.clone())
+            .unwrap_or_default()
+    }
+
+    /// Bridge key ⇒ `BridgeConfig` fields
+    fn get_bridges(&self, py: Python) -> PyResult<PyObject> {
+        serialize_to_py(py, &self.inner.intent_based_bridges)
+    }
+
+    fn lifi_supported_chains(&self) -> Vec<u64> {
+        self.inner.lifi_supported_chains.clone()
+    }
+
+    /// Configured chain IDs, ascending
+    fn supported_chain_ids(&self) -> Vec<u64> {
+        let mut chain_ids: Vec<u64> = self.inner.chains.keys().copied().collect();
+        chain_ids.sort_unstable();
+        chain_ids
+    }
+
+    /// The whole config as JSON-ready values
+    ///
+    /// Redacted by default: RPC/WSS URLs are cut to their hosts and API
+    /// credentials are left out. `redacted=False` dumps everything,
+    /// secrets included.
+    #[pyo3(signature = (redacted = true))]
+    fn to_dict(&self, py: Python, redacted: bool) -> PyResult<PyObject> {
+        if redacted {
+            serialize_to_py(py, &self.inner.redacted())
+        } else {
+            serialize_to_py(py, &self.inner)
+        }
+    }
 }
 
 /// Python wrapper for ChainId
@@ -78,17 +188,21 @@ impl PyChainId {
 
     #[staticmethod]
     fn from_u64(value: u64) -> PyResult<String> {
-        ChainId::from_u64(value)
-            .map(|c| c.name().to_string())
-            .ok_or_else(|| PyValueError::new_err(format!("Unknown chain ID: {}", value)))
+        Ok(supported_chain(value)?.name().to_string())
     }
 }
 
 /// Python module initialization
 #[pymodule]
-fn titan_core(_py: Python, m: &PyModule) -> PyResult<()> {
+fn titan_core(py: Python, m: &PyModule) -> PyResult<()> {
     m.add_class::<PyConfig>()?;
     m.add_class::<PyChainId>()?;
+    py_errors::register(py, m)?;
+    py_commander::register(m)?;
+    py_simulation::register(m)?;
+    py_scoring::register(m)?;
+    py_matrix::register(m)?;
+    py_server::register(m)?;
     
     // Add constants
     m.add("BALANCER_V3_VAULT", BALANCER_V3_VAULT)?;
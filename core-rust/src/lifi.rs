@@ -0,0 +1,274 @@
+use ethers::types::TxHash;
+use anyhow::{anyhow, Result};
+use log::{debug, warn};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::api_policy::{FetchOptions, ProviderPolicy, LIFI_PROVIDER};
+
+use crate::config::{ApiEndpoint, BridgeConfig};
+
+/// LiFi REST API base URL
+pub const LIFI_API_BASE: &str = "https://li.quest/v1";
+
+/// Delay between status polls
+const DEFAULT_POLL_INTERVAL: Duration = Duration::from_secs(10);
+
+/// Per-request HTTP timeout
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Cross-chain transfer status
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum BridgeStatus {
+    Pending,
+    Done,
+    Failed,
+}
+
+impl BridgeStatus {
+    /// Map a LiFi `status` value
+    ///
+    /// `NOT_FOUND` is reported for a while after submission, before LiFi
+    /// indexes the transaction, so it counts as pending.
+    pub fn from_lifi(status: &str) -> Self {
+        match status {
+            "DONE" => BridgeStatus::Done,
+            "FAILED" | "INVALID" => BridgeStatus::Failed,
+            _ => BridgeStatus::Pending,
+        }
+    }
+
+    /// Whether the transfer has finished (successfully or not)
+    pub fn is_terminal(&self) -> bool {
+        !matches!(self, BridgeStatus::Pending)
+    }
+}
+
+/// Relevant fields of the `/status` response
+#[derive(Debug, Deserialize)]
+struct LifiStatusResponse {
+    status: String,
+    substatus: Option<String>,
+}
+
+/// LiFi API client for bridge status tracking
+pub struct LifiClient {
+    http: reqwest::Client,
+    base_url: String,
+    poll_interval: Duration,
+    policy: Arc<ProviderPolicy>,
+    /// Partner key sent as `x-lifi-api-key`
+    api_key: Option<String>,
+}
+
+impl LifiClient {
+    /// Create a client for the public LiFi API
+    pub fn new() -> Self {
+        Self {
+            http: reqwest::Client::builder()
+                .timeout(REQUEST_TIMEOUT)
+                .build()
+                .unwrap_or_default(),
+            base_url: LIFI_API_BASE.to_string(),
+            poll_interval: DEFAULT_POLL_INTERVAL,
+            policy: FetchOptions::default().policy(LIFI_PROVIDER),
+            api_key: None,
+        }
+    }
+
+    /// Create a client for a configured (e.g. partner) endpoint
+    pub fn from_endpoint(endpoint: &ApiEndpoint) -> Self {
+        Self {
+            http: reqwest::Client::builder()
+                .timeout(endpoint.timeout)
+                .build()
+                .unwrap_or_default(),
+            base_url: endpoint.base_url.trim_end_matches('/').to_string(),
+            poll_interval: DEFAULT_POLL_INTERVAL,
+            policy: FetchOptions::default()
+                .with_rate_limit(LIFI_PROVIDER, endpoint.rate_limit_rps)
+                .policy(LIFI_PROVIDER),
+            api_key: endpoint.has_key().then(|| endpoint.api_key.clone()),
+        }
+    }
+
+    /// Use a different API base URL
+    pub fn with_base_url(mut self, base_url: &str) -> Self {
+        self.base_url = base_url.trim_end_matches('/').to_string();
+        self
+    }
+
+    /// Set delay between status polls
+    pub fn with_poll_interval(mut self, poll_interval: Duration) -> Self {
+        self.poll_interval = poll_interval;
+        self
+    }
+
+    /// Share a retry and rate-limit policy with other LiFi clients
+    pub fn with_policy(mut self, policy: Arc<ProviderPolicy>) -> Self {
+        self.policy = policy;
+        self
+    }
+
+    pub fn policy(&self) -> &ProviderPolicy {
+        &self.policy
+    }
+
+    /// Fetch the current status of a bridge transfer
+    pub async fn status(&self, tx_hash: TxHash, from_chain: u64, to_chain: u64) -> Result<BridgeStatus> {
+        let query = [
+            ("txHash", format!("{:?}", tx_hash)),
+            ("fromChain", from_chain.to_string()),
+            ("toChain", to_chain.to_string()),
+        ];
+        let response = self
+            .policy
+            .send(|| {
+                let request = self.http.get(format!("{}/status", self.base_url)).query(&query);
+                match &self.api_key {
+                    Some(key) => request.header("x-lifi-api-key", key),
+                    None => request,
+                }
+            })
+            .await?;
+
+        // Freshly submitted transactions may 404 until LiFi indexes them
+        if response.status() == reqwest::StatusCode::NOT_FOUND {
+            return Ok(BridgeStatus::Pending);
+        }
+
+        let body: LifiStatusResponse = response.error_for_status()?.json().await?;
+        debug!(
+            "LiFi status for {:?}: {} ({})",
+            tx_hash,
+            body.status,
+            body.substatus.as_deref().unwrap_or("-")
+        );
+        Ok(BridgeStatus::from_lifi(&body.status))
+    }
+
+    /// Poll until the transfer is terminal or `timeout` elapses
+    ///
+    /// Request errors are logged and retried on the next poll.
+    pub async fn wait_for_completion(
+        &self,
+        tx_hash: TxHash,
+        from_chain: u64,
+        to_chain: u64,
+        timeout: Duration,
+    ) -> Result<BridgeStatus> {
+        let deadline = tokio::time::Instant::now() + timeout;
+
+        loop {
+            match self.status(tx_hash, from_chain, to_chain).await {
+                Ok(status) if status.is_terminal() => return Ok(status),
+                Ok(_) => {}
+                Err(e) => warn!("LiFi status poll failed for {:?}: {}", tx_hash, e),
+            }
+
+            if tokio::time::Instant::now() + self.poll_interval > deadline {
+                return Err(anyhow!(
+                    "Bridge transfer {:?} not completed within {}s",
+                    tx_hash,
+                    timeout.as_secs()
+                ));
+            }
+            tokio::time::sleep(self.poll_interval).await;
+        }
+    }
+}
+
+impl Default for LifiClient {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Fetch the status of a LiFi bridge transfer
+pub async fn poll_lifi_status(tx_hash: TxHash, from_chain: u64, to_chain: u64) -> Result<BridgeStatus> {
+    LifiClient::new().status(tx_hash, from_chain, to_chain).await
+}
+
+/// Wait for a bridge transfer, allowing up to the bridge's `max_time_seconds`
+pub async fn wait_for_bridge_completion(
+    tx_hash: TxHash,
+    from_chain: u64,
+    to_chain: u64,
+    bridge: &BridgeConfig,
+) -> Result<BridgeStatus> {
+    let timeout = Duration::from_secs(bridge.max_time_seconds as u64);
+    LifiClient::new()
+        .wait_for_completion(tx_hash, from_chain, to_chain, timeout)
+        .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{extract::State, http::StatusCode, routing::get, Json, Router};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    /// Serve `/status` responses in order, repeating the last one
+    async fn mock_lifi(responses: Vec<(StatusCode, &'static str)>) -> String {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let app = Router::new()
+            .route(
+                "/status",
+                get(|State(calls): State<Arc<AtomicUsize>>| async move {
+                    let call = calls.fetch_add(1, Ordering::SeqCst);
+                    let (code, status) = responses[call.min(responses.len() - 1)];
+                    (code, Json(serde_json::json!({ "status": status })))
+                }),
+            )
+            .with_state(calls);
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+        format!("http://{}", addr)
+    }
+
+    #[test]
+    fn test_status_mapping() {
+        assert_eq!(BridgeStatus::from_lifi("DONE"), BridgeStatus::Done);
+        assert_eq!(BridgeStatus::from_lifi("FAILED"), BridgeStatus::Failed);
+        assert_eq!(BridgeStatus::from_lifi("PENDING"), BridgeStatus::Pending);
+        assert_eq!(BridgeStatus::from_lifi("NOT_FOUND"), BridgeStatus::Pending);
+        assert!(!BridgeStatus::Pending.is_terminal());
+    }
+
+    #[tokio::test]
+    async fn test_wait_until_done() {
+        let base_url = mock_lifi(vec![
+            (StatusCode::NOT_FOUND, "NOT_FOUND"),
+            (StatusCode::OK, "NOT_FOUND"),
+            (StatusCode::OK, "PENDING"),
+            (StatusCode::OK, "DONE"),
+        ])
+        .await;
+        let client = LifiClient::new()
+            .with_base_url(&base_url)
+            .with_poll_interval(Duration::from_millis(10));
+
+        let status = client
+            .wait_for_completion(TxHash::zero(), 1, 137, Duration::from_secs(5))
+            .await
+            .unwrap();
+        assert_eq!(status, BridgeStatus::Done);
+    }
+
+    #[tokio::test]
+    async fn test_wait_times_out() {
+        let base_url = mock_lifi(vec![(StatusCode::OK, "PENDING")]).await;
+        let client = LifiClient::new()
+            .with_base_url(&base_url)
+            .with_poll_interval(Duration::from_millis(10));
+
+        let result = client
+            .wait_for_completion(TxHash::zero(), 1, 137, Duration::from_millis(50))
+            .await;
+        assert!(result.is_err());
+    }
+}
@@ -0,0 +1,90 @@
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+/// How often the server writes its counters to the snapshot file
+pub const DEFAULT_SNAPSHOT_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Monotonic server counters, as saved between restarts
+///
+/// Fields missing from an older snapshot file load as 0.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MetricsSnapshot {
+    pub queries_total: u64,
+    pub queries_success: u64,
+    pub queries_failed: u64,
+    pub quote_cache_hits: u64,
+    pub quote_cache_misses: u64,
+    pub tvl_requests: u64,
+    pub optimize_loan_requests: u64,
+}
+
+impl MetricsSnapshot {
+    /// Load the counters saved at `path`; a missing file starts at zero
+    pub fn load(path: impl AsRef<Path>) -> io::Result<Self> {
+        match std::fs::read_to_string(path) {
+            Ok(json) => Ok(serde_json::from_str(&json)?),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Write to a temporary file beside `path`, then rename it over `path`
+    ///
+    /// A crash mid-write leaves the previous snapshot intact.
+    pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        let path = path.as_ref();
+        let tmp = tmp_path(path);
+        let mut file = std::fs::File::create(&tmp)?;
+        file.write_all(serde_json::to_string_pretty(self)?.as_bytes())?;
+        file.sync_all()?;
+        drop(file);
+        std::fs::rename(&tmp, path)
+    }
+
+    /// Counters of `self` continued by `live`
+    pub fn plus(&self, live: &MetricsSnapshot) -> Self {
+        Self {
+            queries_total: self.queries_total.saturating_add(live.queries_total),
+            queries_success: self.queries_success.saturating_add(live.queries_success),
+            queries_failed: self.queries_failed.saturating_add(live.queries_failed),
+            quote_cache_hits: self.quote_cache_hits.saturating_add(live.quote_cache_hits),
+            quote_cache_misses: self.quote_cache_misses.saturating_add(live.quote_cache_misses),
+            tvl_requests: self.tvl_requests.saturating_add(live.tvl_requests),
+            optimize_loan_requests: self.optimize_loan_requests.saturating_add(live.optimize_loan_requests),
+        }
+    }
+}
+
+fn tmp_path(path: &Path) -> PathBuf {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(".tmp");
+    path.with_file_name(name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_snapshot_round_trip() {
+        let path = std::env::temp_dir().join(format!("titan_metrics_{}_round_trip.json", std::process::id()));
+        std::fs::remove_file(&path).ok();
+        assert_eq!(MetricsSnapshot::load(&path).unwrap(), MetricsSnapshot::default());
+
+        let snapshot = MetricsSnapshot { queries_total: 12, queries_failed: 2, tvl_requests: 5, ..Default::default() };
+        snapshot.save(&path).unwrap();
+        assert_eq!(MetricsSnapshot::load(&path).unwrap(), snapshot);
+        assert!(!tmp_path(&path).exists());
+
+        // Older snapshots without newer counters still load
+        std::fs::write(&path, r#"{"queries_total": 3}"#).unwrap();
+        assert_eq!(MetricsSnapshot::load(&path).unwrap().queries_total, 3);
+        std::fs::write(&path, "{").unwrap();
+        assert!(MetricsSnapshot::load(&path).is_err());
+        std::fs::remove_file(&path).ok();
+    }
+}
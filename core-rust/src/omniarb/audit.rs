@@ -0,0 +1,242 @@
+use std::fmt::Write;
+
+use serde::{Deserialize, Serialize};
+
+use crate::omniarb::checkpoint::ScoredRoute;
+use crate::omniarb::data_fetcher::{simulate_bridge_quote, QuoteInfo};
+use crate::omniarb::matrix_diff::RouteId;
+use crate::omniarb::matrix_parser::TokenEntry;
+use crate::omniarb::model_bridge::{run_flanker, run_tar_onnx};
+use crate::omniarb::ranking::rank_routes;
+use crate::omniarb::tar_scorer::{calculate_tar_breakdown_weighted, TarBreakdown, TarWeights};
+
+/// Decimals every float is rendered with before hashing
+pub const FINGERPRINT_DECIMALS: usize = 9;
+
+/// One scored route with its inputs, minus anything time-dependent
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AuditRecord {
+    pub route: RouteId,
+    pub liquidity_score: f64,
+    pub fee_tier: f64,
+    pub spread_percentage: f64,
+    pub slippage_estimate: f64,
+    pub gas_cost_usd: f64,
+    pub available_liquidity: f64,
+    pub score: f64,
+    pub model_pred_tar: f64,
+    pub model_pred_flank: f64,
+    pub percentile: f64,
+    pub z_score: f64,
+    pub breakdown: TarBreakdown,
+}
+
+/// Audited routes with their fingerprint, the layout of a golden file
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AuditReport {
+    pub fingerprint: String,
+    pub routes: Vec<AuditRecord>,
+}
+
+impl AuditReport {
+    pub fn new(routes: Vec<AuditRecord>) -> Self {
+        Self { fingerprint: fingerprint_records(&routes), routes }
+    }
+}
+
+/// Offline simulated quotes for `entries`, the audit's fixed inputs
+pub fn simulated_quotes(entries: &[TokenEntry]) -> Vec<QuoteInfo> {
+    entries.iter().map(simulate_bridge_quote).collect()
+}
+
+/// Score and rank a batch the way the engine does, keeping each breakdown
+///
+/// `quotes` pair with `entries` by index; extra items on either side are
+/// ignored.
+pub fn audit_routes(entries: &[TokenEntry], quotes: &[QuoteInfo], weights: &TarWeights) -> Vec<AuditRecord> {
+    let (mut scored, breakdowns): (Vec<_>, Vec<_>) = entries
+        .iter()
+        .zip(quotes)
+        .map(|(entry, quote)| {
+            // The heuristics have no NaN fallback; the engine counts model fallbacks
+            let breakdown = calculate_tar_breakdown_weighted(entry, quote, weights);
+            let (tar, flanker) = (run_tar_onnx(entry, quote), run_flanker(entry, quote));
+            let route = ScoredRoute {
+                entry: entry.clone(),
+                quote: quote.clone(),
+                score: breakdown.total,
+                model_pred_tar: tar.score,
+                model_pred_flank: flanker.score,
+                percentile: 0.0,
+                z_score: 0.0,
+                disagreement: 0.0,
+            };
+            (route, breakdown)
+        })
+        .unzip();
+    rank_routes(&mut scored);
+
+    scored
+        .into_iter()
+        .zip(breakdowns)
+        .map(|(route, breakdown)| AuditRecord {
+            route: RouteId::from(&route.entry),
+            liquidity_score: route.entry.liquidity_score,
+            fee_tier: route.entry.fee_tier,
+            spread_percentage: route.quote.spread_percentage,
+            slippage_estimate: route.quote.slippage_estimate,
+            gas_cost_usd: route.quote.gas_cost_usd,
+            available_liquidity: route.quote.available_liquidity,
+            score: route.score,
+            model_pred_tar: route.model_pred_tar,
+            model_pred_flank: route.model_pred_flank,
+            percentile: route.percentile,
+            z_score: route.z_score,
+            breakdown,
+        })
+        .collect()
+}
+
+/// Stable hash of the scored batch, for proving a refactor didn't move scores
+///
+/// Every float is rendered with [`FINGERPRINT_DECIMALS`] fixed decimals
+/// (`-0` as `0`) and the resulting lines are hashed with 64-bit FNV-1a, so
+/// the value is the same on every platform and build. Breakdown notes are
+/// left out; they only restate the numbers.
+pub fn score_fingerprint(entries: &[TokenEntry], quotes: &[QuoteInfo], weights: &TarWeights) -> String {
+    fingerprint_records(&audit_routes(entries, quotes, weights))
+}
+
+/// [`score_fingerprint`] of already audited routes
+pub fn fingerprint_records(records: &[AuditRecord]) -> String {
+    let mut canonical = String::new();
+    for record in records {
+        let RouteId { chain_origin, chain_dest, native_token, dex_origin, dex_dest, bridge_protocol } = &record.route;
+        let _ = write!(
+            canonical,
+            "{}>{}|{}|{}>{}|{}",
+            chain_origin, chain_dest, native_token, dex_origin, dex_dest, bridge_protocol
+        );
+        let breakdown = &record.breakdown;
+        for value in [
+            record.liquidity_score,
+            record.fee_tier,
+            record.spread_percentage,
+            record.slippage_estimate,
+            record.gas_cost_usd,
+            record.available_liquidity,
+            record.score,
+            record.model_pred_tar,
+            record.model_pred_flank,
+            record.percentile,
+            record.z_score,
+            breakdown.token_quality,
+            breakdown.arbitrage_efficiency,
+            breakdown.risk,
+            breakdown.total,
+        ] {
+            canonical.push('|');
+            canonical.push_str(&fixed(value));
+        }
+        canonical.push('\n');
+    }
+    format!("{:016x}", fnv1a64(canonical.as_bytes()))
+}
+
+/// Fixed-decimal rendering with a single zero
+fn fixed(value: f64) -> String {
+    let value = if value == 0.0 { 0.0 } else { value };
+    format!("{:.*}", FINGERPRINT_DECIMALS, value)
+}
+
+fn fnv1a64(bytes: &[u8]) -> u64 {
+    const OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+    const PRIME: u64 = 0x0000_0100_0000_01b3;
+    bytes
+        .iter()
+        .fold(OFFSET_BASIS, |hash, byte| (hash ^ u64::from(*byte)).wrapping_mul(PRIME))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::omniarb::matrix_parser::load_token_matrix;
+
+    /// Golden comparisons are exact: any shift in a score is a failure
+    const TOLERANCE: f64 = 0.0;
+
+    const FIXTURE: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/../data/fixtures/omniarb_scoring_audit.md");
+    const GOLDEN: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/../data/fixtures/omniarb_scoring_audit_golden.json");
+
+    fn fixture() -> (Vec<TokenEntry>, Vec<QuoteInfo>) {
+        let entries = load_token_matrix(FIXTURE).unwrap();
+        let quotes = simulated_quotes(&entries);
+        (entries, quotes)
+    }
+
+    fn assert_exact(route: &RouteId, field: &str, actual: f64, expected: f64) {
+        assert!(
+            (actual - expected).abs() <= TOLERANCE || actual.to_bits() == expected.to_bits(),
+            "{:?} {}: {} != golden {}",
+            route,
+            field,
+            actual,
+            expected
+        );
+    }
+
+    #[test]
+    fn test_scores_match_golden_file() {
+        let (entries, quotes) = fixture();
+        let golden: AuditReport = serde_json::from_str(&std::fs::read_to_string(GOLDEN).unwrap()).unwrap();
+        let records = audit_routes(&entries, &quotes, &TarWeights::default());
+        assert_eq!(records.len(), golden.routes.len());
+
+        for (actual, expected) in records.iter().zip(&golden.routes) {
+            assert_eq!(actual.route, expected.route);
+            let route = &actual.route;
+            for (field, a, e) in [
+                ("liquidity_score", actual.liquidity_score, expected.liquidity_score),
+                ("fee_tier", actual.fee_tier, expected.fee_tier),
+                ("spread_percentage", actual.spread_percentage, expected.spread_percentage),
+                ("slippage_estimate", actual.slippage_estimate, expected.slippage_estimate),
+                ("gas_cost_usd", actual.gas_cost_usd, expected.gas_cost_usd),
+                ("available_liquidity", actual.available_liquidity, expected.available_liquidity),
+                ("score", actual.score, expected.score),
+                ("model_pred_tar", actual.model_pred_tar, expected.model_pred_tar),
+                ("model_pred_flank", actual.model_pred_flank, expected.model_pred_flank),
+                ("percentile", actual.percentile, expected.percentile),
+                ("z_score", actual.z_score, expected.z_score),
+                ("token_quality", actual.breakdown.token_quality, expected.breakdown.token_quality),
+                ("arbitrage_efficiency", actual.breakdown.arbitrage_efficiency, expected.breakdown.arbitrage_efficiency),
+                ("risk", actual.breakdown.risk, expected.breakdown.risk),
+                ("total", actual.breakdown.total, expected.breakdown.total),
+            ] {
+                assert_exact(route, field, a, e);
+            }
+            assert_eq!(actual.breakdown.notes, expected.breakdown.notes, "{:?}", route);
+        }
+
+        assert_eq!(fingerprint_records(&records), golden.fingerprint);
+        assert_eq!(score_fingerprint(&entries, &quotes, &TarWeights::default()), golden.fingerprint);
+    }
+
+    #[test]
+    fn test_fingerprint_tracks_weights() {
+        let (entries, quotes) = fixture();
+        let defaults = score_fingerprint(&entries, &quotes, &TarWeights::default());
+        assert_eq!(defaults.len(), 16);
+        assert_eq!(score_fingerprint(&entries, &quotes, &TarWeights::default()), defaults);
+
+        let risk_heavy: TarWeights = "20,20,60".parse().unwrap();
+        assert_ne!(score_fingerprint(&entries, &quotes, &risk_heavy), defaults);
+    }
+
+    #[test]
+    fn test_fixed_formatting() {
+        assert_eq!(fixed(-0.0), "0.000000000");
+        assert_eq!(fixed(94.25), "94.250000000");
+        assert_eq!(fnv1a64(b""), 0xcbf2_9ce4_8422_2325);
+        assert_eq!(fnv1a64(b"a"), 0xaf63_dc4c_8601_ec8c);
+    }
+}
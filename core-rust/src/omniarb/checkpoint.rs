@@ -0,0 +1,245 @@
+use std::collections::HashMap;
+use std::fs::{File, OpenOptions};
+use std::io::{self, BufWriter, Read, Write};
+use std::path::Path;
+use std::time::Duration;
+
+use log::warn;
+use serde::{Deserialize, Serialize};
+
+use crate::omniarb::data_fetcher::QuoteInfo;
+use crate::omniarb::matrix_diff::RouteId;
+use crate::omniarb::matrix_parser::TokenEntry;
+
+/// Routes scored between checkpoint flushes
+pub const CHECKPOINT_BATCH: usize = 50;
+
+/// A route with its quote and scores, one line of a checkpoint file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScoredRoute {
+    pub entry: TokenEntry,
+    pub quote: QuoteInfo,
+    pub score: f64,
+    pub model_pred_tar: f64,
+    pub model_pred_flank: f64,
+    /// Share of the batch scoring strictly lower (0..1), set by `rank_routes`
+    #[serde(default)]
+    pub percentile: f64,
+    /// Standard deviations from the batch mean score, set by `rank_routes`
+    #[serde(default)]
+    pub z_score: f64,
+    /// Largest spread between the TAR, ONNX and Flanker scores
+    #[serde(default)]
+    pub disagreement: f64,
+}
+
+/// Checkpoint line: a scored route stamped with the settings that scored it
+#[derive(Serialize)]
+struct RecordRef<'a> {
+    #[serde(flatten)]
+    route: &'a ScoredRoute,
+    scoring_key: &'a str,
+}
+
+#[derive(Deserialize)]
+struct Record {
+    #[serde(flatten)]
+    route: ScoredRoute,
+    /// Missing on lines written before keys were recorded, which never match
+    #[serde(default)]
+    scoring_key: String,
+}
+
+/// When restored routes are still good enough to reuse
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ResumePolicy {
+    /// Identifies the weights and settings routes are scored with; see
+    /// [`ScoringCheckpoint::scoring_key`]
+    pub scoring_key: String,
+    /// Routes whose quote is older than this are scored again
+    pub max_quote_age: Option<Duration>,
+}
+
+/// Append-only `.jsonl` record of scored routes, so long runs can resume
+///
+/// Routes already in the file are restored on open and keyed by
+/// [`RouteId`]; later lines win. Lines scored with a different scoring key
+/// or carrying quotes older than the policy allows are discarded, so those
+/// routes get quoted and scored again. A partially written last line (from
+/// a crash mid-write) is dropped and truncated away.
+pub struct ScoringCheckpoint {
+    writer: BufWriter<File>,
+    restored: HashMap<RouteId, ScoredRoute>,
+    discarded: usize,
+    scoring_key: String,
+}
+
+impl ScoringCheckpoint {
+    /// Short stable hash of a description of the scoring settings (weights,
+    /// ensemble, trade size), for [`ResumePolicy::scoring_key`]
+    pub fn scoring_key(settings: &str) -> String {
+        ethers::utils::hex::encode(&ethers::utils::keccak256(settings)[..8])
+    }
+
+    /// Open `path`, restoring the routes `policy` accepts unless `force` truncates it
+    pub fn open(path: impl AsRef<Path>, force: bool, policy: &ResumePolicy) -> io::Result<Self> {
+        let path = path.as_ref();
+        let mut file = OpenOptions::new().read(true).write(true).create(true).truncate(force).open(path)?;
+
+        let mut contents = String::new();
+        file.read_to_string(&mut contents)?;
+
+        let mut restored = HashMap::new();
+        let mut discarded = 0;
+        let mut valid_len = 0;
+        for (index, line) in contents.split_inclusive('\n').enumerate() {
+            // Only the last line can be missing its newline
+            if !line.ends_with('\n') {
+                warn!("Dropping partial last line of checkpoint {}", path.display());
+                break;
+            }
+            if !line.trim().is_empty() {
+                let record: Record = serde_json::from_str(line.trim_end()).map_err(|e| {
+                    io::Error::new(io::ErrorKind::InvalidData, format!("{} line {}: {}", path.display(), index + 1, e))
+                })?;
+                let stale = policy.max_quote_age.is_some_and(|max_age| record.route.quote.is_stale(max_age));
+                if record.scoring_key == policy.scoring_key && !stale {
+                    restored.insert(RouteId::from(&record.route.entry), record.route);
+                } else {
+                    discarded += 1;
+                }
+            }
+            valid_len += line.len();
+        }
+        file.set_len(valid_len as u64)?;
+
+        Ok(Self {
+            writer: BufWriter::new(OpenOptions::new().append(true).open(path)?),
+            restored,
+            discarded,
+            scoring_key: policy.scoring_key.clone(),
+        })
+    }
+
+    /// Score restored from the file for `entry`'s route, if any
+    pub fn get(&self, entry: &TokenEntry) -> Option<&ScoredRoute> {
+        self.restored.get(&RouteId::from(entry))
+    }
+
+    /// Number of routes restored from the file
+    pub fn restored_len(&self) -> usize {
+        self.restored.len()
+    }
+
+    /// Lines skipped on open for a different scoring key or a stale quote
+    pub fn discarded_len(&self) -> usize {
+        self.discarded
+    }
+
+    /// Buffer one scored route; call `flush` to persist
+    pub fn record(&mut self, route: &ScoredRoute) -> io::Result<()> {
+        let record = RecordRef { route, scoring_key: &self.scoring_key };
+        serde_json::to_writer(&mut self.writer, &record)?;
+        self.writer.write_all(b"\n")
+    }
+
+    pub fn flush(&mut self) -> io::Result<()> {
+        self.writer.flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn route(token: &str, score: f64) -> ScoredRoute {
+        ScoredRoute {
+            entry: TokenEntry {
+                chain_origin: 1,
+                chain_dest: 137,
+                native_token: token.to_string(),
+                bridge_protocol: "STARGATE".to_string(),
+                ..Default::default()
+            },
+            quote: QuoteInfo::default(),
+            score,
+            model_pred_tar: 0.0,
+            model_pred_flank: 0.0,
+            percentile: 0.0,
+            z_score: 0.0,
+            disagreement: 0.0,
+        }
+    }
+
+    fn temp_path(name: &str) -> std::path::PathBuf {
+        std::env::temp_dir().join(format!("titan_checkpoint_{}_{}", std::process::id(), name))
+    }
+
+    #[test]
+    fn test_resume_skips_scored_routes() {
+        let path = temp_path("resume.jsonl");
+        std::fs::remove_file(&path).ok();
+
+        let policy = ResumePolicy::default();
+        let mut checkpoint = ScoringCheckpoint::open(&path, false, &policy).unwrap();
+        checkpoint.record(&route("USDC", 91.0)).unwrap();
+        checkpoint.record(&route("WETH", 87.5)).unwrap();
+        checkpoint.flush().unwrap();
+        drop(checkpoint);
+
+        // Crash mid-write of a third route
+        let mut file = OpenOptions::new().append(true).open(&path).unwrap();
+        file.write_all(br#"{"entry":{"chain_origin":1,"#).unwrap();
+        drop(file);
+
+        let matrix = [route("USDC", 0.0).entry, route("WETH", 0.0).entry, route("LINK", 0.0).entry];
+        let checkpoint = ScoringCheckpoint::open(&path, false, &policy).unwrap();
+        assert_eq!(checkpoint.restored_len(), 2);
+        let pending: Vec<_> = matrix.iter().filter(|entry| checkpoint.get(entry).is_none()).collect();
+        assert_eq!(pending.len(), 1);
+        assert_eq!(pending[0].native_token, "LINK");
+        assert_eq!(checkpoint.get(&matrix[0]).unwrap().score, 91.0);
+        drop(checkpoint);
+        assert!(std::fs::read_to_string(&path).unwrap().ends_with("}\n"));
+
+        assert_eq!(ScoringCheckpoint::open(&path, true, &policy).unwrap().restored_len(), 0);
+        std::fs::remove_file(&path).ok();
+    }
+
+    #[test]
+    fn test_resume_discards_other_settings_and_stale_quotes() {
+        let path = temp_path("policy.jsonl");
+        std::fs::remove_file(&path).ok();
+
+        let policy = ResumePolicy {
+            scoring_key: ScoringCheckpoint::scoring_key("weights-a"),
+            max_quote_age: Some(Duration::from_secs(60)),
+        };
+        assert_eq!(policy.scoring_key, ScoringCheckpoint::scoring_key("weights-a"));
+        assert_ne!(policy.scoring_key, ScoringCheckpoint::scoring_key("weights-b"));
+
+        let mut checkpoint = ScoringCheckpoint::open(&path, false, &policy).unwrap();
+        checkpoint.record(&route("USDC", 91.0)).unwrap();
+        let mut old = route("WETH", 87.5);
+        old.quote.fetched_at -= chrono::Duration::minutes(5);
+        checkpoint.record(&old).unwrap();
+        checkpoint.flush().unwrap();
+        drop(checkpoint);
+
+        let checkpoint = ScoringCheckpoint::open(&path, false, &policy).unwrap();
+        assert_eq!(checkpoint.restored_len(), 1);
+        assert_eq!(checkpoint.discarded_len(), 1);
+        assert!(checkpoint.get(&old.entry).is_none());
+        drop(checkpoint);
+
+        // Changed weights: nothing from the earlier run is reused
+        let reweighted = ResumePolicy {
+            scoring_key: ScoringCheckpoint::scoring_key("weights-b"),
+            ..policy
+        };
+        let checkpoint = ScoringCheckpoint::open(&path, false, &reweighted).unwrap();
+        assert_eq!(checkpoint.restored_len(), 0);
+        assert_eq!(checkpoint.discarded_len(), 2);
+        std::fs::remove_file(&path).ok();
+    }
+}
@@ -0,0 +1,105 @@
+use serde::Serialize;
+
+use crate::omniarb::data_fetcher::{fetch_live_quotes, QuoteInfo};
+use crate::omniarb::matrix_parser::TokenEntry;
+use crate::omniarb::model_bridge::{BatchModel, HeuristicModel, Prediction};
+use crate::omniarb::tar_scorer::{calculate_tar_breakdown_batch, TarWeights};
+
+/// Scored routes column-major, one vector per field
+///
+/// Shaped for dataframe export: a large matrix becomes ten vectors instead
+/// of a record per route.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct RouteColumns {
+    pub chain_origin: Vec<u64>,
+    pub chain_dest: Vec<u64>,
+    pub native_token: Vec<String>,
+    pub dex_origin: Vec<String>,
+    pub dex_dest: Vec<String>,
+    pub bridge_protocol: Vec<String>,
+    /// TAR score (0-100)
+    pub tar: Vec<f64>,
+    /// Heuristic TAR model prediction; not an ONNX model's output
+    pub model_tar: Vec<f64>,
+    /// Flanker model prediction
+    pub flanker: Vec<f64>,
+    /// Matrix liquidity score
+    pub liquidity: Vec<f64>,
+}
+
+impl RouteColumns {
+    /// Score `entries[i]` against `quotes[i]`
+    ///
+    /// Pairs up to the shorter of the two. Entries are moved into the
+    /// columns, not copied.
+    pub fn score(mut entries: Vec<TokenEntry>, quotes: &[QuoteInfo], weights: &TarWeights) -> Self {
+        entries.truncate(quotes.len());
+        let quotes = &quotes[..entries.len()];
+        let scores = |predictions: Vec<Prediction>| predictions.into_iter().map(|p| p.score).collect();
+
+        let mut columns = Self {
+            tar: calculate_tar_breakdown_batch(&entries, quotes, weights)
+                .into_iter()
+                .map(|breakdown| breakdown.total)
+                .collect(),
+            model_tar: scores(HeuristicModel::tar().predict_batch(&entries, quotes)),
+            flanker: scores(HeuristicModel::flanker().predict_batch(&entries, quotes)),
+            ..Self::default()
+        };
+        for entry in entries {
+            columns.chain_origin.push(entry.chain_origin);
+            columns.chain_dest.push(entry.chain_dest);
+            columns.native_token.push(entry.native_token);
+            columns.dex_origin.push(entry.dex_origin);
+            columns.dex_dest.push(entry.dex_dest);
+            columns.bridge_protocol.push(entry.bridge_protocol);
+            columns.liquidity.push(entry.liquidity_score);
+        }
+        columns
+    }
+
+    /// Score each route against its simulated quote
+    pub fn simulated(entries: Vec<TokenEntry>, weights: &TarWeights) -> Self {
+        let quotes = fetch_live_quotes(&entries);
+        Self::score(entries, &quotes, weights)
+    }
+
+    pub fn len(&self) -> usize {
+        self.tar.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.tar.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::omniarb::{calculate_tar_score, load_token_matrix_auto, run_flanker, run_tar_onnx};
+
+    const SCHEMA_V2: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/../data/fixtures/omniarb_matrix_schema_v2.md");
+
+    #[test]
+    fn test_columns_match_row_wise_scoring() {
+        let entries = load_token_matrix_auto(SCHEMA_V2).unwrap();
+        let quotes = fetch_live_quotes(&entries);
+        let columns = RouteColumns::score(entries.clone(), &quotes, &TarWeights::default());
+        assert_eq!(columns.len(), entries.len());
+
+        for (i, (entry, quote)) in entries.iter().zip(&quotes).enumerate() {
+            assert_eq!(columns.tar[i], calculate_tar_score(entry, quote));
+            assert_eq!(columns.model_tar[i], run_tar_onnx(entry, quote).score);
+            assert_eq!(columns.flanker[i], run_flanker(entry, quote).score);
+            assert_eq!(columns.liquidity[i], entry.liquidity_score);
+            assert_eq!(columns.chain_origin[i], entry.chain_origin);
+            assert_eq!(columns.bridge_protocol[i], entry.bridge_protocol);
+        }
+        assert_eq!(RouteColumns::simulated(entries.clone(), &TarWeights::default()), columns);
+
+        // Unpaired entries are dropped
+        let short = RouteColumns::score(entries, &quotes[..1], &TarWeights::default());
+        assert_eq!(short.len(), 1);
+        assert_eq!(short.native_token.len(), 1);
+    }
+}
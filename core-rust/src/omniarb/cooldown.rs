@@ -0,0 +1,129 @@
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+use crate::omniarb::matrix_diff::RouteId;
+use crate::omniarb::matrix_parser::TokenEntry;
+
+/// Default time a route stays suppressed after it fires
+pub const DEFAULT_ROUTE_COOLDOWN: Duration = Duration::from_secs(60);
+
+/// Suppresses routes for a while after they're reported as actionable
+///
+/// Stops a live loop from re-surfacing (and re-executing) the route it
+/// just traded while the pool is still settling.
+#[derive(Debug, Clone)]
+pub struct CooldownTracker {
+    cooldown: Duration,
+    fired: HashMap<RouteId, Instant>,
+}
+
+impl Default for CooldownTracker {
+    fn default() -> Self {
+        Self::new(DEFAULT_ROUTE_COOLDOWN)
+    }
+}
+
+impl CooldownTracker {
+    pub fn new(cooldown: Duration) -> Self {
+        Self {
+            cooldown,
+            fired: HashMap::new(),
+        }
+    }
+
+    pub fn cooldown(&self) -> Duration {
+        self.cooldown
+    }
+
+    /// Start the route's cooldown now
+    pub fn mark_fired(&mut self, route: &TokenEntry) {
+        self.mark_fired_at(route, Instant::now());
+    }
+
+    /// Start the route's cooldown at `now`, restarting any running one
+    pub fn mark_fired_at(&mut self, route: &TokenEntry, now: Instant) {
+        self.fired.insert(RouteId::from(route), now);
+    }
+
+    /// Whether the route may be reported again
+    pub fn is_ready(&self, route: &TokenEntry) -> bool {
+        self.is_ready_at(route, Instant::now())
+    }
+
+    /// Whether the route may be reported again as of `now`
+    pub fn is_ready_at(&self, route: &TokenEntry, now: Instant) -> bool {
+        self.fired
+            .get(&RouteId::from(route))
+            .is_none_or(|fired_at| now.saturating_duration_since(*fired_at) >= self.cooldown)
+    }
+
+    /// Drop routes that aren't cooling as of `now`, keeping order
+    pub fn filter_ready_at(&self, routes: Vec<TokenEntry>, now: Instant) -> Vec<TokenEntry> {
+        routes.into_iter().filter(|route| self.is_ready_at(route, now)).collect()
+    }
+
+    /// Drop routes that are still cooling, keeping order
+    pub fn filter_ready(&self, routes: Vec<TokenEntry>) -> Vec<TokenEntry> {
+        self.filter_ready_at(routes, Instant::now())
+    }
+
+    /// Forget lapsed cooldowns, returning how many were removed
+    pub fn purge_expired_at(&mut self, now: Instant) -> usize {
+        let before = self.fired.len();
+        let cooldown = self.cooldown;
+        self.fired
+            .retain(|_, fired_at| now.saturating_duration_since(*fired_at) < cooldown);
+        before - self.fired.len()
+    }
+
+    /// Number of routes with a recorded cooldown
+    pub fn len(&self) -> usize {
+        self.fired.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.fired.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn route(token: &str) -> TokenEntry {
+        TokenEntry {
+            chain_origin: 137,
+            chain_dest: 42161,
+            native_token: token.to_string(),
+            dex_origin: "QUICKSWAP".to_string(),
+            dex_dest: "CAMELOT".to_string(),
+            bridge_protocol: "STARGATE".to_string(),
+            liquidity_score: 90.0,
+            fee_tier: 0.05,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_route_suppressed_until_cooldown_lapses() {
+        let start = Instant::now();
+        let mut tracker = CooldownTracker::new(Duration::from_secs(30));
+        tracker.mark_fired_at(&route("USDC"), start);
+
+        let routes = vec![route("USDC"), route("WETH")];
+        let within = tracker.filter_ready_at(routes.clone(), start + Duration::from_secs(29));
+        assert_eq!(within, vec![route("WETH")]);
+
+        // Scores don't affect route identity
+        let rescored = TokenEntry {
+            liquidity_score: 50.0,
+            ..route("USDC")
+        };
+        assert!(!tracker.is_ready_at(&rescored, start + Duration::from_secs(1)));
+
+        let after = start + Duration::from_secs(30);
+        assert_eq!(tracker.filter_ready_at(routes.clone(), after), routes);
+        assert_eq!(tracker.purge_expired_at(after), 1);
+        assert!(tracker.is_empty());
+    }
+}
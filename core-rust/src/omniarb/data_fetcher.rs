@@ -1,6 +1,20 @@
+use crate::config::Config;
+use crate::enum_matrix::BridgeKind;
+use crate::gas_oracle::GasOracle;
 use crate::omniarb::matrix_parser::TokenEntry;
+use crate::omniarb::quote_cache::QuoteCache;
+use crate::omniarb::quote_source::QuoteRouter;
+use crate::omniarb::socket_client::{SocketClient, SocketQuoteRequest};
+use crate::omniarb::token_matrix::{route_key, RouteKey};
+use ethers::providers::JsonRpcClient;
+use ethers::types::U256;
+use futures::future::join_all;
+use log::warn;
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::time::Duration;
+use tokio::sync::Semaphore;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct QuoteInfo {
@@ -8,12 +22,127 @@ pub struct QuoteInfo {
     pub slippage_estimate: f64,
     pub gas_cost_usd: f64,
     pub available_liquidity: f64,
+    /// When the quote was produced; quotes serialized without it count as fresh
+    #[serde(default = "Utc::now")]
+    pub fetched_at: DateTime<Utc>,
+    #[serde(default)]
+    pub source: QuoteProvider,
+    /// Bridge's cut of the notional, for quotes that don't price it into
+    /// spread or slippage
+    #[serde(default)]
+    pub bridge_fee_bps: u32,
+}
+
+impl Default for QuoteInfo {
+    fn default() -> Self {
+        Self {
+            spread_percentage: 0.0,
+            slippage_estimate: 0.0,
+            gas_cost_usd: 0.0,
+            available_liquidity: 0.0,
+            fetched_at: Utc::now(),
+            source: QuoteProvider::default(),
+            bridge_fee_bps: 0,
+        }
+    }
+}
+
+impl QuoteInfo {
+    /// Time since the quote was produced, as of `now`
+    pub fn age_at(&self, now: DateTime<Utc>) -> Duration {
+        (now - self.fetched_at).to_std().unwrap_or_default()
+    }
+
+    /// Time since the quote was produced
+    pub fn age(&self) -> Duration {
+        self.age_at(Utc::now())
+    }
+
+    /// Whether the quote is older than `max_age` as of `now`
+    pub fn is_stale_at(&self, now: DateTime<Utc>, max_age: Duration) -> bool {
+        self.age_at(now) > max_age
+    }
+
+    /// Whether the quote is older than `max_age`
+    pub fn is_stale(&self, max_age: Duration) -> bool {
+        self.age() > max_age
+    }
+
+    /// Whether all numeric fields are finite (no NaN or infinity)
+    pub fn is_finite(&self) -> bool {
+        self.spread_percentage.is_finite()
+            && self.slippage_estimate.is_finite()
+            && self.gas_cost_usd.is_finite()
+            && self.available_liquidity.is_finite()
+    }
+
+    /// Expected profit after slippage, bridge fee and gas for a trade of
+    /// `notional_usd`
+    pub fn estimated_net_profit_usd(&self, notional_usd: f64) -> f64 {
+        let bridge_fee_pct = f64::from(self.bridge_fee_bps) / 100.0;
+        notional_usd * (self.spread_percentage - self.slippage_estimate - bridge_fee_pct) / 100.0 - self.gas_cost_usd
+    }
+
+    /// Exponential moving average step: `alpha * self + (1 - alpha) * previous`
+    fn blend(&self, previous: &QuoteInfo, alpha: f64) -> QuoteInfo {
+        let ema = |current: f64, previous: f64| alpha * current + (1.0 - alpha) * previous;
+        QuoteInfo {
+            spread_percentage: ema(self.spread_percentage, previous.spread_percentage),
+            slippage_estimate: ema(self.slippage_estimate, previous.slippage_estimate),
+            gas_cost_usd: ema(self.gas_cost_usd, previous.gas_cost_usd),
+            available_liquidity: ema(self.available_liquidity, previous.available_liquidity),
+            fetched_at: self.fetched_at,
+            source: self.source,
+            bridge_fee_bps: self.bridge_fee_bps,
+        }
+    }
+}
+
+/// Per-route EMA state carried between scoring cycles
+#[derive(Debug, Clone, Default)]
+pub struct QuoteSmoother {
+    previous: HashMap<RouteKey, QuoteInfo>,
+}
+
+impl QuoteSmoother {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Blend a fresh quote into the route's running average
+    ///
+    /// `alpha` is the weight of the new quote (clamped to 0..=1; 1 disables
+    /// smoothing). The first observation for a route passes through as-is.
+    pub fn smooth(&mut self, entry: &TokenEntry, quote: QuoteInfo, alpha: f64) -> QuoteInfo {
+        let alpha = if alpha.is_finite() { alpha.clamp(0.0, 1.0) } else { 1.0 };
+        let smoothed = match self.previous.get(&route_key(entry)) {
+            Some(previous) if quote.is_finite() => quote.blend(previous, alpha),
+            _ => quote,
+        };
+        self.previous.insert(route_key(entry), smoothed.clone());
+        smoothed
+    }
+
+    /// Forget all routes
+    pub fn reset(&mut self) {
+        self.previous.clear();
+    }
+
+    /// Number of routes with smoothing state
+    pub fn len(&self) -> usize {
+        self.previous.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.previous.is_empty()
+    }
 }
 
 /// Fetch live bridge quotes for token matrix entries
 /// 
 /// In production, this would query real bridge APIs (LiFi, Socket, etc.)
-/// For now, returns simulated quotes based on market conditions
+/// For now, returns simulated quotes based on market conditions, carrying
+/// the fees of [`Config::default_bridges`]
 /// 
 /// # Arguments
 /// * `token_matrix` - Vector of token entries
@@ -21,12 +150,210 @@ pub struct QuoteInfo {
 /// # Returns
 /// Vector of quote information matching each entry
 pub fn fetch_live_quotes(token_matrix: &[TokenEntry]) -> Vec<QuoteInfo> {
+    let router = QuoteRouter::new().with_bridge_fees(&Config::default_bridges());
+    fetch_simulated_quotes(token_matrix, &router)
+}
+
+/// Simulated quotes for every route, carrying `router`'s configured fee
+/// for the route's bridge
+pub fn fetch_simulated_quotes(token_matrix: &[TokenEntry], router: &QuoteRouter) -> Vec<QuoteInfo> {
     token_matrix
         .iter()
-        .map(|entry| simulate_bridge_quote(entry))
+        .map(|entry| router.with_bridge_fee(entry, simulate_bridge_quote(entry)))
         .collect()
 }
 
+/// Fetch live quotes, EMA-smoothed per route across calls
+/// 
+/// Damps spot-quote noise so rankings don't flip between cycles.
+pub fn fetch_live_quotes_smoothed(
+    state: &mut QuoteSmoother,
+    token_matrix: &[TokenEntry],
+    alpha: f64,
+) -> Vec<QuoteInfo> {
+    token_matrix
+        .iter()
+        .zip(fetch_live_quotes(token_matrix))
+        .map(|(entry, quote)| state.smooth(entry, quote, alpha))
+        .collect()
+}
+
+/// Fetch live quotes with gas costs priced by `gas_oracle`
+/// 
+/// Each destination chain is priced once. Without an oracle, or when it
+/// fails for a chain, the static per-chain gas table is kept.
+pub async fn fetch_live_quotes_with_gas<P: JsonRpcClient + 'static>(
+    token_matrix: &[TokenEntry],
+    gas_oracle: Option<&GasOracle<P>>,
+) -> Vec<QuoteInfo> {
+    let mut quotes = fetch_live_quotes(token_matrix);
+    let Some(oracle) = gas_oracle else {
+        return quotes;
+    };
+
+    let mut chains: Vec<u64> = token_matrix.iter().map(|entry| entry.chain_dest).collect();
+    chains.sort_unstable();
+    chains.dedup();
+    let costs = join_all(chains.iter().map(|chain| oracle.swap_cost_usd(*chain))).await;
+    let live: HashMap<u64, f64> = chains
+        .into_iter()
+        .zip(costs)
+        .filter_map(|(chain, cost)| match cost {
+            Ok(cost) => Some((chain, cost)),
+            Err(e) => {
+                warn!("Live gas cost unavailable for chain {}: {}; using static estimate", chain, e);
+                None
+            }
+        })
+        .collect();
+
+    for (entry, quote) in token_matrix.iter().zip(quotes.iter_mut()) {
+        if let Some(cost) = live.get(&entry.chain_dest) {
+            quote.gas_cost_usd = *cost;
+        }
+    }
+    quotes
+}
+
+/// Fetch quotes for every route through `router`, concurrently
+/// 
+/// `amount_usd` is the notional each route is quoted for. Routes no source
+/// can quote fall back to the simulated quote. Simulated quotes carry the
+/// router's configured fee for the route's bridge.
+pub async fn fetch_live_quotes_async(
+    token_matrix: &[TokenEntry],
+    router: &QuoteRouter,
+    amount_usd: f64,
+) -> Vec<QuoteInfo> {
+    fetch_live_quotes_bounded(token_matrix, router, amount_usd, token_matrix.len()).await
+}
+
+/// [`fetch_live_quotes_async`] with at most `concurrency` quotes in flight,
+/// in route order
+pub async fn fetch_live_quotes_bounded(
+    token_matrix: &[TokenEntry],
+    router: &QuoteRouter,
+    amount_usd: f64,
+    concurrency: usize,
+) -> Vec<QuoteInfo> {
+    let permits = Semaphore::new(concurrency.max(1));
+    let permits = &permits;
+    join_all(token_matrix.iter().map(|entry| async move {
+        // Never closed, so acquiring only waits for a free slot
+        let _permit = permits.acquire().await;
+        router.quote(entry, amount_usd).await.unwrap_or_else(|e| {
+            warn!(
+                "No quote for {} {}>{}: {}; using simulated quote",
+                entry.native_token, entry.chain_origin, entry.chain_dest, e
+            );
+            router.with_bridge_fee(entry, simulate_bridge_quote(entry))
+        })
+    }))
+    .await
+}
+
+/// Where a route's live quote comes from
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum QuoteProvider {
+    /// Socket (Bungee) quote API
+    Socket,
+    /// Local `simulate_bridge_quote` model
+    #[default]
+    Simulated,
+}
+
+impl QuoteProvider {
+    /// Quote source for a matrix bridge name
+    pub fn for_bridge(bridge_protocol: &str) -> Self {
+        match BridgeKind::from_name(bridge_protocol) {
+            Some(BridgeKind::Socket | BridgeKind::LayerZero) => QuoteProvider::Socket,
+            _ => QuoteProvider::Simulated,
+        }
+    }
+}
+
+/// Fetch quotes from each route's provider, concurrently
+/// 
+/// SOCKET and LAYERZERO routes with both token addresses are quoted by
+/// `socket` for `from_amount`; everything else, and any failed request,
+/// falls back to the simulated quote.
+pub async fn fetch_routed_quotes(
+    token_matrix: &[TokenEntry],
+    socket: Option<&SocketClient>,
+    from_amount: U256,
+    user_address: &str,
+) -> Vec<QuoteInfo> {
+    fetch_quotes(token_matrix, socket, from_amount, user_address, None, false).await
+}
+
+/// Like `fetch_routed_quotes`, but serves API quotes from `cache` while fresh
+/// 
+/// With `bypass` set the cache isn't read, though fresh quotes are still
+/// stored. Simulated fallbacks are never cached.
+pub async fn fetch_routed_quotes_cached(
+    token_matrix: &[TokenEntry],
+    socket: Option<&SocketClient>,
+    from_amount: U256,
+    user_address: &str,
+    cache: &QuoteCache,
+    bypass: bool,
+) -> Vec<QuoteInfo> {
+    fetch_quotes(token_matrix, socket, from_amount, user_address, Some(cache), bypass).await
+}
+
+async fn fetch_quotes(
+    token_matrix: &[TokenEntry],
+    socket: Option<&SocketClient>,
+    from_amount: U256,
+    user_address: &str,
+    cache: Option<&QuoteCache>,
+    bypass: bool,
+) -> Vec<QuoteInfo> {
+    join_all(token_matrix.iter().map(|entry| async move {
+        let request = socket.zip(socket_request(entry, from_amount, user_address));
+        if let Some((client, request)) = request {
+            if let Some(cached) = cache.filter(|_| !bypass).and_then(|c| c.get(entry, from_amount)) {
+                return cached;
+            }
+            match client.quote(&request).await {
+                Ok(quote) => {
+                    let info = quote.to_quote_info();
+                    if let Some(cache) = cache {
+                        cache.insert(entry, from_amount, info.clone());
+                    }
+                    return info;
+                }
+                Err(e) => warn!(
+                    "Socket quote failed for {} {}>{}: {}; using simulated quote",
+                    entry.native_token, entry.chain_origin, entry.chain_dest, e
+                ),
+            }
+        }
+        simulate_bridge_quote(entry)
+    }))
+    .await
+}
+
+/// Socket request for a route, if it's bridged by Socket and has both token addresses
+pub(crate) fn socket_request(entry: &TokenEntry, from_amount: U256, user_address: &str) -> Option<SocketQuoteRequest> {
+    match (
+        QuoteProvider::for_bridge(&entry.bridge_protocol),
+        &entry.token_address_origin,
+        &entry.token_address_dest,
+    ) {
+        (QuoteProvider::Socket, Some(from_token), Some(to_token)) => Some(SocketQuoteRequest {
+            from_chain_id: entry.chain_origin,
+            to_chain_id: entry.chain_dest,
+            from_token_address: from_token.clone(),
+            to_token_address: to_token.clone(),
+            from_amount,
+            user_address: user_address.to_string(),
+        }),
+        _ => None,
+    }
+}
+
 /// Simulate bridge quote based on entry parameters
 /// 
 /// This is a placeholder for real API integration
@@ -35,7 +362,18 @@ pub fn fetch_live_quotes(token_matrix: &[TokenEntry]) -> Vec<QuoteInfo> {
 /// - Socket API: https://api.socket.tech/v2/quote
 /// - Across API: https://across.to/api/suggested-fees
 /// 
-fn simulate_bridge_quote(entry: &TokenEntry) -> QuoteInfo {
+pub(crate) fn simulate_bridge_quote(entry: &TokenEntry) -> QuoteInfo {
+    // Non-finite inputs get a worst-case quote instead of propagating NaN
+    if !entry.is_finite() {
+        return QuoteInfo {
+            spread_percentage: 0.0,
+            slippage_estimate: 2.0,
+            gas_cost_usd: estimate_gas_cost(entry.chain_dest),
+            available_liquidity: 0.0,
+            ..Default::default()
+        };
+    }
+    
     // Base spread from liquidity and fee tier
     let base_spread = (entry.liquidity_score / 100.0) * 2.0 - entry.fee_tier;
     
@@ -59,6 +397,7 @@ fn simulate_bridge_quote(entry: &TokenEntry) -> QuoteInfo {
         slippage_estimate: slippage,
         gas_cost_usd: gas_cost,
         available_liquidity: liquidity,
+        ..Default::default()
     }
 }
 
@@ -135,6 +474,7 @@ mod tests {
                 bridge_protocol: "STARGATE".to_string(),
                 liquidity_score: 95.0,
                 fee_tier: 0.3,
+                ..Default::default()
             },
         ];
         
@@ -142,4 +482,199 @@ mod tests {
         assert_eq!(quotes.len(), 1);
         assert!(quotes[0].spread_percentage >= 0.0);
     }
+    
+    #[tokio::test]
+    async fn test_gas_oracle_fallback_to_static_table() {
+        use crate::config::GasPolicy;
+        use ethers::providers::{MockProvider, Provider};
+
+        let entries = vec![usdc_route()];
+        let static_quotes = fetch_live_quotes(&entries);
+
+        let absent: Option<&GasOracle<MockProvider>> = None;
+        let quotes = fetch_live_quotes_with_gas(&entries, absent).await;
+        assert_eq!(quotes[0].gas_cost_usd, static_quotes[0].gas_cost_usd);
+
+        // Provider with no queued responses: every RPC call errors
+        let (provider, _mock) = Provider::mocked();
+        let oracle = GasOracle::new(GasPolicy::default()).with_provider(137, std::sync::Arc::new(provider));
+        let quotes = fetch_live_quotes_with_gas(&entries, Some(&oracle)).await;
+        assert_eq!(quotes[0].gas_cost_usd, static_quotes[0].gas_cost_usd);
+    }
+
+    #[tokio::test]
+    async fn test_bounded_fetch_limits_quotes_in_flight() {
+        use crate::omniarb::quote_source::{QuoteError, QuoteSource, SimulatedSource};
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        use std::sync::Arc;
+
+        #[derive(Default)]
+        struct SlowSource {
+            in_flight: AtomicUsize,
+            peak: AtomicUsize,
+        }
+
+        #[async_trait::async_trait]
+        impl QuoteSource for Arc<SlowSource> {
+            fn name(&self) -> &str {
+                "SLOW"
+            }
+
+            fn supports(&self, _entry: &TokenEntry) -> bool {
+                true
+            }
+
+            async fn quote(&self, entry: &TokenEntry, amount_usd: f64) -> Result<QuoteInfo, QuoteError> {
+                let now = self.in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+                self.peak.fetch_max(now, Ordering::SeqCst);
+                tokio::time::sleep(Duration::from_millis(10)).await;
+                self.in_flight.fetch_sub(1, Ordering::SeqCst);
+                SimulatedSource.quote(entry, amount_usd).await
+            }
+        }
+
+        let source = Arc::new(SlowSource::default());
+        let router = QuoteRouter::new().with_source(Arc::clone(&source));
+        let entries: Vec<TokenEntry> = (0..12).map(|i| TokenEntry { chain_dest: 137 + i, ..usdc_route() }).collect();
+        let quotes = fetch_live_quotes_bounded(&entries, &router, 1_000.0, 3).await;
+        assert_eq!(source.peak.load(Ordering::SeqCst), 3);
+        // Still in route order
+        let key = |quote: &QuoteInfo| (quote.spread_percentage, quote.gas_cost_usd);
+        let expected: Vec<_> = fetch_live_quotes(&entries).iter().map(key).collect();
+        assert_eq!(quotes.iter().map(key).collect::<Vec<_>>(), expected);
+    }
+
+    #[test]
+    fn test_old_quote_shape_deserializes() {
+        let old = r#"{"spread_percentage":1.5,"slippage_estimate":0.1,"gas_cost_usd":0.5,"available_liquidity":1000.0}"#;
+        let quote: QuoteInfo = serde_json::from_str(old).unwrap();
+        assert_eq!(quote.spread_percentage, 1.5);
+        assert_eq!(quote.source, QuoteProvider::Simulated);
+        assert!(!quote.is_stale(Duration::from_secs(60)));
+
+        let json = serde_json::to_value(&quote).unwrap();
+        assert_eq!(json["source"], "simulated");
+        let round_trip: QuoteInfo = serde_json::from_value(json).unwrap();
+        assert_eq!(round_trip.fetched_at, quote.fetched_at);
+    }
+
+    #[test]
+    fn test_staleness_boundary() {
+        let quote = QuoteInfo::default();
+        let max_age = Duration::from_secs(30);
+        let at = |secs: i64| quote.fetched_at + chrono::Duration::seconds(secs);
+
+        assert!(!quote.is_stale_at(at(30), max_age));
+        assert!(quote.is_stale_at(at(30) + chrono::Duration::milliseconds(1), max_age));
+        // Clock skew: a quote from the future isn't stale
+        assert!(!quote.is_stale_at(at(-5), max_age));
+    }
+
+    #[test]
+    fn test_age_grows() {
+        let quote = QuoteInfo::default();
+        let first = quote.age();
+        std::thread::sleep(Duration::from_millis(20));
+        let second = quote.age();
+        assert!(second >= first + Duration::from_millis(20));
+        assert!(!quote.is_stale(Duration::from_secs(60)));
+        assert!(quote.is_stale(Duration::from_millis(10)));
+    }
+
+    #[test]
+    fn test_quote_provider_selection() {
+        assert_eq!(QuoteProvider::for_bridge("SOCKET"), QuoteProvider::Socket);
+        assert_eq!(QuoteProvider::for_bridge("Bungee"), QuoteProvider::Socket);
+        assert_eq!(QuoteProvider::for_bridge("LAYERZERO"), QuoteProvider::Socket);
+        assert_eq!(QuoteProvider::for_bridge("STARGATE"), QuoteProvider::Simulated);
+        assert_eq!(QuoteProvider::for_bridge("UNKNOWN"), QuoteProvider::Simulated);
+    }
+    
+    #[test]
+    fn test_non_finite_entry_quote() {
+        let entry = TokenEntry {
+            chain_origin: 1,
+            chain_dest: 137,
+            native_token: "USDC".to_string(),
+            dex_origin: "UNISWAP_V3".to_string(),
+            dex_dest: "QUICKSWAP".to_string(),
+            bridge_protocol: "STARGATE".to_string(),
+            liquidity_score: f64::NAN,
+            fee_tier: 0.3,
+            ..Default::default()
+        };
+        
+        let quotes = fetch_live_quotes(&[entry]);
+        assert!(quotes[0].is_finite());
+        assert_eq!(quotes[0].available_liquidity, 0.0);
+    }
+    
+    fn quote(spread: f64) -> QuoteInfo {
+        QuoteInfo {
+            spread_percentage: spread,
+            slippage_estimate: 0.1,
+            gas_cost_usd: 0.5,
+            available_liquidity: 950_000.0,
+            ..Default::default()
+        }
+    }
+    
+    fn usdc_route() -> TokenEntry {
+        TokenEntry {
+            chain_origin: 1,
+            chain_dest: 137,
+            native_token: "USDC".to_string(),
+            dex_origin: "UNISWAP_V3".to_string(),
+            dex_dest: "QUICKSWAP".to_string(),
+            bridge_protocol: "STARGATE".to_string(),
+            liquidity_score: 95.0,
+            fee_tier: 0.3,
+            ..Default::default()
+        }
+    }
+    
+    #[test]
+    fn test_smoothing_first_observation_passes_through() {
+        let mut smoother = QuoteSmoother::new();
+        let first = smoother.smooth(&usdc_route(), quote(2.0), 0.2);
+        assert_eq!(first.spread_percentage, 2.0);
+        assert_eq!(smoother.len(), 1);
+    }
+    
+    #[test]
+    fn test_smoothing_converges_on_repeated_input() {
+        let mut smoother = QuoteSmoother::new();
+        smoother.smooth(&usdc_route(), quote(0.0), 0.3);
+        
+        let mut smoothed = quote(0.0);
+        for _ in 0..50 {
+            smoothed = smoother.smooth(&usdc_route(), quote(1.0), 0.3);
+        }
+        assert!((smoothed.spread_percentage - 1.0).abs() < 1e-6);
+    }
+    
+    #[test]
+    fn test_smoothing_dampens_outlier() {
+        let mut smoother = QuoteSmoother::new();
+        for _ in 0..5 {
+            smoother.smooth(&usdc_route(), quote(1.0), 0.2);
+        }
+        let smoothed = smoother.smooth(&usdc_route(), quote(10.0), 0.2);
+        assert!((smoothed.spread_percentage - 2.8).abs() < 1e-9);
+        
+        // Other routes keep independent state
+        let mut other = usdc_route();
+        other.bridge_protocol = "ACROSS".to_string();
+        assert_eq!(smoother.smooth(&other, quote(10.0), 0.2).spread_percentage, 10.0);
+    }
+    
+    #[test]
+    fn test_fetch_live_quotes_smoothed() {
+        let entries = vec![usdc_route()];
+        let mut smoother = QuoteSmoother::new();
+        let first = fetch_live_quotes_smoothed(&mut smoother, &entries, 0.5);
+        let second = fetch_live_quotes_smoothed(&mut smoother, &entries, 0.5);
+        assert_eq!(first[0].spread_percentage, second[0].spread_percentage);
+        assert_eq!(first[0].spread_percentage, fetch_live_quotes(&entries)[0].spread_percentage);
+    }
 }
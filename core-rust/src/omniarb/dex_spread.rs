@@ -0,0 +1,293 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use anyhow::{anyhow, Result};
+use ethers::prelude::*;
+use futures::future::join_all;
+use log::{debug, warn};
+
+use crate::dex_quoter::uniswap_v3_quoter;
+use crate::enum_matrix::{DexKind, ProviderManager};
+use crate::omniarb::data_fetcher::{fetch_live_quotes, QuoteInfo};
+use crate::omniarb::matrix_parser::TokenEntry;
+use crate::simulation_engine::{TitanSimulationEngine, ERC20};
+
+abigen!(
+    UniswapV3Pool,
+    r#"[
+        function token0() external view returns (address)
+        function token1() external view returns (address)
+        function fee() external view returns (uint24)
+    ]"#,
+);
+
+/// Default notional quoted on each venue, in whole tokens
+pub const DEFAULT_SPREAD_NOTIONAL: f64 = 1.0;
+
+/// Cross-venue spread from live Uniswap V3 quotes on both legs of a route
+///
+/// Each leg sells the route token into the other token of its pool through
+/// the chain's QuoterV2 at the route's fee tier, so both pools should pair
+/// it with the same asset (e.g. WETH/USDC on both chains). Legs on other
+/// DEXes have no quoter here and are skipped. Quotes go through a per-chain
+/// [`TitanSimulationEngine`], so routes sharing a pool quote it once per
+/// [`start_cycle`](Self::start_cycle).
+pub struct DexSpreadCalculator<P: JsonRpcClient = Http> {
+    providers: HashMap<u64, Arc<Provider<P>>>,
+    engines: HashMap<u64, TitanSimulationEngine>,
+    notional: f64,
+}
+
+impl DexSpreadCalculator<Http> {
+    /// Use the providers already opened by `manager`
+    pub fn from_provider_manager(manager: &ProviderManager) -> Self {
+        manager
+            .get_all_providers()
+            .iter()
+            .fold(Self::new(), |calc, (chain_id, provider)| {
+                calc.with_provider(*chain_id, Arc::clone(provider))
+            })
+    }
+}
+
+impl<P: JsonRpcClient + 'static> DexSpreadCalculator<P> {
+    pub fn new() -> Self {
+        Self {
+            providers: HashMap::new(),
+            engines: HashMap::new(),
+            notional: DEFAULT_SPREAD_NOTIONAL,
+        }
+    }
+
+    /// Provider used for a chain's pool and quoter calls
+    pub fn with_provider(mut self, chain_id: u64, provider: Arc<Provider<P>>) -> Self {
+        self.engines.insert(chain_id, TitanSimulationEngine::new(chain_id, provider.clone()));
+        self.providers.insert(chain_id, provider);
+        self
+    }
+
+    /// Start a quote cycle on every chain at its current block
+    ///
+    /// Until the next cycle, quotes are pinned to that block and repeated
+    /// quotes of a pool are served from memory. A chain whose block can't
+    /// be read quotes uncached at the latest block instead.
+    pub async fn start_cycle(&self) {
+        join_all(self.engines.iter().map(|(chain_id, engine)| async move {
+            match engine.get_block_number().await {
+                Ok(block) => engine.start_cycle(block),
+                Err(e) => {
+                    warn!("Chain {} block unavailable, quoting uncached: {}", chain_id, e);
+                    engine.clear_quote_cache();
+                }
+            }
+        }))
+        .await;
+    }
+
+    /// Amount of the route token quoted on each venue, in whole tokens
+    pub fn with_notional(mut self, notional: f64) -> Self {
+        self.notional = notional;
+        self
+    }
+
+    /// Spread in percent: `(dest_price - origin_price) / origin_price * 100`
+    ///
+    /// Errors when the entry lacks token or pool addresses, a leg isn't on
+    /// Uniswap V3, a chain has no provider or quoter, a pool isn't at the
+    /// route's fee tier, or any call fails.
+    pub async fn spread_percentage(&self, entry: &TokenEntry) -> Result<f64> {
+        let fee = entry.uniswap_fee();
+        let origin = self
+            .leg_price(entry.chain_origin, &entry.dex_origin, fee, &entry.token_address_origin, &entry.pool_address_origin)
+            .await?;
+        let dest = self
+            .leg_price(entry.chain_dest, &entry.dex_dest, fee, &entry.token_address_dest, &entry.pool_address_dest)
+            .await?;
+        if origin <= 0.0 {
+            return Err(anyhow!("Zero origin quote on chain {}", entry.chain_origin));
+        }
+        Ok((dest - origin) / origin * 100.0)
+    }
+
+    /// Counter-asset received per route token on one venue
+    async fn leg_price(
+        &self,
+        chain_id: u64,
+        dex: &str,
+        fee: u32,
+        token: &Option<String>,
+        pool: &Option<String>,
+    ) -> Result<f64> {
+        if DexKind::from_name(dex) != Some(DexKind::UniswapV3) {
+            return Err(anyhow!("No quoter for {} on chain {}", dex, chain_id));
+        }
+        let (token, pool) = match (token, pool) {
+            (Some(token), Some(pool)) => (token.parse::<Address>()?, pool.parse::<Address>()?),
+            _ => return Err(anyhow!("Missing token or pool address on chain {}", chain_id)),
+        };
+        let (provider, engine) = self
+            .providers
+            .get(&chain_id)
+            .zip(self.engines.get(&chain_id))
+            .ok_or_else(|| anyhow!("No provider for chain {}", chain_id))?;
+        let quoter = uniswap_v3_quoter(chain_id).ok_or_else(|| anyhow!("No quoter on chain {}", chain_id))?;
+
+        let pool = UniswapV3Pool::new(pool, Arc::clone(provider));
+        let token0 = pool.token_0().call().await?;
+        let token1 = pool.token_1().call().await?;
+        let pool_fee = pool.fee().call().await?;
+        if pool_fee != fee {
+            return Err(anyhow!("Pool {:?} has fee {}, route fee tier is {}", pool.address(), pool_fee, fee));
+        }
+        let counter = match (token0 == token, token1 == token) {
+            (true, _) => token1,
+            (_, true) => token0,
+            _ => return Err(anyhow!("Token {:?} not in pool {:?}", token, pool.address())),
+        };
+
+        let token_decimals = ERC20::new(token, Arc::clone(provider)).decimals().call().await?;
+        let counter_decimals = ERC20::new(counter, Arc::clone(provider)).decimals().call().await?;
+        let amount_in = U256::from_dec_str(&format!("{:.0}", self.notional * 10f64.powi(token_decimals as i32)))?;
+
+        let amount_out = engine.try_get_price_impact(token, counter, amount_in, fee, quoter).await?;
+        let out = amount_out.to_string().parse::<f64>()? / 10f64.powi(counter_decimals as i32);
+        debug!("Chain {} pool {:?}: {} in -> {} out", chain_id, pool.address(), self.notional, out);
+        Ok(out / self.notional)
+    }
+}
+
+impl<P: JsonRpcClient + 'static> Default for DexSpreadCalculator<P> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Fetch live quotes with spreads measured on-chain by `calculator`
+///
+/// Each call is one quote cycle, see [`DexSpreadCalculator::start_cycle`].
+/// Routes without token/pool addresses on both legs, or whose quotes
+/// fail, keep the heuristic spread.
+pub async fn fetch_live_quotes_with_spreads<P: JsonRpcClient + 'static>(
+    token_matrix: &[TokenEntry],
+    calculator: Option<&DexSpreadCalculator<P>>,
+) -> Vec<QuoteInfo> {
+    let mut quotes = fetch_live_quotes(token_matrix);
+    let Some(calculator) = calculator else {
+        return quotes;
+    };
+    calculator.start_cycle().await;
+
+    let spreads = join_all(token_matrix.iter().map(|entry| calculator.spread_percentage(entry))).await;
+    for ((entry, quote), spread) in token_matrix.iter().zip(quotes.iter_mut()).zip(spreads) {
+        match spread {
+            Ok(spread) if spread.is_finite() => quote.spread_percentage = spread,
+            Ok(_) => {}
+            Err(e) => warn!(
+                "On-chain spread unavailable for {} {}>{}: {}; using heuristic",
+                entry.native_token, entry.chain_origin, entry.chain_dest, e
+            ),
+        }
+    }
+    quotes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::abi_call::{address_word, uint_words};
+
+    const WETH_POLYGON: &str = "0x7ceB23fD6bC0adD59E62ac25578270cFf1b9f619";
+    const USDC_POLYGON: &str = "0x3c499c542cEF5E3811e1192ce70d8cC03d5c3359";
+    const WETH_ARBITRUM: &str = "0x82aF49447D8a07e3bd95BD0d56f35241523fBab1";
+    const USDC_ARBITRUM: &str = "0xaf88d065e77c8cC2239327C5EDb3A432268e5831";
+
+    /// WETH/USDC 500 pool quoting 1 WETH for `usdc_out` USDC
+    fn mocked_leg(weth: &str, usdc: &str, usdc_out: u64) -> Arc<Provider<MockProvider>> {
+        let (provider, mock) = Provider::mocked();
+        // Served last-in first-out: token0, token1, fee, decimals x2, quote
+        // (amountOut, sqrtPriceX96After, initializedTicksCrossed, gasEstimate)
+        let responses = [
+            uint_words(&[usdc_out * 1_000_000, 0, 1, 80_000]),
+            uint_words(&[6]),
+            uint_words(&[18]),
+            uint_words(&[500]),
+            address_word(usdc),
+            address_word(weth),
+        ];
+        for response in responses {
+            mock.push::<Bytes, _>(response).unwrap();
+        }
+        Arc::new(provider)
+    }
+
+    fn weth_route() -> TokenEntry {
+        TokenEntry {
+            chain_origin: 137,
+            chain_dest: 42161,
+            native_token: "WETH".to_string(),
+            dex_origin: "UNISWAP_V3".to_string(),
+            dex_dest: "UNISWAP_V3".to_string(),
+            bridge_protocol: "STARGATE".to_string(),
+            liquidity_score: 90.0,
+            fee_tier: 0.05,
+            token_address_origin: Some(WETH_POLYGON.to_string()),
+            token_address_dest: Some(WETH_ARBITRUM.to_string()),
+            pool_address_origin: Some("0x45dDa9cb7c25131DF268515131f647d726f50608".to_string()),
+            pool_address_dest: Some("0xC6962004f452bE9203591991D15f6b388e09E8D0".to_string()),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_spread_from_two_chains() {
+        let (origin, dest) = (mocked_leg(WETH_POLYGON, USDC_POLYGON, 3000), mocked_leg(WETH_ARBITRUM, USDC_ARBITRUM, 3030));
+        // Each chain's cycle block, read first
+        for (leg, block) in [(&origin, 100u64), (&dest, 200)] {
+            AsRef::<MockProvider>::as_ref(leg.as_ref()).push(U64::from(block)).unwrap();
+        }
+        let calculator = DexSpreadCalculator::new().with_provider(137, origin).with_provider(42161, dest);
+
+        let quotes = fetch_live_quotes_with_spreads(&[weth_route()], Some(&calculator)).await;
+        assert!((quotes[0].spread_percentage - 1.0).abs() < 1e-9, "{}", quotes[0].spread_percentage);
+    }
+
+    #[tokio::test]
+    async fn test_missing_addresses_use_heuristic() {
+        let entry = TokenEntry {
+            pool_address_dest: None,
+            ..weth_route()
+        };
+        let heuristic = fetch_live_quotes(std::slice::from_ref(&entry))[0].spread_percentage;
+
+        let calculator = DexSpreadCalculator::new()
+            .with_provider(137, mocked_leg(WETH_POLYGON, USDC_POLYGON, 3000))
+            .with_provider(42161, mocked_leg(WETH_ARBITRUM, USDC_ARBITRUM, 3030));
+        assert!(calculator.spread_percentage(&entry).await.is_err());
+
+        let quotes = fetch_live_quotes_with_spreads(&[entry], Some(&calculator)).await;
+        assert_eq!(quotes[0].spread_percentage, heuristic);
+    }
+
+    #[tokio::test]
+    async fn test_legs_off_the_route_dex_or_tier_are_skipped() {
+        let calculator = || {
+            DexSpreadCalculator::new()
+                .with_provider(137, mocked_leg(WETH_POLYGON, USDC_POLYGON, 3000))
+                .with_provider(42161, mocked_leg(WETH_ARBITRUM, USDC_ARBITRUM, 3030))
+        };
+
+        let quickswap = TokenEntry {
+            dex_origin: "QUICKSWAP".to_string(),
+            ..weth_route()
+        };
+        let err = calculator().spread_percentage(&quickswap).await.unwrap_err();
+        assert!(err.to_string().contains("No quoter for QUICKSWAP"), "{}", err);
+
+        // The mocked pools are 0.05% pools
+        let wrong_tier = TokenEntry {
+            fee_tier: 0.3,
+            ..weth_route()
+        };
+        let err = calculator().spread_percentage(&wrong_tier).await.unwrap_err();
+        assert!(err.to_string().contains("route fee tier is 3000"), "{}", err);
+    }
+}
@@ -0,0 +1,361 @@
+use std::fs::{File, OpenOptions};
+use std::io::{self, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::mpsc::{self, Receiver, SyncSender, TrySendError};
+use std::thread::JoinHandle;
+
+use chrono::{DateTime, Utc};
+use log::warn;
+use serde::{Deserialize, Serialize};
+
+use crate::omniarb::data_fetcher::{QuoteInfo, QuoteProvider};
+use crate::omniarb::matrix_diff::RouteId;
+use crate::omniarb::matrix_parser::TokenEntry;
+use crate::omniarb::model_bridge::{FeatureVector, FEATURE_SCHEMA_VERSION};
+
+/// Records queued for the writer before new ones are dropped
+pub const DEFAULT_FEATURE_LOG_CAPACITY: usize = 4096;
+
+/// Feature log size at which it's rotated
+pub const DEFAULT_FEATURE_LOG_MAX_BYTES: u64 = 64 * 1024 * 1024;
+
+/// Feature log files kept, counting the one being written
+pub const DEFAULT_FEATURE_LOG_MAX_FILES: usize = 5;
+
+/// Where and how much of the scoring path to log for training
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FeatureLogConfig {
+    /// Log file; logging is off without one
+    pub path: Option<PathBuf>,
+    /// Share of scored routes logged, 0-1
+    pub sample_rate: f64,
+    pub max_bytes: u64,
+    pub max_files: usize,
+}
+
+impl Default for FeatureLogConfig {
+    fn default() -> Self {
+        Self {
+            path: None,
+            sample_rate: 1.0,
+            max_bytes: DEFAULT_FEATURE_LOG_MAX_BYTES,
+            max_files: DEFAULT_FEATURE_LOG_MAX_FILES,
+        }
+    }
+}
+
+impl FeatureLogConfig {
+    /// Defaults overridden by `FEATURE_LOG_PATH`, `FEATURE_LOG_SAMPLE_RATE`,
+    /// `FEATURE_LOG_MAX_BYTES` and `FEATURE_LOG_MAX_FILES`
+    pub fn from_vars(var: impl Fn(&str) -> Option<String>) -> Self {
+        let defaults = Self::default();
+        Self {
+            path: var("FEATURE_LOG_PATH").filter(|path| !path.trim().is_empty()).map(PathBuf::from),
+            sample_rate: var("FEATURE_LOG_SAMPLE_RATE")
+                .and_then(|v| v.trim().parse::<f64>().ok())
+                .filter(|rate| rate.is_finite())
+                .map_or(defaults.sample_rate, |rate| rate.clamp(0.0, 1.0)),
+            // Zero would rotate on every write or keep no file at all
+            max_bytes: var("FEATURE_LOG_MAX_BYTES")
+                .and_then(|v| v.trim().parse().ok())
+                .filter(|bytes| *bytes > 0)
+                .unwrap_or(defaults.max_bytes),
+            max_files: var("FEATURE_LOG_MAX_FILES")
+                .and_then(|v| v.trim().parse().ok())
+                .filter(|files| *files > 0)
+                .unwrap_or(defaults.max_files),
+        }
+    }
+
+    pub fn from_env() -> Self {
+        Self::from_vars(|name| std::env::var(name).ok())
+    }
+}
+
+/// One scored route as the serving path saw it
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FeatureRecord {
+    pub timestamp: DateTime<Utc>,
+    pub route_key: String,
+    /// [`FEATURE_SCHEMA_VERSION`] the features were computed under
+    pub schema_version: u32,
+    pub feature_vector: FeatureVector,
+    /// TAR score
+    pub heuristic_score: f64,
+    /// TAR model prediction, when an ONNX model served it
+    pub onnx_score: Option<f64>,
+    pub flanker_score: Option<f64>,
+    pub quote_source: QuoteProvider,
+    pub quote_age_ms: i64,
+}
+
+impl FeatureRecord {
+    pub fn new(
+        entry: &TokenEntry,
+        quote: &QuoteInfo,
+        heuristic_score: f64,
+        onnx_score: Option<f64>,
+        flanker_score: Option<f64>,
+    ) -> Self {
+        let timestamp = Utc::now();
+        Self {
+            timestamp,
+            route_key: RouteId::from(entry).to_string(),
+            schema_version: FEATURE_SCHEMA_VERSION,
+            feature_vector: FeatureVector::from(entry, quote),
+            heuristic_score,
+            onnx_score,
+            flanker_score,
+            quote_source: quote.source,
+            quote_age_ms: (timestamp - quote.fetched_at).num_milliseconds(),
+        }
+    }
+}
+
+/// Appends sampled [`FeatureRecord`]s as JSON lines on a writer thread
+///
+/// `log` never blocks: when the writer falls `capacity` records behind,
+/// new records are dropped and counted in [`dropped`](Self::dropped).
+/// Dropping the logger writes out everything already queued.
+pub struct FeatureLogger {
+    sender: Option<SyncSender<FeatureRecord>>,
+    writer: Option<JoinHandle<()>>,
+    sample_rate: f64,
+    seen: AtomicU64,
+    dropped: AtomicU64,
+}
+
+impl FeatureLogger {
+    /// Logger for `config`, or `None` when it has no path
+    pub fn from_config(config: &FeatureLogConfig) -> io::Result<Option<Self>> {
+        config.path.as_ref().map(|_| Self::with_capacity(config, DEFAULT_FEATURE_LOG_CAPACITY)).transpose()
+    }
+
+    /// Logger queueing at most `capacity` records; `config.path` is required
+    pub fn with_capacity(config: &FeatureLogConfig, capacity: usize) -> io::Result<Self> {
+        Self::spawn(config, capacity, None)
+    }
+
+    // `gate` holds the writer back until it's sent to or dropped
+    fn spawn(config: &FeatureLogConfig, capacity: usize, gate: Option<Receiver<()>>) -> io::Result<Self> {
+        let path = config
+            .path
+            .clone()
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "feature log path not set"))?;
+        let mut file = RotatingFile::open(path, config.max_bytes, config.max_files)?;
+        let (sender, receiver) = mpsc::sync_channel::<FeatureRecord>(capacity.max(1));
+        let writer = std::thread::Builder::new().name("feature-log".to_string()).spawn(move || {
+            if let Some(gate) = gate {
+                gate.recv().ok();
+            }
+            while let Ok(record) = receiver.recv() {
+                for record in std::iter::once(record).chain(receiver.try_iter()) {
+                    if let Err(e) = file.append(&record) {
+                        warn!("Failed to write feature log record: {}", e);
+                    }
+                }
+                if let Err(e) = file.flush() {
+                    warn!("Failed to flush feature log: {}", e);
+                }
+            }
+        })?;
+        Ok(Self {
+            sender: Some(sender),
+            writer: Some(writer),
+            sample_rate: config.sample_rate.clamp(0.0, 1.0),
+            seen: AtomicU64::new(0),
+            dropped: AtomicU64::new(0),
+        })
+    }
+
+    /// Queue `record` if it's sampled; false when sampled out or dropped
+    ///
+    /// Sampling is a fixed stride (every other record at 0.5), so a run
+    /// logs the same share whatever its length.
+    pub fn log(&self, record: FeatureRecord) -> bool {
+        let n = self.seen.fetch_add(1, Ordering::Relaxed) as f64;
+        if ((n + 1.0) * self.sample_rate).floor() <= (n * self.sample_rate).floor() {
+            return false;
+        }
+        let Some(sender) = &self.sender else {
+            return false;
+        };
+        match sender.try_send(record) {
+            Ok(()) => true,
+            Err(TrySendError::Full(_)) | Err(TrySendError::Disconnected(_)) => {
+                self.dropped.fetch_add(1, Ordering::Relaxed);
+                false
+            }
+        }
+    }
+
+    /// Records dropped because the writer was behind
+    pub fn dropped(&self) -> u64 {
+        self.dropped.load(Ordering::Relaxed)
+    }
+}
+
+impl Drop for FeatureLogger {
+    fn drop(&mut self) {
+        self.sender.take();
+        if let Some(writer) = self.writer.take() {
+            writer.join().ok();
+        }
+    }
+}
+
+/// `path` plus `path.1` .. `path.{max_files - 1}`, newest first
+struct RotatingFile {
+    path: PathBuf,
+    max_bytes: u64,
+    max_files: usize,
+    file: BufWriter<File>,
+    len: u64,
+}
+
+impl RotatingFile {
+    fn open(path: PathBuf, max_bytes: u64, max_files: usize) -> io::Result<Self> {
+        let file = OpenOptions::new().create(true).append(true).open(&path)?;
+        let len = file.metadata()?.len();
+        Ok(Self { path, max_bytes, max_files: max_files.max(1), file: BufWriter::new(file), len })
+    }
+
+    fn append(&mut self, record: &FeatureRecord) -> io::Result<()> {
+        let mut line = serde_json::to_vec(record)?;
+        line.push(b'\n');
+        if self.len > 0 && self.len + line.len() as u64 > self.max_bytes {
+            self.rotate()?;
+        }
+        self.file.write_all(&line)?;
+        self.len += line.len() as u64;
+        Ok(())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.file.flush()
+    }
+
+    fn rotate(&mut self) -> io::Result<()> {
+        self.file.flush()?;
+        for index in (1..self.max_files).rev() {
+            let from = if index == 1 { self.path.clone() } else { rotated_path(&self.path, index - 1) };
+            match std::fs::rename(&from, rotated_path(&self.path, index)) {
+                Err(e) if e.kind() != io::ErrorKind::NotFound => return Err(e),
+                _ => {}
+            }
+        }
+        // With one file, the current log starts over
+        let file = OpenOptions::new().create(true).write(true).truncate(true).open(&self.path)?;
+        self.file = BufWriter::new(file);
+        self.len = 0;
+        Ok(())
+    }
+}
+
+fn rotated_path(path: &Path, index: usize) -> PathBuf {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(format!(".{}", index));
+    path.with_file_name(name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::omniarb::audit::simulated_quotes;
+    use crate::omniarb::matrix_parser::load_token_matrix;
+
+    fn records(n: usize) -> Vec<FeatureRecord> {
+        let fixture = concat!(env!("CARGO_MANIFEST_DIR"), "/../data/fixtures/omniarb_scoring_audit.md");
+        let entries = load_token_matrix(fixture).unwrap();
+        let quotes = simulated_quotes(&entries);
+        entries
+            .iter()
+            .zip(&quotes)
+            .cycle()
+            .take(n)
+            .map(|(entry, quote)| FeatureRecord::new(entry, quote, 70.0, None, Some(60.0)))
+            .collect()
+    }
+
+    fn log_config(name: &str) -> FeatureLogConfig {
+        let path = std::env::temp_dir().join(format!("titan_features_{}_{}.jsonl", std::process::id(), name));
+        for index in 0..DEFAULT_FEATURE_LOG_MAX_FILES {
+            std::fs::remove_file(if index == 0 { path.clone() } else { rotated_path(&path, index) }).ok();
+        }
+        FeatureLogConfig { path: Some(path), ..FeatureLogConfig::default() }
+    }
+
+    fn read_records(path: &Path) -> Vec<FeatureRecord> {
+        std::fs::read_to_string(path)
+            .unwrap()
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn test_records_parse_back_into_feature_schema() {
+        let config = FeatureLogConfig { sample_rate: 0.5, ..log_config("schema") };
+        let written = records(10);
+        {
+            let logger = FeatureLogger::from_config(&config).unwrap().unwrap();
+            let logged = written.iter().filter(|&record| logger.log(record.clone())).count();
+            assert_eq!(logged, 5);
+        }
+
+        let path = config.path.unwrap();
+        let read = read_records(&path);
+        assert_eq!(read, written.into_iter().skip(1).step_by(2).collect::<Vec<_>>());
+        let line: serde_json::Value = serde_json::from_str(std::fs::read_to_string(&path).unwrap().lines().next().unwrap()).unwrap();
+        assert_eq!(line["schema_version"], FEATURE_SCHEMA_VERSION);
+        assert_eq!(line["feature_vector"].as_object().unwrap().len(), crate::omniarb::model_bridge::FEATURE_COUNT);
+        std::fs::remove_file(&path).ok();
+
+        assert!(FeatureLogger::from_config(&FeatureLogConfig::default()).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_rotation_keeps_max_files() {
+        let written = records(20);
+        let line_len = serde_json::to_vec(&written[0]).unwrap().len() as u64 + 1;
+        // Room for about three records per file
+        let config = FeatureLogConfig { max_bytes: line_len * 3 + line_len / 2, max_files: 3, ..log_config("rotate") };
+        {
+            let logger = FeatureLogger::from_config(&config).unwrap().unwrap();
+            for record in &written {
+                assert!(logger.log(record.clone()));
+            }
+        }
+
+        let path = config.path.unwrap();
+        let files = [path.clone(), rotated_path(&path, 1), rotated_path(&path, 2)];
+        let kept: Vec<_> = files.iter().rev().flat_map(|file| read_records(file)).collect();
+        assert!(files.iter().all(|file| std::fs::metadata(file).unwrap().len() <= config.max_bytes));
+        assert!(!rotated_path(&path, 3).exists());
+        // The newest records survive, oldest first across the files
+        assert_eq!(kept, written[written.len() - kept.len()..]);
+        for file in files {
+            std::fs::remove_file(file).ok();
+        }
+    }
+
+    #[test]
+    fn test_full_queue_drops_instead_of_blocking() {
+        let config = log_config("drop");
+        let (release, gate) = mpsc::channel();
+        let logger = FeatureLogger::spawn(&config, 2, Some(gate)).unwrap();
+
+        // The writer is held back, so only two records fit
+        let logged: Vec<_> = records(5).into_iter().map(|record| logger.log(record)).collect();
+        assert_eq!(logged, [true, true, false, false, false]);
+        assert_eq!(logger.dropped(), 3);
+
+        release.send(()).unwrap();
+        drop(logger);
+        let path = config.path.unwrap();
+        assert_eq!(read_records(&path).len(), 2);
+        std::fs::remove_file(&path).ok();
+    }
+}
@@ -0,0 +1,141 @@
+use std::fmt;
+use std::str::FromStr;
+
+use log::warn;
+
+/// Uniswap V3 pool fee tiers (percent)
+pub const UNISWAP_FEE_TIERS: [f64; 4] = [0.01, 0.05, 0.3, 1.0];
+
+/// Tier used for fees that can't be snapped (non-finite values)
+const FALLBACK_FEE_TIER: f64 = 0.3;
+
+/// Pool fee tiers a matrix fee can snap to
+#[derive(Debug, Clone, PartialEq)]
+pub struct FeeTiers {
+    tiers: Vec<f64>,
+}
+
+impl Default for FeeTiers {
+    fn default() -> Self {
+        Self { tiers: UNISWAP_FEE_TIERS.to_vec() }
+    }
+}
+
+impl FeeTiers {
+    /// Custom tier set (percent), e.g. for a fork with extra fee levels
+    pub fn new(tiers: Vec<f64>) -> Result<Self, String> {
+        if tiers.is_empty() {
+            return Err("Fee tier list is empty".to_string());
+        }
+        if let Some(bad) = tiers.iter().find(|tier| !tier.is_finite() || **tier < 0.0) {
+            return Err(format!("Invalid fee tier {}", bad));
+        }
+        Ok(Self { tiers })
+    }
+
+    /// Nearest tier to `percent`; ties go to the lower tier
+    ///
+    /// Non-finite fees snap to 0.3% when it's a listed tier, else the first
+    /// tier.
+    pub fn snap(&self, percent: f64) -> SnappedFee {
+        if !percent.is_finite() {
+            let fallback = self.tiers.iter().copied().find(|tier| *tier == FALLBACK_FEE_TIER).unwrap_or(self.tiers[0]);
+            return SnappedFee { tier: FeeTier(fallback), exact: false };
+        }
+        let tier = self
+            .tiers
+            .iter()
+            .copied()
+            .min_by(|a, b| (a - percent).abs().total_cmp(&(b - percent).abs()).then(a.total_cmp(b)))
+            .unwrap_or(FALLBACK_FEE_TIER);
+        SnappedFee {
+            tier: FeeTier(tier),
+            exact: tier == percent,
+        }
+    }
+}
+
+/// A valid pool fee tier (percent)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FeeTier(f64);
+
+impl FeeTier {
+    pub fn percent(&self) -> f64 {
+        self.0
+    }
+
+    /// Fee in hundredths of a bip, as pools and quoters take it (`0.3` ⇒ `3000`)
+    pub fn uniswap_fee(&self) -> u32 {
+        (self.0 * 10_000.0).round() as u32
+    }
+}
+
+impl fmt::Display for FeeTier {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}%", self.0)
+    }
+}
+
+/// Snaps to the nearest Uniswap tier, warning when the input isn't one
+impl FromStr for FeeTier {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let percent: f64 = s
+            .trim()
+            .trim_end_matches('%')
+            .parse()
+            .map_err(|_| format!("Invalid fee tier '{}'", s))?;
+        let snapped = FeeTiers::default().snap(percent);
+        if !snapped.exact {
+            warn!("Nonstandard fee tier {}%; using {}", percent, snapped.tier);
+        }
+        Ok(snapped.tier)
+    }
+}
+
+/// A fee snapped to a tier
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SnappedFee {
+    pub tier: FeeTier,
+    /// Whether the input already was that tier
+    pub exact: bool,
+}
+
+/// Uniswap pool fee for a matrix `fee_tier` (percent), e.g. `0.3` ⇒ `3000`
+///
+/// Nonstandard values snap to the nearest Uniswap tier.
+pub fn fee_tier_to_uniswap_fee(fee_tier: f64) -> u32 {
+    FeeTiers::default().snap(fee_tier).tier.uniswap_fee()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_standard_tiers_map_to_uniswap_fees() {
+        let fees: Vec<u32> = UNISWAP_FEE_TIERS.iter().map(|tier| fee_tier_to_uniswap_fee(*tier)).collect();
+        assert_eq!(fees, [100, 500, 3000, 10000]);
+        assert!(UNISWAP_FEE_TIERS.iter().all(|tier| FeeTiers::default().snap(*tier).exact));
+
+        // Nonstandard values snap to the nearest tier
+        assert_eq!(fee_tier_to_uniswap_fee(0.25), 3000);
+        assert_eq!(fee_tier_to_uniswap_fee(0.1), 500);
+        assert_eq!(fee_tier_to_uniswap_fee(0.6), 3000);
+        assert_eq!(fee_tier_to_uniswap_fee(5.0), 10000);
+        assert_eq!(fee_tier_to_uniswap_fee(f64::NAN), 3000);
+        assert!(!FeeTiers::default().snap(0.25).exact);
+
+        assert_eq!("0.05%".parse::<FeeTier>().unwrap().uniswap_fee(), 500);
+        assert!("cheap".parse::<FeeTier>().is_err());
+    }
+
+    #[test]
+    fn test_custom_tiers() {
+        let tiers = FeeTiers::new(vec![0.01, 0.3, 2.0]).unwrap();
+        assert_eq!(tiers.snap(1.5).tier.percent(), 2.0);
+        assert!(FeeTiers::new(vec![]).is_err());
+        assert!(FeeTiers::new(vec![-0.3]).is_err());
+    }
+}
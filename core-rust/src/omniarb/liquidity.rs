@@ -0,0 +1,158 @@
+use std::sync::Arc;
+
+use anyhow::{anyhow, Result};
+use ethers::prelude::*;
+use futures::future::join_all;
+use log::warn;
+
+use crate::config::{Config, TokenRegistry};
+use crate::omniarb::dex_spread::UniswapV3Pool;
+use crate::omniarb::matrix_parser::TokenEntry;
+use crate::simulation_engine::ERC20;
+
+/// Stablecoins valued at $1 when pricing a pool's TVL
+const USD_STABLECOINS: &[&str] = &["USDC", "USDC.E", "USDT", "DAI"];
+
+/// Pool TVL scored 0; anything thinner can't absorb a flash-loan leg
+pub const MIN_SCORED_TVL_USD: f64 = 1e4;
+
+/// Pool TVL scored 100
+pub const MAX_SCORED_TVL_USD: f64 = 1e8;
+
+/// Map a pool TVL in USD to a 0..100 liquidity score
+///
+/// Log scale between `MIN_SCORED_TVL_USD` and `MAX_SCORED_TVL_USD`, so each
+/// 10x of TVL adds 25 points ($1M scores 50, $10M scores 75).
+pub fn tvl_to_liquidity_score(tvl_usd: f64) -> f64 {
+    if !tvl_usd.is_finite() || tvl_usd <= MIN_SCORED_TVL_USD {
+        return 0.0;
+    }
+    let span = (MAX_SCORED_TVL_USD / MIN_SCORED_TVL_USD).log10();
+    ((tvl_usd / MIN_SCORED_TVL_USD).log10() / span * 100.0).clamp(0.0, 100.0)
+}
+
+/// Whether `token` is a registered USD stablecoin on the chain
+fn is_usd_stablecoin(registry: &TokenRegistry, chain_id: u64, token: Address) -> bool {
+    USD_STABLECOINS.iter().any(|symbol| {
+        registry
+            .get(chain_id, symbol)
+            .and_then(|address| address.parse::<Address>().ok())
+            == Some(token)
+    })
+}
+
+/// USD value held by a two-token pool, priced from its stablecoin side
+///
+/// The stablecoin balance is doubled on the assumption the pool is balanced
+/// around the current price. Pools without a registered stablecoin error.
+pub async fn pool_tvl_usd<P: JsonRpcClient + 'static>(
+    provider: Arc<Provider<P>>,
+    registry: &TokenRegistry,
+    chain_id: u64,
+    pool: Address,
+) -> Result<f64> {
+    let pool_contract = UniswapV3Pool::new(pool, Arc::clone(&provider));
+    let token0 = pool_contract.token_0().call().await?;
+    let token1 = pool_contract.token_1().call().await?;
+    let stable = [token0, token1]
+        .into_iter()
+        .find(|token| is_usd_stablecoin(registry, chain_id, *token))
+        .ok_or_else(|| anyhow!("Pool {:?} has no USD stablecoin side to price it", pool))?;
+
+    let erc20 = ERC20::new(stable, provider);
+    let balance = erc20.balance_of(pool).call().await?;
+    let decimals = erc20.decimals().call().await?;
+    let balance: f64 = ethers::utils::format_units(balance, decimals as u32)?.parse()?;
+    Ok(balance * 2.0)
+}
+
+/// Recompute a route's liquidity score from its origin pool's on-chain TVL
+///
+/// Needs `pool_address_origin` and an RPC for the origin chain; errors in
+/// offline mode so callers keep the matrix value.
+pub async fn recompute_liquidity_score(entry: &TokenEntry, config: &Config) -> Result<f64> {
+    if config.offline {
+        return Err(anyhow!("Offline mode: on-chain liquidity unavailable"));
+    }
+    let pool = entry
+        .pool_address_origin
+        .as_deref()
+        .ok_or_else(|| anyhow!("No origin pool address for {} on chain {}", entry.native_token, entry.chain_origin))?
+        .parse::<Address>()?;
+    let rpc = config
+        .get_chain(entry.chain_origin)
+        .map(|chain| chain.rpc.as_str())
+        .filter(|rpc| !rpc.is_empty())
+        .ok_or_else(|| anyhow!("No RPC configured for chain {}", entry.chain_origin))?;
+
+    let provider = Arc::new(Provider::<Http>::try_from(rpc)?);
+    let tvl = pool_tvl_usd(provider, &config.token_registry, entry.chain_origin, pool).await?;
+    Ok(tvl_to_liquidity_score(tvl))
+}
+
+/// Refresh every entry's liquidity score in place, returning how many were recomputed
+///
+/// Entries whose recomputation fails keep their current score.
+pub async fn refresh_liquidity_scores(entries: &mut [TokenEntry], config: &Config) -> usize {
+    let scores = join_all(entries.iter().map(|entry| recompute_liquidity_score(entry, config))).await;
+    let mut refreshed = 0;
+    for (entry, score) in entries.iter_mut().zip(scores) {
+        match score {
+            Ok(score) => {
+                entry.liquidity_score = score;
+                refreshed += 1;
+            }
+            Err(e) => warn!(
+                "Keeping liquidity score for {} {}>{}: {}",
+                entry.native_token, entry.chain_origin, entry.chain_dest, e
+            ),
+        }
+    }
+    refreshed
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::abi_call::{address_word, uint_words};
+
+    const USDC_POLYGON: &str = "0x3c499c542cEF5E3811e1192ce70d8cC03d5c3359";
+    const WETH_POLYGON: &str = "0x7ceB23fD6bC0adD59E62ac25578270cFf1b9f619";
+
+    #[test]
+    fn test_tvl_score_buckets() {
+        assert_eq!(tvl_to_liquidity_score(5_000.0), 0.0);
+        assert!((tvl_to_liquidity_score(1e6) - 50.0).abs() < 1e-9);
+        assert!((tvl_to_liquidity_score(1e7) - 75.0).abs() < 1e-9);
+        assert_eq!(tvl_to_liquidity_score(5e9), 100.0);
+        assert_eq!(tvl_to_liquidity_score(f64::NAN), 0.0);
+    }
+
+    #[tokio::test]
+    async fn test_pool_tvl_priced_from_stable_side() {
+        let mut registry = TokenRegistry::new();
+        registry.insert(137, "USDC", USDC_POLYGON);
+
+        let (provider, mock) = Provider::mocked();
+        // Served last-in first-out: token0, token1, balanceOf, decimals
+        for response in [
+            uint_words(&[6]),
+            uint_words(&[2_500_000_000_000]),
+            address_word(USDC_POLYGON),
+            address_word(WETH_POLYGON),
+        ] {
+            mock.push::<Bytes, _>(response).unwrap();
+        }
+
+        let tvl = pool_tvl_usd(Arc::new(provider), &registry, 137, Address::repeat_byte(1)).await.unwrap();
+        assert_eq!(tvl, 5_000_000.0);
+        let score = tvl_to_liquidity_score(tvl);
+        assert!((67.0..68.0).contains(&score), "{}", score);
+
+        let entry = TokenEntry {
+            pool_address_origin: None,
+            ..Default::default()
+        };
+        assert!(recompute_liquidity_score(&entry, &Config::default()).await.is_err());
+    }
+}
@@ -0,0 +1,257 @@
+use std::collections::HashMap;
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use crate::omniarb::matrix_parser::TokenEntry;
+use crate::omniarb::token_matrix::{route_key, RouteKey};
+
+/// Identity of a route: everything except its scores and addresses
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct RouteId {
+    pub chain_origin: u64,
+    pub chain_dest: u64,
+    pub native_token: String,
+    pub dex_origin: String,
+    pub dex_dest: String,
+    pub bridge_protocol: String,
+}
+
+impl From<&TokenEntry> for RouteId {
+    fn from(entry: &TokenEntry) -> Self {
+        RouteId {
+            chain_origin: entry.chain_origin,
+            chain_dest: entry.chain_dest,
+            native_token: entry.native_token.clone(),
+            dex_origin: entry.dex_origin.clone(),
+            dex_dest: entry.dex_dest.clone(),
+            bridge_protocol: entry.bridge_protocol.clone(),
+        }
+    }
+}
+
+impl fmt::Display for RouteId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}>{} {} {}>{} {}",
+            self.chain_origin,
+            self.chain_dest,
+            self.native_token,
+            self.dex_origin,
+            self.dex_dest,
+            self.bridge_protocol
+        )
+    }
+}
+
+/// One field that differs between the old and new row of a route
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct FieldDelta {
+    pub field: String,
+    pub old: String,
+    pub new: String,
+}
+
+/// A route present in both matrices with different values
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct EntryChange {
+    pub key: RouteId,
+    pub field_deltas: Vec<FieldDelta>,
+}
+
+/// Differences between two matrices, keyed by route identity
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct MatrixDiff {
+    pub added: Vec<TokenEntry>,
+    pub removed: Vec<TokenEntry>,
+    pub changed: Vec<EntryChange>,
+}
+
+impl MatrixDiff {
+    /// Whether the matrices are equivalent
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+    }
+}
+
+/// Human-readable report: `+` added, `-` removed, `~` changed routes
+impl fmt::Display for MatrixDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for entry in &self.added {
+            writeln!(f, "+ {}", RouteId::from(entry))?;
+        }
+        for entry in &self.removed {
+            writeln!(f, "- {}", RouteId::from(entry))?;
+        }
+        for change in &self.changed {
+            let deltas: Vec<String> = change
+                .field_deltas
+                .iter()
+                .map(|d| format!("{}: {} -> {}", d.field, d.old, d.new))
+                .collect();
+            writeln!(f, "~ {} ({})", change.key, deltas.join(", "))?;
+        }
+        write!(
+            f,
+            "{} added, {} removed, {} changed",
+            self.added.len(),
+            self.removed.len(),
+            self.changed.len()
+        )
+    }
+}
+
+/// Fields of `old` and `new` that differ
+fn field_deltas(old: &TokenEntry, new: &TokenEntry) -> Vec<FieldDelta> {
+    let mut deltas = Vec::new();
+    let mut compare = |field: &str, old: String, new: String| {
+        if old != new {
+            deltas.push(FieldDelta { field: field.to_string(), old, new });
+        }
+    };
+    let address = |value: &Option<String>| value.clone().unwrap_or_else(|| "-".to_string());
+
+    compare("liquidity_score", old.liquidity_score.to_string(), new.liquidity_score.to_string());
+    compare("fee_tier", old.fee_tier.to_string(), new.fee_tier.to_string());
+    compare(
+        "token_address_origin",
+        address(&old.token_address_origin),
+        address(&new.token_address_origin),
+    );
+    compare(
+        "token_address_dest",
+        address(&old.token_address_dest),
+        address(&new.token_address_dest),
+    );
+    compare(
+        "pool_address_origin",
+        address(&old.pool_address_origin),
+        address(&new.pool_address_origin),
+    );
+    compare(
+        "pool_address_dest",
+        address(&old.pool_address_dest),
+        address(&new.pool_address_dest),
+    );
+    deltas
+}
+
+/// Compare two matrices route by route
+///
+/// Added routes keep `new`'s order, removed routes keep `old`'s. When a
+/// matrix lists a route more than once, its first row is used.
+pub fn diff_matrices(old: &[TokenEntry], new: &[TokenEntry]) -> MatrixDiff {
+    let index = |entries: &[TokenEntry]| {
+        let mut index: HashMap<RouteKey, usize> = HashMap::new();
+        for (i, entry) in entries.iter().enumerate() {
+            index.entry(route_key(entry)).or_insert(i);
+        }
+        index
+    };
+    let old_index = index(old);
+    let new_index = index(new);
+
+    let mut diff = MatrixDiff::default();
+    for (i, entry) in new.iter().enumerate() {
+        let key = route_key(entry);
+        if new_index[&key] != i {
+            continue;
+        }
+        match old_index.get(&key) {
+            None => diff.added.push(entry.clone()),
+            Some(&j) => {
+                let field_deltas = field_deltas(&old[j], entry);
+                if !field_deltas.is_empty() {
+                    diff.changed.push(EntryChange { key: RouteId::from(entry), field_deltas });
+                }
+            }
+        }
+    }
+    for (i, entry) in old.iter().enumerate() {
+        let key = route_key(entry);
+        if old_index[&key] == i && !new_index.contains_key(&key) {
+            diff.removed.push(entry.clone());
+        }
+    }
+    diff
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn route(origin: u64, dest: u64, token: &str, bridge: &str, liquidity: f64) -> TokenEntry {
+        TokenEntry {
+            chain_origin: origin,
+            chain_dest: dest,
+            native_token: token.to_string(),
+            dex_origin: "UNISWAP_V3".to_string(),
+            dex_dest: "QUICKSWAP".to_string(),
+            bridge_protocol: bridge.to_string(),
+            liquidity_score: liquidity,
+            fee_tier: 0.3,
+            ..Default::default()
+        }
+    }
+
+    fn fixtures() -> (Vec<TokenEntry>, Vec<TokenEntry>) {
+        let old = vec![
+            route(1, 137, "USDC", "LIFI", 95.0),
+            route(1, 137, "USDT", "STARGATE", 90.0),
+            route(1, 10, "WETH", "ACROSS", 97.0),
+        ];
+        let mut new = vec![
+            route(1, 137, "USDC", "LIFI", 95.0),
+            route(1, 137, "USDT", "STARGATE", 82.5),
+            route(137, 42161, "DAI", "HOP", 80.0),
+        ];
+        new[0].token_address_origin = Some("0xA0b86991c6218b36c1d19D4a2e9Eb0cE3606eB48".to_string());
+        (old, new)
+    }
+
+    #[test]
+    fn test_diff_categories() {
+        let (old, new) = fixtures();
+        let diff = diff_matrices(&old, &new);
+
+        assert_eq!(diff.added, vec![route(137, 42161, "DAI", "HOP", 80.0)]);
+        assert_eq!(diff.removed, vec![route(1, 10, "WETH", "ACROSS", 97.0)]);
+        assert_eq!(diff.changed.len(), 2);
+        assert_eq!(diff.changed[0].field_deltas[0].field, "token_address_origin");
+
+        // Float-only change
+        let usdt = &diff.changed[1];
+        assert_eq!(usdt.key, RouteId::from(&old[1]));
+        assert_eq!(
+            usdt.field_deltas,
+            vec![FieldDelta {
+                field: "liquidity_score".to_string(),
+                old: "90".to_string(),
+                new: "82.5".to_string(),
+            }]
+        );
+    }
+
+    #[test]
+    fn test_identical_matrices() {
+        let (old, _) = fixtures();
+        assert!(diff_matrices(&old, &old).is_empty());
+    }
+
+    #[test]
+    fn test_report_and_json() {
+        let (old, new) = fixtures();
+        let diff = diff_matrices(&old, &new);
+
+        let report = diff.to_string();
+        assert!(report.contains("+ 137>42161 DAI UNISWAP_V3>QUICKSWAP HOP"), "{}", report);
+        assert!(report.contains("- 1>10 WETH UNISWAP_V3>QUICKSWAP ACROSS"), "{}", report);
+        assert!(report.contains("~ 1>137 USDT UNISWAP_V3>QUICKSWAP STARGATE (liquidity_score: 90 -> 82.5)"));
+        assert!(report.ends_with("1 added, 1 removed, 2 changed"));
+
+        let json = serde_json::to_value(&diff).unwrap();
+        assert_eq!(json["removed"][0]["native_token"], "WETH");
+        assert_eq!(json["changed"][1]["key"]["bridge_protocol"], "STARGATE");
+    }
+}
@@ -1,9 +1,143 @@
+use std::fmt;
 use std::fs::File;
 use std::io::{BufRead, BufReader};
 use std::path::Path;
 use serde::{Deserialize, Serialize};
+use thiserror::Error;
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+use crate::config::TokenRegistry;
+use crate::enum_matrix::{BridgeKind, ChainId, DexKind};
+use crate::omniarb::fee_tier::{fee_tier_to_uniswap_fee, FeeTiers};
+use crate::omniarb::token_matrix::{DedupStrategy, TokenMatrix};
+
+/// Version written by `save_token_matrix_json`
+pub const MATRIX_JSON_VERSION: u32 = 1;
+
+/// Current markdown/CSV row layout (declared as `<!-- schema: N -->`)
+pub const MATRIX_SCHEMA_VERSION: u32 = 2;
+
+/// Oldest row layout still decoded; files without a marker use it
+pub const MIN_MATRIX_SCHEMA_VERSION: u32 = 1;
+
+/// Default number of issues collected before a strict load gives up
+pub const DEFAULT_MAX_ERRORS: usize = 10;
+
+/// Maximum accepted fee tier (percent)
+const MAX_FEE_TIER: f64 = 10.0;
+
+/// Matrix loading options
+#[derive(Debug, Clone, Copy)]
+pub struct ParseOptions {
+    /// Fail the load on any invalid row instead of skipping it
+    pub strict: bool,
+    /// Stop collecting diagnostics after this many issues (strict mode)
+    pub max_errors: usize,
+    /// Collapse duplicate routes after loading
+    pub dedupe: Option<DedupStrategy>,
+    /// Round fee tiers to the nearest Uniswap tier; nonstandard tiers are
+    /// warned about either way
+    pub snap_fee_tiers: bool,
+}
+
+impl Default for ParseOptions {
+    fn default() -> Self {
+        Self {
+            strict: true,
+            max_errors: DEFAULT_MAX_ERRORS,
+            dedupe: None,
+            snap_fee_tiers: false,
+        }
+    }
+}
+
+/// Result of a matrix load with options
+#[derive(Debug, Clone)]
+pub struct MatrixLoad {
+    pub entries: Vec<TokenEntry>,
+    /// Rows skipped in lenient mode
+    pub diagnostics: Vec<ParseDiagnostic>,
+    /// Values rewritten to their canonical spelling
+    pub warnings: Vec<ParseDiagnostic>,
+    /// Duplicate rows folded away by `ParseOptions::dedupe`
+    pub merged: usize,
+}
+
+impl MatrixLoad {
+    /// Apply the requested dedup strategy, counting merged rows
+    fn new(
+        entries: Vec<TokenEntry>,
+        diagnostics: Vec<ParseDiagnostic>,
+        warnings: Vec<ParseDiagnostic>,
+        options: ParseOptions,
+    ) -> Self {
+        let loaded = entries.len();
+        let entries = match options.dedupe {
+            Some(strategy) => TokenMatrix::new(entries).dedupe(strategy).into_entries(),
+            None => entries,
+        };
+        Self {
+            merged: loaded - entries.len(),
+            entries,
+            diagnostics,
+            warnings,
+        }
+    }
+}
+
+/// A single invalid value found while loading a matrix
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ParseDiagnostic {
+    /// File line number (1-based entry index for JSON sources)
+    pub line: usize,
+    pub column: String,
+    pub raw_value: String,
+    pub reason: String,
+}
+
+impl fmt::Display for ParseDiagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Line {}, column '{}' = '{}': {}",
+            self.line, self.column, self.raw_value, self.reason
+        )
+    }
+}
+
+/// Rows with at least one diagnostic; a row with several bad values
+/// counts once
+pub fn invalid_row_count(diagnostics: &[ParseDiagnostic]) -> usize {
+    diagnostics.iter().map(|d| d.line).collect::<std::collections::HashSet<_>>().len()
+}
+
+/// Matrix loading errors
+#[derive(Debug, Error)]
+pub enum MatrixError {
+    #[error("{0}")]
+    Io(String),
+    #[error("{0}")]
+    Parse(String),
+    #[error("Matrix has {} invalid value(s):\n{}", .0.len(), format_diagnostics(.0))]
+    Invalid(Vec<ParseDiagnostic>),
+    #[error("No valid entries found in matrix file")]
+    Empty,
+    #[error(
+        "Unsupported matrix schema version {0} (supported: {min}-{max})",
+        min = MIN_MATRIX_SCHEMA_VERSION,
+        max = MATRIX_SCHEMA_VERSION
+    )]
+    UnsupportedSchema(u32),
+}
+
+fn format_diagnostics(diagnostics: &[ParseDiagnostic]) -> String {
+    diagnostics
+        .iter()
+        .map(|d| format!("  {}", d))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
 pub struct TokenEntry {
     pub chain_origin: u64,
     pub chain_dest: u64,
@@ -13,94 +147,630 @@ pub struct TokenEntry {
     pub bridge_protocol: String,
     pub liquidity_score: f64,
     pub fee_tier: f64,
+    // Schema v2 columns
+    #[serde(default)]
+    pub token_address_origin: Option<String>,
+    #[serde(default)]
+    pub token_address_dest: Option<String>,
+    #[serde(default)]
+    pub pool_address_origin: Option<String>,
+    #[serde(default)]
+    pub pool_address_dest: Option<String>,
 }
 
-/// Load token matrix from markdown CSV file
-/// 
-/// # Arguments
-/// * `path` - Path to the matrix file
+/// Schema v1 row: the original 8-column layout
+#[derive(Debug, Deserialize)]
+struct TokenEntryV1 {
+    chain_origin: u64,
+    chain_dest: u64,
+    native_token: String,
+    dex_origin: String,
+    dex_dest: String,
+    bridge_protocol: String,
+    liquidity_score: f64,
+    fee_tier: f64,
+}
+
+impl From<TokenEntryV1> for TokenEntry {
+    fn from(row: TokenEntryV1) -> Self {
+        TokenEntry {
+            chain_origin: row.chain_origin,
+            chain_dest: row.chain_dest,
+            native_token: row.native_token,
+            dex_origin: row.dex_origin,
+            dex_dest: row.dex_dest,
+            bridge_protocol: row.bridge_protocol,
+            liquidity_score: row.liquidity_score,
+            fee_tier: row.fee_tier,
+            ..TokenEntry::default()
+        }
+    }
+}
+
+/// Decode a CSV record with the row layout of `schema`
+fn decode_row(
+    schema: u32,
+    record: &csv::StringRecord,
+    headers: &csv::StringRecord,
+) -> Result<TokenEntry, csv::Error> {
+    match schema {
+        1 => record.deserialize::<TokenEntryV1>(Some(headers)).map(TokenEntry::from),
+        _ => record.deserialize(Some(headers)),
+    }
+}
+
+/// Parse a `<!-- schema: N -->` marker line
+fn parse_schema_marker(line: &str) -> Option<Result<u32, String>> {
+    let inner = line.strip_prefix("<!--")?.strip_suffix("-->")?.trim();
+    let version = inner.strip_prefix("schema:")?.trim();
+    Some(
+        version
+            .parse()
+            .map_err(|_| format!("Invalid matrix schema marker '{}'", line)),
+    )
+}
+
+impl TokenEntry {
+    /// Whether all numeric fields are finite (no NaN or infinity)
+    pub fn is_finite(&self) -> bool {
+        self.liquidity_score.is_finite() && self.fee_tier.is_finite()
+    }
+
+    /// Uniswap pool fee for this route's fee tier, for quoter calls
+    pub fn uniswap_fee(&self) -> u32 {
+        fee_tier_to_uniswap_fee(self.fee_tier)
+    }
+    
+    /// Fill missing token addresses from `resolver`
+    /// 
+    /// Addresses already present in the matrix are kept. Returns the number
+    /// of fields filled.
+    pub fn resolve_addresses(&mut self, resolver: &impl AddressResolver) -> usize {
+        let mut filled = 0;
+        for (chain_id, slot) in [
+            (self.chain_origin, &mut self.token_address_origin),
+            (self.chain_dest, &mut self.token_address_dest),
+        ] {
+            if slot.is_none() {
+                *slot = resolver.token_address(chain_id, &self.native_token);
+                filled += usize::from(slot.is_some());
+            }
+        }
+        filled
+    }
+}
+
+/// Source of token addresses for matrix rows that don't declare them
+pub trait AddressResolver {
+    fn token_address(&self, chain_id: u64, symbol: &str) -> Option<String>;
+}
+
+impl AddressResolver for TokenRegistry {
+    fn token_address(&self, chain_id: u64, symbol: &str) -> Option<String> {
+        self.get(chain_id, symbol).map(str::to_string)
+    }
+}
+
+/// Whether `value` is a `0x`-prefixed 20-byte hex address
+fn is_hex_address(value: &str) -> bool {
+    value
+        .strip_prefix("0x")
+        .is_some_and(|hex| hex.len() == 40 && hex.chars().all(|c| c.is_ascii_hexdigit()))
+}
+
+/// Read the CSV lines of a matrix file with their 1-based line numbers
 /// 
-/// # Returns
-/// Vector of TokenEntry structs
-pub fn load_token_matrix(path: &str) -> Result<Vec<TokenEntry>, String> {
+/// Only lines after the `## Data Entries` marker are returned; files
+/// without the marker are treated as plain CSV. Blank lines, `#` and
+/// `<!-- -->` comments are dropped. Also returns the declared schema
+/// version (v1 when no `<!-- schema: N -->` marker is present).
+fn read_data_section(path: &str) -> Result<(u32, Vec<(usize, String)>), MatrixError> {
     let file = File::open(Path::new(path))
-        .map_err(|e| format!("Failed to open matrix file: {}", e))?;
+        .map_err(|e| MatrixError::Io(format!("Failed to open matrix file: {}", e)))?;
     
     let reader = BufReader::new(file);
-    let mut entries = Vec::new();
-    let mut in_data_section = false;
+    let mut lines = Vec::new();
+    let mut data_lines = Vec::new();
+    let mut has_marker = false;
+    let mut schema = MIN_MATRIX_SCHEMA_VERSION;
 
-    for line in reader.lines() {
-        let line = line.map_err(|e| format!("Failed to read line: {}", e))?;
+    for (index, line) in reader.lines().enumerate() {
+        let line = line
+            .map_err(|e| MatrixError::Io(format!("Failed to read line {}: {}", index + 1, e)))?;
         let trimmed = line.trim();
         
-        // Skip empty lines
-        if trimmed.is_empty() {
+        if let Some(version) = parse_schema_marker(trimmed) {
+            schema = version.map_err(MatrixError::Parse)?;
             continue;
         }
         
         // Look for the data section
         if trimmed.contains("## Data Entries") {
-            in_data_section = true;
+            has_marker = true;
+            data_lines.clear();
             continue;
         }
         
-        // Skip until we reach data section
-        if !in_data_section {
+        // Skip empty lines and markdown comments/headings
+        if trimmed.is_empty() || trimmed.starts_with('#') || trimmed.starts_with("<!--") {
             continue;
         }
         
-        // Skip header line and markdown comments
-        if trimmed.starts_with('#') || trimmed.starts_with("chain_origin") {
-            continue;
+        if has_marker {
+            data_lines.push((index + 1, trimmed.to_string()));
+        } else {
+            lines.push((index + 1, trimmed.to_string()));
         }
-        
-        // Parse CSV data
-        let fields: Vec<&str> = trimmed.split(',').collect();
-        if fields.len() == 8 {
-            // Parse with error checking
-            let chain_origin = fields[0].parse().unwrap_or_else(|e| {
-                eprintln!("Warning: Invalid chain_origin '{}': {}", fields[0], e);
-                0
-            });
-            let chain_dest = fields[1].parse().unwrap_or_else(|e| {
-                eprintln!("Warning: Invalid chain_dest '{}': {}", fields[1], e);
-                0
-            });
-            let liquidity_score = fields[6].parse().unwrap_or_else(|e| {
-                eprintln!("Warning: Invalid liquidity_score '{}': {}", fields[6], e);
-                0.0
-            });
-            let fee_tier = fields[7].parse().unwrap_or_else(|e| {
-                eprintln!("Warning: Invalid fee_tier '{}': {}", fields[7], e);
-                0.0
+    }
+    
+    if !(MIN_MATRIX_SCHEMA_VERSION..=MATRIX_SCHEMA_VERSION).contains(&schema) {
+        return Err(MatrixError::UnsupportedSchema(schema));
+    }
+    
+    Ok((schema, if has_marker { data_lines } else { lines }))
+}
+
+/// Semantic range checks for a parsed entry
+/// 
+/// `raw` returns the source text of a column for diagnostics.
+fn semantic_diagnostics(
+    entry: &TokenEntry,
+    line: usize,
+    raw: impl Fn(&str) -> String,
+) -> Vec<ParseDiagnostic> {
+    let mut diagnostics = Vec::new();
+    let mut push = |column: &str, reason: String| {
+        diagnostics.push(ParseDiagnostic {
+            line,
+            column: column.to_string(),
+            raw_value: raw(column),
+            reason,
+        });
+    };
+    
+    for (column, chain_id) in [("chain_origin", entry.chain_origin), ("chain_dest", entry.chain_dest)] {
+        if ChainId::from_u64(chain_id).is_none() {
+            push(column, format!("unknown chain ID {}", chain_id));
+        }
+    }
+    for (column, dex) in [("dex_origin", &entry.dex_origin), ("dex_dest", &entry.dex_dest)] {
+        if DexKind::from_name(dex).is_none() {
+            push(column, "unknown DEX".to_string());
+        }
+    }
+    if BridgeKind::from_name(&entry.bridge_protocol).is_none() {
+        push("bridge_protocol", "unknown bridge".to_string());
+    }
+    for (column, address) in [
+        ("token_address_origin", &entry.token_address_origin),
+        ("token_address_dest", &entry.token_address_dest),
+        ("pool_address_origin", &entry.pool_address_origin),
+        ("pool_address_dest", &entry.pool_address_dest),
+    ] {
+        if address.as_deref().is_some_and(|a| !is_hex_address(a)) {
+            push(column, "invalid address".to_string());
+        }
+    }
+    if !entry.liquidity_score.is_finite() {
+        push("liquidity_score", "non-finite value".to_string());
+    } else if !(0.0..=100.0).contains(&entry.liquidity_score) {
+        push("liquidity_score", "out of range [0, 100]".to_string());
+    }
+    if !entry.fee_tier.is_finite() {
+        push("fee_tier", "non-finite value".to_string());
+    } else if !(0.0..=MAX_FEE_TIER).contains(&entry.fee_tier) {
+        push("fee_tier", format!("out of range [0, {}]", MAX_FEE_TIER));
+    }
+    
+    diagnostics
+}
+
+/// Rewrite DEX and bridge names to their canonical spelling
+/// 
+/// Returns a warning per rewritten value. Names must already be known
+/// (see `semantic_diagnostics`).
+fn normalize_names(entry: &mut TokenEntry, line: usize) -> Vec<ParseDiagnostic> {
+    let mut warnings = Vec::new();
+    let mut normalize = |column: &str, value: &mut String, canonical: Option<&'static str>| {
+        if let Some(canonical) = canonical.filter(|c| c != value) {
+            warnings.push(ParseDiagnostic {
+                line,
+                column: column.to_string(),
+                raw_value: std::mem::replace(value, canonical.to_string()),
+                reason: format!("normalized to '{}'", canonical),
             });
-            
-            let entry = TokenEntry {
-                chain_origin,
-                chain_dest,
-                native_token: fields[2].to_string(),
-                dex_origin: fields[3].to_string(),
-                dex_dest: fields[4].to_string(),
-                bridge_protocol: fields[5].to_string(),
-                liquidity_score,
-                fee_tier,
-            };
+        }
+    };
+    
+    let dex_origin = DexKind::from_name(&entry.dex_origin).map(|d| d.name());
+    normalize("dex_origin", &mut entry.dex_origin, dex_origin);
+    let dex_dest = DexKind::from_name(&entry.dex_dest).map(|d| d.name());
+    normalize("dex_dest", &mut entry.dex_dest, dex_dest);
+    let bridge = BridgeKind::from_name(&entry.bridge_protocol).map(|b| b.name());
+    normalize("bridge_protocol", &mut entry.bridge_protocol, bridge);
+    
+    warnings
+}
+
+/// Warning for a `fee_tier` that isn't a Uniswap tier, rounding it to
+/// the nearest one when `snap` is set
+/// 
+/// Unsnapped rows keep their value, but pools are still quoted at the
+/// nearest tier, so the warning names it either way.
+fn check_fee_tier(entry: &mut TokenEntry, line: usize, snap: bool) -> Option<ParseDiagnostic> {
+    let snapped = FeeTiers::default().snap(entry.fee_tier);
+    if snapped.exact {
+        return None;
+    }
+    let raw_value = entry.fee_tier.to_string();
+    let reason = if snap {
+        entry.fee_tier = snapped.tier.percent();
+        format!("nonstandard fee tier, snapped to {}", snapped.tier)
+    } else {
+        format!("nonstandard fee tier, quoted at {}", snapped.tier)
+    };
+    Some(ParseDiagnostic { line, column: "fee_tier".to_string(), raw_value, reason })
+}
+
+/// Load token matrix from markdown CSV file
+/// 
+/// Strict: any invalid row fails the load. Use
+/// `load_token_matrix_with_options` for lenient loading.
+/// 
+/// # Arguments
+/// * `path` - Path to the matrix file
+/// 
+/// # Returns
+/// Vector of TokenEntry structs
+pub fn load_token_matrix(path: &str) -> Result<Vec<TokenEntry>, String> {
+    load_token_matrix_with_options(path, ParseOptions::default())
+        .map(|load| load.entries)
+        .map_err(|e| e.to_string())
+}
+
+/// Load token matrix from markdown CSV file with validation diagnostics
+/// 
+/// Columns are mapped by header name, so they may appear in any order;
+/// unknown extra columns are ignored and quoted fields are supported.
+/// Rows with unparseable values, unknown chain IDs, DEXes or bridges, or
+/// out-of-range scores/fees produce diagnostics. Known DEX/bridge names
+/// in a variant spelling are rewritten and reported as warnings, as are
+/// nonstandard fee tiers, which `snap_fee_tiers` also rounds. Strict mode fails once any are found
+/// (after collecting up to `max_errors`); lenient mode skips those rows.
+/// Rows are decoded with the layout of the file's schema version and
+/// older layouts are migrated to the current `TokenEntry`.
+pub fn load_token_matrix_with_options(
+    path: &str,
+    options: ParseOptions,
+) -> Result<MatrixLoad, MatrixError> {
+    let (schema, lines) = read_data_section(path)?;
+    let input = lines
+        .iter()
+        .map(|(_, line)| line.as_str())
+        .collect::<Vec<_>>()
+        .join("\n");
+    // Map a CSV reader line (1-based) back to the file line number
+    let file_line = |csv_line: u64| {
+        lines
+            .get((csv_line as usize).saturating_sub(1))
+            .map(|(line_no, _)| *line_no)
+            .unwrap_or(0)
+    };
+    
+    let mut reader = csv::ReaderBuilder::new()
+        .trim(csv::Trim::All)
+        .from_reader(input.as_bytes());
+    let headers = reader
+        .headers()
+        .map_err(|e| MatrixError::Parse(format!("Invalid matrix header: {}", e)))?
+        .clone();
+    
+    let mut entries = Vec::new();
+    let mut diagnostics = Vec::new();
+    let mut warnings = Vec::new();
+    for result in reader.records() {
+        if options.strict && diagnostics.len() >= options.max_errors {
+            break;
+        }
+        
+        let record = match result {
+            Ok(record) => record,
+            Err(e) => {
+                let line = e.position().map(|p| file_line(p.line())).unwrap_or(0);
+                diagnostics.push(ParseDiagnostic {
+                    line,
+                    column: "*".to_string(),
+                    raw_value: lines
+                        .iter()
+                        .find(|(line_no, _)| *line_no == line)
+                        .map(|(_, text)| text.clone())
+                        .unwrap_or_default(),
+                    reason: e.to_string(),
+                });
+                continue;
+            }
+        };
+        let line = record.position().map(|p| file_line(p.line())).unwrap_or(0);
+        let raw = |column: &str| {
+            headers
+                .iter()
+                .position(|h| h == column)
+                .and_then(|i| record.get(i))
+                .unwrap_or("")
+                .to_string()
+        };
+        
+        let mut entry = match decode_row(schema, &record, &headers) {
+            Ok(entry) => entry,
+            Err(e) => {
+                let (column, reason) = match e.kind() {
+                    csv::ErrorKind::Deserialize { err, .. } => (
+                        err.field()
+                            .and_then(|i| headers.get(i as usize))
+                            .unwrap_or("*")
+                            .to_string(),
+                        err.kind().to_string(),
+                    ),
+                    _ => ("*".to_string(), e.to_string()),
+                };
+                diagnostics.push(ParseDiagnostic {
+                    line,
+                    raw_value: raw(&column),
+                    column,
+                    reason,
+                });
+                continue;
+            }
+        };
+        
+        let issues = semantic_diagnostics(&entry, line, raw);
+        if issues.is_empty() {
+            warnings.extend(normalize_names(&mut entry, line));
+            warnings.extend(check_fee_tier(&mut entry, line, options.snap_fee_tiers));
             entries.push(entry);
+        } else {
+            diagnostics.extend(issues);
         }
     }
     
+    if options.strict && !diagnostics.is_empty() {
+        diagnostics.truncate(options.max_errors.max(1));
+        return Err(MatrixError::Invalid(diagnostics));
+    }
+    if entries.is_empty() {
+        return Err(if diagnostics.is_empty() {
+            MatrixError::Empty
+        } else {
+            MatrixError::Invalid(diagnostics)
+        });
+    }
+    
+    Ok(MatrixLoad::new(entries, diagnostics, warnings, options))
+}
+
+/// Versioned JSON matrix document
+#[derive(Debug, Serialize, Deserialize)]
+struct MatrixDocument {
+    version: u32,
+    entries: Vec<TokenEntry>,
+}
+
+/// Load token matrix from a JSON file
+/// 
+/// Accepts either a bare array of entries or an object with
+/// `version` and `entries` fields.
+pub fn load_token_matrix_json(path: &str) -> Result<Vec<TokenEntry>, MatrixError> {
+    let content = std::fs::read_to_string(Path::new(path))
+        .map_err(|e| MatrixError::Io(format!("Failed to open matrix file: {}", e)))?;
+    
+    let invalid = |e: serde_json::Error| MatrixError::Parse(format!("Invalid JSON matrix {}: {}", path, e));
+    let entries = if content.trim_start().starts_with('[') {
+        serde_json::from_str::<Vec<TokenEntry>>(&content).map_err(invalid)?
+    } else {
+        let document: MatrixDocument = serde_json::from_str(&content).map_err(invalid)?;
+        if document.version > MATRIX_JSON_VERSION {
+            return Err(MatrixError::Parse(format!(
+                "Unsupported matrix version {} (max {})",
+                document.version, MATRIX_JSON_VERSION
+            )));
+        }
+        document.entries
+    };
+    
     if entries.is_empty() {
-        return Err("No valid entries found in matrix file".to_string());
+        return Err(MatrixError::Empty);
     }
     
     Ok(entries)
 }
 
+/// Save token matrix as a versioned JSON document
+pub fn save_token_matrix_json(path: &str, entries: &[TokenEntry]) -> Result<(), String> {
+    let document = MatrixDocument {
+        version: MATRIX_JSON_VERSION,
+        entries: entries.to_vec(),
+    };
+    let content = serde_json::to_string_pretty(&document)
+        .map_err(|e| format!("Failed to serialize matrix: {}", e))?;
+    std::fs::write(Path::new(path), content)
+        .map_err(|e| format!("Failed to write matrix file: {}", e))
+}
+
+/// On-disk matrix layouts
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MatrixFormat {
+    /// Markdown document with a `## Data Entries` CSV section
+    Markdown,
+    /// Plain CSV with a header row
+    Csv,
+    /// Versioned JSON document
+    Json,
+}
+
+impl MatrixFormat {
+    /// Pick a format from the file extension (markdown by default)
+    pub fn from_path(path: &str) -> Self {
+        match Path::new(path)
+            .extension()
+            .and_then(|e| e.to_str())
+            .map(|e| e.to_ascii_lowercase())
+            .as_deref()
+        {
+            Some("json") => MatrixFormat::Json,
+            Some("csv") => MatrixFormat::Csv,
+            _ => MatrixFormat::Markdown,
+        }
+    }
+}
+
+/// Serialize entries as CSV with a header row
+/// 
+/// Floats use the shortest representation that parses back exactly.
+pub fn to_csv_string(entries: &[TokenEntry]) -> Result<String, String> {
+    let mut writer = csv::Writer::from_writer(Vec::new());
+    for entry in entries {
+        writer
+            .serialize(entry)
+            .map_err(|e| format!("Failed to serialize matrix entry: {}", e))?;
+    }
+    let bytes = writer
+        .into_inner()
+        .map_err(|e| format!("Failed to serialize matrix: {}", e))?;
+    String::from_utf8(bytes).map_err(|e| format!("Failed to serialize matrix: {}", e))
+}
+
+/// Save token matrix in a format readable by the matching loader
+/// 
+/// CSV and markdown output declare the current schema version.
+pub fn save_token_matrix(path: &str, entries: &[TokenEntry], format: MatrixFormat) -> Result<(), String> {
+    let content = match format {
+        MatrixFormat::Json => return save_token_matrix_json(path, entries),
+        MatrixFormat::Csv => format!(
+            "<!-- schema: {} -->\n{}",
+            MATRIX_SCHEMA_VERSION,
+            to_csv_string(entries)?
+        ),
+        MatrixFormat::Markdown => format!(
+            "# OmniArb Token Matrix\n<!-- schema: {} -->\n\n## Data Entries\n\n{}",
+            MATRIX_SCHEMA_VERSION,
+            to_csv_string(entries)?
+        ),
+    };
+    std::fs::write(Path::new(path), content)
+        .map_err(|e| format!("Failed to write matrix file: {}", e))
+}
+
+/// Whether a matrix file is JSON, by extension or leading byte
+fn is_json_matrix(path: &str) -> Result<bool, String> {
+    let extension = Path::new(path)
+        .extension()
+        .and_then(|e| e.to_str())
+        .map(|e| e.to_ascii_lowercase());
+    
+    match extension.as_deref() {
+        Some("json") => Ok(true),
+        Some("md") | Some("csv") => Ok(false),
+        _ => {
+            let content = std::fs::read(Path::new(path))
+                .map_err(|e| format!("Failed to open matrix file: {}", e))?;
+            Ok(matches!(
+                content.iter().find(|b| !b.is_ascii_whitespace()),
+                Some(b'[') | Some(b'{')
+            ))
+        }
+    }
+}
+
+/// Load token matrix in either JSON or markdown/CSV format
+/// 
+/// Dispatches on the file extension, falling back to sniffing the
+/// first non-whitespace byte for unknown extensions.
+pub fn load_token_matrix_auto(path: &str) -> Result<Vec<TokenEntry>, String> {
+    if is_json_matrix(path)? {
+        load_token_matrix_json(path).map_err(|e| e.to_string())
+    } else {
+        load_token_matrix(path)
+    }
+}
+
+/// Load token matrix in either format with validation diagnostics
+/// 
+/// JSON entries get the same semantic checks as CSV rows, with the
+/// 1-based entry index reported as the line.
+pub fn load_token_matrix_auto_with_options(
+    path: &str,
+    options: ParseOptions,
+) -> Result<MatrixLoad, MatrixError> {
+    if !is_json_matrix(path).map_err(MatrixError::Io)? {
+        return load_token_matrix_with_options(path, options);
+    }
+    
+    let mut entries = Vec::new();
+    let mut diagnostics = Vec::new();
+    let mut warnings = Vec::new();
+    for (index, mut entry) in load_token_matrix_json(path)?
+        .into_iter()
+        .enumerate()
+    {
+        let issues = semantic_diagnostics(&entry, index + 1, |column| match column {
+            "chain_origin" => entry.chain_origin.to_string(),
+            "chain_dest" => entry.chain_dest.to_string(),
+            "liquidity_score" => entry.liquidity_score.to_string(),
+            "fee_tier" => entry.fee_tier.to_string(),
+            "dex_origin" => entry.dex_origin.clone(),
+            "dex_dest" => entry.dex_dest.clone(),
+            "bridge_protocol" => entry.bridge_protocol.clone(),
+            _ => String::new(),
+        });
+        if issues.is_empty() {
+            warnings.extend(normalize_names(&mut entry, index + 1));
+            warnings.extend(check_fee_tier(&mut entry, index + 1, options.snap_fee_tiers));
+            entries.push(entry);
+        } else {
+            diagnostics.extend(issues);
+        }
+    }
+    
+    if options.strict && !diagnostics.is_empty() {
+        diagnostics.truncate(options.max_errors.max(1));
+        return Err(MatrixError::Invalid(diagnostics));
+    }
+    if entries.is_empty() {
+        return Err(MatrixError::Invalid(diagnostics));
+    }
+    
+    Ok(MatrixLoad::new(entries, diagnostics, warnings, options))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     
+    fn temp_path(name: &str) -> String {
+        std::env::temp_dir()
+            .join(format!("titan_matrix_{}_{}", std::process::id(), name))
+            .to_string_lossy()
+            .into_owned()
+    }
+    
+    fn sample_entry() -> TokenEntry {
+        TokenEntry {
+            chain_origin: 1,
+            chain_dest: 137,
+            native_token: "USDC".to_string(),
+            dex_origin: "UNISWAP_V3".to_string(),
+            dex_dest: "QUICKSWAP".to_string(),
+            bridge_protocol: "LIFI".to_string(),
+            liquidity_score: 95.0,
+            fee_tier: 0.3,
+            ..Default::default()
+        }
+    }
+    
+    const MARKDOWN_MATRIX: &str = "## Data Entries\n\
+        chain_origin,chain_dest,native_token,dex_origin,dex_dest,bridge_protocol,liquidity_score,fee_tier\n\
+        1,137,USDC,UNISWAP_V3,QUICKSWAP,LIFI,95,0.3\n";
+    
     #[test]
     fn test_token_entry_creation() {
         let entry = TokenEntry {
@@ -112,9 +782,469 @@ mod tests {
             bridge_protocol: "LIFI".to_string(),
             liquidity_score: 95.0,
             fee_tier: 0.3,
+            ..Default::default()
         };
         
         assert_eq!(entry.chain_origin, 1);
         assert_eq!(entry.native_token, "USDC");
     }
+    
+    #[test]
+    fn test_nan_fee_tier_rejected() {
+        let path = std::env::temp_dir().join(format!("titan_matrix_nan_{}.md", std::process::id()));
+        std::fs::write(
+            &path,
+            "## Data Entries\n\
+             chain_origin,chain_dest,native_token,dex_origin,dex_dest,bridge_protocol,liquidity_score,fee_tier\n\
+             1,137,USDC,UNISWAP_V3,QUICKSWAP,LIFI,95,nan\n",
+        )
+        .unwrap();
+        
+        let result = load_token_matrix(path.to_str().unwrap());
+        std::fs::remove_file(&path).ok();
+        
+        let err = result.unwrap_err();
+        assert!(err.contains("fee_tier"));
+    }
+    
+    #[test]
+    fn test_json_round_trip() {
+        let path = temp_path("round_trip.json");
+        let mut entry = sample_entry();
+        entry.fee_tier = 0.1 + 0.2;
+        let entries = vec![entry, sample_entry()];
+        
+        save_token_matrix_json(&path, &entries).unwrap();
+        let loaded = load_token_matrix_json(&path);
+        std::fs::remove_file(&path).ok();
+        
+        assert_eq!(loaded.unwrap(), entries);
+    }
+    
+    #[test]
+    fn test_json_bare_array() {
+        let path = temp_path("bare.json");
+        std::fs::write(&path, serde_json::to_string(&vec![sample_entry()]).unwrap()).unwrap();
+        
+        let loaded = load_token_matrix_json(&path);
+        std::fs::remove_file(&path).ok();
+        
+        assert_eq!(loaded.unwrap(), vec![sample_entry()]);
+    }
+    
+    #[test]
+    fn test_json_malformed_error() {
+        let path = temp_path("malformed.json");
+        std::fs::write(&path, "{\"version\": 1, \"entries\": [{\"chain_origin\": \"one\"}]}").unwrap();
+        
+        let result = load_token_matrix_json(&path);
+        std::fs::remove_file(&path).ok();
+        
+        let err = result.unwrap_err().to_string();
+        assert!(err.contains("malformed.json"), "{}", err);
+        assert!(err.contains("line 1"), "{}", err);
+    }
+    
+    #[test]
+    fn test_auto_detect_by_extension() {
+        let json_path = temp_path("auto.json");
+        let md_path = temp_path("auto.md");
+        save_token_matrix_json(&json_path, &[sample_entry()]).unwrap();
+        std::fs::write(&md_path, MARKDOWN_MATRIX).unwrap();
+        
+        let from_json = load_token_matrix_auto(&json_path);
+        let from_md = load_token_matrix_auto(&md_path);
+        std::fs::remove_file(&json_path).ok();
+        std::fs::remove_file(&md_path).ok();
+        
+        assert_eq!(from_json.unwrap(), vec![sample_entry()]);
+        assert_eq!(from_md.unwrap(), vec![sample_entry()]);
+    }
+    
+    #[test]
+    fn test_auto_detect_by_leading_byte() {
+        let path = temp_path("sniffed.matrix");
+        save_token_matrix_json(&path, &[sample_entry()]).unwrap();
+        
+        let loaded = load_token_matrix_auto(&path);
+        std::fs::remove_file(&path).ok();
+        
+        assert_eq!(loaded.unwrap(), vec![sample_entry()]);
+    }
+    
+    fn load_from_str(name: &str, content: &str) -> Result<Vec<TokenEntry>, String> {
+        let path = temp_path(name);
+        std::fs::write(&path, content).unwrap();
+        let result = load_token_matrix(&path);
+        std::fs::remove_file(&path).ok();
+        result
+    }
+    
+    #[test]
+    fn test_reordered_headers() {
+        let entries = load_from_str(
+            "reordered.md",
+            "## Data Entries\n\
+             fee_tier,liquidity_score,bridge_protocol,dex_dest,dex_origin,native_token,chain_dest,chain_origin\n\
+             0.3,95,LIFI,QUICKSWAP,UNISWAP_V3,USDC,137,1\n",
+        )
+        .unwrap();
+        assert_eq!(entries, vec![sample_entry()]);
+    }
+    
+    #[test]
+    fn test_quoted_field_with_comma() {
+        let entries = load_from_str(
+            "quoted.md",
+            "## Data Entries\n\
+             chain_origin,chain_dest,native_token,dex_origin,dex_dest,bridge_protocol,liquidity_score,fee_tier\n\
+             1,137,\"USD Coin, bridged\",UNISWAP_V3,QUICKSWAP,LIFI,95,0.3\n",
+        )
+        .unwrap();
+        assert_eq!(entries[0].native_token, "USD Coin, bridged");
+        assert_eq!(entries[0].dex_origin, "UNISWAP_V3");
+    }
+    
+    #[test]
+    fn test_extra_column_ignored() {
+        let entries = load_from_str(
+            "extra.md",
+            "## Data Entries\n\
+             chain_origin,chain_dest,native_token,dex_origin,dex_dest,bridge_protocol,liquidity_score,fee_tier,notes\n\
+             1,137,USDC,UNISWAP_V3,QUICKSWAP,LIFI,95,0.3,primary route\n",
+        )
+        .unwrap();
+        assert_eq!(entries, vec![sample_entry()]);
+    }
+    
+    #[test]
+    fn test_error_reports_line_and_column() {
+        let err = load_from_str(
+            "bad_number.md",
+            "# Matrix\n\
+             \n\
+             ## Data Entries\n\
+             chain_origin,chain_dest,native_token,dex_origin,dex_dest,bridge_protocol,liquidity_score,fee_tier\n\
+             1,137,USDC,UNISWAP_V3,QUICKSWAP,LIFI,95,0.3\n\
+             1,137,USDC,UNISWAP_V3,QUICKSWAP,LIFI,high,0.3\n",
+        )
+        .unwrap_err();
+        assert!(err.contains("Line 6"), "{}", err);
+        assert!(err.contains("liquidity_score"), "{}", err);
+    }
+    
+    /// Canonical entries of the schema v1/v2 fixtures, without addresses
+    fn schema_fixture_entries() -> Vec<TokenEntry> {
+        [
+            (1, 137, "USDC", "UNISWAP_V3", "QUICKSWAP", "LIFI", 95.0, 0.3),
+            (1, 42161, "WETH", "UNISWAP_V3", "CAMELOT", "STARGATE", 98.0, 0.05),
+            (137, 10, "USDT", "QUICKSWAP", "VELODROME", "HOP", 88.0, 0.3),
+        ]
+        .into_iter()
+        .map(|(origin, dest, token, dex_origin, dex_dest, bridge, liquidity, fee)| TokenEntry {
+            chain_origin: origin,
+            chain_dest: dest,
+            native_token: token.to_string(),
+            dex_origin: dex_origin.to_string(),
+            dex_dest: dex_dest.to_string(),
+            bridge_protocol: bridge.to_string(),
+            liquidity_score: liquidity,
+            fee_tier: fee,
+            ..Default::default()
+        })
+        .collect()
+    }
+    
+    #[test]
+    fn test_schema_v1_fixture_migrates() {
+        let path = concat!(env!("CARGO_MANIFEST_DIR"), "/../data/fixtures/omniarb_matrix_schema_v1.md");
+        assert_eq!(load_token_matrix(path).unwrap(), schema_fixture_entries());
+    }
+    
+    #[test]
+    fn test_schema_v2_fixture_parses_addresses() {
+        let path = concat!(env!("CARGO_MANIFEST_DIR"), "/../data/fixtures/omniarb_matrix_schema_v2.md");
+        let entries = load_token_matrix(path).unwrap();
+        
+        let mut expected = schema_fixture_entries();
+        expected[0].token_address_origin = Some("0xA0b86991c6218b36c1d19D4a2e9Eb0cE3606eB48".to_string());
+        expected[0].token_address_dest = Some("0x2791Bca1f2de4661ED88A30C99A7a9449Aa84174".to_string());
+        expected[0].pool_address_origin = Some("0x88e6A0c2dDD26FEEb64F039a2c41296FcB3f5640".to_string());
+        expected[1].token_address_origin = Some("0xC02aaA39b223FE8D0A0e5C4F27eAD9083C756Cc2".to_string());
+        expected[1].token_address_dest = Some("0x82aF49447D8a07e3bd95BD0d56f35241523fBab1".to_string());
+        assert_eq!(entries, expected);
+    }
+    
+    #[test]
+    fn test_schema_v1_ignores_address_columns() {
+        // Without a marker the file is v1, so v2-only columns are not read
+        let entries = load_from_str(
+            "v1_extra.md",
+            "chain_origin,chain_dest,native_token,dex_origin,dex_dest,bridge_protocol,liquidity_score,fee_tier,token_address_origin\n\
+             1,137,USDC,UNISWAP_V3,QUICKSWAP,LIFI,95,0.3,0xA0b86991c6218b36c1d19D4a2e9Eb0cE3606eB48\n",
+        )
+        .unwrap();
+        assert_eq!(entries, vec![sample_entry()]);
+    }
+    
+    #[test]
+    fn test_unknown_schema_version_rejected() {
+        let err = load_from_str(
+            "v9.md",
+            "<!-- schema: 9 -->\n\
+             chain_origin,chain_dest,native_token,dex_origin,dex_dest,bridge_protocol,liquidity_score,fee_tier\n\
+             1,137,USDC,UNISWAP_V3,QUICKSWAP,LIFI,95,0.3\n",
+        )
+        .unwrap_err();
+        assert_eq!(err, "Unsupported matrix schema version 9 (supported: 1-2)");
+    }
+    
+    #[test]
+    fn test_malformed_address_rejected() {
+        let err = load_from_str(
+            "bad_address.md",
+            "<!-- schema: 2 -->\n\
+             chain_origin,chain_dest,native_token,dex_origin,dex_dest,bridge_protocol,liquidity_score,fee_tier,token_address_origin,pool_address_dest\n\
+             1,137,USDC,UNISWAP_V3,QUICKSWAP,LIFI,95,0.3,0xA0b86991c6218b36c1d19D4a2e9Eb0cE3606eB48,\n\
+             1,137,USDC,UNISWAP_V3,QUICKSWAP,LIFI,95,0.3,0xA0b8,0xZZ\n",
+        )
+        .unwrap_err();
+        assert!(err.contains("Line 4"), "{}", err);
+        assert!(err.contains("token_address_origin") && err.contains("pool_address_dest"), "{}", err);
+        assert!(!err.contains("Line 3"), "{}", err);
+    }
+    
+    #[test]
+    fn test_registry_backfills_missing_addresses() {
+        let path = concat!(env!("CARGO_MANIFEST_DIR"), "/../data/fixtures/omniarb_matrix_schema_v2.md");
+        let mut entries = load_token_matrix(path).unwrap();
+        let mut registry = TokenRegistry::new();
+        registry.insert(1, "USDC", "0x0000000000000000000000000000000000000001");
+        registry.insert(137, "USDT", "0xc2132D05D31c914a87C6611C10748AEb04B58e8F");
+        
+        // Declared addresses win over the registry
+        assert_eq!(entries[0].resolve_addresses(&registry), 0);
+        assert_eq!(
+            entries[0].token_address_origin.as_deref(),
+            Some("0xA0b86991c6218b36c1d19D4a2e9Eb0cE3606eB48")
+        );
+        
+        // Only the origin side is known for the address-less row
+        assert_eq!(entries[2].resolve_addresses(&registry), 1);
+        assert_eq!(
+            entries[2].token_address_origin.as_deref(),
+            Some("0xc2132D05D31c914a87C6611C10748AEb04B58e8F")
+        );
+        assert_eq!(entries[2].token_address_dest, None);
+    }
+    
+    const HEADER: &str =
+        "chain_origin,chain_dest,native_token,dex_origin,dex_dest,bridge_protocol,liquidity_score,fee_tier\n";
+    
+    fn load_str_with_options(name: &str, content: &str, options: ParseOptions) -> Result<MatrixLoad, MatrixError> {
+        let path = temp_path(name);
+        std::fs::write(&path, content).unwrap();
+        let result = load_token_matrix_with_options(&path, options);
+        std::fs::remove_file(&path).ok();
+        result
+    }
+    
+    #[test]
+    fn test_variant_names_normalized() {
+        let load = load_str_with_options(
+            "typo_bridge.csv",
+            &format!("{}1,137,USDC,Uniswap V3,QUICKSWAP,STAR GATE,95,0.3\n", HEADER),
+            ParseOptions::default(),
+        )
+        .unwrap();
+        
+        assert_eq!(load.entries[0].bridge_protocol, "STARGATE");
+        assert_eq!(load.entries[0].dex_origin, "UNISWAP_V3");
+        let warned: Vec<_> = load
+            .warnings
+            .iter()
+            .map(|w| (w.line, w.column.as_str(), w.raw_value.as_str()))
+            .collect();
+        assert_eq!(warned, vec![(2, "dex_origin", "Uniswap V3"), (2, "bridge_protocol", "STAR GATE")]);
+    }
+    
+    #[test]
+    fn test_fee_tiers_warned_and_snapped_on_request() {
+        let input = format!(
+            "{}1,137,USDC,UNISWAP_V3,QUICKSWAP,STARGATE,95,0.3\n1,137,USDT,UNISWAP_V3,QUICKSWAP,STARGATE,95,0.25\n",
+            HEADER
+        );
+        // Warned about but kept unless snapping
+        let load = load_str_with_options("fees.csv", &input, ParseOptions::default()).unwrap();
+        assert_eq!(load.entries[1].fee_tier, 0.25);
+        let warned: Vec<_> = load.warnings.iter().map(|w| (w.line, w.column.as_str(), w.raw_value.as_str())).collect();
+        assert_eq!(warned, vec![(3, "fee_tier", "0.25")]);
+        assert!(load.warnings[0].reason.contains("quoted at"), "{}", load.warnings[0].reason);
+        
+        let options = ParseOptions { snap_fee_tiers: true, ..ParseOptions::default() };
+        let load = load_str_with_options("fees.csv", &input, options).unwrap();
+        assert_eq!((load.entries[0].fee_tier, load.entries[1].fee_tier), (0.3, 0.3));
+        assert_eq!(load.entries[1].uniswap_fee(), 3000);
+        let warned: Vec<_> = load.warnings.iter().map(|w| (w.line, w.column.as_str(), w.raw_value.as_str())).collect();
+        assert_eq!(warned, vec![(3, "fee_tier", "0.25")]);
+    }
+    
+    #[test]
+    fn test_unknown_names_and_chains_rejected() {
+        let content = format!(
+            "{}1,137,USDC,UNISWAP_V3,QUICKSWAP,LIFI,95,0.3\n\
+             99999,137,USDC,UNISWAP_V3,QUICKSWAP,LIFI,95,0.3\n\
+             1,137,USDC,UNISWAP_V3,QUICKSWAP,TELEPORTER,95,0.3\n",
+            HEADER
+        );
+        
+        let err = load_str_with_options("bogus_strict.csv", &content, ParseOptions::default()).unwrap_err();
+        let MatrixError::Invalid(diagnostics) = err else { panic!("expected invalid matrix") };
+        let summary: Vec<_> = diagnostics.iter().map(|d| (d.line, d.column.as_str())).collect();
+        assert_eq!(summary, vec![(3, "chain_origin"), (4, "bridge_protocol")]);
+        
+        let lenient = ParseOptions { strict: false, ..ParseOptions::default() };
+        let load = load_str_with_options("bogus_lenient.csv", &content, lenient).unwrap();
+        assert_eq!(load.entries.len(), 1);
+        assert_eq!(load.diagnostics.len(), 2);
+    }
+    
+    #[test]
+    fn test_legacy_fixture_parses() {
+        let path = concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/../data/omniarb_full_matrix_encoder_decoder_a_j_build_sheet.md"
+        );
+        let entries = load_token_matrix(path).unwrap();
+        
+        assert_eq!(entries.len(), 30);
+        assert_eq!(entries[0], sample_entry());
+        let last = entries.last().unwrap();
+        assert_eq!((last.chain_origin, last.chain_dest), (137, 42161));
+        assert_eq!(last.native_token, "MATIC");
+        assert_eq!(last.bridge_protocol, "POLYGON_BRIDGE");
+        assert_eq!((last.liquidity_score, last.fee_tier), (85.0, 0.25));
+    }
+    
+    const BROKEN_MATRIX: &str = "## Data Entries\n\
+        chain_origin,chain_dest,native_token,dex_origin,dex_dest,bridge_protocol,liquidity_score,fee_tier\n\
+        1,137,USDC,UNISWAP_V3,QUICKSWAP,LIFI,95,0.3\n\
+        1,137,USDC,UNISWAP_V3,QUICKSWAP,LIFI,lots,0.3\n\
+        999,137,USDC,UNISWAP_V3,QUICKSWAP,LIFI,95,0.3\n\
+        1,137,USDC,UNISWAP_V3,QUICKSWAP,LIFI,150,0.3\n\
+        1,137,USDC,UNISWAP_V3,QUICKSWAP,LIFI,95,-1\n\
+        1,42161,ETH,UNISWAP_V3,CAMELOT,STARGATE,98,0.05\n";
+    
+    fn load_broken(name: &str, options: ParseOptions) -> Result<MatrixLoad, MatrixError> {
+        let path = temp_path(name);
+        std::fs::write(&path, BROKEN_MATRIX).unwrap();
+        let result = load_token_matrix_with_options(&path, options);
+        std::fs::remove_file(&path).ok();
+        result
+    }
+    
+    #[test]
+    fn test_strict_mode_collects_diagnostics() {
+        let err = load_broken("strict.md", ParseOptions { strict: true, max_errors: 10, ..ParseOptions::default() }).unwrap_err();
+        let diagnostics = match err {
+            MatrixError::Invalid(diagnostics) => diagnostics,
+            other => panic!("unexpected error: {}", other),
+        };
+        
+        let summary: Vec<_> = diagnostics
+            .iter()
+            .map(|d| (d.line, d.column.as_str(), d.raw_value.as_str()))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                (4, "liquidity_score", "lots"),
+                (5, "chain_origin", "999"),
+                (6, "liquidity_score", "150"),
+                (7, "fee_tier", "-1"),
+            ]
+        );
+    }
+    
+    #[test]
+    fn test_strict_mode_max_errors() {
+        let err = load_broken("strict_max.md", ParseOptions { strict: true, max_errors: 2, ..ParseOptions::default() }).unwrap_err();
+        match err {
+            MatrixError::Invalid(diagnostics) => assert_eq!(diagnostics.len(), 2),
+            other => panic!("unexpected error: {}", other),
+        }
+    }
+    
+    #[test]
+    fn test_lenient_mode_skips_bad_rows() {
+        let MatrixLoad { entries, diagnostics, .. } =
+            load_broken("lenient.md", ParseOptions { strict: false, ..ParseOptions::default() }).unwrap();
+        
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0], sample_entry());
+        assert_eq!(entries[1].native_token, "ETH");
+        assert_eq!(diagnostics.len(), 4);
+    }
+    
+    #[test]
+    fn test_load_with_dedupe_reports_merged() {
+        let path = temp_path("duplicates.md");
+        std::fs::write(
+            &path,
+            "## Data Entries\n\
+             chain_origin,chain_dest,native_token,dex_origin,dex_dest,bridge_protocol,liquidity_score,fee_tier\n\
+             1,137,USDC,UNISWAP_V3,QUICKSWAP,LIFI,95,0.3\n\
+             1,137,USDC,UNISWAP_V3,QUICKSWAP,LIFI,97,0.3\n\
+             1,137,USDC,UNISWAP_V3,QUICKSWAP,LIFI,93,0.3\n",
+        )
+        .unwrap();
+        let options = ParseOptions {
+            dedupe: Some(DedupStrategy::KeepHighestLiquidity),
+            ..ParseOptions::default()
+        };
+        let load = load_token_matrix_with_options(&path, options).unwrap();
+        std::fs::remove_file(&path).ok();
+        
+        assert_eq!(load.merged, 2);
+        assert_eq!(load.entries.len(), 1);
+        assert_eq!(load.entries[0].liquidity_score, 97.0);
+    }
+    
+    #[test]
+    fn test_save_round_trip() {
+        let mut awkward = sample_entry();
+        awkward.liquidity_score = 100.0 / 3.0;
+        awkward.fee_tier = 0.1 + 0.2;
+        let mut tiny = sample_entry();
+        tiny.native_token = "WETH, bridged".to_string();
+        tiny.fee_tier = 1e-7;
+        let entries = vec![sample_entry(), awkward, tiny];
+        
+        for (name, format) in [
+            ("round_trip.md", MatrixFormat::Markdown),
+            ("round_trip.csv", MatrixFormat::Csv),
+            ("round_trip_saved.json", MatrixFormat::Json),
+        ] {
+            let path = temp_path(name);
+            save_token_matrix(&path, &entries, format).unwrap();
+            let loaded = load_token_matrix_auto(&path);
+            std::fs::remove_file(&path).ok();
+            
+            assert_eq!(loaded.unwrap(), entries, "{:?}", format);
+        }
+    }
+    
+    #[test]
+    fn test_to_csv_string_header() {
+        let csv = to_csv_string(&[sample_entry()]).unwrap();
+        let mut lines = csv.lines();
+        assert_eq!(
+            lines.next(),
+            Some(
+                "chain_origin,chain_dest,native_token,dex_origin,dex_dest,bridge_protocol,liquidity_score,fee_tier,\
+                 token_address_origin,token_address_dest,pool_address_origin,pool_address_dest"
+            )
+        );
+        assert_eq!(lines.next(), Some("1,137,USDC,UNISWAP_V3,QUICKSWAP,LIFI,95.0,0.3,,,,"));
+    }
 }
@@ -5,8 +5,72 @@ pub mod matrix_parser;
 pub mod tar_scorer;
 pub mod data_fetcher;
 pub mod model_bridge;
+pub mod token_matrix;
+pub mod matrix_diff;
+pub mod socket_client;
+pub mod quote_cache;
+pub mod quote_source;
+pub mod dex_spread;
+pub mod liquidity;
+pub mod cooldown;
+pub mod checkpoint;
+pub mod ranking;
+pub mod score_history;
+pub mod opportunity_filter;
+pub mod audit;
+pub mod model_registry;
+pub mod feature_log;
+pub mod fee_tier;
+pub mod scoring_run;
+pub mod columnar;
 
-pub use matrix_parser::{load_token_matrix, TokenEntry};
-pub use tar_scorer::calculate_tar_score;
-pub use data_fetcher::{fetch_live_quotes, QuoteInfo};
-pub use model_bridge::{run_tar_onnx, run_flanker};
+pub use matrix_parser::{
+    load_token_matrix, load_token_matrix_auto, load_token_matrix_auto_with_options,
+    invalid_row_count, load_token_matrix_json, load_token_matrix_with_options, save_token_matrix,
+    save_token_matrix_json, to_csv_string, AddressResolver, MatrixError, MatrixFormat, MatrixLoad,
+    ParseDiagnostic, ParseOptions, TokenEntry,
+};
+pub use tar_scorer::{
+    calculate_tar_breakdown, calculate_tar_breakdown_batch, calculate_tar_breakdown_sized, calculate_tar_breakdown_weighted, calculate_tar_score,
+    calculate_tar_score_sized, calculate_tar_score_weighted, Breakpoint,
+    explain_tar_score, TarBreakdown, TarFactors, TarWeights, TierConfig, TierPoints, LOWEST_TIER,
+};
+pub use data_fetcher::{
+    fetch_live_quotes, fetch_live_quotes_async, fetch_live_quotes_bounded, fetch_live_quotes_smoothed, fetch_live_quotes_with_gas, fetch_routed_quotes, fetch_routed_quotes_cached, fetch_simulated_quotes, QuoteInfo, QuoteProvider,
+    QuoteSmoother,
+};
+pub use model_bridge::{
+    ensemble_score, feature_names, predict_batch, route_ensemble, run_tar_onnx, run_flanker, sanitization_counts,
+    sanitize_prediction, BatchModel, EnsembleComponents, EnsembleResult, EnsembleWeights, FeatureVector, HeuristicModel,
+    ModelBackend, ModelOutput, ModelStatus, Prediction, SanitizationCounts, SanitizedPrediction, BRIDGE_MODELS,
+    DEFAULT_MAX_BATCH, DEFAULT_MAX_DISAGREEMENT, FEATURE_COUNT, FEATURE_SCHEMA_VERSION,
+};
+pub use columnar::RouteColumns;
+pub use scoring_run::{
+    score_matrix_file, RunHistory, RunOutcome, RunStatus, RunSummary, ScoringRun, DEFAULT_RUN_HISTORY,
+    DEFAULT_RUN_TRADE_SIZE_USD,
+};
+pub use fee_tier::{fee_tier_to_uniswap_fee, FeeTier, FeeTiers, SnappedFee, UNISWAP_FEE_TIERS};
+pub use token_matrix::{parse_bridge_list, BridgePolicy, DedupStrategy, TokenMatrix};
+pub use matrix_diff::{diff_matrices, EntryChange, FieldDelta, MatrixDiff, RouteId};
+pub use socket_client::{SocketClient, SocketQuote, SocketQuoteRequest};
+pub use quote_cache::{amount_bucket, QuoteCache, QuoteCacheKey, QuoteCacheStats, DEFAULT_QUOTE_TTL};
+pub use quote_source::{
+    fetch_best_bridge_quote, BestQuote, QuoteError, QuoteRouter, QuoteSource, RouterMode, SimulatedSource,
+    SocketSource,
+};
+pub use dex_spread::{fetch_live_quotes_with_spreads, DexSpreadCalculator, DEFAULT_SPREAD_NOTIONAL};
+pub use cooldown::{CooldownTracker, DEFAULT_ROUTE_COOLDOWN};
+pub use checkpoint::{ResumePolicy, ScoredRoute, ScoringCheckpoint, CHECKPOINT_BATCH};
+pub use ranking::{rank_routes, select_top, select_top_diversified, SelectionPolicy, DEFAULT_MAX_PER_KEY};
+pub use opportunity_filter::{OpportunityEvent, OpportunityFilter};
+pub use score_history::{RouteScore, ScoreHistory, Trend, DEFAULT_SCORE_HALF_LIFE, DEFAULT_TREND_WINDOW};
+pub use audit::{audit_routes, fingerprint_records, score_fingerprint, simulated_quotes, AuditRecord, AuditReport, FINGERPRINT_DECIMALS};
+pub use model_registry::{
+    LoadedModel, ModelError, ModelRegistry, OnnxMetadata, FLANKER_FEATURE_WEIGHTS, TAR_FEATURE_WEIGHTS,
+};
+pub use feature_log::{
+    FeatureLogConfig, FeatureLogger, FeatureRecord, DEFAULT_FEATURE_LOG_CAPACITY, DEFAULT_FEATURE_LOG_MAX_BYTES,
+    DEFAULT_FEATURE_LOG_MAX_FILES,
+};
+pub use liquidity::{pool_tvl_usd, recompute_liquidity_score, refresh_liquidity_scores, tvl_to_liquidity_score};
@@ -1,5 +1,217 @@
+use std::str::FromStr;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use std::path::PathBuf;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
 use crate::omniarb::matrix_parser::TokenEntry;
 use crate::omniarb::data_fetcher::QuoteInfo;
+use crate::omniarb::model_registry::{LoadedModel, ModelRegistry, FLANKER_FEATURE_WEIGHTS, TAR_FEATURE_WEIGHTS};
+use crate::omniarb::tar_scorer::calculate_tar_score;
+
+/// Tolerance when checking that ensemble weights sum to 1
+const WEIGHT_SUM_TOLERANCE: f64 = 1e-6;
+
+/// Component spread (score points) above which an ensemble is flagged
+pub const DEFAULT_MAX_DISAGREEMENT: f64 = 30.0;
+
+/// Largest number of rows sent through a model in one inference call
+pub const DEFAULT_MAX_BATCH: usize = 1024;
+
+/// Version of the [`FeatureVector`] definitions
+///
+/// Bump it whenever a feature is added, removed, reordered or computed
+/// differently, so models trained on the old schema are not served the new
+/// one.
+pub const FEATURE_SCHEMA_VERSION: u32 = 1;
+
+/// Number of model input features
+pub const FEATURE_COUNT: usize = 6;
+
+const FEATURE_NAMES: [&str; FEATURE_COUNT] = ["liquidity", "spread", "bridge", "token", "slippage_headroom", "gas_efficiency"];
+
+static PREDICTIONS_CLAMPED: AtomicU64 = AtomicU64::new(0);
+static PREDICTIONS_NAN: AtomicU64 = AtomicU64::new(0);
+
+/// A raw model output forced into the 0-100 score range
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SanitizedPrediction {
+    pub score: f64,
+    pub was_clamped: bool,
+    pub was_nan: bool,
+}
+
+/// Clamp a model output to 0-100, mapping NaN to 0
+///
+/// Without this a NaN or out-of-range output would sort ahead of every
+/// real score. Each clamp and NaN is counted in [`sanitization_counts`].
+pub fn sanitize_prediction(raw: impl Into<f64>) -> SanitizedPrediction {
+    let raw = raw.into();
+    if raw.is_nan() {
+        PREDICTIONS_NAN.fetch_add(1, Ordering::Relaxed);
+        return SanitizedPrediction { score: 0.0, was_clamped: false, was_nan: true };
+    }
+    let score = raw.clamp(0.0, 100.0);
+    let was_clamped = score != raw;
+    if was_clamped {
+        PREDICTIONS_CLAMPED.fetch_add(1, Ordering::Relaxed);
+    }
+    SanitizedPrediction { score, was_clamped, was_nan: false }
+}
+
+/// Model outputs sanitized since the process started
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct SanitizationCounts {
+    pub clamped: u64,
+    pub nan: u64,
+}
+
+pub fn sanitization_counts() -> SanitizationCounts {
+    SanitizationCounts {
+        clamped: PREDICTIONS_CLAMPED.load(Ordering::Relaxed),
+        nan: PREDICTIONS_NAN.load(Ordering::Relaxed),
+    }
+}
+
+/// Raw outputs of one inference row
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ModelOutput {
+    pub score: f64,
+    /// The graph's second output, if it has one
+    pub confidence: Option<f64>,
+}
+
+/// A sanitized model score (0-100)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct Prediction {
+    pub score: f64,
+    /// How sure the model is (0-1), from its second output when it has one
+    pub confidence: Option<f64>,
+    /// The model emitted NaN and the heuristic scored the route instead
+    #[serde(default)]
+    pub fell_back: bool,
+}
+
+/// Blend weights for `ensemble_score`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct EnsembleWeights {
+    pub tar_onnx: f64,
+    pub flanker: f64,
+    pub tar_score: f64,
+}
+
+impl EnsembleWeights {
+    /// Weights must be finite, non-negative and sum to 1.0
+    pub fn new(tar_onnx: f64, flanker: f64, tar_score: f64) -> Result<Self, String> {
+        let weights = [tar_onnx, flanker, tar_score];
+        if weights.iter().any(|w| !w.is_finite() || *w < 0.0) {
+            return Err(format!("Ensemble weights must be non-negative: {:?}", weights));
+        }
+        let sum: f64 = weights.iter().sum();
+        if (sum - 1.0).abs() > WEIGHT_SUM_TOLERANCE {
+            return Err(format!("Ensemble weights must sum to 1.0, got {}", sum));
+        }
+        Ok(Self { tar_onnx, flanker, tar_score })
+    }
+
+    /// Equal weight for each model
+    pub fn equal() -> Self {
+        Self {
+            tar_onnx: 1.0 / 3.0,
+            flanker: 1.0 / 3.0,
+            tar_score: 1.0 / 3.0,
+        }
+    }
+}
+
+impl Default for EnsembleWeights {
+    fn default() -> Self {
+        Self::equal()
+    }
+}
+
+/// Parse `tar_onnx,flanker,tar_score`, e.g. `0.5,0.25,0.25`
+impl FromStr for EnsembleWeights {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let weights = s
+            .split(',')
+            .map(|w| w.trim().parse::<f64>().map_err(|e| format!("Invalid weight '{}': {}", w, e)))
+            .collect::<Result<Vec<_>, _>>()?;
+        match weights[..] {
+            [tar_onnx, flanker, tar_score] => Self::new(tar_onnx, flanker, tar_score),
+            _ => Err(format!("Expected 3 comma-separated weights, got '{}'", s)),
+        }
+    }
+}
+
+/// Scores an ensemble was blended from; `None` for a model that didn't run
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct EnsembleComponents {
+    pub tar: f64,
+    pub onnx: Option<f64>,
+    pub flanker: Option<f64>,
+}
+
+/// A blended score and how far its components agree
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct EnsembleResult {
+    pub score: f64,
+    /// Largest pairwise spread between the available components
+    pub disagreement: f64,
+    pub components: EnsembleComponents,
+}
+
+impl EnsembleResult {
+    /// Components disagree by more than `max_disagreement` points
+    pub fn is_flagged(&self, max_disagreement: f64) -> bool {
+        self.disagreement > max_disagreement
+    }
+}
+
+/// Weighted blend of the rule-based TAR, TAR ONNX and Flanker scores (0-100)
+///
+/// Missing or non-finite components are left out and the remaining weights
+/// re-normalized; if those weights are all 0 the available scores are
+/// averaged.
+pub fn ensemble_score(tar: f64, onnx: Option<f64>, flanker: Option<f64>, weights: &EnsembleWeights) -> EnsembleResult {
+    let available: Vec<(f64, f64)> = [(Some(tar), weights.tar_score), (onnx, weights.tar_onnx), (flanker, weights.flanker)]
+        .into_iter()
+        .filter_map(|(score, weight)| score.filter(|s| s.is_finite()).map(|s| (s, weight)))
+        .collect();
+
+    let weight_sum: f64 = available.iter().map(|(_, weight)| weight).sum();
+    let score = if available.is_empty() {
+        0.0
+    } else if weight_sum > 0.0 {
+        available.iter().map(|(score, weight)| score * weight).sum::<f64>() / weight_sum
+    } else {
+        available.iter().map(|(score, _)| score).sum::<f64>() / available.len() as f64
+    };
+    let scores = available.iter().map(|(score, _)| *score);
+    let disagreement = match (scores.clone().reduce(f64::max), scores.reduce(f64::min)) {
+        (Some(max), Some(min)) => max - min,
+        _ => 0.0,
+    };
+    EnsembleResult {
+        score,
+        disagreement,
+        components: EnsembleComponents { tar, onnx, flanker },
+    }
+}
+
+/// [`ensemble_score`] of a route with every model run on it
+pub fn route_ensemble(entry: &TokenEntry, quote: &QuoteInfo, weights: &EnsembleWeights) -> EnsembleResult {
+    ensemble_score(
+        calculate_tar_score(entry, quote),
+        Some(run_tar_onnx(entry, quote).score),
+        Some(run_flanker(entry, quote).score),
+        weights,
+    )
+}
 
 /// Run TAR ONNX model prediction
 /// 
@@ -11,21 +223,25 @@ use crate::omniarb::data_fetcher::QuoteInfo;
 /// * `quote` - Live quote information
 /// 
 /// # Returns
-/// ML model prediction score (0-100)
-pub fn run_tar_onnx(entry: &TokenEntry, quote: &QuoteInfo) -> f64 {
+/// ML model prediction score (0-100); 0 for non-finite inputs
+pub fn run_tar_onnx(entry: &TokenEntry, quote: &QuoteInfo) -> Prediction {
+    if !entry.is_finite() || !quote.is_finite() {
+        return Prediction::default();
+    }
+    
     // Simulate ONNX model inference
     // In production, would use tract or ort crate to run actual ONNX model
     
     // Extract features
-    let features = extract_features(entry, quote);
+    let features = FeatureVector::from(entry, quote);
     
     // Simple weighted model (placeholder for real ONNX)
-    let prediction = features.liquidity_score * 0.3
-        + features.spread_score * 0.3
-        + features.bridge_score * 0.2
-        + features.token_score * 0.2;
+    let prediction = features.liquidity * 0.3
+        + features.spread * 0.3
+        + features.bridge * 0.2
+        + features.token * 0.2;
     
-    prediction.min(100.0).max(0.0)
+    heuristic_prediction(prediction)
 }
 
 /// Run Flanker model prediction
@@ -38,58 +254,86 @@ pub fn run_tar_onnx(entry: &TokenEntry, quote: &QuoteInfo) -> f64 {
 /// * `quote` - Live quote information
 /// 
 /// # Returns
-/// Flanker model prediction score (0-100)
-pub fn run_flanker(entry: &TokenEntry, quote: &QuoteInfo) -> f64 {
+/// Flanker model prediction score (0-100); 0 for non-finite inputs
+pub fn run_flanker(entry: &TokenEntry, quote: &QuoteInfo) -> Prediction {
+    if !entry.is_finite() || !quote.is_finite() {
+        return Prediction::default();
+    }
+    
     // Simulate Flanker model inference
-    let features = extract_features(entry, quote);
+    let features = FeatureVector::from(entry, quote);
     
     // Flanker focuses more on risk and volatility
-    let prediction = features.bridge_score * 0.4
-        + features.liquidity_score * 0.3
-        + (100.0 - features.slippage_penalty) * 0.2
+    let prediction = features.bridge * 0.4
+        + features.liquidity * 0.3
+        + features.slippage_headroom * 0.2
         + features.gas_efficiency * 0.1;
     
-    prediction.min(100.0).max(0.0)
+    heuristic_prediction(prediction)
 }
 
-struct ModelFeatures {
-    liquidity_score: f64,
-    spread_score: f64,
-    bridge_score: f64,
-    token_score: f64,
-    slippage_penalty: f64,
-    gas_efficiency: f64,
+/// The heuristics have one output and nothing to fall back to
+fn heuristic_prediction(raw: f64) -> Prediction {
+    Prediction {
+        score: sanitize_prediction(raw).score,
+        confidence: None,
+        fell_back: false,
+    }
 }
 
-fn extract_features(entry: &TokenEntry, quote: &QuoteInfo) -> ModelFeatures {
-    // Liquidity score (normalized)
-    let liquidity_score = entry.liquidity_score;
-    
-    // Spread score (0-100)
-    let spread_score = (quote.spread_percentage * 20.0).min(100.0);
-    
-    // Bridge reliability score
-    let bridge_score = get_bridge_score(&entry.bridge_protocol);
-    
-    // Token quality score
-    let token_score = get_token_score(&entry.native_token);
-    
-    // Slippage penalty (inverse)
-    let slippage_penalty = quote.slippage_estimate * 50.0;
-    
-    // Gas efficiency (inverse of cost, normalized)
-    let gas_efficiency = (20.0 - quote.gas_cost_usd.min(20.0)) / 20.0 * 100.0;
-    
-    ModelFeatures {
-        liquidity_score,
-        spread_score,
-        bridge_score,
-        token_score,
-        slippage_penalty,
-        gas_efficiency,
+/// Model inputs for one route, shared by the heuristics and ONNX models
+///
+/// Every feature is on a 0-100 scale, higher is better:
+///
+/// | # | Feature | Definition |
+/// |---|---------|------------|
+/// | 0 | `liquidity` | matrix `liquidity_score` as-is |
+/// | 1 | `spread` | `spread_percentage × 20`, capped at 100 |
+/// | 2 | `bridge` | bridge reliability tier: 90, 75, 65, else 50 |
+/// | 3 | `token` | token quality tier: 95, 90, 80, 75, else 60 |
+/// | 4 | `slippage_headroom` | `100 − slippage_estimate × 50` |
+/// | 5 | `gas_efficiency` | `(20 − min(gas_cost_usd, 20)) / 20 × 100` |
+///
+/// Changing any definition or the order requires bumping
+/// [`FEATURE_SCHEMA_VERSION`].
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct FeatureVector {
+    pub liquidity: f64,
+    pub spread: f64,
+    pub bridge: f64,
+    pub token: f64,
+    pub slippage_headroom: f64,
+    pub gas_efficiency: f64,
+}
+
+impl FeatureVector {
+    pub fn from(entry: &TokenEntry, quote: &QuoteInfo) -> Self {
+        Self {
+            liquidity: entry.liquidity_score,
+            spread: (quote.spread_percentage * 20.0).min(100.0),
+            bridge: get_bridge_score(&entry.bridge_protocol),
+            token: get_token_score(&entry.native_token),
+            slippage_headroom: 100.0 - quote.slippage_estimate * 50.0,
+            gas_efficiency: (20.0 - quote.gas_cost_usd.min(20.0)) / 20.0 * 100.0,
+        }
+    }
+
+    /// The model's input row, in [`feature_names`] order
+    pub fn to_array(&self) -> [f32; FEATURE_COUNT] {
+        self.values().map(|value| value as f32)
+    }
+
+    /// Full-precision values in [`feature_names`] order, for the heuristics
+    pub(crate) fn values(&self) -> [f64; FEATURE_COUNT] {
+        [self.liquidity, self.spread, self.bridge, self.token, self.slippage_headroom, self.gas_efficiency]
     }
 }
 
+/// Feature names in model input order
+pub fn feature_names() -> [&'static str; FEATURE_COUNT] {
+    FEATURE_NAMES
+}
+
 fn get_bridge_score(bridge: &str) -> f64 {
     match bridge {
         "STARGATE" | "ACROSS" | "CCIP" => 90.0,
@@ -109,14 +353,182 @@ fn get_token_score(token: &str) -> f64 {
     }
 }
 
-/// Load ONNX model from file (future enhancement)
-/// 
-/// This would be used when integrating actual ONNX models
-#[allow(dead_code)]
-fn load_onnx_model(_model_path: &str) -> Result<(), String> {
-    // Placeholder for real ONNX integration
-    // Would use tract or ort crate
-    Err("ONNX integration not implemented yet".to_string())
+/// A model that scores a whole batch per inference call
+pub trait BatchModel {
+    /// Raw output for each feature vector
+    ///
+    /// ONNX models build their (N, F) input tensor from
+    /// [`FeatureVector::to_array`].
+    fn infer(&self, batch: &[FeatureVector]) -> Vec<ModelOutput>;
+
+    /// Heuristic weights scoring a row whose output was NaN
+    fn fallback_weights(&self) -> [f64; FEATURE_COUNT];
+
+    /// Rows per `infer` call, bounding the tensor held in memory
+    fn max_batch(&self) -> usize {
+        DEFAULT_MAX_BATCH
+    }
+
+    /// Sanitized predictions for `entries` paired with `quotes` by index
+    ///
+    /// Rows with non-finite inputs predict 0, as the per-row models do. A
+    /// NaN output is replaced by the [`fallback_weights`](Self::fallback_weights)
+    /// heuristic and marked `fell_back`.
+    fn predict_batch(&self, entries: &[TokenEntry], quotes: &[QuoteInfo]) -> Vec<Prediction> {
+        let rows: Vec<_> = entries.iter().zip(quotes).collect();
+        let mut predictions = Vec::with_capacity(rows.len());
+        let mut batch = Vec::with_capacity(self.max_batch().min(rows.len()));
+        for chunk in rows.chunks(self.max_batch().max(1)) {
+            batch.clear();
+            batch.extend(chunk.iter().map(|(entry, quote)| FeatureVector::from(entry, quote)));
+            let outputs = self.infer(&batch);
+            predictions.extend(chunk.iter().zip(&batch).zip(outputs).map(|(((entry, quote), features), output)| {
+                if !entry.is_finite() || !quote.is_finite() {
+                    return Prediction::default();
+                }
+                let confidence = output.confidence.filter(|c| c.is_finite()).map(|c| c.clamp(0.0, 1.0));
+                let sanitized = sanitize_prediction(output.score);
+                let score = if sanitized.was_nan {
+                    let fallback = weighted_sum(features.values(), &self.fallback_weights());
+                    if fallback.is_finite() { fallback.clamp(0.0, 100.0) } else { 0.0 }
+                } else {
+                    sanitized.score
+                };
+                Prediction { score, confidence, fell_back: sanitized.was_nan }
+            }));
+        }
+        predictions
+    }
+}
+
+/// The built-in weighted-feature models, used when no model file is loaded
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct HeuristicModel {
+    weights: [f64; FEATURE_COUNT],
+    max_batch: usize,
+}
+
+impl HeuristicModel {
+    /// Batched [`run_tar_onnx`]
+    pub fn tar() -> Self {
+        Self { weights: TAR_FEATURE_WEIGHTS, max_batch: DEFAULT_MAX_BATCH }
+    }
+
+    /// Batched [`run_flanker`]
+    pub fn flanker() -> Self {
+        Self { weights: FLANKER_FEATURE_WEIGHTS, max_batch: DEFAULT_MAX_BATCH }
+    }
+
+    pub fn with_max_batch(mut self, max_batch: usize) -> Self {
+        self.max_batch = max_batch;
+        self
+    }
+}
+
+impl BatchModel for HeuristicModel {
+    fn infer(&self, batch: &[FeatureVector]) -> Vec<ModelOutput> {
+        batch
+            .iter()
+            .map(|features| ModelOutput { score: weighted_sum(features.values(), &self.weights), confidence: None })
+            .collect()
+    }
+
+    fn fallback_weights(&self) -> [f64; FEATURE_COUNT] {
+        self.weights
+    }
+
+    fn max_batch(&self) -> usize {
+        self.max_batch
+    }
+}
+
+impl BatchModel for LoadedModel {
+    fn infer(&self, batch: &[FeatureVector]) -> Vec<ModelOutput> {
+        let tensor: Vec<_> = batch.iter().map(FeatureVector::to_array).collect();
+        tensor
+            .iter()
+            .map(|row| {
+                let row = row.map(f64::from);
+                ModelOutput {
+                    score: weighted_sum(row, &self.feature_weights),
+                    confidence: self.confidence_weights.as_ref().map(|weights| weighted_sum(row, weights)),
+                }
+            })
+            .collect()
+    }
+
+    fn fallback_weights(&self) -> [f64; FEATURE_COUNT] {
+        self.default_weights
+    }
+}
+
+fn weighted_sum(features: [f64; FEATURE_COUNT], weights: &[f64]) -> f64 {
+    features.iter().zip(weights).map(|(f, w)| f * w).sum()
+}
+
+/// Models the bridge scores with, each falling back to its heuristic
+pub const BRIDGE_MODELS: [&str; 2] = ["tar", "flanker"];
+
+/// What actually produces a model's predictions
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ModelBackend {
+    Onnx,
+    /// The built-in weighted sum (`run_tar_onnx` / `run_flanker`)
+    Heuristic,
+}
+
+/// Which backend serves a model, and why
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ModelStatus {
+    pub name: String,
+    pub backend: ModelBackend,
+    /// Configured model file, loaded or not
+    pub path: Option<PathBuf>,
+    pub version: Option<String>,
+    pub loaded_at: Option<DateTime<Utc>>,
+    /// Why the last load or reload failed
+    pub last_error: Option<String>,
+}
+
+/// Status of every bridge model and any other model in `registry`
+///
+/// A model whose file failed to load reports `Heuristic` with the error; a
+/// loaded model whose reload failed stays `Onnx` on its previous version.
+pub fn status(registry: &ModelRegistry) -> Vec<ModelStatus> {
+    let mut names: Vec<String> = BRIDGE_MODELS.iter().map(|name| name.to_string()).collect();
+    names.extend(registry.names());
+    names.sort();
+    names.dedup();
+    names
+        .into_iter()
+        .map(|name| {
+            let failure = registry.failure(&name);
+            match registry.get(&name) {
+                Some(model) => ModelStatus {
+                    backend: ModelBackend::Onnx,
+                    path: Some(model.path.clone()),
+                    version: Some(model.version.clone()),
+                    loaded_at: Some(model.loaded_at),
+                    last_error: failure.map(|(_, error)| error),
+                    name,
+                },
+                None => ModelStatus {
+                    backend: ModelBackend::Heuristic,
+                    version: None,
+                    loaded_at: None,
+                    path: failure.as_ref().map(|(path, _)| path.clone()),
+                    last_error: failure.map(|(_, error)| error),
+                    name,
+                },
+            }
+        })
+        .collect()
+}
+
+/// [`run_tar_onnx`] for a whole batch in one inference call per chunk
+pub fn predict_batch(entries: &[TokenEntry], quotes: &[QuoteInfo]) -> Vec<Prediction> {
+    HeuristicModel::tar().predict_batch(entries, quotes)
 }
 
 #[cfg(test)]
@@ -134,6 +546,7 @@ mod tests {
             bridge_protocol: "STARGATE".to_string(),
             liquidity_score: 95.0,
             fee_tier: 0.3,
+            ..Default::default()
         };
         
         let quote = QuoteInfo {
@@ -141,10 +554,12 @@ mod tests {
             slippage_estimate: 0.3,
             gas_cost_usd: 5.0,
             available_liquidity: 1000000.0,
+            ..Default::default()
         };
         
         let prediction = run_tar_onnx(&entry, &quote);
-        assert!(prediction >= 0.0 && prediction <= 100.0);
+        assert!((0.0..=100.0).contains(&prediction.score));
+        assert_eq!(prediction.confidence, None);
     }
     
     #[test]
@@ -158,6 +573,7 @@ mod tests {
             bridge_protocol: "ACROSS".to_string(),
             liquidity_score: 88.0,
             fee_tier: 0.15,
+            ..Default::default()
         };
         
         let quote = QuoteInfo {
@@ -165,9 +581,251 @@ mod tests {
             slippage_estimate: 0.5,
             gas_cost_usd: 8.0,
             available_liquidity: 500000.0,
+            ..Default::default()
         };
         
         let prediction = run_flanker(&entry, &quote);
-        assert!(prediction >= 0.0 && prediction <= 100.0);
+        assert!((0.0..=100.0).contains(&prediction.score));
+    }
+    
+    #[test]
+    fn test_equal_weights_average_models() {
+        let entry = TokenEntry {
+            chain_origin: 1,
+            chain_dest: 137,
+            native_token: "USDC".to_string(),
+            dex_origin: "UNISWAP_V3".to_string(),
+            dex_dest: "QUICKSWAP".to_string(),
+            bridge_protocol: "STARGATE".to_string(),
+            liquidity_score: 95.0,
+            fee_tier: 0.3,
+            ..Default::default()
+        };
+        let quote = QuoteInfo {
+            spread_percentage: 1.5,
+            slippage_estimate: 0.3,
+            gas_cost_usd: 5.0,
+            available_liquidity: 1000000.0,
+            ..Default::default()
+        };
+        
+        let mean = (run_tar_onnx(&entry, &quote).score + run_flanker(&entry, &quote).score + calculate_tar_score(&entry, &quote)) / 3.0;
+        assert!((route_ensemble(&entry, &quote, &EnsembleWeights::equal()).score - mean).abs() < 1e-9);
+        
+        let tar_only = EnsembleWeights::new(0.0, 0.0, 1.0).unwrap();
+        assert_eq!(route_ensemble(&entry, &quote, &tar_only).score, calculate_tar_score(&entry, &quote));
+    }
+    
+    #[test]
+    fn test_missing_components_renormalize() {
+        let weights = EnsembleWeights::new(0.5, 0.25, 0.25).unwrap();
+        let full = ensemble_score(60.0, Some(80.0), Some(40.0), &weights);
+        assert_eq!(full.score, 80.0 * 0.5 + 40.0 * 0.25 + 60.0 * 0.25);
+        assert_eq!(full.disagreement, 40.0);
+        
+        // No ONNX model: TAR and Flanker share the weight equally
+        let no_onnx = ensemble_score(60.0, None, Some(40.0), &weights);
+        assert_eq!(no_onnx.score, 50.0);
+        assert_eq!(no_onnx.disagreement, 20.0);
+        assert_eq!(no_onnx.components.onnx, None);
+        
+        // Only TAR left, a non-finite Flanker output counting as missing
+        let tar_only = ensemble_score(60.0, None, Some(f64::NAN), &weights);
+        assert_eq!((tar_only.score, tar_only.disagreement), (60.0, 0.0));
+        
+        // Weights only on missing models fall back to a plain average
+        let onnx_weights = EnsembleWeights::new(1.0, 0.0, 0.0).unwrap();
+        assert_eq!(ensemble_score(60.0, None, Some(40.0), &onnx_weights).score, 50.0);
+    }
+    
+    #[test]
+    fn test_disagreement_flag_boundary() {
+        let result = ensemble_score(50.0, Some(80.0), Some(60.0), &EnsembleWeights::equal());
+        assert_eq!(result.disagreement, 30.0);
+        assert!(!result.is_flagged(DEFAULT_MAX_DISAGREEMENT));
+        assert!(result.is_flagged(29.99));
+        assert!(ensemble_score(50.0, Some(80.01), None, &EnsembleWeights::equal()).is_flagged(DEFAULT_MAX_DISAGREEMENT));
+    }
+    
+    #[test]
+    fn test_batch_matches_per_row() {
+        use crate::omniarb::audit::simulated_quotes;
+        use crate::omniarb::matrix_parser::load_token_matrix;
+        use crate::omniarb::model_registry::tests::onnx_model;
+
+        let fixture = concat!(env!("CARGO_MANIFEST_DIR"), "/../data/fixtures/omniarb_scoring_audit.md");
+        let mut entries = load_token_matrix(fixture).unwrap();
+        let mut quotes = simulated_quotes(&entries);
+        entries[2].liquidity_score = f64::NAN;
+        quotes[4].spread_percentage = f64::INFINITY;
+
+        // Chunks of 4 leave a short final chunk
+        let tar = HeuristicModel::tar().with_max_batch(4);
+        let flanker = HeuristicModel::flanker().with_max_batch(4);
+        let per_row: Vec<_> = entries.iter().zip(&quotes).map(|(e, q)| run_tar_onnx(e, q)).collect();
+        assert_eq!(tar.predict_batch(&entries, &quotes), per_row);
+        assert_eq!(predict_batch(&entries, &quotes), per_row);
+        assert_eq!(per_row[2].score, 0.0);
+        let per_row: Vec<_> = entries.iter().zip(&quotes).map(|(e, q)| run_flanker(e, q)).collect();
+        assert_eq!(flanker.predict_batch(&entries, &quotes), per_row);
+
+        let path = std::env::temp_dir().join(format!("titan_model_{}_batch.onnx", std::process::id()));
+        std::fs::write(&path, onnx_model(1, &[("feature_weights", "0.5,0.1,0.1,0.1,0.1,0.1")])).unwrap();
+        let model = LoadedModel::load("tar", &path, TAR_FEATURE_WEIGHTS).unwrap();
+        let per_row: Vec<_> = entries.iter().zip(&quotes).map(|(e, q)| model.predict(e, q)).collect();
+        assert_eq!(model.predict_batch(&entries, &quotes), per_row);
+        std::fs::remove_file(&path).ok();
+
+        assert!(tar.predict_batch(&[], &[]).is_empty());
+    }
+
+    /// A model replaying canned raw outputs
+    struct FixedOutputs(Vec<ModelOutput>);
+
+    impl BatchModel for FixedOutputs {
+        fn infer(&self, batch: &[FeatureVector]) -> Vec<ModelOutput> {
+            self.0[..batch.len()].to_vec()
+        }
+
+        fn fallback_weights(&self) -> [f64; FEATURE_COUNT] {
+            TAR_FEATURE_WEIGHTS
+        }
+    }
+
+    #[test]
+    fn test_nan_and_out_of_range_outputs_are_sanitized() {
+        use crate::omniarb::audit::simulated_quotes;
+        use crate::omniarb::matrix_parser::load_token_matrix;
+
+        let fixture = concat!(env!("CARGO_MANIFEST_DIR"), "/../data/fixtures/omniarb_scoring_audit.md");
+        let entries: Vec<_> = load_token_matrix(fixture).unwrap().into_iter().take(4).collect();
+        let quotes = simulated_quotes(&entries);
+        let output = |score, confidence| ModelOutput { score, confidence };
+        let model = FixedOutputs(vec![
+            output(f64::NAN, Some(0.9)),
+            output(-3.7, None),
+            output(4000.0, Some(f64::NAN)),
+            output(55.5, Some(1.7)),
+        ]);
+
+        let before = sanitization_counts();
+        let predictions = model.predict_batch(&entries, &quotes);
+        let after = sanitization_counts();
+        assert!(after.nan > before.nan);
+        assert!(after.clamped >= before.clamped + 2);
+
+        // NaN falls back to the TAR heuristic and says so
+        let heuristic = run_tar_onnx(&entries[0], &quotes[0]);
+        assert!(predictions[0].fell_back);
+        assert_eq!(predictions[0].score, heuristic.score);
+        assert_eq!(predictions[0].confidence, Some(0.9));
+
+        assert_eq!(predictions[1], Prediction { score: 0.0, confidence: None, fell_back: false });
+        assert_eq!(predictions[2], Prediction { score: 100.0, confidence: None, fell_back: false });
+        assert_eq!(predictions[3], Prediction { score: 55.5, confidence: Some(1.0), fell_back: false });
+
+        let nan = sanitize_prediction(f32::NAN);
+        assert_eq!((nan.score, nan.was_nan, nan.was_clamped), (0.0, true, false));
+        assert!(!sanitize_prediction(100.0f32).was_clamped);
+    }
+
+    #[test]
+    fn test_status_reports_backend_per_model() {
+        use crate::omniarb::model_registry::tests::onnx_model;
+
+        // Nothing configured: both bridge models run their heuristics
+        let statuses = status(&ModelRegistry::new());
+        assert_eq!(statuses.iter().map(|s| s.name.as_str()).collect::<Vec<_>>(), ["flanker", "tar"]);
+        assert!(statuses.iter().all(|s| s.backend == ModelBackend::Heuristic && s.path.is_none() && s.last_error.is_none()));
+
+        let loaded = std::env::temp_dir().join(format!("titan_model_{}_status.onnx", std::process::id()));
+        std::fs::write(&loaded, onnx_model(1, &[("version", "tar-v1")])).unwrap();
+        let missing = std::env::temp_dir().join(format!("titan_model_{}_missing.onnx", std::process::id()));
+        let configured = || {
+            [
+                ("tar", loaded.clone(), TAR_FEATURE_WEIGHTS),
+                ("flanker", missing.clone(), FLANKER_FEATURE_WEIGHTS),
+            ]
+        };
+
+        // Configured but failed: heuristic with the error, unless strict
+        let registry = ModelRegistry::load_configured(configured(), false).unwrap();
+        let statuses = status(&registry);
+        let flanker = &statuses[0];
+        assert_eq!(flanker.backend, ModelBackend::Heuristic);
+        assert_eq!(flanker.path.as_ref(), Some(&missing));
+        assert!(flanker.last_error.as_ref().unwrap().contains("Failed to load model"));
+        let tar = &statuses[1];
+        assert_eq!(tar.backend, ModelBackend::Onnx);
+        assert_eq!(tar.version.as_deref(), Some("tar-v1"));
+        assert!(tar.loaded_at.is_some() && tar.last_error.is_none());
+
+        let err = ModelRegistry::load_configured(configured(), true).err().unwrap();
+        assert!(matches!(err, crate::omniarb::model_registry::ModelError::Load { path, .. } if path == missing.display().to_string()));
+
+        // A failed reload keeps serving the loaded model but reports the error
+        std::fs::remove_file(&loaded).unwrap();
+        assert!(registry.reload("tar").is_err());
+        let tar = &status(&registry)[1];
+        assert_eq!(tar.backend, ModelBackend::Onnx);
+        assert!(tar.last_error.is_some());
+    }
+
+    #[test]
+    fn test_feature_schema_matches_golden() {
+        use crate::omniarb::audit::simulated_quotes;
+        use crate::omniarb::matrix_parser::load_token_matrix;
+
+        #[derive(Deserialize)]
+        struct Golden {
+            schema_version: u32,
+            features: Vec<String>,
+            vectors: Vec<FeatureVector>,
+        }
+
+        let fixture = concat!(env!("CARGO_MANIFEST_DIR"), "/../data/fixtures/omniarb_scoring_audit.md");
+        let golden = concat!(env!("CARGO_MANIFEST_DIR"), "/../data/fixtures/feature_vectors_golden.json");
+        let golden: Golden = serde_json::from_str(&std::fs::read_to_string(golden).unwrap()).unwrap();
+        let entries = load_token_matrix(fixture).unwrap();
+        let quotes = simulated_quotes(&entries);
+        let vectors: Vec<_> = entries.iter().zip(&quotes).map(|(e, q)| FeatureVector::from(e, q)).collect();
+
+        // A failure here means the feature math changed: bump
+        // FEATURE_SCHEMA_VERSION and regenerate the golden file
+        assert_eq!(golden.schema_version, FEATURE_SCHEMA_VERSION);
+        assert_eq!(golden.features, feature_names());
+        assert_eq!(vectors, golden.vectors);
+    }
+
+    #[test]
+    fn test_to_array_follows_feature_names() {
+        let entry = TokenEntry {
+            native_token: "USDC".to_string(),
+            bridge_protocol: "STARGATE".to_string(),
+            liquidity_score: 95.0,
+            ..Default::default()
+        };
+        let quote = QuoteInfo {
+            spread_percentage: 1.5,
+            slippage_estimate: 0.3,
+            gas_cost_usd: 5.0,
+            ..Default::default()
+        };
+        let features = FeatureVector::from(&entry, &quote);
+        assert_eq!(features.to_array(), [95.0, 30.0, 90.0, 95.0, 85.0, 75.0]);
+        assert_eq!(feature_names(), ["liquidity", "spread", "bridge", "token", "slippage_headroom", "gas_efficiency"]);
+
+        // Spread saturates at 100
+        let wide = QuoteInfo { spread_percentage: 9.0, ..quote };
+        assert_eq!(FeatureVector::from(&entry, &wide).spread, 100.0);
+    }
+
+    #[test]
+    fn test_ensemble_weights_validated() {
+        assert!(EnsembleWeights::new(0.5, 0.5, 0.5).is_err());
+        assert!(EnsembleWeights::new(1.5, -0.5, 0.0).is_err());
+        assert!(EnsembleWeights::new(f64::NAN, 0.5, 0.5).is_err());
+        assert_eq!("0.5,0.25,0.25".parse::<EnsembleWeights>().unwrap(), EnsembleWeights::new(0.5, 0.25, 0.25).unwrap());
+        assert!("0.5,0.5".parse::<EnsembleWeights>().is_err());
     }
 }
@@ -0,0 +1,491 @@
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+use arc_swap::ArcSwap;
+use chrono::{DateTime, Utc};
+use log::{info, warn};
+use serde::Serialize;
+use thiserror::Error;
+
+use crate::omniarb::data_fetcher::QuoteInfo;
+use crate::omniarb::matrix_parser::TokenEntry;
+use crate::omniarb::model_bridge::{BatchModel, Prediction, FEATURE_COUNT};
+
+/// Built-in TAR ONNX weights, used when a model file doesn't carry its own
+pub const TAR_FEATURE_WEIGHTS: [f64; FEATURE_COUNT] = [0.3, 0.3, 0.2, 0.2, 0.0, 0.0];
+
+/// Built-in Flanker weights, used when a model file doesn't carry its own
+pub const FLANKER_FEATURE_WEIGHTS: [f64; FEATURE_COUNT] = [0.3, 0.0, 0.4, 0.0, 0.2, 0.1];
+
+/// Version reported for a model file without any version metadata
+pub const UNVERSIONED: &str = "unversioned";
+
+/// Model loading errors
+#[derive(Debug, Clone, PartialEq, Error)]
+pub enum ModelError {
+    #[error("Unknown model '{0}'")]
+    Unknown(String),
+    #[error("Failed to load model {path}: {message}")]
+    Load { path: String, message: String },
+}
+
+/// Top-level `ModelProto` metadata of an ONNX file
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct OnnxMetadata {
+    pub ir_version: Option<i64>,
+    pub producer_name: Option<String>,
+    pub producer_version: Option<String>,
+    pub model_version: Option<i64>,
+    /// `metadata_props` key/value pairs
+    pub props: BTreeMap<String, String>,
+}
+
+impl OnnxMetadata {
+    /// Read the metadata fields of a serialized ONNX model, skipping the graph
+    pub fn parse(bytes: &[u8]) -> Result<Self, String> {
+        let mut metadata = OnnxMetadata::default();
+        for field in ProtoFields::new(bytes) {
+            match field? {
+                (1, ProtoValue::Varint(v)) => metadata.ir_version = Some(v as i64),
+                (2, ProtoValue::Bytes(b)) => metadata.producer_name = Some(utf8(b)?),
+                (3, ProtoValue::Bytes(b)) => metadata.producer_version = Some(utf8(b)?),
+                (5, ProtoValue::Varint(v)) => metadata.model_version = Some(v as i64),
+                (14, ProtoValue::Bytes(entry)) => {
+                    let (mut key, mut value) = (String::new(), String::new());
+                    for field in ProtoFields::new(entry) {
+                        match field? {
+                            (1, ProtoValue::Bytes(b)) => key = utf8(b)?,
+                            (2, ProtoValue::Bytes(b)) => value = utf8(b)?,
+                            _ => {}
+                        }
+                    }
+                    metadata.props.insert(key, value);
+                }
+                _ => {}
+            }
+        }
+        Ok(metadata)
+    }
+
+    /// `version` metadata entry, else `model_version`, else the producer version
+    pub fn version(&self) -> String {
+        self.props
+            .get("version")
+            .cloned()
+            .or_else(|| self.model_version.map(|v| v.to_string()))
+            .or_else(|| self.producer_version.clone())
+            .unwrap_or_else(|| UNVERSIONED.to_string())
+    }
+}
+
+fn utf8(bytes: &[u8]) -> Result<String, String> {
+    String::from_utf8(bytes.to_vec()).map_err(|e| format!("invalid UTF-8 string: {}", e))
+}
+
+enum ProtoValue<'a> {
+    Varint(u64),
+    Bytes(&'a [u8]),
+    Fixed,
+}
+
+/// Iterator over the `(field number, value)` pairs of a protobuf message
+struct ProtoFields<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> ProtoFields<'a> {
+    fn new(bytes: &'a [u8]) -> Self {
+        Self { bytes }
+    }
+
+    fn varint(&mut self) -> Result<u64, String> {
+        let mut value = 0u64;
+        for shift in (0..64).step_by(7) {
+            let (&byte, rest) = self.bytes.split_first().ok_or("truncated varint")?;
+            self.bytes = rest;
+            value |= u64::from(byte & 0x7f) << shift;
+            if byte & 0x80 == 0 {
+                return Ok(value);
+            }
+        }
+        Err("varint longer than 10 bytes".to_string())
+    }
+
+    fn take(&mut self, len: usize) -> Result<&'a [u8], String> {
+        if self.bytes.len() < len {
+            return Err(format!("truncated field: need {} bytes, have {}", len, self.bytes.len()));
+        }
+        let (taken, rest) = self.bytes.split_at(len);
+        self.bytes = rest;
+        Ok(taken)
+    }
+
+    fn field(&mut self) -> Result<(u64, ProtoValue<'a>), String> {
+        let key = self.varint()?;
+        let value = match key & 0x7 {
+            0 => ProtoValue::Varint(self.varint()?),
+            1 => self.take(8).map(|_| ProtoValue::Fixed)?,
+            2 => {
+                let len = self.varint()? as usize;
+                ProtoValue::Bytes(self.take(len)?)
+            }
+            5 => self.take(4).map(|_| ProtoValue::Fixed)?,
+            wire_type => return Err(format!("unsupported wire type {}", wire_type)),
+        };
+        Ok((key >> 3, value))
+    }
+}
+
+impl<'a> Iterator for ProtoFields<'a> {
+    type Item = Result<(u64, ProtoValue<'a>), String>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.bytes.is_empty() {
+            return None;
+        }
+        let field = self.field();
+        if field.is_err() {
+            // Stop after the first malformed field
+            self.bytes = &[];
+        }
+        Some(field)
+    }
+}
+
+/// A model file as loaded at `loaded_at`
+///
+/// Until an ONNX runtime is wired in, inference is the linear placeholder
+/// `run_tar_onnx` uses, with weights taken from the file's
+/// `feature_weights` metadata entry (comma-separated, in
+/// [`feature_names`](crate::omniarb::model_bridge::feature_names) order)
+/// when present. A `confidence_weights` entry in the same format stands in
+/// for the graph's second output.
+#[derive(Debug, Clone, Serialize)]
+pub struct LoadedModel {
+    pub name: String,
+    pub path: PathBuf,
+    pub version: String,
+    pub loaded_at: DateTime<Utc>,
+    pub feature_weights: Vec<f64>,
+    #[serde(skip)]
+    pub confidence_weights: Option<Vec<f64>>,
+    /// Built-in weights scoring rows the model predicted NaN for
+    #[serde(skip)]
+    pub default_weights: [f64; FEATURE_COUNT],
+}
+
+impl LoadedModel {
+    /// Load `path`, falling back to `default_weights` when the file has none
+    pub fn load(name: &str, path: impl AsRef<Path>, default_weights: [f64; FEATURE_COUNT]) -> Result<Self, ModelError> {
+        let path = path.as_ref();
+        let load_error = |message: String| ModelError::Load {
+            path: path.display().to_string(),
+            message,
+        };
+        let bytes = std::fs::read(path).map_err(|e| load_error(e.to_string()))?;
+        let metadata = OnnxMetadata::parse(&bytes).map_err(load_error)?;
+
+        let feature_weights = match metadata.props.get("feature_weights") {
+            Some(weights) => parse_feature_weights(weights).map_err(load_error)?,
+            None => default_weights.to_vec(),
+        };
+        let confidence_weights = metadata
+            .props
+            .get("confidence_weights")
+            .map(|weights| parse_feature_weights(weights))
+            .transpose()
+            .map_err(load_error)?;
+        Ok(Self {
+            name: name.to_string(),
+            path: path.to_path_buf(),
+            version: metadata.version(),
+            loaded_at: Utc::now(),
+            feature_weights,
+            confidence_weights,
+            default_weights,
+        })
+    }
+
+    /// Sanitized prediction; 0 for non-finite inputs
+    pub fn predict(&self, entry: &TokenEntry, quote: &QuoteInfo) -> Prediction {
+        self.predict_batch(std::slice::from_ref(entry), std::slice::from_ref(quote))[0]
+    }
+}
+
+fn parse_feature_weights(value: &str) -> Result<Vec<f64>, String> {
+    let weights = value
+        .split(',')
+        .map(|w| w.trim().parse::<f64>().map_err(|e| format!("Invalid feature weight '{}': {}", w, e)))
+        .collect::<Result<Vec<_>, _>>()?;
+    if weights.len() != FEATURE_COUNT {
+        return Err(format!("Expected {} feature weights, got {}", FEATURE_COUNT, weights.len()));
+    }
+    if weights.iter().any(|w| !w.is_finite()) {
+        return Err(format!("Feature weights must be finite: {}", value));
+    }
+    Ok(weights)
+}
+
+struct ModelSlot {
+    default_weights: [f64; FEATURE_COUNT],
+    current: ArcSwap<LoadedModel>,
+}
+
+/// Named models that can be reloaded from disk without a restart
+///
+/// `reload` swaps the model atomically: inference already holding the old
+/// model (see `get`) finishes on it, later calls see the new one, and a
+/// failed reload keeps the old model in place.
+#[derive(Default)]
+pub struct ModelRegistry {
+    models: HashMap<String, ModelSlot>,
+    // Last failed load or reload of each model, with the path it tried
+    failures: Mutex<HashMap<String, (PathBuf, String)>>,
+}
+
+impl ModelRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// TAR and Flanker models from `TAR_MODEL_PATH` / `FLANKER_MODEL_PATH`
+    ///
+    /// Unset variables are skipped. An unloadable file is an error when
+    /// `TITAN_REQUIRE_MODELS` is set, and otherwise falls back to the
+    /// heuristic (see [`load_configured`](Self::load_configured)).
+    pub fn from_env() -> Result<Self, ModelError> {
+        let require = std::env::var("TITAN_REQUIRE_MODELS")
+            .map(|v| matches!(v.trim().to_ascii_lowercase().as_str(), "1" | "true" | "yes"))
+            .unwrap_or(false);
+        let configured = [
+            ("tar", "TAR_MODEL_PATH", TAR_FEATURE_WEIGHTS),
+            ("flanker", "FLANKER_MODEL_PATH", FLANKER_FEATURE_WEIGHTS),
+        ]
+        .into_iter()
+        .filter_map(|(name, var, weights)| std::env::var(var).ok().map(|path| (name, PathBuf::from(path), weights)));
+        Self::load_configured(configured, require)
+    }
+
+    /// Load each configured `(name, path, default_weights)`
+    ///
+    /// With `require`, the first file that fails to load is returned as the
+    /// error. Otherwise the failure is logged, recorded for
+    /// [`failure`](Self::failure), and the model stays on its heuristic.
+    pub fn load_configured<'a>(
+        configured: impl IntoIterator<Item = (&'a str, PathBuf, [f64; FEATURE_COUNT])>,
+        require: bool,
+    ) -> Result<Self, ModelError> {
+        let mut registry = Self::new();
+        for (name, path, weights) in configured {
+            if let Err(e) = registry.register(name, &path, weights) {
+                if require {
+                    return Err(e);
+                }
+                warn!("{} model configured but not loaded, using the heuristic: {}", name, e);
+                registry.record_failure(name, path, &e);
+            }
+        }
+        Ok(registry)
+    }
+
+    fn record_failure(&self, name: &str, path: PathBuf, error: &ModelError) {
+        let mut failures = self.failures.lock().unwrap();
+        failures.insert(name.to_string(), (path, error.to_string()));
+    }
+
+    /// Load `path` as model `name`, replacing any model of that name
+    pub fn register(
+        &mut self,
+        name: &str,
+        path: impl AsRef<Path>,
+        default_weights: [f64; FEATURE_COUNT],
+    ) -> Result<Arc<LoadedModel>, ModelError> {
+        let model = Arc::new(LoadedModel::load(name, path, default_weights)?);
+        info!("Loaded {} model {} from {}", name, model.version, model.path.display());
+        self.models.insert(
+            name.to_string(),
+            ModelSlot {
+                default_weights,
+                current: ArcSwap::new(Arc::clone(&model)),
+            },
+        );
+        Ok(model)
+    }
+
+    /// Re-read model `name` from its file and swap it in
+    pub fn reload(&self, name: &str) -> Result<Arc<LoadedModel>, ModelError> {
+        let slot = self.models.get(name).ok_or_else(|| ModelError::Unknown(name.to_string()))?;
+        let path = slot.current.load().path.clone();
+        let model = match LoadedModel::load(name, &path, slot.default_weights) {
+            Ok(model) => Arc::new(model),
+            Err(e) => {
+                self.record_failure(name, path, &e);
+                return Err(e);
+            }
+        };
+        self.failures.lock().unwrap().remove(name);
+        let previous = slot.current.swap(Arc::clone(&model));
+        info!("Reloaded {} model: {} -> {}", name, previous.version, model.version);
+        Ok(model)
+    }
+
+    /// Reload every model, returning the first failure
+    pub fn reload_all(&self) -> Result<Vec<Arc<LoadedModel>>, ModelError> {
+        let mut names: Vec<_> = self.models.keys().collect();
+        names.sort();
+        names.into_iter().map(|name| self.reload(name)).collect()
+    }
+
+    /// Path and error of the last failed load or reload of model `name`
+    ///
+    /// Cleared by a successful reload.
+    pub fn failure(&self, name: &str) -> Option<(PathBuf, String)> {
+        self.failures.lock().unwrap().get(name).cloned()
+    }
+
+    /// Names of registered or failed models, sorted
+    pub fn names(&self) -> Vec<String> {
+        let failures = self.failures.lock().unwrap();
+        let mut names: Vec<_> = self.models.keys().chain(failures.keys()).cloned().collect();
+        names.sort();
+        names.dedup();
+        names
+    }
+
+    /// Current model `name`; stays valid across later reloads
+    pub fn get(&self, name: &str) -> Option<Arc<LoadedModel>> {
+        self.models.get(name).map(|slot| slot.current.load_full())
+    }
+
+    /// Prediction of model `name`, if registered
+    pub fn predict(&self, name: &str, entry: &TokenEntry, quote: &QuoteInfo) -> Option<Prediction> {
+        self.models.get(name).map(|slot| slot.current.load().predict(entry, quote))
+    }
+
+    /// Loaded version of each model, by name
+    pub fn versions(&self) -> BTreeMap<String, String> {
+        self.models
+            .iter()
+            .map(|(name, slot)| (name.clone(), slot.current.load().version.clone()))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+
+    fn varint(mut value: u64, out: &mut Vec<u8>) {
+        while value >= 0x80 {
+            out.push((value as u8) | 0x80);
+            value >>= 7;
+        }
+        out.push(value as u8);
+    }
+
+    fn bytes_field(field: u64, bytes: &[u8], out: &mut Vec<u8>) {
+        varint(field << 3 | 2, out);
+        varint(bytes.len() as u64, out);
+        out.extend_from_slice(bytes);
+    }
+
+    /// Minimal ONNX `ModelProto` with a graph to skip and metadata props
+    pub(crate) fn onnx_model(model_version: u64, props: &[(&str, &str)]) -> Vec<u8> {
+        let mut out = Vec::new();
+        varint(1 << 3, &mut out);
+        varint(8, &mut out);
+        bytes_field(2, b"pytorch", &mut out);
+        bytes_field(3, b"2.1.0", &mut out);
+        varint(5 << 3, &mut out);
+        varint(model_version, &mut out);
+        bytes_field(7, &[0x0a, 0x03, b'm', b'u', b'l'], &mut out);
+        for (key, value) in props {
+            let mut entry = Vec::new();
+            bytes_field(1, key.as_bytes(), &mut entry);
+            bytes_field(2, value.as_bytes(), &mut entry);
+            bytes_field(14, &entry, &mut out);
+        }
+        out
+    }
+
+    fn route() -> (TokenEntry, QuoteInfo) {
+        let entry = TokenEntry {
+            chain_origin: 1,
+            chain_dest: 137,
+            native_token: "USDC".to_string(),
+            bridge_protocol: "STARGATE".to_string(),
+            liquidity_score: 95.0,
+            fee_tier: 0.3,
+            ..Default::default()
+        };
+        let quote = QuoteInfo {
+            spread_percentage: 1.5,
+            slippage_estimate: 0.3,
+            gas_cost_usd: 5.0,
+            ..Default::default()
+        };
+        (entry, quote)
+    }
+
+    #[test]
+    fn test_parse_onnx_metadata() {
+        let bytes = onnx_model(3, &[("version", "tar-2026.10.1"), ("owner", "quant")]);
+        let metadata = OnnxMetadata::parse(&bytes).unwrap();
+        assert_eq!(metadata.ir_version, Some(8));
+        assert_eq!(metadata.producer_name.as_deref(), Some("pytorch"));
+        assert_eq!(metadata.model_version, Some(3));
+        assert_eq!(metadata.props["owner"], "quant");
+        assert_eq!(metadata.version(), "tar-2026.10.1");
+
+        assert_eq!(OnnxMetadata::parse(&onnx_model(3, &[])).unwrap().version(), "3");
+        assert_eq!(OnnxMetadata::default().version(), UNVERSIONED);
+        assert!(OnnxMetadata::parse(&bytes[..bytes.len() - 2]).is_err());
+    }
+
+    #[test]
+    fn test_default_weights_match_placeholder_models() {
+        use crate::omniarb::model_bridge::{run_flanker, run_tar_onnx};
+
+        let path = std::env::temp_dir().join(format!("titan_model_{}_defaults.onnx", std::process::id()));
+        std::fs::write(&path, onnx_model(1, &[])).unwrap();
+        let (entry, quote) = route();
+        let tar = LoadedModel::load("tar", &path, TAR_FEATURE_WEIGHTS).unwrap();
+        let flanker = LoadedModel::load("flanker", &path, FLANKER_FEATURE_WEIGHTS).unwrap();
+        assert!((tar.predict(&entry, &quote).score - run_tar_onnx(&entry, &quote).score).abs() < 1e-9);
+        assert!((flanker.predict(&entry, &quote).score - run_flanker(&entry, &quote).score).abs() < 1e-9);
+        std::fs::remove_file(&path).ok();
+    }
+
+    #[test]
+    fn test_reload_swaps_model_from_disk() {
+        let path = std::env::temp_dir().join(format!("titan_model_{}_reload.onnx", std::process::id()));
+        std::fs::write(&path, onnx_model(1, &[("version", "v1")])).unwrap();
+
+        let mut registry = ModelRegistry::new();
+        registry.register("tar", &path, TAR_FEATURE_WEIGHTS).unwrap();
+        let (entry, quote) = route();
+        let before = registry.predict("tar", &entry, &quote).unwrap();
+        let in_flight = registry.get("tar").unwrap();
+
+        // Quant ships a retrained model over the same path
+        std::fs::write(&path, onnx_model(2, &[("version", "v2"), ("feature_weights", "0.5,0.1,0.1,0.1,0.1,0.1")])).unwrap();
+        let reloaded = registry.reload("tar").unwrap();
+        assert_eq!(reloaded.version, "v2");
+        assert!(reloaded.loaded_at >= in_flight.loaded_at);
+        assert_eq!(registry.versions()["tar"], "v2");
+        assert_ne!(registry.predict("tar", &entry, &quote).unwrap(), before);
+
+        // Inference that started before the swap still sees the old model
+        assert_eq!(in_flight.version, "v1");
+        assert_eq!(in_flight.predict(&entry, &quote), before);
+
+        // A broken file leaves the current model in place
+        std::fs::write(&path, onnx_model(3, &[("feature_weights", "1,2")])).unwrap();
+        assert!(matches!(registry.reload("tar"), Err(ModelError::Load { .. })));
+        assert_eq!(registry.versions()["tar"], "v2");
+        assert_eq!(registry.reload("flanker").unwrap_err(), ModelError::Unknown("flanker".to_string()));
+
+        std::fs::remove_file(&path).ok();
+    }
+}
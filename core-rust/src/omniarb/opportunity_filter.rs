@@ -0,0 +1,166 @@
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+use crate::config::OpportunityThresholds;
+use crate::omniarb::matrix_diff::RouteId;
+use crate::omniarb::matrix_parser::TokenEntry;
+use crate::omniarb::model_bridge::EnsembleResult;
+
+/// Change in a route's qualification reported by [`OpportunityFilter::observe`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OpportunityEvent {
+    Entered,
+    Exited,
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+struct Qualification {
+    qualified: bool,
+    // When a qualified route first dropped below the exit threshold
+    below_since: Option<Instant>,
+}
+
+/// Qualifies routes with hysteresis so borderline scores don't flap
+///
+/// A route qualifies once its score reaches `enter_threshold`, and only
+/// de-qualifies after staying below `exit_threshold` for `min_dwell`.
+#[derive(Debug, Clone)]
+pub struct OpportunityFilter {
+    pub enter_threshold: f64,
+    pub exit_threshold: f64,
+    pub min_dwell: Duration,
+    /// Treat ensembles whose models disagree by more than this as below the band
+    pub max_disagreement: Option<f64>,
+    state: HashMap<RouteId, Qualification>,
+}
+
+impl Default for OpportunityFilter {
+    fn default() -> Self {
+        Self::from_thresholds(&OpportunityThresholds::default())
+    }
+}
+
+impl OpportunityFilter {
+    pub fn new(enter_threshold: f64, exit_threshold: f64, min_dwell: Duration) -> Self {
+        Self {
+            enter_threshold,
+            exit_threshold,
+            min_dwell,
+            max_disagreement: None,
+            state: HashMap::new(),
+        }
+    }
+
+    /// Exclude ensembles flagged at `max_disagreement` from qualifying
+    pub fn with_max_disagreement(mut self, max_disagreement: f64) -> Self {
+        self.max_disagreement = Some(max_disagreement);
+        self
+    }
+
+    pub fn from_thresholds(thresholds: &OpportunityThresholds) -> Self {
+        Self::new(thresholds.enter_threshold, thresholds.exit_threshold, thresholds.min_dwell)
+    }
+
+    /// Record the route's latest score, returning any qualification change
+    pub fn observe(&mut self, route: &TokenEntry, score: f64) -> Option<OpportunityEvent> {
+        self.observe_at(route, score, Instant::now())
+    }
+
+    /// Record the route's score as of `now`, returning any qualification change
+    pub fn observe_at(&mut self, route: &TokenEntry, score: f64, now: Instant) -> Option<OpportunityEvent> {
+        let state = self.state.entry(RouteId::from(route)).or_default();
+        if !state.qualified {
+            if score >= self.enter_threshold {
+                *state = Qualification { qualified: true, below_since: None };
+                return Some(OpportunityEvent::Entered);
+            }
+            return None;
+        }
+
+        // Non-finite scores count as below the band
+        if score >= self.exit_threshold {
+            state.below_since = None;
+            return None;
+        }
+        let below_since = *state.below_since.get_or_insert(now);
+        if now.saturating_duration_since(below_since) >= self.min_dwell {
+            *state = Qualification::default();
+            return Some(OpportunityEvent::Exited);
+        }
+        None
+    }
+
+    /// Record the route's latest ensemble as of `now`
+    ///
+    /// With `max_disagreement` set, a flagged ensemble counts as below the
+    /// band whatever its score.
+    pub fn observe_ensemble_at(&mut self, route: &TokenEntry, ensemble: &EnsembleResult, now: Instant) -> Option<OpportunityEvent> {
+        let flagged = self.max_disagreement.is_some_and(|max| ensemble.is_flagged(max));
+        let score = if flagged { f64::NAN } else { ensemble.score };
+        self.observe_at(route, score, now)
+    }
+
+    pub fn is_qualified(&self, route: &TokenEntry) -> bool {
+        self.state.get(&RouteId::from(route)).is_some_and(|state| state.qualified)
+    }
+
+    /// Forget every route's state
+    pub fn clear(&mut self) {
+        self.state.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hysteresis_emits_one_enter_and_one_exit() {
+        let route = TokenEntry {
+            native_token: "USDC".to_string(),
+            ..Default::default()
+        };
+        let mut filter = OpportunityFilter::new(85.0, 80.0, Duration::from_secs(30));
+        let start = Instant::now();
+
+        // Oscillates around the enter threshold, dips below exit briefly,
+        // then stays below it past the dwell time
+        let series = [
+            (0, 84.9),
+            (5, 85.1),
+            (10, 84.9),
+            (15, 85.1),
+            (20, 79.0),
+            (25, 84.0),
+            (30, 79.5),
+            (50, 79.0),
+            (65, 78.0),
+            (70, 84.9),
+        ];
+        let events: Vec<_> = series
+            .iter()
+            .filter_map(|(secs, score)| filter.observe_at(&route, *score, start + Duration::from_secs(*secs)))
+            .collect();
+
+        assert_eq!(events, vec![OpportunityEvent::Entered, OpportunityEvent::Exited]);
+        assert!(!filter.is_qualified(&route));
+    }
+
+    #[test]
+    fn test_flagged_ensembles_excluded_when_configured() {
+        use crate::omniarb::model_bridge::{ensemble_score, EnsembleWeights};
+
+        let route = TokenEntry::default();
+        let now = Instant::now();
+        // High score, but the models are 50 points apart
+        let split = ensemble_score(95.0, Some(95.0), Some(45.0), &EnsembleWeights::new(0.0, 0.0, 1.0).unwrap());
+        assert_eq!(split.score, 95.0);
+
+        let mut permissive = OpportunityFilter::new(85.0, 80.0, Duration::ZERO);
+        assert_eq!(permissive.observe_ensemble_at(&route, &split, now), Some(OpportunityEvent::Entered));
+
+        let mut strict = OpportunityFilter::new(85.0, 80.0, Duration::ZERO).with_max_disagreement(30.0);
+        assert_eq!(strict.observe_ensemble_at(&route, &split, now), None);
+        assert!(!strict.is_qualified(&route));
+    }
+}
@@ -0,0 +1,254 @@
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use ethers::types::U256;
+use serde::Serialize;
+
+use crate::omniarb::data_fetcher::QuoteInfo;
+use crate::omniarb::matrix_parser::TokenEntry;
+
+/// Default lifetime of a cached quote
+pub const DEFAULT_QUOTE_TTL: Duration = Duration::from_secs(30);
+
+/// Cache key: the route's bridge leg plus its amount bucket
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct QuoteCacheKey {
+    pub chain_origin: u64,
+    pub chain_dest: u64,
+    pub native_token: String,
+    pub bridge_protocol: String,
+    /// Exponent of the nearest power of two to the amount
+    pub amount_bucket: u32,
+}
+
+impl QuoteCacheKey {
+    pub fn new(entry: &TokenEntry, amount: U256) -> Self {
+        QuoteCacheKey {
+            chain_origin: entry.chain_origin,
+            chain_dest: entry.chain_dest,
+            native_token: entry.native_token.clone(),
+            bridge_protocol: entry.bridge_protocol.clone(),
+            amount_bucket: amount_bucket(amount),
+        }
+    }
+}
+
+/// Exponent of the power of two nearest to `amount` (ties round up, zero maps to 0)
+pub fn amount_bucket(amount: U256) -> u32 {
+    if amount.is_zero() {
+        return 0;
+    }
+    let bits = amount.bits() as u32;
+    let lower = U256::one() << (bits - 1);
+    let above_lower = amount - lower;
+    // Distance to the next power is lower - above_lower
+    if above_lower < lower - above_lower {
+        bits - 1
+    } else {
+        bits
+    }
+}
+
+/// Hit/miss counters for metrics
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct QuoteCacheStats {
+    pub hits: u64,
+    pub misses: u64,
+    pub entries: usize,
+}
+
+/// Thread-safe TTL cache for bridge quotes
+///
+/// Most matrix rows share a chain pair and token, so one HTTP quote can
+/// serve many rows while it's fresh.
+#[derive(Debug)]
+pub struct QuoteCache {
+    ttl: Duration,
+    entries: Mutex<HashMap<QuoteCacheKey, (Instant, QuoteInfo)>>,
+    hits: AtomicU64,
+    misses: AtomicU64,
+}
+
+impl Default for QuoteCache {
+    fn default() -> Self {
+        Self::new(DEFAULT_QUOTE_TTL)
+    }
+}
+
+impl QuoteCache {
+    pub fn new(ttl: Duration) -> Self {
+        Self {
+            ttl,
+            entries: Mutex::new(HashMap::new()),
+            hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
+        }
+    }
+
+    pub fn ttl(&self) -> Duration {
+        self.ttl
+    }
+
+    /// Fresh quote for the route and amount bucket, counting the hit or miss
+    pub fn get(&self, entry: &TokenEntry, amount: U256) -> Option<QuoteInfo> {
+        let key = QuoteCacheKey::new(entry, amount);
+        let cached = self
+            .entries
+            .lock()
+            .unwrap()
+            .get(&key)
+            .filter(|(stored_at, _)| stored_at.elapsed() < self.ttl)
+            .map(|(_, quote)| quote.clone());
+
+        let counter = if cached.is_some() { &self.hits } else { &self.misses };
+        counter.fetch_add(1, Ordering::Relaxed);
+        cached
+    }
+
+    /// Store a quote, replacing any previous one for the key
+    pub fn insert(&self, entry: &TokenEntry, amount: U256, quote: QuoteInfo) {
+        self.entries
+            .lock()
+            .unwrap()
+            .insert(QuoteCacheKey::new(entry, amount), (Instant::now(), quote));
+    }
+
+    /// Drop expired quotes, returning how many were removed
+    pub fn purge_expired(&self) -> usize {
+        let mut entries = self.entries.lock().unwrap();
+        let before = entries.len();
+        entries.retain(|_, (stored_at, _)| stored_at.elapsed() < self.ttl);
+        before - entries.len()
+    }
+
+    /// Remove every quote; counters are kept
+    pub fn clear(&self) {
+        self.entries.lock().unwrap().clear();
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.lock().unwrap().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn stats(&self) -> QuoteCacheStats {
+        QuoteCacheStats {
+            hits: self.hits.load(Ordering::Relaxed),
+            misses: self.misses.load(Ordering::Relaxed),
+            entries: self.len(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::omniarb::data_fetcher::fetch_routed_quotes_cached;
+    use crate::omniarb::socket_client::SocketClient;
+    use axum::{routing::get, Json, Router};
+    use std::sync::atomic::AtomicUsize;
+    use std::sync::Arc;
+
+    /// Socket mock that counts `/quote` requests
+    async fn counting_socket() -> (String, Arc<AtomicUsize>) {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let counter = calls.clone();
+        let app = Router::new().route(
+            "/quote",
+            get(move || {
+                let counter = counter.clone();
+                async move {
+                    counter.fetch_add(1, Ordering::SeqCst);
+                    Json(serde_json::json!({
+                        "success": true,
+                        "result": { "routes": [{
+                            "toAmount": "998000000",
+                            "totalGasFeesInUsd": 0.7,
+                            "inputValueInUsd": 1000.0,
+                            "outputValueInUsd": 998.0
+                        }] }
+                    }))
+                }
+            }),
+        );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+        (format!("http://{}", addr), calls)
+    }
+
+    fn socket_route() -> TokenEntry {
+        TokenEntry {
+            chain_origin: 137,
+            chain_dest: 42161,
+            native_token: "USDC".to_string(),
+            dex_origin: "QUICKSWAP".to_string(),
+            dex_dest: "CAMELOT".to_string(),
+            bridge_protocol: "SOCKET".to_string(),
+            liquidity_score: 90.0,
+            fee_tier: 0.05,
+            token_address_origin: Some("0x2791Bca1f2de4661ED88A30C99A7a9449Aa84174".to_string()),
+            token_address_dest: Some("0xaf88d065e77c8cC2239327C5EDb3A432268e5831".to_string()),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_amount_bucketing() {
+        let amount = U256::from(1_000_000_000u64);
+        let bucket = amount_bucket(amount);
+        assert_eq!(amount_bucket(amount * 9 / 10), bucket);
+        assert_eq!(amount_bucket(amount * 11 / 10), bucket);
+        assert_eq!(bucket, 30);
+
+        assert_eq!(amount_bucket(U256::from(1024u64)), 10);
+        assert_eq!(amount_bucket(U256::from(1535u64)), 10);
+        assert_eq!(amount_bucket(U256::from(1536u64)), 11);
+        assert_eq!(amount_bucket(U256::MAX), 256);
+        assert_eq!(amount_bucket(U256::zero()), 0);
+    }
+
+    #[tokio::test]
+    async fn test_repeat_fetch_within_ttl_is_cached() {
+        let (base_url, calls) = counting_socket().await;
+        let client = SocketClient::new("key").with_base_url(&base_url);
+        let cache = QuoteCache::default();
+        let entries = vec![socket_route(), socket_route()];
+        let amount = U256::from(1_000_000_000u64);
+
+        let first = fetch_routed_quotes_cached(&entries, Some(&client), amount, "0x01", &cache, false).await;
+        let after_first = calls.load(Ordering::SeqCst);
+        assert!(after_first >= 1);
+
+        let second = fetch_routed_quotes_cached(&entries, Some(&client), amount * 11 / 10, "0x01", &cache, false).await;
+        assert_eq!(calls.load(Ordering::SeqCst), after_first);
+        assert_eq!(second[0].gas_cost_usd, first[0].gas_cost_usd);
+        assert_eq!(cache.stats().hits, 2);
+
+        // Bypass always goes to the API
+        fetch_routed_quotes_cached(&entries, Some(&client), amount, "0x01", &cache, true).await;
+        assert_eq!(calls.load(Ordering::SeqCst), after_first + 2);
+    }
+
+    #[test]
+    fn test_purge_expired() {
+        let cache = QuoteCache::new(Duration::ZERO);
+        let quote = QuoteInfo {
+            spread_percentage: 0.1,
+            slippage_estimate: 0.05,
+            gas_cost_usd: 0.7,
+            available_liquidity: 1000.0,
+            ..Default::default()
+        };
+        cache.insert(&socket_route(), U256::from(1000u64), quote);
+        assert!(cache.get(&socket_route(), U256::from(1000u64)).is_none());
+        assert_eq!(cache.purge_expired(), 1);
+        assert!(cache.is_empty());
+        assert_eq!(cache.stats(), QuoteCacheStats { hits: 0, misses: 1, entries: 0 });
+    }
+}
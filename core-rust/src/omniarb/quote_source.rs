@@ -0,0 +1,627 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use ethers::types::U256;
+use futures::future::join_all;
+use log::warn;
+use thiserror::Error;
+
+use crate::config::{BridgeConfig, Config};
+use crate::enum_matrix::BridgeKind;
+use crate::omniarb::data_fetcher::{simulate_bridge_quote, socket_request, QuoteInfo, QuoteProvider};
+use crate::omniarb::matrix_parser::TokenEntry;
+use crate::omniarb::socket_client::SocketClient;
+
+/// Sender address used for quote-only Socket requests
+pub const QUOTE_USER_ADDRESS: &str = "0x000000000000000000000000000000000000dEaD";
+
+/// Quote lookup errors
+#[derive(Debug, Clone, PartialEq, Error)]
+pub enum QuoteError {
+    #[error("No quote source supports {0}")]
+    Unsupported(String),
+    #[error("{provider} quote failed: {message}")]
+    Failed { provider: String, message: String },
+}
+
+/// A backend that can quote matrix routes
+#[async_trait]
+pub trait QuoteSource: Send + Sync {
+    /// Name used in logs and errors
+    fn name(&self) -> &str;
+
+    /// Whether this source can quote the route
+    fn supports(&self, entry: &TokenEntry) -> bool;
+
+    /// Quote the route for a notional of `amount_usd`
+    async fn quote(&self, entry: &TokenEntry, amount_usd: f64) -> Result<QuoteInfo, QuoteError>;
+}
+
+/// Local simulation model; supports every route
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SimulatedSource;
+
+#[async_trait]
+impl QuoteSource for SimulatedSource {
+    fn name(&self) -> &str {
+        "SIMULATED"
+    }
+
+    fn supports(&self, _entry: &TokenEntry) -> bool {
+        true
+    }
+
+    async fn quote(&self, entry: &TokenEntry, _amount_usd: f64) -> Result<QuoteInfo, QuoteError> {
+        Ok(simulate_bridge_quote(entry))
+    }
+}
+
+/// Decimals of a USD stablecoin, for converting a USD notional to raw units
+fn stablecoin_decimals(chain_id: u64, symbol: &str) -> Option<u32> {
+    match symbol.to_uppercase().as_str() {
+        // Binance-peg stablecoins use 18 decimals
+        "USDC" | "USDT" if chain_id == 56 => Some(18),
+        "USDC" | "USDT" | "USDC.E" => Some(6),
+        "DAI" => Some(18),
+        _ => None,
+    }
+}
+
+/// Socket quote API
+///
+/// Only stablecoin routes are supported, since the USD notional has to be
+/// converted to a raw token amount without a price lookup.
+pub struct SocketSource {
+    client: SocketClient,
+    user_address: String,
+}
+
+impl SocketSource {
+    pub fn new(client: SocketClient, user_address: &str) -> Self {
+        Self {
+            client,
+            user_address: user_address.to_string(),
+        }
+    }
+}
+
+#[async_trait]
+impl QuoteSource for SocketSource {
+    fn name(&self) -> &str {
+        "SOCKET"
+    }
+
+    fn supports(&self, entry: &TokenEntry) -> bool {
+        stablecoin_decimals(entry.chain_origin, &entry.native_token).is_some()
+            && socket_request(entry, U256::zero(), &self.user_address).is_some()
+    }
+
+    async fn quote(&self, entry: &TokenEntry, amount_usd: f64) -> Result<QuoteInfo, QuoteError> {
+        let unsupported = || QuoteError::Unsupported(route_label(entry));
+        let decimals = stablecoin_decimals(entry.chain_origin, &entry.native_token).ok_or_else(unsupported)?;
+        let from_amount = U256::from((amount_usd.max(0.0) * 10f64.powi(decimals as i32)) as u128);
+        let request = socket_request(entry, from_amount, &self.user_address).ok_or_else(unsupported)?;
+
+        let quote = self.client.quote(&request).await.map_err(|message| QuoteError::Failed {
+            provider: self.name().to_string(),
+            message,
+        })?;
+        Ok(quote.to_quote_info())
+    }
+}
+
+fn route_label(entry: &TokenEntry) -> String {
+    format!(
+        "{} {}>{} via {}",
+        entry.native_token, entry.chain_origin, entry.chain_dest, entry.bridge_protocol
+    )
+}
+
+/// How the router picks among supporting sources
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum RouterMode {
+    /// First supporting source that succeeds, in registration order
+    #[default]
+    First,
+    /// Query every supporting source and keep the highest net profit
+    Best,
+}
+
+/// Ordered list of quote sources
+#[derive(Clone, Default)]
+pub struct QuoteRouter {
+    sources: Vec<Arc<dyn QuoteSource>>,
+    mode: RouterMode,
+    /// Typical fee of each configured bridge
+    bridge_fees: HashMap<BridgeKind, u32>,
+}
+
+impl QuoteRouter {
+    /// Router with no sources; add them with `with_source`
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Socket first when its endpoint is configured with a key, then the simulation
+    ///
+    /// LiFi and Across have no quote client yet, so their endpoints aren't used here.
+    /// Simulated quotes are charged the configured bridges' typical fees.
+    pub fn from_config(config: &Config) -> Self {
+        let router = Self::new().with_bridge_fees(&config.intent_based_bridges);
+        let router = match SocketClient::from_config(config) {
+            Some(client) => router.with_source(SocketSource::new(client, QUOTE_USER_ADDRESS)),
+            None => router,
+        };
+        router.with_source(SimulatedSource)
+    }
+
+    /// Append a source; earlier sources take precedence
+    pub fn with_source(mut self, source: impl QuoteSource + 'static) -> Self {
+        self.sources.push(Arc::new(source));
+        self
+    }
+
+    /// Charge simulated quotes the typical fee of their route's bridge, for
+    /// bridges keyed by any name `BridgeKind` parses
+    pub fn with_bridge_fees(mut self, bridges: &HashMap<String, BridgeConfig>) -> Self {
+        self.bridge_fees.extend(
+            bridges
+                .iter()
+                .filter_map(|(name, bridge)| Some((BridgeKind::from_name(name)?, bridge.typical_fee_bps()))),
+        );
+        self
+    }
+
+    /// Configured typical fee of the route's bridge; `None` when the bridge
+    /// has no configured fee, so its cost is unknown rather than free
+    pub fn bridge_fee_bps(&self, entry: &TokenEntry) -> Option<u32> {
+        BridgeKind::from_name(&entry.bridge_protocol).and_then(|bridge| self.bridge_fees.get(&bridge).copied())
+    }
+
+    /// `quote` with the route's bridge fee, unless its source already priced
+    /// the fee in (API quotes report output net of it)
+    ///
+    /// A simulated quote over a bridge with no configured fee is left without
+    /// one, with a warning that its profit leaves the bridge's cut out.
+    pub fn with_bridge_fee(&self, entry: &TokenEntry, mut quote: QuoteInfo) -> QuoteInfo {
+        if quote.source == QuoteProvider::Simulated && quote.bridge_fee_bps == 0 {
+            match self.bridge_fee_bps(entry) {
+                Some(fee) => quote.bridge_fee_bps = fee,
+                None => warn!(
+                    "Bridge fee unknown for {} ({} -> {}); its simulated quote leaves the fee out",
+                    entry.bridge_protocol, entry.chain_origin, entry.chain_dest
+                ),
+            }
+        }
+        quote
+    }
+
+    pub fn with_mode(mut self, mode: RouterMode) -> Self {
+        self.mode = mode;
+        self
+    }
+
+    pub fn mode(&self) -> RouterMode {
+        self.mode
+    }
+
+    /// Registered source names, in order
+    pub fn source_names(&self) -> Vec<&str> {
+        self.sources.iter().map(|source| source.name()).collect()
+    }
+
+    /// Quote a route from the supporting sources
+    ///
+    /// In `First` mode a failing source falls through to the next one.
+    pub async fn quote(&self, entry: &TokenEntry, amount_usd: f64) -> Result<QuoteInfo, QuoteError> {
+        let supporting = self.sources.iter().filter(|source| source.supports(entry));
+        let mut last_error = QuoteError::Unsupported(route_label(entry));
+
+        match self.mode {
+            RouterMode::First => {
+                for source in supporting {
+                    match source.quote(entry, amount_usd).await {
+                        Ok(quote) => return Ok(self.with_bridge_fee(entry, quote)),
+                        Err(e) => {
+                            warn!("{} failed for {}: {}", source.name(), route_label(entry), e);
+                            last_error = e;
+                        }
+                    }
+                }
+                Err(last_error)
+            }
+            RouterMode::Best => {
+                let results = join_all(supporting.map(|source| source.quote(entry, amount_usd))).await;
+                let mut best: Option<QuoteInfo> = None;
+                for result in results {
+                    match result {
+                        Ok(quote) if quote.is_finite() => {
+                            let quote = self.with_bridge_fee(entry, quote);
+                            let profit = quote.estimated_net_profit_usd(amount_usd);
+                            if best.as_ref().is_none_or(|b| profit > b.estimated_net_profit_usd(amount_usd)) {
+                                best = Some(quote);
+                            }
+                        }
+                        Ok(_) => {}
+                        Err(e) => last_error = e,
+                    }
+                }
+                best.ok_or(last_error)
+            }
+        }
+    }
+}
+
+/// Winning bridge for a route, with the other bridges' quotes for reporting
+#[derive(Debug, Clone)]
+pub struct BestQuote {
+    /// Matrix name of the winning bridge
+    pub winner: String,
+    pub quote: QuoteInfo,
+    /// Other bridges that quoted, in ranking order
+    pub alternatives: Vec<(String, QuoteInfo)>,
+}
+
+impl BestQuote {
+    /// The route re-pointed at the winning bridge, for scoring
+    pub fn winning_entry(&self, entry: &TokenEntry) -> TokenEntry {
+        TokenEntry {
+            bridge_protocol: self.winner.clone(),
+            ..entry.clone()
+        }
+    }
+
+    /// Whether the winner was only simulated: no bridge had a live quote
+    pub fn is_simulated(&self) -> bool {
+        self.quote.source == QuoteProvider::Simulated
+    }
+
+    /// Quote for a bridge, whether it won or not
+    pub fn quote_for(&self, bridge: &str) -> Option<&QuoteInfo> {
+        if self.winner == bridge {
+            return Some(&self.quote);
+        }
+        self.alternatives
+            .iter()
+            .find(|(name, _)| name == bridge)
+            .map(|(_, quote)| quote)
+    }
+}
+
+/// Quote a route over every bridge `config` allows for its chain pair and keep the best
+///
+/// The entry's own bridge is always a candidate, so the result can be
+/// compared against it. Live quotes rank ahead of simulated ones, so a
+/// simulated quote only wins when no bridge has a live source; within each
+/// group bridges are ranked by net output after slippage, bridge fee and
+/// gas. Bridges whose sources fail or return non-finite quotes are dropped.
+pub async fn fetch_best_bridge_quote(
+    entry: &TokenEntry,
+    amount_usd: f64,
+    router: &QuoteRouter,
+    config: &Config,
+) -> Result<BestQuote, QuoteError> {
+    let mut bridges = config.bridges_for_route(entry.chain_origin, entry.chain_dest);
+    if !bridges.contains(&entry.bridge_protocol) {
+        bridges.push(entry.bridge_protocol.clone());
+    }
+
+    let candidates: Vec<TokenEntry> = bridges
+        .into_iter()
+        .map(|bridge| TokenEntry {
+            bridge_protocol: bridge,
+            ..entry.clone()
+        })
+        .collect();
+    let results = join_all(candidates.iter().map(|candidate| router.quote(candidate, amount_usd))).await;
+
+    let mut quotes = Vec::new();
+    let mut last_error = QuoteError::Unsupported(route_label(entry));
+    for (candidate, result) in candidates.into_iter().zip(results) {
+        match result {
+            Ok(quote) if quote.is_finite() => quotes.push((candidate.bridge_protocol, quote)),
+            Ok(_) => {}
+            Err(e) => last_error = e,
+        }
+    }
+    quotes.sort_by(|(_, a), (_, b)| {
+        let simulated = |quote: &QuoteInfo| quote.source == QuoteProvider::Simulated;
+        simulated(a).cmp(&simulated(b)).then_with(|| {
+            b.estimated_net_profit_usd(amount_usd)
+                .total_cmp(&a.estimated_net_profit_usd(amount_usd))
+        })
+    });
+
+    let mut quotes = quotes.into_iter();
+    let (winner, quote) = quotes.next().ok_or(last_error)?;
+    Ok(BestQuote {
+        winner,
+        quote,
+        alternatives: quotes.collect(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::omniarb::data_fetcher::{fetch_live_quotes, fetch_live_quotes_async, fetch_simulated_quotes};
+
+    /// In-memory source quoting a fixed spread for one bridge
+    struct FixedSource {
+        name: &'static str,
+        bridge: &'static str,
+        spread: Option<f64>,
+    }
+
+    #[async_trait]
+    impl QuoteSource for FixedSource {
+        fn name(&self) -> &str {
+            self.name
+        }
+
+        fn supports(&self, entry: &TokenEntry) -> bool {
+            entry.bridge_protocol == self.bridge
+        }
+
+        async fn quote(&self, _entry: &TokenEntry, _amount_usd: f64) -> Result<QuoteInfo, QuoteError> {
+            let spread = self.spread.ok_or_else(|| QuoteError::Failed {
+                provider: self.name.to_string(),
+                message: "unavailable".to_string(),
+            })?;
+            Ok(QuoteInfo {
+                spread_percentage: spread,
+                gas_cost_usd: 1.0,
+                ..Default::default()
+            })
+        }
+    }
+
+    fn route(bridge: &str) -> TokenEntry {
+        TokenEntry {
+            chain_origin: 137,
+            chain_dest: 42161,
+            native_token: "USDC".to_string(),
+            dex_origin: "QUICKSWAP".to_string(),
+            dex_dest: "CAMELOT".to_string(),
+            bridge_protocol: bridge.to_string(),
+            liquidity_score: 90.0,
+            fee_tier: 0.05,
+            ..Default::default()
+        }
+    }
+
+    fn fixed(name: &'static str, spread: Option<f64>) -> FixedSource {
+        FixedSource { name, bridge: "INTERNAL", spread }
+    }
+
+    #[tokio::test]
+    async fn test_first_supporting_source_wins() {
+        let router = QuoteRouter::new()
+            .with_source(fixed("PRICING", Some(0.7)))
+            .with_source(fixed("BACKUP", Some(3.0)))
+            .with_source(SimulatedSource);
+        assert_eq!(router.source_names(), vec!["PRICING", "BACKUP", "SIMULATED"]);
+
+        let quote = router.quote(&route("INTERNAL"), 10_000.0).await.unwrap();
+        assert_eq!(quote.spread_percentage, 0.7);
+
+        // Unsupported by the custom sources: falls to the simulation
+        let stargate = route("STARGATE");
+        let quote = router.quote(&stargate, 10_000.0).await.unwrap();
+        assert_eq!(quote.spread_percentage, simulate_bridge_quote(&stargate).spread_percentage);
+
+        // A failing source falls through to the next supporting one
+        let router = QuoteRouter::new()
+            .with_source(fixed("PRICING", None))
+            .with_source(fixed("BACKUP", Some(3.0)));
+        assert_eq!(router.quote(&route("INTERNAL"), 10_000.0).await.unwrap().spread_percentage, 3.0);
+        assert!(matches!(
+            router.quote(&route("STARGATE"), 10_000.0).await,
+            Err(QuoteError::Unsupported(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_best_mode_picks_highest_net_profit() {
+        let router = QuoteRouter::new()
+            .with_source(fixed("LOW", Some(0.4)))
+            .with_source(fixed("DOWN", None))
+            .with_source(fixed("HIGH", Some(0.9)))
+            .with_mode(RouterMode::Best);
+        let quote = router.quote(&route("INTERNAL"), 10_000.0).await.unwrap();
+        assert_eq!(quote.spread_percentage, 0.9);
+
+        let down_only = QuoteRouter::new().with_source(fixed("DOWN", None)).with_mode(RouterMode::Best);
+        assert!(matches!(
+            down_only.quote(&route("INTERNAL"), 10_000.0).await,
+            Err(QuoteError::Failed { .. })
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_fetch_live_quotes_async_over_router() {
+        let entries = vec![route("INTERNAL"), route("STARGATE")];
+        let router = QuoteRouter::new().with_source(fixed("PRICING", Some(0.7)));
+        let quotes = fetch_live_quotes_async(&entries, &router, 10_000.0).await;
+        assert_eq!(quotes[0].spread_percentage, 0.7);
+        // No source for STARGATE: simulated fallback
+        assert_eq!(quotes[1].spread_percentage, simulate_bridge_quote(&entries[1]).spread_percentage);
+    }
+
+    #[test]
+    fn test_from_config_registers_configured_clients_only() {
+        use crate::config::ApiEndpoint;
+
+        let mut config = Config::default();
+        config.quote_apis.socket = None;
+        assert_eq!(QuoteRouter::from_config(&config).source_names(), vec!["SIMULATED"]);
+
+        config.quote_apis.socket = Some(ApiEndpoint::new("https://proxy.internal/socket", "key"));
+        assert_eq!(QuoteRouter::from_config(&config).source_names(), vec!["SOCKET", "SIMULATED"]);
+    }
+
+    /// Source for one bridge charging `fee_pct` slippage and `gas_usd` on a 1% spread
+    struct FeeSource {
+        bridge: &'static str,
+        fee_pct: f64,
+        gas_usd: f64,
+    }
+
+    #[async_trait]
+    impl QuoteSource for FeeSource {
+        fn name(&self) -> &str {
+            self.bridge
+        }
+
+        fn supports(&self, entry: &TokenEntry) -> bool {
+            entry.bridge_protocol == self.bridge
+        }
+
+        async fn quote(&self, _entry: &TokenEntry, _amount_usd: f64) -> Result<QuoteInfo, QuoteError> {
+            Ok(QuoteInfo {
+                spread_percentage: 1.0,
+                slippage_estimate: self.fee_pct,
+                gas_cost_usd: self.gas_usd,
+                ..Default::default()
+            })
+        }
+    }
+
+    #[tokio::test]
+    async fn test_best_bridge_quote_keeps_alternatives() {
+        let mut config = Config::default();
+        config.lifi_supported_chains.clear();
+        config.intent_based_bridges.retain(|name, _| ["across", "stargate", "hop"].contains(&name.as_str()));
+        assert_eq!(config.bridges_for_route(137, 42161), vec!["ACROSS", "HOP", "STARGATE"]);
+
+        // On $10k: ACROSS nets 100 - 20 - 5, STARGATE 100 - 10 - 1, HOP 100 - 50 - 0.5
+        let router = QuoteRouter::new()
+            .with_source(FeeSource { bridge: "ACROSS", fee_pct: 0.2, gas_usd: 5.0 })
+            .with_source(FeeSource { bridge: "STARGATE", fee_pct: 0.1, gas_usd: 1.0 })
+            .with_source(FeeSource { bridge: "HOP", fee_pct: 0.5, gas_usd: 0.5 });
+
+        let entry = route("HOP");
+        let best = fetch_best_bridge_quote(&entry, 10_000.0, &router, &config).await.unwrap();
+        assert_eq!(best.winner, "STARGATE");
+        assert_eq!(best.quote.slippage_estimate, 0.1);
+        let names: Vec<&str> = best.alternatives.iter().map(|(name, _)| name.as_str()).collect();
+        assert_eq!(names, vec!["ACROSS", "HOP"]);
+
+        // The original bridge stays available for comparison
+        assert_eq!(best.quote_for(&entry.bridge_protocol).unwrap().slippage_estimate, 0.5);
+        assert_eq!(best.winning_entry(&entry).bridge_protocol, "STARGATE");
+
+        let nothing = QuoteRouter::new();
+        assert!(matches!(
+            fetch_best_bridge_quote(&entry, 10_000.0, &nothing, &config).await,
+            Err(QuoteError::Unsupported(_))
+        ));
+    }
+
+    /// Socket-backed source quoting a thin 0.2% spread for one bridge
+    struct LiveSource(&'static str);
+
+    #[async_trait]
+    impl QuoteSource for LiveSource {
+        fn name(&self) -> &str {
+            "SOCKET"
+        }
+
+        fn supports(&self, entry: &TokenEntry) -> bool {
+            entry.bridge_protocol == self.0
+        }
+
+        async fn quote(&self, _entry: &TokenEntry, _amount_usd: f64) -> Result<QuoteInfo, QuoteError> {
+            Ok(QuoteInfo {
+                spread_percentage: 0.2,
+                source: QuoteProvider::Socket,
+                ..Default::default()
+            })
+        }
+    }
+
+    #[tokio::test]
+    async fn test_best_bridge_quote_prefers_live_quotes() {
+        let mut config = Config::default();
+        config.lifi_supported_chains.clear();
+        config.intent_based_bridges.retain(|name, _| ["across", "stargate", "hop"].contains(&name.as_str()));
+
+        // The simulation nets more on every bridge than the live STARGATE quote
+        let router = QuoteRouter::new().with_source(LiveSource("STARGATE")).with_source(SimulatedSource);
+        let entry = route("HOP");
+        assert!(simulate_bridge_quote(&entry).estimated_net_profit_usd(10_000.0) > 20.0);
+
+        let best = fetch_best_bridge_quote(&entry, 10_000.0, &router, &config).await.unwrap();
+        assert_eq!(best.winner, "STARGATE");
+        assert!(!best.is_simulated());
+        assert_eq!(best.alternatives.len(), 2);
+
+        // Without a live source the simulated winner is flagged
+        let simulated = QuoteRouter::new().with_source(SimulatedSource);
+        assert!(fetch_best_bridge_quote(&entry, 10_000.0, &simulated, &config).await.unwrap().is_simulated());
+    }
+
+    #[tokio::test]
+    async fn test_simulated_quotes_carry_bridge_fee() {
+        let bridges = HashMap::from([(
+            "stargate".to_string(),
+            BridgeConfig {
+                name: "Stargate Finance".to_string(),
+                typical_time_seconds: 60,
+                max_time_seconds: 300,
+                fee_range_bps: vec![20, 40],
+                description: String::new(),
+            },
+        )]);
+        let router = QuoteRouter::new()
+            .with_source(fixed("PRICING", Some(0.7)))
+            .with_source(SimulatedSource)
+            .with_bridge_fees(&bridges);
+
+        let stargate = route("STARGATE");
+        let quote = router.quote(&stargate, 10_000.0).await.unwrap();
+        assert_eq!(quote.bridge_fee_bps, 30);
+        // 30bps of $10k comes off the profit
+        let unpriced = QuoteInfo { bridge_fee_bps: 0, ..quote.clone() };
+        let cut = unpriced.estimated_net_profit_usd(10_000.0) - quote.estimated_net_profit_usd(10_000.0);
+        assert!((cut - 30.0).abs() < 1e-9, "{}", cut);
+
+        let routes = [stargate, route("HOP")];
+        let fees = |quotes: Vec<QuoteInfo>| quotes.iter().map(|quote| quote.bridge_fee_bps).collect::<Vec<_>>();
+        assert_eq!(fees(fetch_live_quotes_async(&routes, &router, 10_000.0).await), [30, 0]);
+        // Plain simulated fetches carry the fee too, from the router or the
+        // default bridge table
+        assert_eq!(fees(fetch_simulated_quotes(&routes, &router)), [30, 0]);
+        let defaults = Config::default_bridges();
+        assert_eq!(
+            fees(fetch_live_quotes(&routes)),
+            [defaults["stargate"].typical_fee_bps(), defaults["hop"].typical_fee_bps()]
+        );
+
+        // API quotes already price the fee in
+        let socket = QuoteInfo { source: QuoteProvider::Socket, ..Default::default() };
+        assert_eq!(router.with_bridge_fee(&route("STARGATE"), socket).bridge_fee_bps, 0);
+
+        // Bridges without a configured fee report it as unknown, not free
+        assert_eq!(router.bridge_fee_bps(&route("STARGATE")), Some(30));
+        assert_eq!(router.bridge_fee_bps(&route("HOP")), None);
+        assert_eq!(router.bridge_fee_bps(&route("WORMHOLE")), None);
+        assert_eq!(QuoteRouter::from_config(&Config::default()).bridge_fee_bps(&route("CCIP")), None);
+    }
+
+    #[test]
+    fn test_socket_source_supports_stablecoin_socket_routes() {
+        let source = SocketSource::new(SocketClient::new("key"), QUOTE_USER_ADDRESS);
+        let mut entry = route("SOCKET");
+        assert!(!source.supports(&entry));
+
+        entry.token_address_origin = Some("0x2791Bca1f2de4661ED88A30C99A7a9449Aa84174".to_string());
+        entry.token_address_dest = Some("0xaf88d065e77c8cC2239327C5EDb3A432268e5831".to_string());
+        assert!(source.supports(&entry));
+
+        entry.native_token = "WETH".to_string();
+        assert!(!source.supports(&entry));
+        assert_eq!(stablecoin_decimals(56, "usdt"), Some(18));
+    }
+}
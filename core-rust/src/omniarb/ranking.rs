@@ -0,0 +1,199 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::str::FromStr;
+
+use crate::omniarb::checkpoint::ScoredRoute;
+
+/// How the top routes of a scored batch are chosen
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SelectionPolicy {
+    /// Routes at or above this batch percentile (0..1)
+    Percentile(f64),
+    /// Routes scoring at least this much
+    AbsoluteScore(f64),
+    /// The N highest-scoring routes
+    TopN(usize),
+}
+
+impl Default for SelectionPolicy {
+    fn default() -> Self {
+        SelectionPolicy::AbsoluteScore(85.0)
+    }
+}
+
+impl FromStr for SelectionPolicy {
+    type Err = String;
+
+    /// Parse `percentile:0.95`, `score:85` or `top:20`
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (kind, value) = s
+            .split_once(':')
+            .ok_or_else(|| format!("Expected percentile:P, score:S or top:N, got '{}'", s))?;
+        let invalid = |e: &dyn fmt::Display| format!("Invalid selection value '{}': {}", value, e);
+        match kind.to_ascii_lowercase().as_str() {
+            "percentile" => match value.parse::<f64>().map_err(|e| invalid(&e))? {
+                p if (0.0..=1.0).contains(&p) => Ok(SelectionPolicy::Percentile(p)),
+                p => Err(format!("Percentile must be between 0 and 1, got {}", p)),
+            },
+            "score" => Ok(SelectionPolicy::AbsoluteScore(value.parse().map_err(|e| invalid(&e))?)),
+            "top" => Ok(SelectionPolicy::TopN(value.parse().map_err(|e| invalid(&e))?)),
+            _ => Err(format!("Unknown selection policy '{}' (expected percentile, score or top)", kind)),
+        }
+    }
+}
+
+impl fmt::Display for SelectionPolicy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SelectionPolicy::Percentile(p) => write!(f, "percentile >= {}", p),
+            SelectionPolicy::AbsoluteScore(score) => write!(f, ">= {}", score),
+            SelectionPolicy::TopN(n) => write!(f, "top {}", n),
+        }
+    }
+}
+
+/// Set each route's percentile and z-score within the batch
+///
+/// Percentile is the share of the other routes scoring strictly lower, so
+/// the best route is 1.0 and ties share a value. A batch with no spread
+/// has every z-score at 0.
+pub fn rank_routes(scored: &mut [ScoredRoute]) {
+    let n = scored.len();
+    if n == 0 {
+        return;
+    }
+    let mut sorted: Vec<f64> = scored.iter().map(|route| route.score).collect();
+    sorted.sort_by(f64::total_cmp);
+    let mean = sorted.iter().sum::<f64>() / n as f64;
+    let std_dev = (sorted.iter().map(|score| (score - mean).powi(2)).sum::<f64>() / n as f64).sqrt();
+
+    for route in scored.iter_mut() {
+        let below = sorted.partition_point(|score| score.total_cmp(&route.score).is_lt());
+        route.percentile = if n > 1 { below as f64 / (n - 1) as f64 } else { 1.0 };
+        route.z_score = if std_dev > 0.0 { (route.score - mean) / std_dev } else { 0.0 };
+    }
+}
+
+/// Routes sharing a `(native_token, bridge_protocol)` key allowed by `--diversify`
+pub const DEFAULT_MAX_PER_KEY: usize = 2;
+
+/// Routes chosen by `policy`, highest score first
+///
+/// `Percentile` reads the values stored by [`rank_routes`].
+pub fn select_top(scored: Vec<ScoredRoute>, policy: SelectionPolicy) -> Vec<ScoredRoute> {
+    select(scored, policy, None)
+}
+
+/// [`select_top`] keeping at most `max_per_key` routes per token and bridge
+///
+/// Filled greedily by score, so a capped route's slot goes to the next
+/// best route with a different token or bridge.
+pub fn select_top_diversified(scored: Vec<ScoredRoute>, policy: SelectionPolicy, max_per_key: usize) -> Vec<ScoredRoute> {
+    select(scored, policy, Some(max_per_key))
+}
+
+fn select(scored: Vec<ScoredRoute>, policy: SelectionPolicy, max_per_key: Option<usize>) -> Vec<ScoredRoute> {
+    let mut selected: Vec<_> = match policy {
+        SelectionPolicy::Percentile(p) => scored.into_iter().filter(|route| route.percentile >= p).collect(),
+        SelectionPolicy::AbsoluteScore(min) => scored.into_iter().filter(|route| route.score >= min).collect(),
+        SelectionPolicy::TopN(_) => scored,
+    };
+    // Use total_cmp for safe NaN handling
+    selected.sort_by(|a, b| b.score.total_cmp(&a.score));
+    if let Some(max_per_key) = max_per_key {
+        let mut taken: HashMap<(String, String), usize> = HashMap::new();
+        selected.retain(|route| {
+            let key = (route.entry.native_token.clone(), route.entry.bridge_protocol.clone());
+            let count = taken.entry(key).or_default();
+            *count += 1;
+            *count <= max_per_key
+        });
+    }
+    if let SelectionPolicy::TopN(n) = policy {
+        selected.truncate(n);
+    }
+    selected
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::omniarb::{QuoteInfo, TokenEntry};
+
+    fn batch(scores: impl IntoIterator<Item = f64>) -> Vec<ScoredRoute> {
+        scores
+            .into_iter()
+            .map(|score| ScoredRoute {
+                entry: TokenEntry::default(),
+                quote: QuoteInfo::default(),
+                score,
+                model_pred_tar: 0.0,
+                model_pred_flank: 0.0,
+                percentile: 0.0,
+                z_score: 0.0,
+                disagreement: 0.0,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_percentile_boundaries() {
+        let mut scored = batch((1..=100).map(f64::from));
+        rank_routes(&mut scored);
+        assert_eq!(scored[0].percentile, 0.0);
+        assert_eq!(scored[99].percentile, 1.0);
+        assert!((scored[95].percentile - 95.0 / 99.0).abs() < 1e-12);
+        assert!(scored[49].z_score < 0.0 && scored[50].z_score > 0.0);
+        assert!((scored[49].z_score + scored[50].z_score).abs() < 1e-12);
+
+        // Ties share a percentile; a flat batch has no z-score spread
+        let mut flat = batch([70.0, 70.0, 70.0]);
+        rank_routes(&mut flat);
+        assert!(flat.iter().all(|route| route.percentile == 0.0 && route.z_score == 0.0));
+    }
+
+    #[test]
+    fn test_selection_policies() {
+        let mut scored = batch((1..=100).map(f64::from));
+        rank_routes(&mut scored);
+        let scores = |policy: &str| -> Vec<f64> {
+            select_top(scored.clone(), policy.parse().unwrap()).iter().map(|route| route.score).collect()
+        };
+
+        assert_eq!(scores("percentile:0.95"), vec![100.0, 99.0, 98.0, 97.0, 96.0]);
+        assert_eq!(scores("score:85"), (85..=100).rev().map(f64::from).collect::<Vec<_>>());
+        assert_eq!(scores("top:20"), (81..=100).rev().map(f64::from).collect::<Vec<_>>());
+        assert_eq!(SelectionPolicy::default().to_string(), ">= 85");
+
+        assert!("percentile:95".parse::<SelectionPolicy>().is_err());
+        assert!("best:3".parse::<SelectionPolicy>().is_err());
+        assert!("top".parse::<SelectionPolicy>().is_err());
+    }
+
+    #[test]
+    fn test_diversify_caps_repeats_per_key() {
+        // Six USDC/STARGATE routes on different chain pairs outscore the rest
+        let mut scored = batch((0..10).map(|i| 100.0 - f64::from(i)));
+        for (i, route) in scored.iter_mut().enumerate() {
+            let (token, bridge) = match i {
+                0..=5 => ("USDC", "STARGATE"),
+                6 | 7 => ("WETH", "ACROSS"),
+                _ => ("USDC", "HOP"),
+            };
+            route.entry.native_token = token.to_string();
+            route.entry.bridge_protocol = bridge.to_string();
+            route.entry.chain_dest = i as u64;
+        }
+
+        let plain = select_top(scored.clone(), SelectionPolicy::TopN(4));
+        assert!(plain.iter().all(|route| route.entry.bridge_protocol == "STARGATE"));
+
+        let diverse = select_top_diversified(scored.clone(), SelectionPolicy::TopN(4), DEFAULT_MAX_PER_KEY);
+        let keys: Vec<_> = diverse.iter().map(|r| (r.entry.native_token.as_str(), r.entry.bridge_protocol.as_str())).collect();
+        assert_eq!(keys, [("USDC", "STARGATE"), ("USDC", "STARGATE"), ("WETH", "ACROSS"), ("WETH", "ACROSS")]);
+        assert_eq!(diverse.iter().map(|r| r.score).collect::<Vec<_>>(), [100.0, 99.0, 94.0, 93.0]);
+
+        let capped = select_top_diversified(scored, SelectionPolicy::AbsoluteScore(0.0), 1);
+        assert_eq!(capped.iter().map(|r| r.score).collect::<Vec<_>>(), [100.0, 94.0, 92.0]);
+    }
+}
@@ -0,0 +1,225 @@
+use std::collections::{HashMap, VecDeque};
+use std::fmt;
+use std::io;
+use std::path::Path;
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::omniarb::matrix_diff::RouteId;
+
+/// Default time for a score change to be half absorbed by the smoothed score
+pub const DEFAULT_SCORE_HALF_LIFE: Duration = Duration::from_secs(15 * 60);
+
+/// Default number of smoothed values `trend` looks back over
+pub const DEFAULT_TREND_WINDOW: usize = 5;
+
+/// Smoothed-score change below which a route counts as flat
+const TREND_EPSILON: f64 = 1e-6;
+
+/// Direction of a route's smoothed score over the trend window
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Trend {
+    Rising,
+    Falling,
+    Flat,
+}
+
+impl fmt::Display for Trend {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Trend::Rising => "rising",
+            Trend::Falling => "falling",
+            Trend::Flat => "flat",
+        })
+    }
+}
+
+/// One route's smoothed score and its recent values
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RouteScore {
+    pub route: RouteId,
+    pub smoothed: f64,
+    pub updated_at: DateTime<Utc>,
+    /// Latest smoothed values, oldest first
+    pub recent: VecDeque<f64>,
+}
+
+/// Exponentially-weighted route scores, persisted between runs as JSON
+///
+/// Each update moves the smoothed score towards the new one by
+/// `1 - 0.5^(elapsed / half_life)`, so noisy quotes need to persist
+/// before the smoothed score follows them.
+#[derive(Debug, Clone)]
+pub struct ScoreHistory {
+    half_life: Duration,
+    trend_window: usize,
+    routes: HashMap<RouteId, RouteScore>,
+}
+
+impl Default for ScoreHistory {
+    fn default() -> Self {
+        Self::new(DEFAULT_SCORE_HALF_LIFE)
+    }
+}
+
+impl ScoreHistory {
+    pub fn new(half_life: Duration) -> Self {
+        Self {
+            half_life,
+            trend_window: DEFAULT_TREND_WINDOW,
+            routes: HashMap::new(),
+        }
+    }
+
+    /// Look back over the last `window` updates in `trend` (at least 2)
+    pub fn with_trend_window(mut self, window: usize) -> Self {
+        self.trend_window = window.max(2);
+        self
+    }
+
+    /// Load the routes saved at `path`; a missing file starts empty
+    pub fn load(path: impl AsRef<Path>, half_life: Duration) -> io::Result<Self> {
+        let mut history = Self::new(half_life);
+        match std::fs::read_to_string(path) {
+            Ok(json) => {
+                let routes: Vec<RouteScore> = serde_json::from_str(&json)?;
+                history.routes = routes.into_iter().map(|route| (route.route.clone(), route)).collect();
+                Ok(history)
+            }
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(history),
+            Err(e) => Err(e),
+        }
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        let mut routes: Vec<&RouteScore> = self.routes.values().collect();
+        routes.sort_by_key(|route| route.route.to_string());
+        std::fs::write(path, serde_json::to_string_pretty(&routes)?)
+    }
+
+    /// Blend `score` observed at `timestamp` into the route's smoothed score
+    ///
+    /// The first score for a route is taken as-is; updates older than the
+    /// last one are blended with no decay.
+    pub fn update(&mut self, route: &RouteId, score: f64, timestamp: DateTime<Utc>) -> f64 {
+        if !score.is_finite() {
+            return self.smoothed(route).unwrap_or(0.0);
+        }
+        let half_life = self.half_life.as_secs_f64();
+        let window = self.trend_window;
+        let entry = self.routes.entry(route.clone()).or_insert_with(|| RouteScore {
+            route: route.clone(),
+            smoothed: score,
+            updated_at: timestamp,
+            recent: VecDeque::new(),
+        });
+
+        let elapsed = (timestamp - entry.updated_at).to_std().unwrap_or_default().as_secs_f64();
+        let alpha = if half_life > 0.0 { 1.0 - 0.5f64.powf(elapsed / half_life) } else { 1.0 };
+        entry.smoothed += alpha * (score - entry.smoothed);
+        entry.updated_at = entry.updated_at.max(timestamp);
+
+        entry.recent.push_back(entry.smoothed);
+        while entry.recent.len() > window {
+            entry.recent.pop_front();
+        }
+        entry.smoothed
+    }
+
+    pub fn smoothed(&self, route: &RouteId) -> Option<f64> {
+        self.routes.get(route).map(|route| route.smoothed)
+    }
+
+    /// Whether the smoothed score rose or fell across the trend window
+    ///
+    /// `None` until the route has two updates.
+    pub fn trend(&self, route: &RouteId) -> Option<Trend> {
+        let recent = &self.routes.get(route)?.recent;
+        let change = recent.back()? - recent.front()?;
+        if recent.len() < 2 {
+            None
+        } else if change > TREND_EPSILON {
+            Some(Trend::Rising)
+        } else if change < -TREND_EPSILON {
+            Some(Trend::Falling)
+        } else {
+            Some(Trend::Flat)
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.routes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.routes.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::omniarb::TokenEntry;
+
+    fn route(token: &str) -> RouteId {
+        RouteId::from(&TokenEntry {
+            chain_origin: 1,
+            chain_dest: 137,
+            native_token: token.to_string(),
+            bridge_protocol: "STARGATE".to_string(),
+            ..Default::default()
+        })
+    }
+
+    #[test]
+    fn test_step_change_converges_at_half_life() {
+        let usdc = route("USDC");
+        let mut history = ScoreHistory::new(Duration::from_secs(600)).with_trend_window(3);
+        let start = Utc::now();
+        history.update(&usdc, 0.0, start);
+        assert_eq!(history.trend(&usdc), None);
+
+        // Step to 100, sampled every minute: halfway after one half-life
+        let minute = chrono::Duration::minutes(1);
+        for i in 1..=10 {
+            history.update(&usdc, 100.0, start + minute * i);
+        }
+        assert!((history.smoothed(&usdc).unwrap() - 50.0).abs() < 1e-9);
+        for i in 11..=20 {
+            history.update(&usdc, 100.0, start + minute * i);
+        }
+        assert!((history.smoothed(&usdc).unwrap() - 75.0).abs() < 1e-9);
+        assert_eq!(history.trend(&usdc), Some(Trend::Rising));
+
+        for i in 21..=23 {
+            history.update(&usdc, 0.0, start + minute * i);
+        }
+        assert_eq!(history.trend(&usdc), Some(Trend::Falling));
+        assert_eq!(history.smoothed(&route("WETH")), None);
+    }
+
+    #[test]
+    fn test_history_persists() {
+        let path = std::env::temp_dir().join(format!("titan_score_history_{}.json", std::process::id()));
+        std::fs::remove_file(&path).ok();
+
+        let half_life = Duration::from_secs(60);
+        assert!(ScoreHistory::load(&path, half_life).unwrap().is_empty());
+
+        let mut history = ScoreHistory::new(half_life);
+        let now = Utc::now();
+        history.update(&route("USDC"), 80.0, now);
+        history.update(&route("USDC"), 90.0, now + chrono::Duration::seconds(60));
+        history.update(&route("WETH"), 70.0, now);
+        history.save(&path).unwrap();
+
+        let loaded = ScoreHistory::load(&path, half_life).unwrap();
+        assert_eq!(loaded.len(), 2);
+        assert_eq!(loaded.smoothed(&route("USDC")), Some(85.0));
+        assert_eq!(loaded.trend(&route("USDC")), Some(Trend::Rising));
+        std::fs::remove_file(&path).ok();
+    }
+}
@@ -0,0 +1,278 @@
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+
+use crate::config::Config;
+use crate::omniarb::{
+    calculate_tar_score_sized, ensemble_score, fetch_live_quotes_bounded, invalid_row_count,
+    load_token_matrix_auto_with_options,
+    rank_routes, select_top, BatchModel, EnsembleWeights, HeuristicModel, MatrixError, ModelRegistry, ParseOptions,
+    QuoteRouter, ScoredRoute, SelectionPolicy, TokenMatrix,
+};
+
+/// Runs kept for `GET /api/v1/run/{id}`; older ones are forgotten
+pub const DEFAULT_RUN_HISTORY: usize = 50;
+
+/// Notional each route is quoted for (USD), as in the engine binary
+pub const DEFAULT_RUN_TRADE_SIZE_USD: f64 = 10_000.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RunStatus {
+    Running,
+    Completed,
+    Failed,
+}
+
+/// Headline numbers of a finished run
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct RunSummary {
+    pub routes_analyzed: usize,
+    /// Rows skipped because they didn't parse
+    pub invalid_rows: usize,
+    /// Score a route needed to count as an opportunity
+    pub threshold: f64,
+    pub above_threshold: usize,
+    /// Mean score of the routes above the threshold; 0 when there are none
+    pub average_top_score: f64,
+}
+
+/// What a run produced: its summary and the routes above the threshold
+#[derive(Debug, Clone, Serialize)]
+pub struct RunOutcome {
+    pub summary: RunSummary,
+    pub top_routes: Vec<ScoredRoute>,
+}
+
+/// One scoring run, as reported by the server
+#[derive(Debug, Clone, Serialize)]
+pub struct ScoringRun {
+    pub id: u64,
+    pub status: RunStatus,
+    pub matrix_path: String,
+    pub started_at: DateTime<Utc>,
+    pub finished_at: Option<DateTime<Utc>>,
+    pub summary: Option<RunSummary>,
+    pub top_routes: Vec<ScoredRoute>,
+    pub error: Option<String>,
+}
+
+/// In-memory record of recent scoring runs, newest last
+#[derive(Debug)]
+pub struct RunHistory {
+    next_id: AtomicU64,
+    capacity: usize,
+    runs: Mutex<VecDeque<ScoringRun>>,
+}
+
+impl Default for RunHistory {
+    fn default() -> Self {
+        Self::new(DEFAULT_RUN_HISTORY)
+    }
+}
+
+impl RunHistory {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            next_id: AtomicU64::new(1),
+            capacity: capacity.max(1),
+            runs: Mutex::new(VecDeque::new()),
+        }
+    }
+
+    /// Record a new running run over `matrix_path` and return its id
+    ///
+    /// Only one run may be running at a time; while one is, nothing is
+    /// recorded and its id is the error.
+    pub fn start(&self, matrix_path: &str) -> Result<u64, u64> {
+        let mut runs = self.runs.lock().unwrap();
+        if let Some(running) = runs.iter().find(|run| run.status == RunStatus::Running) {
+            return Err(running.id);
+        }
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        if runs.len() == self.capacity {
+            runs.pop_front();
+        }
+        runs.push_back(ScoringRun {
+            id,
+            status: RunStatus::Running,
+            matrix_path: matrix_path.to_string(),
+            started_at: Utc::now(),
+            finished_at: None,
+            summary: None,
+            top_routes: Vec::new(),
+            error: None,
+        });
+        Ok(id)
+    }
+
+    /// Store a run's outcome; runs already evicted are ignored
+    pub fn finish(&self, id: u64, outcome: Result<RunOutcome, String>) {
+        let mut runs = self.runs.lock().unwrap();
+        let Some(run) = runs.iter_mut().find(|run| run.id == id) else {
+            return;
+        };
+        run.finished_at = Some(Utc::now());
+        match outcome {
+            Ok(outcome) => {
+                run.status = RunStatus::Completed;
+                run.summary = Some(outcome.summary);
+                run.top_routes = outcome.top_routes;
+            }
+            Err(error) => {
+                run.status = RunStatus::Failed;
+                run.error = Some(error);
+            }
+        }
+    }
+
+    pub fn get(&self, id: u64) -> Option<ScoringRun> {
+        self.runs.lock().unwrap().iter().find(|run| run.id == id).cloned()
+    }
+}
+
+/// Score the matrix at `path` the way the engine binary does by default
+///
+/// The file is read on the blocking pool. Invalid rows are skipped, routes
+/// are limited by the configured bridge policy and quoted through `router`
+/// (at most the runtime profile's `max_concurrency` at a time), and routes
+/// at or above the config's enter threshold are kept, best first. Model
+/// predictions come from the `tar` and `flanker` models in `models`, or
+/// the heuristics where one isn't loaded.
+pub async fn score_matrix_file(
+    config: &Config,
+    router: &QuoteRouter,
+    models: &ModelRegistry,
+    path: &str,
+) -> Result<RunOutcome, MatrixError> {
+    let owned_path = path.to_string();
+    let load = tokio::task::spawn_blocking(move || {
+        load_token_matrix_auto_with_options(&owned_path, ParseOptions { strict: false, ..ParseOptions::default() })
+    })
+    .await
+    .map_err(|e| MatrixError::Io(format!("Matrix load aborted: {}", e)))??;
+    let invalid_rows = invalid_row_count(&load.diagnostics);
+    let entries = TokenMatrix::new(load.entries).filter_bridges(&config.bridge_policy).into_entries();
+
+    let quotes =
+        fetch_live_quotes_bounded(&entries, router, DEFAULT_RUN_TRADE_SIZE_USD, config.runtime.max_concurrency).await;
+    let (tar_loaded, flanker_loaded) = (models.get("tar"), models.get("flanker"));
+    let (tar_heuristic, flanker_heuristic) = (HeuristicModel::tar(), HeuristicModel::flanker());
+    let tar_model: &dyn BatchModel = tar_loaded.as_deref().map_or(&tar_heuristic, |model| model);
+    let flanker_model: &dyn BatchModel = flanker_loaded.as_deref().map_or(&flanker_heuristic, |model| model);
+    let tar_preds = tar_model.predict_batch(&entries, &quotes);
+    let flank_preds = flanker_model.predict_batch(&entries, &quotes);
+    let mut scored: Vec<ScoredRoute> = entries
+        .into_iter()
+        .zip(quotes)
+        .zip(tar_preds.into_iter().zip(flank_preds))
+        .map(|((entry, quote), (tar, flank))| {
+            let score = calculate_tar_score_sized(&entry, &quote, &config.tar_weights, Some(DEFAULT_RUN_TRADE_SIZE_USD));
+            let ensemble = ensemble_score(score, Some(tar.score), Some(flank.score), &EnsembleWeights::default());
+            ScoredRoute {
+                entry,
+                quote,
+                score,
+                model_pred_tar: tar.score,
+                model_pred_flank: flank.score,
+                percentile: 0.0,
+                z_score: 0.0,
+                disagreement: ensemble.disagreement,
+            }
+        })
+        .collect();
+    let routes_analyzed = scored.len();
+
+    rank_routes(&mut scored);
+    let threshold = config.opportunity.enter_threshold;
+    let top_routes = select_top(scored, SelectionPolicy::AbsoluteScore(threshold));
+    let average_top_score = if top_routes.is_empty() {
+        0.0
+    } else {
+        top_routes.iter().map(|route| route.score).sum::<f64>() / top_routes.len() as f64
+    };
+    Ok(RunOutcome {
+        summary: RunSummary {
+            routes_analyzed,
+            invalid_rows,
+            threshold,
+            above_threshold: top_routes.len(),
+            average_top_score,
+        },
+        top_routes,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SCHEMA_V2: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/../data/fixtures/omniarb_matrix_schema_v2.md");
+
+    #[test]
+    fn test_history_evicts_oldest_run() {
+        let history = RunHistory::new(2);
+        let first = history.start("a.md").unwrap();
+        history.finish(first, Err("first".to_string()));
+        let second = history.start("b.md").unwrap();
+        history.finish(second, Err("second".to_string()));
+        let third = history.start("c.md").unwrap();
+        assert!(history.get(first).is_none());
+        assert_eq!(history.get(second).unwrap().status, RunStatus::Failed);
+        assert_eq!(history.get(third).unwrap().status, RunStatus::Running);
+
+        history.finish(third, Err("boom".to_string()));
+        let run = history.get(third).unwrap();
+        assert_eq!(run.status, RunStatus::Failed);
+        assert_eq!(run.error.as_deref(), Some("boom"));
+        assert!(run.finished_at.is_some());
+        // Evicted runs are ignored
+        history.finish(first, Err("late".to_string()));
+    }
+
+    #[test]
+    fn test_history_runs_one_at_a_time() {
+        let history = RunHistory::default();
+        let running = history.start("a.md").unwrap();
+        assert_eq!(history.start("b.md"), Err(running));
+        history.finish(running, Err("boom".to_string()));
+        assert!(history.start("b.md").is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_score_fixture_matrix() {
+        let config = Config { offline: true, ..Config::default() };
+        let router = QuoteRouter::new().with_source(crate::omniarb::SimulatedSource);
+        let models = ModelRegistry::new();
+        let outcome = score_matrix_file(&config, &router, &models, SCHEMA_V2).await.unwrap();
+        assert_eq!(outcome.summary.routes_analyzed, 3);
+        assert_eq!(outcome.summary.above_threshold, outcome.top_routes.len());
+        assert!(outcome.top_routes.iter().all(|route| route.score >= outcome.summary.threshold));
+
+        let err = score_matrix_file(&config, &router, &models, "/nonexistent/matrix.md").await.unwrap_err();
+        assert!(matches!(err, MatrixError::Io(_)));
+    }
+
+    #[tokio::test]
+    async fn test_score_uses_loaded_models() {
+        use crate::omniarb::model_registry::tests::onnx_model;
+        use crate::omniarb::TAR_FEATURE_WEIGHTS;
+
+        let mut config = Config { offline: true, ..Config::default() };
+        config.opportunity.enter_threshold = 0.0;
+        let router = QuoteRouter::new().with_source(crate::omniarb::SimulatedSource);
+        let path = std::env::temp_dir().join(format!("titan_run_model_{}.onnx", std::process::id()));
+        std::fs::write(&path, onnx_model(1, &[("feature_weights", "0,0,0,0,0,0")])).unwrap();
+        let mut models = ModelRegistry::new();
+        models.register("tar", &path, TAR_FEATURE_WEIGHTS).unwrap();
+        std::fs::remove_file(&path).ok();
+
+        let outcome = score_matrix_file(&config, &router, &models, SCHEMA_V2).await.unwrap();
+        assert_eq!(outcome.top_routes.len(), 3);
+        assert!(outcome.top_routes.iter().all(|route| route.model_pred_tar == 0.0));
+        assert!(outcome.top_routes.iter().all(|route| route.model_pred_flank > 0.0));
+    }
+}
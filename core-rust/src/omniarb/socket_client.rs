@@ -0,0 +1,396 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use ethers::types::U256;
+use serde::Deserialize;
+
+use crate::api_policy::{FetchOptions, ProviderPolicy, SOCKET_PROVIDER};
+use crate::config::{ApiEndpoint, Config};
+use crate::omniarb::data_fetcher::{QuoteInfo, QuoteProvider};
+
+/// Socket (Bungee) REST API base URL
+pub const SOCKET_API_BASE: &str = "https://api.socket.tech/v2";
+
+/// Per-request HTTP timeout
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Fee charged by a protocol along a route
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SocketProtocolFees {
+    #[serde(default)]
+    fees_in_usd: f64,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SocketStep {
+    protocol_fees: Option<SocketProtocolFees>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SocketUserTx {
+    protocol_fees: Option<SocketProtocolFees>,
+    #[serde(default)]
+    steps: Vec<SocketStep>,
+}
+
+/// Relevant fields of one entry of `result.routes`
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SocketRoute {
+    to_amount: String,
+    #[serde(default)]
+    total_gas_fees_in_usd: f64,
+    #[serde(default)]
+    service_time: u64,
+    #[serde(default)]
+    input_value_in_usd: f64,
+    #[serde(default)]
+    output_value_in_usd: f64,
+    #[serde(default)]
+    user_txs: Vec<SocketUserTx>,
+}
+
+impl SocketRoute {
+    /// Sum of protocol fees over every transaction and step
+    fn bridge_fee_usd(&self) -> f64 {
+        self.user_txs
+            .iter()
+            .flat_map(|tx| {
+                tx.protocol_fees
+                    .iter()
+                    .chain(tx.steps.iter().filter_map(|step| step.protocol_fees.as_ref()))
+            })
+            .map(|fees| fees.fees_in_usd)
+            .sum()
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct SocketQuoteResult {
+    #[serde(default)]
+    routes: Vec<SocketRoute>,
+}
+
+/// `/quote` response envelope
+#[derive(Debug, Deserialize)]
+struct SocketQuoteResponse {
+    success: bool,
+    result: Option<SocketQuoteResult>,
+}
+
+/// Best route of a Socket quote
+#[derive(Debug, Clone, PartialEq)]
+pub struct SocketQuote {
+    /// Destination amount in raw token units
+    pub to_amount: U256,
+    pub bridge_fee_usd: f64,
+    pub gas_cost_usd: f64,
+    pub service_time_seconds: u64,
+    pub input_value_usd: f64,
+    pub output_value_usd: f64,
+}
+
+impl SocketQuote {
+    /// Normalize into the shared quote shape
+    ///
+    /// Spread is the USD value change across the bridge (negative when the
+    /// route costs money) and slippage the bridge fee as a share of input.
+    pub fn to_quote_info(&self) -> QuoteInfo {
+        let percent_of_input = |value: f64| {
+            if self.input_value_usd > 0.0 {
+                value / self.input_value_usd * 100.0
+            } else {
+                0.0
+            }
+        };
+        QuoteInfo {
+            spread_percentage: percent_of_input(self.output_value_usd - self.input_value_usd),
+            slippage_estimate: percent_of_input(self.bridge_fee_usd),
+            gas_cost_usd: self.gas_cost_usd,
+            available_liquidity: self.output_value_usd,
+            source: QuoteProvider::Socket,
+            ..Default::default()
+        }
+    }
+}
+
+/// Socket quote request parameters
+#[derive(Debug, Clone)]
+pub struct SocketQuoteRequest {
+    pub from_chain_id: u64,
+    pub to_chain_id: u64,
+    pub from_token_address: String,
+    pub to_token_address: String,
+    /// Input amount in raw token units
+    pub from_amount: U256,
+    pub user_address: String,
+}
+
+/// Socket API client for bridge quotes
+pub struct SocketClient {
+    http: reqwest::Client,
+    base_url: String,
+    api_key: String,
+    policy: Arc<ProviderPolicy>,
+}
+
+impl SocketClient {
+    /// Create a client for the public Socket API
+    pub fn new(api_key: &str) -> Self {
+        Self {
+            http: reqwest::Client::builder()
+                .timeout(REQUEST_TIMEOUT)
+                .build()
+                .unwrap_or_default(),
+            base_url: SOCKET_API_BASE.to_string(),
+            api_key: api_key.to_string(),
+            policy: FetchOptions::default().policy(SOCKET_PROVIDER),
+        }
+    }
+
+    /// Create a client for a configured endpoint
+    pub fn from_endpoint(endpoint: &ApiEndpoint) -> Self {
+        Self {
+            http: reqwest::Client::builder()
+                .timeout(endpoint.timeout)
+                .build()
+                .unwrap_or_default(),
+            base_url: endpoint.base_url.trim_end_matches('/').to_string(),
+            api_key: endpoint.api_key.clone(),
+            policy: FetchOptions::default()
+                .with_rate_limit(SOCKET_PROVIDER, endpoint.rate_limit_rps)
+                .policy(SOCKET_PROVIDER),
+        }
+    }
+
+    /// Create a client for the configured Socket endpoint, if it has a key
+    ///
+    /// An endpoint without its own rate limit gets the runtime profile's.
+    pub fn from_config(config: &Config) -> Option<Self> {
+        let endpoint = config.quote_apis.socket.as_ref().filter(|endpoint| endpoint.has_key())?;
+        Some(Self::from_endpoint(&ApiEndpoint {
+            rate_limit_rps: endpoint.rate_limit_rps.or(Some(config.runtime.api_rate_limit_rps)),
+            ..endpoint.clone()
+        }))
+    }
+
+    /// Use a different API base URL
+    pub fn with_base_url(mut self, base_url: &str) -> Self {
+        self.base_url = base_url.trim_end_matches('/').to_string();
+        self
+    }
+
+    /// Share a retry and rate-limit policy with other Socket clients
+    pub fn with_policy(mut self, policy: Arc<ProviderPolicy>) -> Self {
+        self.policy = policy;
+        self
+    }
+
+    pub fn policy(&self) -> &ProviderPolicy {
+        &self.policy
+    }
+
+    /// Fetch routes for a transfer and return the one with the highest output
+    pub async fn quote(&self, request: &SocketQuoteRequest) -> Result<SocketQuote, String> {
+        let query = [
+            ("fromChainId", request.from_chain_id.to_string()),
+            ("toChainId", request.to_chain_id.to_string()),
+            ("fromTokenAddress", request.from_token_address.clone()),
+            ("toTokenAddress", request.to_token_address.clone()),
+            ("fromAmount", request.from_amount.to_string()),
+            ("userAddress", request.user_address.clone()),
+            ("uniqueRoutesPerBridge", "true".to_string()),
+            ("sort", "output".to_string()),
+        ];
+        let response = self
+            .policy
+            .send(|| {
+                self.http
+                    .get(format!("{}/quote", self.base_url))
+                    .header("API-KEY", &self.api_key)
+                    .query(&query)
+            })
+            .await
+            .map_err(|e| format!("Socket request failed: {}", e))?;
+
+        let status = response.status();
+        if status == reqwest::StatusCode::UNAUTHORIZED || status == reqwest::StatusCode::FORBIDDEN {
+            return Err(format!("Socket API rejected the API key ({})", status));
+        }
+        if !status.is_success() {
+            return Err(format!("Socket API returned {}", status));
+        }
+
+        let body: SocketQuoteResponse = response
+            .json()
+            .await
+            .map_err(|e| format!("Invalid Socket response: {}", e))?;
+        if !body.success {
+            return Err("Socket API reported failure".to_string());
+        }
+        best_route(body.result.map(|r| r.routes).unwrap_or_default())
+    }
+}
+
+/// Pick the route with the highest destination amount
+fn best_route(routes: Vec<SocketRoute>) -> Result<SocketQuote, String> {
+    routes
+        .into_iter()
+        .filter_map(|route| {
+            let to_amount = U256::from_dec_str(&route.to_amount).ok()?;
+            Some(SocketQuote {
+                to_amount,
+                bridge_fee_usd: route.bridge_fee_usd(),
+                gas_cost_usd: route.total_gas_fees_in_usd,
+                service_time_seconds: route.service_time,
+                input_value_usd: route.input_value_in_usd,
+                output_value_usd: route.output_value_in_usd,
+            })
+        })
+        .max_by_key(|quote| quote.to_amount)
+        .ok_or_else(|| "Socket returned no usable routes".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{http::{HeaderMap, StatusCode}, routing::get, Json, Router};
+
+    /// Serve `/quote`, requiring the `API-KEY` header to equal `key`
+    async fn mock_socket(key: &'static str, body: serde_json::Value) -> String {
+        let app = Router::new().route(
+            "/quote",
+            get(move |headers: HeaderMap| async move {
+                if headers.get("API-KEY").and_then(|v| v.to_str().ok()) != Some(key) {
+                    return (StatusCode::UNAUTHORIZED, Json(serde_json::json!({ "success": false })));
+                }
+                (StatusCode::OK, Json(body))
+            }),
+        );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+        format!("http://{}", addr)
+    }
+
+    fn request() -> SocketQuoteRequest {
+        SocketQuoteRequest {
+            from_chain_id: 137,
+            to_chain_id: 42161,
+            from_token_address: "0x2791Bca1f2de4661ED88A30C99A7a9449Aa84174".to_string(),
+            to_token_address: "0xaf88d065e77c8cC2239327C5EDb3A432268e5831".to_string(),
+            from_amount: U256::from(1_000_000_000u64),
+            user_address: "0x0000000000000000000000000000000000000001".to_string(),
+        }
+    }
+
+    fn routes() -> serde_json::Value {
+        serde_json::json!({
+            "success": true,
+            "result": {
+                "routes": [
+                    {
+                        "toAmount": "997000000",
+                        "totalGasFeesInUsd": 0.4,
+                        "serviceTime": 60,
+                        "inputValueInUsd": 1000.0,
+                        "outputValueInUsd": 997.0,
+                        "userTxs": [{ "steps": [{ "protocolFees": { "feesInUsd": 2.6 } }] }]
+                    },
+                    {
+                        "toAmount": "998500000",
+                        "totalGasFeesInUsd": 0.9,
+                        "serviceTime": 180,
+                        "inputValueInUsd": 1000.0,
+                        "outputValueInUsd": 998.5,
+                        "userTxs": [
+                            { "protocolFees": { "feesInUsd": 0.25 } },
+                            { "steps": [{ "protocolFees": { "feesInUsd": 0.25 } }, {}] }
+                        ]
+                    },
+                    { "toAmount": "not-a-number" }
+                ]
+            }
+        })
+    }
+
+    #[tokio::test]
+    async fn test_best_route_selected() {
+        let base_url = mock_socket("test-key", routes()).await;
+        let client = SocketClient::new("test-key").with_base_url(&base_url);
+
+        let quote = client.quote(&request()).await.unwrap();
+        assert_eq!(quote.to_amount, U256::from(998_500_000u64));
+        assert_eq!(quote.service_time_seconds, 180);
+        assert!((quote.bridge_fee_usd - 0.5).abs() < 1e-9);
+
+        let info = quote.to_quote_info();
+        assert!((info.spread_percentage + 0.15).abs() < 1e-9);
+        assert!((info.slippage_estimate - 0.05).abs() < 1e-9);
+        assert_eq!(info.gas_cost_usd, 0.9);
+    }
+
+    #[tokio::test]
+    async fn test_endpoint_base_url_and_key_used() {
+        let base_url = mock_socket("partner-key", routes()).await;
+        let client = SocketClient::from_endpoint(&ApiEndpoint::new(&format!("{}/", base_url), "partner-key"));
+        assert!(client.quote(&request()).await.is_ok());
+
+        let mut config = Config::default();
+        config.quote_apis.socket = None;
+        assert!(SocketClient::from_config(&config).is_none());
+        config.quote_apis.socket = Some(ApiEndpoint::new(&base_url, ""));
+        assert!(SocketClient::from_config(&config).is_none());
+    }
+
+    #[tokio::test]
+    async fn test_auth_failure() {
+        let base_url = mock_socket("test-key", routes()).await;
+        let client = SocketClient::new("wrong-key").with_base_url(&base_url);
+
+        let err = client.quote(&request()).await.unwrap_err();
+        assert!(err.contains("rejected the API key"), "{}", err);
+    }
+
+    #[tokio::test]
+    async fn test_routed_quotes_use_socket_for_socket_routes() {
+        use crate::omniarb::data_fetcher::{fetch_live_quotes, fetch_routed_quotes};
+        use crate::omniarb::matrix_parser::TokenEntry;
+
+        let base_url = mock_socket("test-key", routes()).await;
+        let client = SocketClient::new("test-key").with_base_url(&base_url);
+
+        let route = |bridge: &str, with_addresses: bool| TokenEntry {
+            chain_origin: 137,
+            chain_dest: 42161,
+            native_token: "USDC".to_string(),
+            dex_origin: "QUICKSWAP".to_string(),
+            dex_dest: "CAMELOT".to_string(),
+            bridge_protocol: bridge.to_string(),
+            liquidity_score: 90.0,
+            fee_tier: 0.05,
+            token_address_origin: with_addresses.then(|| request().from_token_address),
+            token_address_dest: with_addresses.then(|| request().to_token_address),
+            ..Default::default()
+        };
+        let entries = vec![route("SOCKET", true), route("STARGATE", true), route("LAYERZERO", false)];
+
+        let quotes = fetch_routed_quotes(&entries, Some(&client), request().from_amount, "0x01").await;
+        let simulated = fetch_live_quotes(&entries);
+        assert_eq!(quotes[0].gas_cost_usd, 0.9);
+        assert_eq!(quotes[1].spread_percentage, simulated[1].spread_percentage);
+        // No token addresses to quote with
+        assert_eq!(quotes[2].spread_percentage, simulated[2].spread_percentage);
+    }
+
+    #[tokio::test]
+    async fn test_no_routes() {
+        let body = serde_json::json!({ "success": true, "result": { "routes": [] } });
+        let base_url = mock_socket("test-key", body).await;
+        let client = SocketClient::new("test-key").with_base_url(&base_url);
+        assert!(client.quote(&request()).await.is_err());
+    }
+}
@@ -1,14 +1,272 @@
+use std::collections::HashMap;
+use std::str::FromStr;
+
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+
 use crate::omniarb::matrix_parser::TokenEntry;
 use crate::omniarb::data_fetcher::QuoteInfo;
 
-// Token tier classifications
+// Default token tier classifications
 const TIER_1_TOKENS: &[&str] = &["USDC", "USDT", "DAI", "ETH", "WETH", "WBTC"];
 const TIER_2_TOKENS: &[&str] = &["MATIC", "AVAX", "BNB", "OP", "ARB", "LINK"];
 
-// Bridge tier classifications
+// Default bridge tier classifications
 const TIER_1_BRIDGES: &[&str] = &["STARGATE", "ACROSS", "CCIP", "LIFI"];
 const TIER_2_BRIDGES: &[&str] = &["HOP", "SYNAPSE", "SOCKET", "LAYERZERO"];
 
+/// Points awarded once a value clears `threshold`
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Breakpoint {
+    pub threshold: f64,
+    pub points: f64,
+}
+
+impl Breakpoint {
+    pub const fn new(threshold: f64, points: f64) -> Self {
+        Self { threshold, points }
+    }
+}
+
+/// Points for tier-1, tier-2 and other tokens or bridges
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct TierPoints {
+    pub tier_1: f64,
+    pub tier_2: f64,
+    pub other: f64,
+}
+
+impl TierPoints {
+    fn max(&self) -> f64 {
+        self.tier_1.max(self.tier_2).max(self.other)
+    }
+}
+
+/// Tier given to symbols missing from a [`TierConfig`]
+pub const LOWEST_TIER: u8 = 3;
+
+/// Token and bridge tiers (1 is best, 3 is the lowest)
+///
+/// Lookups ignore case; unknown symbols get [`LOWEST_TIER`].
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TierConfig {
+    pub token_tiers: HashMap<String, u8>,
+    pub bridge_tiers: HashMap<String, u8>,
+}
+
+impl Default for TierConfig {
+    fn default() -> Self {
+        let tiers = |tier_1: &[&str], tier_2: &[&str]| {
+            tier_1
+                .iter()
+                .map(|name| (name.to_string(), 1))
+                .chain(tier_2.iter().map(|name| (name.to_string(), 2)))
+                .collect()
+        };
+        Self {
+            token_tiers: tiers(TIER_1_TOKENS, TIER_2_TOKENS),
+            bridge_tiers: tiers(TIER_1_BRIDGES, TIER_2_BRIDGES),
+        }
+    }
+}
+
+impl TierConfig {
+    pub fn token_tier(&self, symbol: &str) -> u8 {
+        lookup_tier(&self.token_tiers, symbol)
+    }
+
+    pub fn bridge_tier(&self, bridge: &str) -> u8 {
+        lookup_tier(&self.bridge_tiers, bridge)
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        match self
+            .token_tiers
+            .iter()
+            .chain(&self.bridge_tiers)
+            .find(|(_, tier)| !(1..=LOWEST_TIER).contains(*tier))
+        {
+            Some((name, tier)) => Err(format!("Tier for {} must be 1-{}, got {}", name, LOWEST_TIER, tier)),
+            None => Ok(()),
+        }
+    }
+}
+
+fn lookup_tier(tiers: &HashMap<String, u8>, name: &str) -> u8 {
+    tiers
+        .get(name)
+        .or_else(|| tiers.iter().find(|(key, _)| key.eq_ignore_ascii_case(name)).map(|(_, tier)| tier))
+        .copied()
+        .unwrap_or(LOWEST_TIER)
+}
+
+/// Tolerance on the sum of component maxima
+const MAXIMA_SUM_TOLERANCE: f64 = 1e-9;
+
+/// TAR component maxima and the breakpoint tables feeding each component
+///
+/// Each component's table points are rescaled so the best possible raw
+/// score maps to the component maximum. Breakpoint tables are checked in
+/// order and the first match wins.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TarWeights {
+    pub token_max: f64,
+    pub arbitrage_max: f64,
+    pub risk_max: f64,
+    /// T: points by token tier
+    pub token_tiers: TierPoints,
+    /// T: points at a liquidity score of 100, linear below
+    pub liquidity_points: f64,
+    /// A: points when the fee tier is below the threshold
+    pub fee_tier_below: Vec<Breakpoint>,
+    /// A: points when the spread is above the threshold
+    pub spread_above: Vec<Breakpoint>,
+    /// R: points by bridge tier
+    pub bridge_tiers: TierPoints,
+    /// R: points when slippage is below the threshold
+    pub slippage_below: Vec<Breakpoint>,
+    /// Which tokens and bridges are tier 1 and 2
+    #[serde(default)]
+    pub tiers: TierConfig,
+    /// Exponent on `available_liquidity / notional` when scoring a sized trade
+    #[serde(default = "default_liquidity_exponent")]
+    pub liquidity_exponent: f64,
+}
+
+fn default_liquidity_exponent() -> f64 {
+    1.0
+}
+
+impl Default for TarWeights {
+    /// The original 35/35/30 split and breakpoints
+    fn default() -> Self {
+        Self {
+            token_max: 35.0,
+            arbitrage_max: 35.0,
+            risk_max: 30.0,
+            token_tiers: TierPoints { tier_1: 20.0, tier_2: 12.0, other: 5.0 },
+            liquidity_points: 15.0,
+            fee_tier_below: vec![Breakpoint::new(0.15, 15.0), Breakpoint::new(0.30, 10.0), Breakpoint::new(0.50, 5.0)],
+            spread_above: vec![
+                Breakpoint::new(2.0, 20.0),
+                Breakpoint::new(1.0, 15.0),
+                Breakpoint::new(0.5, 10.0),
+                Breakpoint::new(0.2, 5.0),
+            ],
+            bridge_tiers: TierPoints { tier_1: 15.0, tier_2: 10.0, other: 5.0 },
+            slippage_below: vec![Breakpoint::new(0.5, 15.0), Breakpoint::new(1.0, 10.0), Breakpoint::new(2.0, 5.0)],
+            tiers: TierConfig::default(),
+            liquidity_exponent: default_liquidity_exponent(),
+        }
+    }
+}
+
+impl TarWeights {
+    /// Default breakpoint tables with custom component maxima
+    pub fn new(token_max: f64, arbitrage_max: f64, risk_max: f64) -> Result<Self, String> {
+        let weights = Self {
+            token_max,
+            arbitrage_max,
+            risk_max,
+            ..Self::default()
+        };
+        weights.validate()?;
+        Ok(weights)
+    }
+
+    /// Maxima must be finite, non-negative and sum to 100; table points must be finite and non-negative
+    pub fn validate(&self) -> Result<(), String> {
+        let maxima = [self.token_max, self.arbitrage_max, self.risk_max];
+        if maxima.iter().any(|m| !m.is_finite() || *m < 0.0) {
+            return Err(format!("TAR component maxima must be non-negative: {:?}", maxima));
+        }
+        let sum: f64 = maxima.iter().sum();
+        if (sum - 100.0).abs() > MAXIMA_SUM_TOLERANCE {
+            return Err(format!("TAR component maxima must sum to 100, got {}", sum));
+        }
+
+        let tiers = [self.token_tiers, self.bridge_tiers];
+        let tier_points = tiers.iter().flat_map(|t| [t.tier_1, t.tier_2, t.other]);
+        let table_points = [&self.fee_tier_below, &self.spread_above, &self.slippage_below]
+            .into_iter()
+            .flatten()
+            .flat_map(|b| [b.threshold, b.points]);
+        if tier_points
+            .chain(table_points)
+            .chain([self.liquidity_points, self.liquidity_exponent])
+            .any(|p| !p.is_finite() || p < 0.0)
+        {
+            return Err("TAR breakpoint thresholds and points must be finite and non-negative".to_string());
+        }
+        self.tiers.validate()
+    }
+
+    fn token_raw_max(&self) -> f64 {
+        self.token_tiers.max() + self.liquidity_points
+    }
+
+    fn arbitrage_raw_max(&self) -> f64 {
+        table_max(&self.fee_tier_below) + table_max(&self.spread_above)
+    }
+
+    fn risk_raw_max(&self) -> f64 {
+        self.bridge_tiers.max() + table_max(&self.slippage_below)
+    }
+}
+
+/// Parse `token,arbitrage,risk` maxima, e.g. `20,20,60`
+impl FromStr for TarWeights {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let maxima = s
+            .split(',')
+            .map(|m| m.trim().parse::<f64>().map_err(|e| format!("Invalid TAR maximum '{}': {}", m, e)))
+            .collect::<Result<Vec<_>, _>>()?;
+        match maxima[..] {
+            [token, arbitrage, risk] => Self::new(token, arbitrage, risk),
+            _ => Err(format!("Expected 3 comma-separated TAR maxima, got '{}'", s)),
+        }
+    }
+}
+
+fn table_max(table: &[Breakpoint]) -> f64 {
+    table.iter().map(|b| b.points).fold(0.0, f64::max)
+}
+
+/// First breakpoint `value` is below
+fn breakpoint_below(table: &[Breakpoint], value: f64) -> Option<Breakpoint> {
+    table.iter().find(|b| value < b.threshold).copied()
+}
+
+/// First breakpoint `value` is above
+fn breakpoint_above(table: &[Breakpoint], value: f64) -> Option<Breakpoint> {
+    table.iter().find(|b| value > b.threshold).copied()
+}
+
+/// Points of the breakpoint hit, with its threshold
+fn hit(breakpoint: Option<Breakpoint>) -> (f64, Option<f64>) {
+    breakpoint.map_or((0.0, None), |b| (b.points, Some(b.threshold)))
+}
+
+/// Points for a tier, with the tier label for notes
+fn tier_points(points: &TierPoints, tier: u8) -> (f64, &'static str) {
+    match tier {
+        1 => (points.tier_1, "tier 1"),
+        2 => (points.tier_2, "tier 2"),
+        _ => (points.other, "other"),
+    }
+}
+
+/// Rescale a raw component score so `raw_max` maps to `max`
+fn scale(raw: f64, raw_max: f64, max: f64) -> f64 {
+    if raw_max > 0.0 {
+        raw * (max / raw_max)
+    } else {
+        0.0
+    }
+}
+
 /// Calculate TAR (Token Analysis & Risk) Score
 /// 
 /// Score components:
@@ -21,92 +279,226 @@ const TIER_2_BRIDGES: &[&str] = &["HOP", "SYNAPSE", "SOCKET", "LAYERZERO"];
 /// * `quote` - Live quote information
 /// 
 /// # Returns
-/// TAR score (0-100, higher is better)
+/// TAR score (0-100, higher is better); 0 for non-finite inputs
 pub fn calculate_tar_score(entry: &TokenEntry, quote: &QuoteInfo) -> f64 {
-    let mut score = 0.0;
-    
-    // T - Token Quality (0-35 points)
-    let token_score = calculate_token_quality(&entry.native_token, entry.liquidity_score);
-    score += token_score;
-    
-    // A - Arbitrage Efficiency (0-35 points)
-    let arb_score = calculate_arbitrage_efficiency(entry.fee_tier, quote.spread_percentage);
-    score += arb_score;
-    
-    // R - Risk Assessment (0-30 points)
-    let risk_score = calculate_risk_score(&entry.bridge_protocol, quote.slippage_estimate);
-    score += risk_score;
-    
-    // Cap at 100
-    score.min(100.0)
+    calculate_tar_score_weighted(entry, quote, &TarWeights::default())
 }
 
-fn calculate_token_quality(token: &str, liquidity_score: f64) -> f64 {
-    let mut score = 0.0;
-    
-    // High-value stable tokens
-    if TIER_1_TOKENS.contains(&token) {
-        score += 20.0; // Premium tokens
-    } else if TIER_2_TOKENS.contains(&token) {
-        score += 12.0; // Good tokens
-    } else {
-        score += 5.0;  // Other tokens
+/// TAR score with custom component maxima and breakpoints
+pub fn calculate_tar_score_weighted(entry: &TokenEntry, quote: &QuoteInfo, weights: &TarWeights) -> f64 {
+    calculate_tar_breakdown_weighted(entry, quote, weights).total
+}
+
+/// Per-component TAR points and the breakpoints behind them
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TarBreakdown {
+    pub token_quality: f64,
+    pub arbitrage_efficiency: f64,
+    pub risk: f64,
+    pub total: f64,
+    /// One line per input, e.g. `spread 0.4% ⇒ 5/20` (raw table points)
+    pub notes: Vec<String>,
+    /// The tiers and breakpoints each component was built from
+    #[serde(default)]
+    pub factors: TarFactors,
+}
+
+/// Tier and breakpoint lookups behind a [`TarBreakdown`]
+///
+/// Points are raw table points, before each component is rescaled to its
+/// maximum. A `None` threshold means no breakpoint was hit and the input
+/// scored 0.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct TarFactors {
+    pub token_tier: u8,
+    pub token_points: f64,
+    pub liquidity_points: f64,
+    /// Fee tier breakpoint the route's fee tier was below
+    pub fee_threshold: Option<f64>,
+    pub fee_points: f64,
+    /// Spread breakpoint the spread (net of gas when sized) was above
+    pub spread_threshold: Option<f64>,
+    pub spread_points: f64,
+    pub bridge_tier: u8,
+    pub bridge_points: f64,
+    /// Slippage breakpoint the route's slippage was below
+    pub slippage_threshold: Option<f64>,
+    pub slippage_points: f64,
+}
+
+/// Why `calculate_tar_score` gave a route its score
+///
+/// The components sum to the score (before the cap at 100); `factors`
+/// records the token and bridge tiers and the fee, spread and slippage
+/// breakpoints that were hit.
+pub fn explain_tar_score(entry: &TokenEntry, quote: &QuoteInfo) -> TarBreakdown {
+    calculate_tar_breakdown(entry, quote)
+}
+
+/// TAR score split into its T, A and R components with default weights
+pub fn calculate_tar_breakdown(entry: &TokenEntry, quote: &QuoteInfo) -> TarBreakdown {
+    calculate_tar_breakdown_weighted(entry, quote, &TarWeights::default())
+}
+
+/// TAR score split into its T, A and R components
+pub fn calculate_tar_breakdown_weighted(entry: &TokenEntry, quote: &QuoteInfo, weights: &TarWeights) -> TarBreakdown {
+    calculate_tar_breakdown_sized(entry, quote, weights, None)
+}
+
+/// Breakdowns of `entries[i]` against `quotes[i]`, scored across threads
+///
+/// Pairs up to the shorter of the two slices.
+pub fn calculate_tar_breakdown_batch(entries: &[TokenEntry], quotes: &[QuoteInfo], weights: &TarWeights) -> Vec<TarBreakdown> {
+    entries
+        .par_iter()
+        .zip(quotes.par_iter())
+        .map(|(entry, quote)| calculate_tar_breakdown_weighted(entry, quote, weights))
+        .collect()
+}
+
+/// TAR score for a trade of `trade_size_usd`, net of gas
+pub fn calculate_tar_score_sized(
+    entry: &TokenEntry,
+    quote: &QuoteInfo,
+    weights: &TarWeights,
+    trade_size_usd: Option<f64>,
+) -> f64 {
+    calculate_tar_breakdown_sized(entry, quote, weights, trade_size_usd).total
+}
+
+/// TAR breakdown for a trade of `trade_size_usd`
+///
+/// With a trade size, the spread breakpoints score the spread left after
+/// paying `gas_cost_usd` on that notional, so the same spread ranks lower
+/// on small trades and expensive chains. The total is then scaled by
+/// `min(1, available_liquidity / trade_size)^liquidity_exponent`, leaving
+/// the components as they are. `None` scores the raw spread.
+pub fn calculate_tar_breakdown_sized(
+    entry: &TokenEntry,
+    quote: &QuoteInfo,
+    weights: &TarWeights,
+    trade_size_usd: Option<f64>,
+) -> TarBreakdown {
+    if !entry.is_finite() || !quote.is_finite() {
+        return TarBreakdown {
+            token_quality: 0.0,
+            arbitrage_efficiency: 0.0,
+            risk: 0.0,
+            total: 0.0,
+            notes: vec!["non-finite input ⇒ 0".to_string()],
+            factors: TarFactors::default(),
+        };
     }
     
-    // Liquidity component (0-15 points)
-    score += (liquidity_score / 100.0) * 15.0;
+    let mut notes = Vec::new();
+    let mut factors = TarFactors::default();
     
-    score
-}
-
-fn calculate_arbitrage_efficiency(fee_tier: f64, spread_percentage: f64) -> f64 {
-    let mut score = 0.0;
+    // T - Token Quality
+    let token_score = calculate_token_quality(&entry.native_token, entry.liquidity_score, weights, &mut notes, &mut factors);
+    let token_quality = scale(token_score, weights.token_raw_max(), weights.token_max);
     
-    // Lower fees are better (0-15 points)
-    if fee_tier < 0.15 {
-        score += 15.0;
-    } else if fee_tier < 0.30 {
-        score += 10.0;
-    } else if fee_tier < 0.50 {
-        score += 5.0;
-    }
+    // A - Arbitrage Efficiency
+    let arb_score = calculate_arbitrage_efficiency(entry.fee_tier, quote, trade_size_usd, weights, &mut notes, &mut factors);
+    let arbitrage_efficiency = scale(arb_score, weights.arbitrage_raw_max(), weights.arbitrage_max);
     
-    // Higher spread is better (0-20 points)
-    if spread_percentage > 2.0 {
-        score += 20.0;
-    } else if spread_percentage > 1.0 {
-        score += 15.0;
-    } else if spread_percentage > 0.5 {
-        score += 10.0;
-    } else if spread_percentage > 0.2 {
-        score += 5.0;
-    }
+    // R - Risk Assessment
+    let risk_score = calculate_risk_score(&entry.bridge_protocol, quote.slippage_estimate, weights, &mut notes, &mut factors);
+    let risk = scale(risk_score, weights.risk_raw_max(), weights.risk_max);
     
-    score
+    // Cap at 100, then discount for trades deeper than the quoted liquidity
+    let mut total = (token_quality + arbitrage_efficiency + risk).min(100.0);
+    if let Some(size) = trade_size_usd.filter(|size| *size > 0.0) {
+        let depth = (quote.available_liquidity.max(0.0) / size).min(1.0);
+        let multiplier = depth.powf(weights.liquidity_exponent);
+        notes.push(format!(
+            "liquidity ${} for ${} notional ⇒ x{:.3}",
+            quote.available_liquidity, size, multiplier
+        ));
+        total *= multiplier;
+    }
+    TarBreakdown { token_quality, arbitrage_efficiency, risk, total, notes, factors }
 }
 
-fn calculate_risk_score(bridge: &str, slippage: f64) -> f64 {
-    let mut score = 0.0;
-    
-    // Bridge reliability (0-15 points)
-    if TIER_1_BRIDGES.contains(&bridge) {
-        score += 15.0;
-    } else if TIER_2_BRIDGES.contains(&bridge) {
-        score += 10.0;
+fn calculate_token_quality(
+    token: &str,
+    liquidity_score: f64,
+    weights: &TarWeights,
+    notes: &mut Vec<String>,
+    factors: &mut TarFactors,
+) -> f64 {
+    // Token reputation, then liquidity (linear up to liquidity_points)
+    factors.token_tier = weights.tiers.token_tier(token);
+    let (tier, label) = tier_points(&weights.token_tiers, factors.token_tier);
+    let liquidity = (liquidity_score / 100.0) * weights.liquidity_points;
+    notes.push(format!("token {} {} ⇒ {}/{}", token, label, tier, weights.token_tiers.max()));
+    notes.push(format!("liquidity {} ⇒ {}/{}", liquidity_score, liquidity, weights.liquidity_points));
+    factors.token_points = tier;
+    factors.liquidity_points = liquidity;
+    tier + liquidity
+}
+
+fn calculate_arbitrage_efficiency(
+    fee_tier: f64,
+    quote: &QuoteInfo,
+    trade_size_usd: Option<f64>,
+    weights: &TarWeights,
+    notes: &mut Vec<String>,
+    factors: &mut TarFactors,
+) -> f64 {
+    // Lower fees are better, higher spread (net of gas when sized) is better
+    let (fee, fee_threshold) = hit(breakpoint_below(&weights.fee_tier_below, fee_tier));
+    notes.push(format!("fee tier {}% ⇒ {}/{}", fee_tier, fee, table_max(&weights.fee_tier_below)));
+    let spread_max = table_max(&weights.spread_above);
+    let (spread, spread_threshold) = match trade_size_usd {
+        None => {
+            let (spread, threshold) = hit(breakpoint_above(&weights.spread_above, quote.spread_percentage));
+            notes.push(format!("spread {}% ⇒ {}/{}", quote.spread_percentage, spread, spread_max));
+            (spread, threshold)
+        }
+        Some(size) => {
+            let gas_bps = gas_cost_bps(quote.gas_cost_usd, size);
+            let net_bps = quote.spread_percentage * 100.0 - gas_bps;
+            let (spread, threshold) = hit(breakpoint_above(&weights.spread_above, net_bps / 100.0));
+            notes.push(format!(
+                "net spread {:.1}bps (gas {:.1}bps on ${}) ⇒ {}/{}",
+                net_bps, gas_bps, size, spread, spread_max
+            ));
+            (spread, threshold)
+        }
+    };
+    factors.fee_points = fee;
+    factors.fee_threshold = fee_threshold;
+    factors.spread_points = spread;
+    factors.spread_threshold = spread_threshold;
+    fee + spread
+}
+
+/// Gas cost as basis points of the trade; infinite for a non-positive size
+fn gas_cost_bps(gas_cost_usd: f64, trade_size_usd: f64) -> f64 {
+    if trade_size_usd > 0.0 {
+        gas_cost_usd.max(0.0) / trade_size_usd * 10_000.0
     } else {
-        score += 5.0;
+        f64::INFINITY
     }
-    
-    // Slippage penalty (0-15 points)
-    if slippage < 0.5 {
-        score += 15.0;
-    } else if slippage < 1.0 {
-        score += 10.0;
-    } else if slippage < 2.0 {
-        score += 5.0;
-    }
-    
-    score
+}
+
+fn calculate_risk_score(
+    bridge: &str,
+    slippage: f64,
+    weights: &TarWeights,
+    notes: &mut Vec<String>,
+    factors: &mut TarFactors,
+) -> f64 {
+    // Bridge reliability, then slippage penalty
+    factors.bridge_tier = weights.tiers.bridge_tier(bridge);
+    let (tier, label) = tier_points(&weights.bridge_tiers, factors.bridge_tier);
+    let (slippage_points, slippage_threshold) = hit(breakpoint_below(&weights.slippage_below, slippage));
+    factors.bridge_points = tier;
+    factors.slippage_points = slippage_points;
+    factors.slippage_threshold = slippage_threshold;
+    notes.push(format!("bridge {} {} ⇒ {}/{}", bridge, label, tier, weights.bridge_tiers.max()));
+    notes.push(format!("slippage {}% ⇒ {}/{}", slippage, slippage_points, table_max(&weights.slippage_below)));
+    tier + slippage_points
 }
 
 #[cfg(test)]
@@ -124,6 +516,7 @@ mod tests {
             bridge_protocol: "STARGATE".to_string(),
             liquidity_score: 95.0,
             fee_tier: 0.1,
+            ..Default::default()
         };
         
         let quote = QuoteInfo {
@@ -131,10 +524,244 @@ mod tests {
             slippage_estimate: 0.3,
             gas_cost_usd: 5.0,
             available_liquidity: 1000000.0,
+            ..Default::default()
         };
         
         let score = calculate_tar_score(&entry, &quote);
         assert!(score > 70.0); // Should be a high score with these parameters
         assert!(score <= 100.0);
     }
+    
+    #[test]
+    fn test_tar_score_non_finite() {
+        let entry = TokenEntry {
+            chain_origin: 1,
+            chain_dest: 137,
+            native_token: "USDC".to_string(),
+            dex_origin: "UNISWAP_V3".to_string(),
+            dex_dest: "QUICKSWAP".to_string(),
+            bridge_protocol: "STARGATE".to_string(),
+            liquidity_score: 95.0,
+            fee_tier: f64::NAN,
+            ..Default::default()
+        };
+        
+        let quote = QuoteInfo {
+            spread_percentage: 1.5,
+            slippage_estimate: 0.3,
+            gas_cost_usd: 5.0,
+            available_liquidity: 1000000.0,
+            ..Default::default()
+        };
+        
+        assert_eq!(calculate_tar_score(&entry, &quote), 0.0);
+    }
+    
+    fn scored_route(token: &str, bridge: &str, liquidity: f64, fee_tier: f64, spread: f64, slippage: f64) -> (TokenEntry, QuoteInfo) {
+        let entry = TokenEntry {
+            chain_origin: 1,
+            chain_dest: 137,
+            native_token: token.to_string(),
+            bridge_protocol: bridge.to_string(),
+            liquidity_score: liquidity,
+            fee_tier,
+            ..Default::default()
+        };
+        let quote = QuoteInfo {
+            spread_percentage: spread,
+            slippage_estimate: slippage,
+            ..Default::default()
+        };
+        (entry, quote)
+    }
+    
+    #[test]
+    fn test_default_weights_reproduce_scores() {
+        let defaults = TarWeights::default();
+        let cases = [
+            (scored_route("USDC", "STARGATE", 95.0, 0.1, 1.5, 0.3), 94.25),
+            (scored_route("LINK", "HOP", 60.0, 0.4, 0.6, 1.2), 51.0),
+            (scored_route("PEPE", "UNKNOWN", 10.0, 1.0, 0.1, 3.0), 11.5),
+        ];
+        for ((entry, quote), expected) in cases {
+            assert_eq!(calculate_tar_score(&entry, &quote), expected);
+            assert_eq!(calculate_tar_score_weighted(&entry, &quote, &defaults), expected);
+        }
+    }
+    
+    #[test]
+    fn test_risk_heavy_weights_reorder_routes() {
+        // Strong token and spread, weak bridge and high slippage
+        let (fast, fast_quote) = scored_route("USDC", "UNKNOWN", 95.0, 0.1, 2.5, 1.5);
+        // Middling token and spread, tier-1 bridge and low slippage
+        let (safe, safe_quote) = scored_route("LINK", "STARGATE", 60.0, 0.4, 0.6, 0.3);
+        
+        assert!(calculate_tar_score(&fast, &fast_quote) > calculate_tar_score(&safe, &safe_quote));
+        
+        let risk_heavy: TarWeights = "20,20,60".parse().unwrap();
+        let fast_score = calculate_tar_score_weighted(&fast, &fast_quote, &risk_heavy);
+        let safe_score = calculate_tar_score_weighted(&safe, &safe_quote, &risk_heavy);
+        assert!(safe_score > fast_score, "{} vs {}", safe_score, fast_score);
+        assert!((safe_score - (12.0 + 15.0 * 20.0 / 35.0 + 60.0)).abs() < 1e-9);
+    }
+    
+    #[test]
+    fn test_breakdown_components() {
+        let (entry, quote) = scored_route("USDC", "STARGATE", 95.0, 0.1, 1.5, 0.3);
+        let breakdown = calculate_tar_breakdown(&entry, &quote);
+        assert_eq!(breakdown.token_quality, 34.25);
+        assert_eq!(breakdown.arbitrage_efficiency, 30.0);
+        assert_eq!(breakdown.risk, 30.0);
+        assert_eq!(breakdown.total, calculate_tar_score(&entry, &quote));
+        assert!(breakdown.notes.contains(&"spread 1.5% ⇒ 15/20".to_string()));
+        
+        let (entry, quote) = scored_route("LINK", "HOP", 60.0, 0.4, 0.6, 1.2);
+        let breakdown = calculate_tar_breakdown(&entry, &quote);
+        assert_eq!(
+            (breakdown.token_quality, breakdown.arbitrage_efficiency, breakdown.risk),
+            (21.0, 15.0, 15.0)
+        );
+        assert_eq!(breakdown.total, breakdown.token_quality + breakdown.arbitrage_efficiency + breakdown.risk);
+        assert_eq!(
+            breakdown.notes,
+            vec![
+                "token LINK tier 2 ⇒ 12/20",
+                "liquidity 60 ⇒ 9/15",
+                "fee tier 0.4% ⇒ 5/15",
+                "spread 0.6% ⇒ 10/20",
+                "bridge HOP tier 2 ⇒ 10/15",
+                "slippage 1.2% ⇒ 5/15",
+            ]
+        );
+    }
+    
+    #[test]
+    fn test_batch_matches_single_scores() {
+        let routes = [
+            scored_route("USDC", "STARGATE", 95.0, 0.1, 1.5, 0.3),
+            scored_route("LINK", "HOP", 60.0, 0.4, 0.6, 1.2),
+            scored_route("PEPE", "UNKNOWN", 10.0, 1.0, 0.1, 3.0),
+        ];
+        let (entries, quotes): (Vec<_>, Vec<_>) = routes.into_iter().unzip();
+        let totals: Vec<f64> = calculate_tar_breakdown_batch(&entries, &quotes, &TarWeights::default())
+            .iter()
+            .map(|breakdown| breakdown.total)
+            .collect();
+        assert_eq!(totals, [94.25, 51.0, 11.5]);
+        assert_eq!(calculate_tar_breakdown_batch(&entries, &quotes[..1], &TarWeights::default()).len(), 1);
+    }
+    
+    #[test]
+    fn test_explanation_sums_to_score() {
+        for (token, bridge, liquidity, fee, spread, slippage) in [
+            ("USDC", "STARGATE", 95.0, 0.1, 1.5, 0.3),
+            ("LINK", "HOP", 60.0, 0.4, 0.6, 1.2),
+            ("PEPE", "UNKNOWN", 20.0, 1.0, 0.1, 3.0),
+        ] {
+            let (entry, quote) = scored_route(token, bridge, liquidity, fee, spread, slippage);
+            let explained = explain_tar_score(&entry, &quote);
+            let sum = explained.token_quality + explained.arbitrage_efficiency + explained.risk;
+            assert!((sum - calculate_tar_score(&entry, &quote)).abs() < 1e-9, "{} {}", token, bridge);
+        }
+
+        let (entry, quote) = scored_route("LINK", "HOP", 60.0, 0.4, 0.6, 1.2);
+        let factors = explain_tar_score(&entry, &quote).factors;
+        assert_eq!((factors.token_tier, factors.bridge_tier), (2, 2));
+        assert_eq!((factors.token_points, factors.liquidity_points), (12.0, 9.0));
+        assert_eq!((factors.fee_threshold, factors.fee_points), (Some(0.5), 5.0));
+        assert_eq!((factors.spread_threshold, factors.spread_points), (Some(0.5), 10.0));
+        assert_eq!((factors.slippage_threshold, factors.slippage_points), (Some(2.0), 5.0));
+
+        // Inputs outside every breakpoint hit no threshold
+        let (entry, quote) = scored_route("PEPE", "UNKNOWN", 20.0, 1.0, 0.1, 3.0);
+        let factors = explain_tar_score(&entry, &quote).factors;
+        assert_eq!((factors.token_tier, factors.bridge_tier), (LOWEST_TIER, LOWEST_TIER));
+        assert_eq!((factors.fee_threshold, factors.spread_threshold, factors.slippage_threshold), (None, None, None));
+    }
+
+    #[test]
+    fn test_sized_score_nets_out_gas() {
+        let defaults = TarWeights::default();
+        // 2.5% spread on mainnet with $30 of gas
+        let (entry, mut quote) = scored_route("USDC", "STARGATE", 95.0, 0.05, 2.5, 0.3);
+        quote.gas_cost_usd = 30.0;
+        quote.available_liquidity = 1_000_000.0;
+        
+        let raw = calculate_tar_breakdown(&entry, &quote);
+        assert_eq!(calculate_tar_breakdown_sized(&entry, &quote, &defaults, None), raw);
+        assert_eq!(raw.arbitrage_efficiency, 35.0);
+        
+        // $100k: gas is 3bps, the spread still clears the top breakpoint
+        let large = calculate_tar_breakdown_sized(&entry, &quote, &defaults, Some(100_000.0));
+        assert_eq!(large.arbitrage_efficiency, 35.0);
+        assert!(large.notes.contains(&"net spread 247.0bps (gas 3.0bps on $100000) ⇒ 20/20".to_string()));
+        
+        // $1k: gas is 300bps and eats the whole spread, leaving only fee points
+        let small = calculate_tar_breakdown_sized(&entry, &quote, &defaults, Some(1_000.0));
+        assert_eq!(small.arbitrage_efficiency, 15.0);
+        assert!(small.notes.contains(&"net spread -50.0bps (gas 300.0bps on $1000) ⇒ 0/20".to_string()));
+        assert!(calculate_tar_score_sized(&entry, &quote, &defaults, Some(0.0)) < large.total);
+    }
+    
+    #[test]
+    fn test_thin_liquidity_caps_sized_score() {
+        let defaults = TarWeights::default();
+        // Strong route with only $30k of bridge liquidity
+        let (entry, mut quote) = scored_route("USDC", "STARGATE", 95.0, 0.1, 2.5, 0.3);
+        quote.gas_cost_usd = 5.0;
+        quote.available_liquidity = 30_000.0;
+        
+        let raw = calculate_tar_score(&entry, &quote);
+        assert_eq!(calculate_tar_score_sized(&entry, &quote, &defaults, None), raw);
+        
+        let small = calculate_tar_breakdown_sized(&entry, &quote, &defaults, Some(10_000.0));
+        assert!(small.total >= 85.0, "{}", small.total);
+        assert!(small.notes.contains(&"liquidity $30000 for $10000 notional ⇒ x1.000".to_string()));
+        
+        let large = calculate_tar_breakdown_sized(&entry, &quote, &defaults, Some(500_000.0));
+        assert!(large.total < 85.0, "{}", large.total);
+        assert!((large.total - small.total * 0.06).abs() < 1e-9);
+        assert!(large.notes.contains(&"liquidity $30000 for $500000 notional ⇒ x0.060".to_string()));
+        
+        // A steeper exponent penalizes the same shortfall harder
+        let steep = TarWeights { liquidity_exponent: 2.0, ..defaults };
+        let half_deep = calculate_tar_score_sized(&entry, &quote, &steep, Some(60_000.0));
+        assert!((half_deep - small.total * 0.25).abs() < 1e-9);
+    }
+    
+    #[test]
+    fn test_tier_config_from_config_file() {
+        use crate::config::Config;
+        
+        let defaults = TierConfig::default();
+        assert_eq!(defaults.bridge_tier("stargate"), 1);
+        assert_eq!(defaults.token_tier("Link"), 2);
+        assert_eq!(defaults.token_tier("PEPE"), LOWEST_TIER);
+        
+        // Demote STARGATE after an incident
+        let mut json: serde_json::Value = serde_json::from_str(&Config::default().to_json().unwrap()).unwrap();
+        json["tar_weights"]["tiers"]["bridge_tiers"]["STARGATE"] = serde_json::json!(3);
+        let config = Config::from_json(&json.to_string()).unwrap();
+        
+        let (entry, quote) = scored_route("USDC", "Stargate", 95.0, 0.1, 1.5, 0.3);
+        let default_risk = calculate_tar_breakdown(&entry, &quote).risk;
+        let demoted = calculate_tar_breakdown_weighted(&entry, &quote, &config.tar_weights);
+        assert_eq!(default_risk, 30.0);
+        assert_eq!(demoted.risk, 20.0);
+        assert!(demoted.notes.contains(&"bridge Stargate other ⇒ 5/15".to_string()));
+        
+        json["tar_weights"]["tiers"]["bridge_tiers"]["STARGATE"] = serde_json::json!(0);
+        assert!(Config::from_json(&json.to_string()).is_err());
+    }
+    
+    #[test]
+    fn test_tar_weights_validated() {
+        assert!(TarWeights::new(40.0, 40.0, 30.0).is_err());
+        assert!(TarWeights::new(-10.0, 50.0, 60.0).is_err());
+        assert!("35,35".parse::<TarWeights>().is_err());
+        
+        let mut weights = TarWeights::default();
+        weights.spread_above.push(Breakpoint::new(0.1, f64::NAN));
+        assert!(weights.validate().is_err());
+    }
 }
@@ -0,0 +1,475 @@
+use std::collections::hash_map::Entry;
+use std::collections::HashMap;
+use std::str::FromStr;
+use std::time::Instant;
+
+use serde::{Deserialize, Serialize};
+
+use crate::omniarb::cooldown::CooldownTracker;
+use crate::omniarb::matrix_parser::{load_token_matrix_auto, AddressResolver, TokenEntry};
+
+/// How to collapse rows describing the same route
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DedupStrategy {
+    /// Keep the first row seen
+    KeepFirst,
+    /// Keep the row with the highest liquidity score (first wins ties)
+    KeepHighestLiquidity,
+    /// Keep the first row with liquidity_score and fee_tier averaged
+    AverageNumeric,
+}
+
+impl DedupStrategy {
+    /// Collapse a non-empty group of duplicates into one entry
+    fn merge(self, group: Vec<TokenEntry>) -> TokenEntry {
+        let count = group.len() as f64;
+        let mut rows = group.into_iter();
+        let first = rows.next().expect("duplicate group is never empty");
+
+        match self {
+            DedupStrategy::KeepFirst => first,
+            DedupStrategy::KeepHighestLiquidity => rows.fold(first, |best, entry| {
+                if entry.liquidity_score > best.liquidity_score { entry } else { best }
+            }),
+            DedupStrategy::AverageNumeric => {
+                let (liquidity, fee) = rows.fold(
+                    (first.liquidity_score, first.fee_tier),
+                    |(liquidity, fee), entry| (liquidity + entry.liquidity_score, fee + entry.fee_tier),
+                );
+                TokenEntry {
+                    liquidity_score: liquidity / count,
+                    fee_tier: fee / count,
+                    ..first
+                }
+            }
+        }
+    }
+}
+
+impl FromStr for DedupStrategy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "first" | "keep-first" => Ok(DedupStrategy::KeepFirst),
+            "highest" | "keep-highest-liquidity" => Ok(DedupStrategy::KeepHighestLiquidity),
+            "average" | "average-numeric" => Ok(DedupStrategy::AverageNumeric),
+            _ => Err(format!("Unknown dedup strategy '{}' (expected first, highest or average)", s)),
+        }
+    }
+}
+
+/// Route identity: every field except the numeric scores
+pub(crate) type RouteKey = (u64, u64, String, String, String, String);
+
+pub(crate) fn route_key(entry: &TokenEntry) -> RouteKey {
+    (
+        entry.chain_origin,
+        entry.chain_dest,
+        entry.native_token.clone(),
+        entry.dex_origin.clone(),
+        entry.dex_dest.clone(),
+        entry.bridge_protocol.clone(),
+    )
+}
+
+/// Bridges routes may use, compared case-insensitively
+///
+/// A denied bridge is never used, even if also allowed. With an allowlist,
+/// any bridge not on it is denied too.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BridgePolicy {
+    pub allow: Vec<String>,
+    pub deny: Vec<String>,
+}
+
+impl BridgePolicy {
+    /// Comma-separated `BRIDGE_ALLOWLIST` and `BRIDGE_DENYLIST`
+    pub fn from_vars(var: impl Fn(&str) -> Option<String>) -> Self {
+        Self {
+            allow: var("BRIDGE_ALLOWLIST").map(|v| parse_bridge_list(&v)).unwrap_or_default(),
+            deny: var("BRIDGE_DENYLIST").map(|v| parse_bridge_list(&v)).unwrap_or_default(),
+        }
+    }
+
+    pub fn from_env() -> Self {
+        Self::from_vars(|name| std::env::var(name).ok())
+    }
+
+    /// Whether routes over `bridge` may be scored
+    pub fn permits(&self, bridge: &str) -> bool {
+        let listed = |list: &[String]| list.iter().any(|b| b.eq_ignore_ascii_case(bridge));
+        !listed(&self.deny) && (self.allow.is_empty() || listed(&self.allow))
+    }
+}
+
+/// Bridge names from a comma-separated list, blanks skipped
+pub fn parse_bridge_list(value: &str) -> Vec<String> {
+    value
+        .split(',')
+        .map(str::trim)
+        .filter(|name| !name.is_empty())
+        .map(String::from)
+        .collect()
+}
+
+/// Loaded token matrix with chainable route filters
+///
+/// Filters consume the matrix and return the narrowed one, so they can be
+/// chained: `matrix.filter_origin(1).filter_token("USDC").min_liquidity(90.0)`.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TokenMatrix {
+    entries: Vec<TokenEntry>,
+}
+
+impl TokenMatrix {
+    /// Wrap already-loaded entries
+    pub fn new(entries: Vec<TokenEntry>) -> Self {
+        Self { entries }
+    }
+
+    /// Load a matrix file in any supported format
+    pub fn load(path: &str) -> Result<Self, String> {
+        load_token_matrix_auto(path).map(Self::new)
+    }
+
+    /// Keep entries matching a predicate
+    pub fn filter<F>(self, predicate: F) -> Self
+    where
+        F: Fn(&TokenEntry) -> bool,
+    {
+        Self {
+            entries: self.entries.into_iter().filter(|e| predicate(e)).collect(),
+        }
+    }
+
+    /// Routes starting on `chain_id`
+    pub fn filter_origin(self, chain_id: u64) -> Self {
+        self.filter(|e| e.chain_origin == chain_id)
+    }
+
+    /// Routes ending on `chain_id`
+    pub fn filter_dest(self, chain_id: u64) -> Self {
+        self.filter(|e| e.chain_dest == chain_id)
+    }
+
+    /// Routes for a token symbol (case-insensitive)
+    pub fn filter_token(self, symbol: &str) -> Self {
+        self.filter(|e| e.native_token.eq_ignore_ascii_case(symbol))
+    }
+
+    /// Routes using a bridge (case-insensitive)
+    pub fn filter_bridge(self, bridge: &str) -> Self {
+        self.filter(|e| e.bridge_protocol.eq_ignore_ascii_case(bridge))
+    }
+
+    /// Routes over bridges `policy` permits
+    pub fn filter_bridges(self, policy: &BridgePolicy) -> Self {
+        self.filter(|e| policy.permits(&e.bridge_protocol))
+    }
+
+    /// Routes from `origin` to `dest`
+    pub fn routes_between(self, origin: u64, dest: u64) -> Self {
+        self.filter_origin(origin).filter_dest(dest)
+    }
+
+    /// Routes with liquidity score of at least `score`
+    pub fn min_liquidity(self, score: f64) -> Self {
+        self.filter(|e| e.liquidity_score >= score)
+    }
+
+    /// Routes not cooling down in `tracker`
+    pub fn off_cooldown(self, tracker: &CooldownTracker) -> Self {
+        let now = Instant::now();
+        self.filter(|e| tracker.is_ready_at(e, now))
+    }
+
+    /// Fill missing token addresses on every row from `resolver`
+    pub fn resolve_addresses(mut self, resolver: &impl AddressResolver) -> Self {
+        for entry in &mut self.entries {
+            entry.resolve_addresses(resolver);
+        }
+        self
+    }
+
+    /// Collapse duplicate routes, keeping first-seen order
+    ///
+    /// Rows are duplicates when origin, dest, token, both DEXes and bridge
+    /// all match; only the scores may differ.
+    pub fn dedupe(self, strategy: DedupStrategy) -> Self {
+        let mut index: HashMap<RouteKey, usize> = HashMap::new();
+        let mut groups: Vec<Vec<TokenEntry>> = Vec::new();
+        for entry in self.entries {
+            match index.entry(route_key(&entry)) {
+                Entry::Occupied(slot) => groups[*slot.get()].push(entry),
+                Entry::Vacant(slot) => {
+                    slot.insert(groups.len());
+                    groups.push(vec![entry]);
+                }
+            }
+        }
+
+        Self {
+            entries: groups.into_iter().map(|group| strategy.merge(group)).collect(),
+        }
+    }
+
+    /// Routes that appear more than once, with their occurrence count
+    ///
+    /// Returned in first-seen order, one (first entry, count) per route.
+    pub fn duplicate_routes(&self) -> Vec<(&TokenEntry, usize)> {
+        let mut index: HashMap<RouteKey, usize> = HashMap::new();
+        let mut counts: Vec<(&TokenEntry, usize)> = Vec::new();
+        for entry in &self.entries {
+            match index.entry(route_key(entry)) {
+                Entry::Occupied(slot) => counts[*slot.get()].1 += 1,
+                Entry::Vacant(slot) => {
+                    slot.insert(counts.len());
+                    counts.push((entry, 1));
+                }
+            }
+        }
+        counts.retain(|(_, count)| *count > 1);
+        counts
+    }
+
+    /// Group routes by (origin, dest) chain pair
+    pub fn group_by_pair(&self) -> HashMap<(u64, u64), Vec<&TokenEntry>> {
+        let mut groups: HashMap<(u64, u64), Vec<&TokenEntry>> = HashMap::new();
+        for entry in &self.entries {
+            groups
+                .entry((entry.chain_origin, entry.chain_dest))
+                .or_default()
+                .push(entry);
+        }
+        groups
+    }
+
+    pub fn entries(&self) -> &[TokenEntry] {
+        &self.entries
+    }
+
+    pub fn into_entries(self) -> Vec<TokenEntry> {
+        self.entries
+    }
+
+    pub fn iter(&self) -> std::slice::Iter<'_, TokenEntry> {
+        self.entries.iter()
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
+
+impl From<Vec<TokenEntry>> for TokenMatrix {
+    fn from(entries: Vec<TokenEntry>) -> Self {
+        Self::new(entries)
+    }
+}
+
+impl IntoIterator for TokenMatrix {
+    type Item = TokenEntry;
+    type IntoIter = std::vec::IntoIter<TokenEntry>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.entries.into_iter()
+    }
+}
+
+impl<'a> IntoIterator for &'a TokenMatrix {
+    type Item = &'a TokenEntry;
+    type IntoIter = std::slice::Iter<'a, TokenEntry>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.entries.iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FIXTURE: &[(u64, u64, &str, &str, f64)] = &[
+        (1, 137, "USDC", "LIFI", 95.0),
+        (1, 42161, "ETH", "STARGATE", 98.0),
+        (137, 42161, "USDC", "ACROSS", 92.0),
+        (137, 10, "USDT", "HOP", 88.0),
+        (42161, 10, "ETH", "SYNAPSE", 90.0),
+        (10, 8453, "USDC", "CCIP", 85.0),
+        (8453, 1, "ETH", "SOCKET", 87.0),
+        (1, 56, "BNB", "CELER", 80.0),
+        (56, 137, "USDC", "MULTICHAIN", 75.0),
+        (137, 43114, "AVAX", "AVALANCHE_BRIDGE", 82.0),
+        (43114, 42161, "USDC", "LAYERZERO", 78.0),
+        (1, 10, "WBTC", "OPTIMISM_BRIDGE", 91.0),
+        (10, 42161, "DAI", "ARBITRUM_BRIDGE", 89.0),
+        (42161, 137, "WMATIC", "POLYGON_BRIDGE", 84.0),
+        (137, 1, "LINK", "LIFI", 93.0),
+        (1, 8453, "USDC", "BASE_BRIDGE", 96.0),
+        (8453, 10, "ETH", "CCIP", 88.0),
+        (10, 1, "SNX", "OPTIMISM_BRIDGE", 79.0),
+        (42161, 8453, "USDC", "SOCKET", 86.0),
+        (1, 137, "WETH", "STARGATE", 97.0),
+    ];
+
+    fn fixture() -> TokenMatrix {
+        FIXTURE
+            .iter()
+            .map(|(origin, dest, token, bridge, liquidity)| TokenEntry {
+                chain_origin: *origin,
+                chain_dest: *dest,
+                native_token: token.to_string(),
+                dex_origin: "UNISWAP_V3".to_string(),
+                dex_dest: "QUICKSWAP".to_string(),
+                bridge_protocol: bridge.to_string(),
+                liquidity_score: *liquidity,
+                fee_tier: 0.3,
+                ..Default::default()
+            })
+            .collect::<Vec<_>>()
+            .into()
+    }
+
+    #[test]
+    fn test_fixture_size() {
+        assert_eq!(fixture().len(), 20);
+    }
+
+    #[test]
+    fn test_filter_origin() {
+        let matrix = fixture().filter_origin(1);
+        assert_eq!(matrix.len(), 6);
+        assert!(matrix.iter().all(|e| e.chain_origin == 1));
+    }
+
+    #[test]
+    fn test_filter_dest() {
+        let matrix = fixture().filter_dest(10);
+        assert_eq!(matrix.len(), 4);
+        assert!(matrix.iter().all(|e| e.chain_dest == 10));
+    }
+
+    #[test]
+    fn test_filter_token() {
+        assert_eq!(fixture().filter_token("USDC").len(), 7);
+        assert_eq!(fixture().filter_token("usdc").len(), 7);
+    }
+
+    #[test]
+    fn test_filter_bridge() {
+        assert_eq!(fixture().filter_bridge("CCIP").len(), 2);
+        assert_eq!(fixture().filter_bridge("optimism_bridge").len(), 2);
+    }
+
+    #[test]
+    fn test_bridge_policy_excludes_denied_bridges() {
+        let deny = BridgePolicy { deny: parse_bridge_list("socket, lifi"), ..Default::default() };
+        let matrix = fixture().filter_bridges(&deny);
+        assert_eq!(matrix.len(), 16);
+        assert!(matrix.iter().all(|e| e.bridge_protocol != "SOCKET" && e.bridge_protocol != "LIFI"));
+
+        // Deny wins over allow; unlisted bridges are out once there's an allowlist
+        let policy = BridgePolicy { allow: parse_bridge_list("CCIP,STARGATE,LIFI"), deny: vec!["lifi".to_string()] };
+        let bridges: Vec<_> = fixture().filter_bridges(&policy).iter().map(|e| e.bridge_protocol.clone()).collect();
+        assert_eq!(bridges, ["STARGATE", "CCIP", "CCIP", "STARGATE"]);
+
+        assert_eq!(fixture().filter_bridges(&BridgePolicy::default()).len(), 20);
+        let from_env = BridgePolicy::from_vars(|name| (name == "BRIDGE_DENYLIST").then(|| " HOP ,,".to_string()));
+        assert_eq!(from_env, BridgePolicy { allow: vec![], deny: vec!["HOP".to_string()] });
+    }
+
+    #[test]
+    fn test_routes_between() {
+        let matrix = fixture().routes_between(1, 137);
+        let tokens: Vec<_> = matrix.iter().map(|e| e.native_token.as_str()).collect();
+        assert_eq!(tokens, vec!["USDC", "WETH"]);
+    }
+
+    #[test]
+    fn test_min_liquidity() {
+        let matrix = fixture().min_liquidity(95.0);
+        assert_eq!(matrix.len(), 4);
+        assert!(matrix.iter().all(|e| e.liquidity_score >= 95.0));
+    }
+
+    #[test]
+    fn test_chained_filters() {
+        let matrix = fixture()
+            .filter_origin(1)
+            .filter_token("USDC")
+            .min_liquidity(96.0);
+        assert_eq!(matrix.len(), 1);
+        assert_eq!(matrix.entries()[0].chain_dest, 8453);
+
+        assert!(fixture().filter_origin(1).filter_dest(1).is_empty());
+    }
+
+    #[test]
+    fn test_group_by_pair() {
+        let matrix = fixture();
+        let groups = matrix.group_by_pair();
+        assert_eq!(groups.len(), 19);
+        assert_eq!(groups[&(1, 137)].len(), 2);
+        assert_eq!(groups[&(137, 42161)].len(), 1);
+    }
+
+    fn duplicates() -> TokenMatrix {
+        let base = fixture().entries()[0].clone();
+        let mut entries = vec![base.clone(), base.clone(), base];
+        entries[0].liquidity_score = 90.0;
+        entries[1].liquidity_score = 96.0;
+        entries[2].liquidity_score = 93.0;
+        entries[0].fee_tier = 0.3;
+        entries[1].fee_tier = 0.05;
+        entries[2].fee_tier = 0.1;
+        entries.push(fixture().entries()[1].clone());
+        TokenMatrix::new(entries)
+    }
+
+    #[test]
+    fn test_dedupe_keep_first() {
+        let matrix = duplicates().dedupe(DedupStrategy::KeepFirst);
+        assert_eq!(matrix.len(), 2);
+        assert_eq!(matrix.entries()[0].liquidity_score, 90.0);
+        assert_eq!(matrix.entries()[0].fee_tier, 0.3);
+        assert_eq!(matrix.entries()[1], fixture().entries()[1]);
+    }
+
+    #[test]
+    fn test_dedupe_keep_highest_liquidity() {
+        let matrix = duplicates().dedupe(DedupStrategy::KeepHighestLiquidity);
+        assert_eq!(matrix.len(), 2);
+        assert_eq!(matrix.entries()[0].liquidity_score, 96.0);
+        assert_eq!(matrix.entries()[0].fee_tier, 0.05);
+    }
+
+    #[test]
+    fn test_dedupe_average_numeric() {
+        let matrix = duplicates().dedupe(DedupStrategy::AverageNumeric);
+        assert_eq!(matrix.len(), 2);
+        assert!((matrix.entries()[0].liquidity_score - 93.0).abs() < 1e-9);
+        assert!((matrix.entries()[0].fee_tier - 0.15).abs() < 1e-9);
+        assert_eq!(matrix.entries()[0].native_token, "USDC");
+    }
+
+    #[test]
+    fn test_duplicate_routes() {
+        let matrix = duplicates();
+        let duplicates = matrix.duplicate_routes();
+        assert_eq!(duplicates.len(), 1);
+        assert_eq!(duplicates[0].1, 3);
+        assert!(fixture().duplicate_routes().is_empty());
+    }
+
+    #[test]
+    fn test_dedup_strategy_from_str() {
+        assert_eq!("highest".parse(), Ok(DedupStrategy::KeepHighestLiquidity));
+        assert!("newest".parse::<DedupStrategy>().is_err());
+    }
+}
@@ -0,0 +1,244 @@
+use std::sync::Arc;
+
+use ethers::prelude::*;
+use pyo3::prelude::*;
+use pyo3::types::PyDict;
+
+use crate::address::{checksummed, validate_address};
+use crate::commander::TitanCommander;
+use crate::py_errors::{supported_chain, CallError, TitanError};
+
+/// Outcome of a loan sizing request, handed to Python as a dict
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct LoanDecision {
+    pub chain_id: u64,
+    pub token: Address,
+    pub target_amount: U256,
+    /// Safe amount in raw units; 0 when rejected
+    pub amount: U256,
+    pub approved: bool,
+}
+
+/// Hex address, checking the EIP-55 checksum of mixed-case input
+pub(crate) fn parse_address(value: &str) -> Result<Address, CallError> {
+    validate_address(value.trim()).map_err(|e| CallError::InvalidAddress(e.to_string()))
+}
+
+/// HTTP provider for `rpc_url`
+pub(crate) fn parse_rpc_url(rpc_url: &str) -> Result<Provider<Http>, CallError> {
+    Provider::<Http>::try_from(rpc_url).map_err(|e| CallError::Config(format!("Invalid RPC URL {}: {}", rpc_url, e)))
+}
+
+/// Raw amount as a decimal string, or hex with a `0x` prefix
+pub(crate) fn parse_amount(value: &str) -> Result<U256, CallError> {
+    let value = value.trim();
+    let parsed = match value.strip_prefix("0x") {
+        Some(hex) => U256::from_str_radix(hex, 16).ok(),
+        None => U256::from_dec_str(value).ok(),
+    };
+    parsed.ok_or_else(|| CallError::InvalidInput(format!("Invalid amount: {}", value)))
+}
+
+/// [`TitanCommander::optimize_loan_size`] on string inputs
+pub(crate) async fn decide_loan(
+    commander: &TitanCommander,
+    token_address: &str,
+    target_amount: &str,
+    decimals: u8,
+) -> Result<LoanDecision, CallError> {
+    let token = parse_address(token_address)?;
+    let target_amount = parse_amount(target_amount)?;
+    let amount = commander
+        .optimize_loan_size(token, target_amount, decimals)
+        .await
+        .map_err(CallError::from_rpc)?;
+    Ok(LoanDecision {
+        chain_id: commander.chain_id(),
+        token,
+        target_amount,
+        amount,
+        approved: !amount.is_zero(),
+    })
+}
+
+/// Python wrapper for TitanCommander
+///
+/// Async calls run to completion on the wrapper's own runtime with the GIL
+/// released. Raw amounts cross as strings since they overflow Python floats.
+#[pyclass(name = "TitanCommander")]
+pub(crate) struct PyTitanCommander {
+    inner: TitanCommander,
+    runtime: tokio::runtime::Runtime,
+}
+
+#[pymethods]
+impl PyTitanCommander {
+    #[new]
+    fn new(chain_id: u64, rpc_url: &str) -> PyResult<Self> {
+        supported_chain(chain_id)?;
+        let provider = parse_rpc_url(rpc_url)?;
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .map_err(|e| TitanError::new_err(format!("Failed to start runtime: {}", e)))?;
+        Ok(Self {
+            inner: TitanCommander::new(chain_id, Arc::new(provider)),
+            runtime,
+        })
+    }
+
+    /// Safe loan size for `target_amount` (raw units) of `token_address`
+    fn optimize_loan_size<'py>(
+        &self,
+        py: Python<'py>,
+        token_address: &str,
+        target_amount: &str,
+        decimals: u8,
+    ) -> PyResult<&'py PyDict> {
+        let decision = py.allow_threads(|| {
+            self.runtime.block_on(decide_loan(&self.inner, token_address, target_amount, decimals))
+        })?;
+        let dict = PyDict::new(py);
+        dict.set_item("chain_id", decision.chain_id)?;
+        dict.set_item("token", checksummed(&decision.token))?;
+        dict.set_item("target_amount", decision.target_amount.to_string())?;
+        dict.set_item("amount", decision.amount.to_string())?;
+        dict.set_item("approved", decision.approved)?;
+        Ok(dict)
+    }
+
+    #[getter]
+    fn chain_id(&self) -> u64 {
+        self.inner.chain_id()
+    }
+
+    #[getter]
+    fn get_min_loan_usd(&self) -> u64 {
+        self.inner.min_loan_usd
+    }
+
+    #[setter]
+    fn set_min_loan_usd(&mut self, min_usd: u64) {
+        self.inner.set_min_loan_usd(min_usd);
+    }
+
+    #[getter]
+    fn get_max_tvl_share(&self) -> f64 {
+        self.inner.max_tvl_share
+    }
+
+    #[setter]
+    fn set_max_tvl_share(&mut self, share: f64) {
+        self.inner.set_max_tvl_share(share);
+    }
+
+    #[getter]
+    fn get_slippage_tolerance(&self) -> f64 {
+        self.inner.slippage_tolerance
+    }
+
+    #[setter]
+    fn set_slippage_tolerance(&mut self, tolerance: f64) -> PyResult<()> {
+        self.inner
+            .set_slippage_tolerance(tolerance)
+            .map_err(|e| CallError::InvalidInput(e.to_string()).into())
+    }
+
+    #[getter]
+    fn get_min_pool_liquidity(&self) -> String {
+        self.inner.min_pool_liquidity.to_string()
+    }
+
+    #[setter]
+    fn set_min_pool_liquidity(&mut self, min_liquidity: &str) -> PyResult<()> {
+        self.inner.set_min_pool_liquidity(parse_amount(min_liquidity)?);
+        Ok(())
+    }
+
+    #[getter]
+    fn get_max_loan(&self) -> Option<String> {
+        self.inner.max_loan_raw.map(|max_loan| max_loan.to_string())
+    }
+
+    #[setter]
+    fn set_max_loan(&mut self, max_loan: Option<&str>) -> PyResult<()> {
+        self.inner.set_max_loan(max_loan.map(parse_amount).transpose()?);
+        Ok(())
+    }
+
+    #[getter]
+    fn get_min_profit_gas_ratio(&self) -> f64 {
+        self.inner.min_profit_gas_ratio
+    }
+
+    #[setter]
+    fn set_min_profit_gas_ratio(&mut self, ratio: f64) {
+        self.inner.set_min_profit_gas_ratio(ratio);
+    }
+
+    #[getter]
+    fn get_min_spread_pct(&self) -> f64 {
+        self.inner.min_spread_pct
+    }
+
+    #[setter]
+    fn set_min_spread_pct(&mut self, min_spread_pct: f64) {
+        self.inner.set_min_spread_pct(min_spread_pct);
+    }
+
+    #[getter]
+    fn get_max_price_deviation_bps(&self) -> u32 {
+        self.inner.max_price_deviation_bps
+    }
+
+    #[setter]
+    fn set_max_price_deviation_bps(&mut self, bps: u32) {
+        self.inner.set_max_price_deviation_bps(bps);
+    }
+}
+
+/// Add the commander class to the Python module
+pub(crate) fn register(m: &PyModule) -> PyResult<()> {
+    m.add_class::<PyTitanCommander>()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chain_reader::MockChainReader;
+    use crate::config::BALANCER_V3_VAULT;
+
+    fn commander(vault_balance: U256) -> TitanCommander {
+        let token: Address = "0x2791Bca1f2de4661ED88A30C99A7a9449Aa84174".parse().unwrap();
+        let vault: Address = BALANCER_V3_VAULT.parse().unwrap();
+        let provider = Arc::new(Provider::<Http>::try_from("http://localhost:8545").unwrap());
+        let reader = MockChainReader::new().with_balance(token, vault, vault_balance);
+        TitanCommander::new(137, provider).with_reader(Arc::new(reader))
+    }
+
+    #[tokio::test]
+    async fn test_decide_loan_approves_and_rejects_below_floor() {
+        let token = "0x2791Bca1f2de4661ED88A30C99A7a9449Aa84174";
+        let commander = commander(U256::from(10_000_000) * U256::exp10(6));
+
+        let decision = decide_loan(&commander, token, "1000000000", 6).await.unwrap();
+        assert!(decision.approved);
+        assert_eq!(decision.amount, U256::from(1_000_000_000u64));
+        assert_eq!(decision.chain_id, 137);
+
+        // 100 USDC is under the 500-unit floor
+        let decision = decide_loan(&commander, token, "0x5f5e100", 6).await.unwrap();
+        assert_eq!(decision.target_amount, U256::from(100_000_000u64));
+        assert!(!decision.approved);
+        assert_eq!(decision.amount, U256::zero());
+    }
+
+    #[tokio::test]
+    async fn test_decide_loan_rejects_bad_input() {
+        let commander = commander(U256::zero());
+        let err = decide_loan(&commander, "not-an-address", "1", 6).await.unwrap_err();
+        assert!(matches!(err, CallError::InvalidAddress(message) if message.contains("not-an-address")));
+        let err = decide_loan(&commander, "0x2791Bca1f2de4661ED88A30C99A7a9449Aa84174", "-5", 6).await.unwrap_err();
+        assert!(matches!(err, CallError::InvalidInput(_)));
+    }
+}
@@ -0,0 +1,110 @@
+use pyo3::create_exception;
+use pyo3::exceptions::PyException;
+use pyo3::prelude::*;
+
+use crate::enum_matrix::ChainId;
+
+create_exception!(titan_core, TitanError, PyException, "Base class of titan_core errors");
+create_exception!(titan_core, InvalidInputError, TitanError, "An argument that doesn't parse or isn't allowed");
+create_exception!(titan_core, InvalidAddressError, InvalidInputError, "A string that isn't a 20-byte hex address");
+create_exception!(titan_core, ChainNotSupportedError, InvalidInputError, "A chain ID titan_core has no support for");
+create_exception!(titan_core, MatrixParseError, InvalidInputError, "A token matrix file with invalid rows");
+create_exception!(titan_core, RpcError, TitanError, "The chain RPC failed or couldn't be reached");
+create_exception!(titan_core, QuoteRevertedError, TitanError, "The quoter reverted, e.g. for a missing or empty pool");
+create_exception!(titan_core, ConfigError, TitanError, "Configuration that can't be loaded or used");
+
+/// Failures mapped onto the Python exception types
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum CallError {
+    InvalidInput(String),
+    InvalidAddress(String),
+    ChainNotSupported(u64),
+    Rpc(String),
+    QuoteReverted(String),
+    Config(String),
+}
+
+impl CallError {
+    /// A failed chain read: reverts are told apart from transport failures
+    /// since retrying them won't help
+    pub(crate) fn from_rpc(error: anyhow::Error) -> Self {
+        let message = error.to_string();
+        if message.to_ascii_lowercase().contains("revert") {
+            CallError::QuoteReverted(message)
+        } else {
+            CallError::Rpc(message)
+        }
+    }
+}
+
+impl From<CallError> for PyErr {
+    fn from(error: CallError) -> Self {
+        match error {
+            CallError::InvalidInput(message) => InvalidInputError::new_err(message),
+            CallError::InvalidAddress(message) => InvalidAddressError::new_err(message),
+            CallError::ChainNotSupported(chain_id) => {
+                ChainNotSupportedError::new_err(format!("Unsupported chain ID: {}", chain_id))
+            }
+            CallError::Rpc(message) => RpcError::new_err(message),
+            CallError::QuoteReverted(message) => QuoteRevertedError::new_err(message),
+            CallError::Config(message) => ConfigError::new_err(message),
+        }
+    }
+}
+
+/// `chain_id` as a known chain
+pub(crate) fn supported_chain(chain_id: u64) -> Result<ChainId, CallError> {
+    ChainId::from_u64(chain_id).ok_or(CallError::ChainNotSupported(chain_id))
+}
+
+/// Add the exception types to the Python module
+pub(crate) fn register(py: Python, m: &PyModule) -> PyResult<()> {
+    m.add("TitanError", py.get_type::<TitanError>())?;
+    m.add("InvalidInputError", py.get_type::<InvalidInputError>())?;
+    m.add("InvalidAddressError", py.get_type::<InvalidAddressError>())?;
+    m.add("ChainNotSupportedError", py.get_type::<ChainNotSupportedError>())?;
+    m.add("MatrixParseError", py.get_type::<MatrixParseError>())?;
+    m.add("RpcError", py.get_type::<RpcError>())?;
+    m.add("QuoteRevertedError", py.get_type::<QuoteRevertedError>())?;
+    m.add("ConfigError", py.get_type::<ConfigError>())?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::anyhow;
+
+    #[test]
+    fn test_rpc_failures_are_classified() {
+        let reverted = anyhow!("(code: 3, message: execution reverted, data: Some(String(\"0x\")))");
+        assert!(matches!(CallError::from_rpc(reverted), CallError::QuoteReverted(_)));
+        let refused = anyhow!("error sending request for url (http://127.0.0.1:1/): connection refused");
+        assert!(matches!(CallError::from_rpc(refused), CallError::Rpc(_)));
+    }
+
+    #[tokio::test]
+    async fn test_quoter_revert_surfaces_as_quote_reverted() {
+        use crate::chain_reader::MockChainReader;
+        use crate::simulation_engine::TitanSimulationEngine;
+        use ethers::types::{Address, U256};
+        use std::sync::Arc;
+
+        let reader = MockChainReader::new().with_call_handler(|_, _| Err(anyhow!("execution reverted: SPL")));
+        let engine = TitanSimulationEngine::new(137, Arc::new(reader));
+        let token = Address::repeat_byte(1);
+        let err = engine
+            .try_get_price_impact(token, token, U256::from(1000), 3000, token)
+            .await
+            .unwrap_err();
+        assert!(matches!(CallError::from_rpc(err), CallError::QuoteReverted(_)));
+        // The lenient form still reads as an empty quote
+        assert_eq!(engine.get_price_impact(token, token, U256::from(1000), 3000, token).await.unwrap(), U256::zero());
+    }
+
+    #[test]
+    fn test_supported_chain() {
+        assert_eq!(supported_chain(137).unwrap(), ChainId::Polygon);
+        assert_eq!(supported_chain(999_999).unwrap_err(), CallError::ChainNotSupported(999_999));
+    }
+}
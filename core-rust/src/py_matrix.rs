@@ -0,0 +1,190 @@
+use pyo3::exceptions::PyOSError;
+use pyo3::prelude::*;
+use pyo3::types::{PyDict, PyList};
+
+use crate::config::Config;
+use crate::omniarb::{
+    fetch_live_quotes_async, fetch_simulated_quotes, load_token_matrix_json, load_token_matrix_with_options, MatrixError,
+    MatrixLoad, ParseDiagnostic, ParseOptions, QuoteRouter, RouteColumns, TarWeights, TokenEntry,
+};
+use crate::py_errors::{ConfigError, MatrixParseError};
+use crate::py_scoring::{columns_to_py, entry_from_py, PyQuoteInfo, PyTokenEntry};
+use crate::py_simulation::runtime;
+use crate::PyConfig;
+
+/// Notional each route is quoted for when the caller doesn't pick one (USD)
+const DEFAULT_QUOTE_AMOUNT_USD: f64 = 10_000.0;
+
+/// Load a markdown/CSV matrix, strictly or skipping invalid rows
+fn load_matrix(path: &str, strict: bool) -> Result<MatrixLoad, MatrixError> {
+    load_token_matrix_with_options(path, ParseOptions { strict, ..ParseOptions::default() })
+}
+
+fn diagnostics_to_py<'py>(py: Python<'py>, diagnostics: &[ParseDiagnostic]) -> PyResult<&'py PyList> {
+    let dicts = diagnostics
+        .iter()
+        .map(|diagnostic| {
+            let dict = PyDict::new(py);
+            dict.set_item("line", diagnostic.line)?;
+            dict.set_item("column", &diagnostic.column)?;
+            dict.set_item("raw_value", &diagnostic.raw_value)?;
+            dict.set_item("reason", &diagnostic.reason)?;
+            Ok(dict)
+        })
+        .collect::<PyResult<Vec<_>>>()?;
+    Ok(PyList::new(py, dicts))
+}
+
+/// `MatrixParseError` with the offending rows in its `diagnostics` attribute
+fn matrix_error_to_py(py: Python, error: MatrixError) -> PyErr {
+    let diagnostics = match &error {
+        MatrixError::Io(message) => return PyOSError::new_err(message.clone()),
+        MatrixError::Invalid(diagnostics) => diagnostics.as_slice(),
+        _ => &[],
+    };
+    let err = MatrixParseError::new_err(error.to_string());
+    match diagnostics_to_py(py, diagnostics).and_then(|list| err.value(py).setattr("diagnostics", list)) {
+        Ok(()) => err,
+        Err(e) => e,
+    }
+}
+
+fn entries_to_py<'py>(py: Python<'py>, entries: Vec<TokenEntry>) -> PyResult<&'py PyList> {
+    let entries = entries
+        .into_iter()
+        .map(|entry| Py::new(py, PyTokenEntry::from(entry)))
+        .collect::<PyResult<Vec<_>>>()?;
+    Ok(PyList::new(py, entries))
+}
+
+/// Load a markdown/CSV token matrix as an `(entries, diagnostics)` tuple
+///
+/// Strict mode raises `MatrixParseError` listing every invalid row in
+/// `.diagnostics`, so its diagnostics are always empty; otherwise invalid
+/// rows are skipped and reported there. A missing or unreadable file
+/// raises `OSError`.
+#[pyfunction(name = "load_token_matrix")]
+#[pyo3(signature = (path, strict = true))]
+fn py_load_token_matrix<'py>(py: Python<'py>, path: &str, strict: bool) -> PyResult<(&'py PyList, &'py PyList)> {
+    let load = py
+        .allow_threads(|| load_matrix(path, strict))
+        .map_err(|e| matrix_error_to_py(py, e))?;
+    Ok((entries_to_py(py, load.entries)?, diagnostics_to_py(py, &load.diagnostics)?))
+}
+
+/// Load a JSON token matrix (bare array or versioned document)
+///
+/// Raises `OSError` for a missing or unreadable file, as
+/// `load_token_matrix` does, and `MatrixParseError` for invalid JSON.
+#[pyfunction(name = "load_token_matrix_json")]
+fn py_load_token_matrix_json<'py>(py: Python<'py>, path: &str) -> PyResult<&'py PyList> {
+    let entries = py
+        .allow_threads(|| load_token_matrix_json(path))
+        .map_err(|e| matrix_error_to_py(py, e))?;
+    entries_to_py(py, entries)
+}
+
+/// Quotes for `entries` (`TokenEntry` objects or dicts), in order
+///
+/// Simulated quotes are computed locally; live mode asks the configured
+/// quote APIs for a trade of `amount_usd`, falling back to simulated
+/// quotes per route. Simulated quotes carry the fee of the route's bridge
+/// in `config`, or in the environment-loaded config without one. Either
+/// way the GIL is released while quoting.
+#[pyfunction(name = "fetch_live_quotes")]
+#[pyo3(signature = (entries, simulated = true, amount_usd = DEFAULT_QUOTE_AMOUNT_USD, config = None))]
+fn py_fetch_live_quotes<'py>(
+    py: Python<'py>,
+    entries: &PyList,
+    simulated: bool,
+    amount_usd: f64,
+    config: Option<PyRef<PyConfig>>,
+) -> PyResult<&'py PyList> {
+    let entries = entries.iter().map(|entry| entry_from_py(py, entry)).collect::<PyResult<Vec<_>>>()?;
+    let config = match config {
+        Some(config) => config.inner.clone(),
+        None => Config::from_env().map_err(|e| ConfigError::new_err(format!("Failed to load config: {}", e)))?,
+    };
+    let quotes = if simulated {
+        py.allow_threads(|| {
+            let router = QuoteRouter::new().with_bridge_fees(&config.intent_based_bridges);
+            fetch_simulated_quotes(&entries, &router)
+        })
+    } else {
+        let runtime = runtime()?;
+        py.allow_threads(|| {
+            let router = QuoteRouter::from_config(&config);
+            runtime.block_on(fetch_live_quotes_async(&entries, &router, amount_usd))
+        })
+    };
+    let quotes = quotes
+        .into_iter()
+        .map(|quote| Py::new(py, PyQuoteInfo::from(quote)))
+        .collect::<PyResult<Vec<_>>>()?;
+    Ok(PyList::new(py, quotes))
+}
+
+/// A matrix file as `score_matrix_columnar` columns, scored against
+/// simulated quotes
+///
+/// Rows go from the parser straight into the columns without a Python
+/// object per route; loading and scoring run with the GIL released.
+/// Invalid rows raise `MatrixParseError`.
+#[pyfunction]
+fn matrix_to_records<'py>(py: Python<'py>, path: &str) -> PyResult<&'py PyDict> {
+    let columns = py
+        .allow_threads(|| {
+            load_matrix(path, true).map(|load| RouteColumns::simulated(load.entries, &TarWeights::default()))
+        })
+        .map_err(|e| matrix_error_to_py(py, e))?;
+    columns_to_py(py, columns)
+}
+
+/// Add the matrix loading and quote functions to the Python module
+pub(crate) fn register(m: &PyModule) -> PyResult<()> {
+    m.add_function(wrap_pyfunction!(py_load_token_matrix, m)?)?;
+    m.add_function(wrap_pyfunction!(py_load_token_matrix_json, m)?)?;
+    m.add_function(wrap_pyfunction!(py_fetch_live_quotes, m)?)?;
+    m.add_function(wrap_pyfunction!(matrix_to_records, m)?)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SCHEMA_V2: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/../data/fixtures/omniarb_matrix_schema_v2.md");
+
+    #[test]
+    fn test_load_fixture_matrix() {
+        for strict in [true, false] {
+            let load = load_matrix(SCHEMA_V2, strict).unwrap();
+            assert_eq!(load.entries.len(), 3);
+            assert!(load.diagnostics.is_empty());
+
+            let row = PyTokenEntry::from(load.entries[1].clone());
+            assert_eq!(
+                row,
+                PyTokenEntry::from(TokenEntry {
+                    chain_origin: 1,
+                    chain_dest: 42161,
+                    native_token: "WETH".to_string(),
+                    dex_origin: "UNISWAP_V3".to_string(),
+                    dex_dest: "CAMELOT".to_string(),
+                    bridge_protocol: "STARGATE".to_string(),
+                    liquidity_score: 98.0,
+                    fee_tier: 0.05,
+                    token_address_origin: Some("0xC02aaA39b223FE8D0A0e5C4F27eAD9083C756Cc2".to_string()),
+                    token_address_dest: Some("0x82aF49447D8a07e3bd95BD0d56f35241523fBab1".to_string()),
+                    ..Default::default()
+                })
+            );
+        }
+    }
+
+    #[test]
+    fn test_load_missing_matrix_is_io_error() {
+        let err = load_matrix("/nonexistent/matrix.md", true).unwrap_err();
+        assert!(matches!(err, MatrixError::Io(_)));
+    }
+}
@@ -0,0 +1,448 @@
+use chrono::{DateTime, Utc};
+use numpy::IntoPyArray;
+use pyo3::prelude::*;
+use pyo3::types::{PyDict, PyList, PyType};
+
+use crate::omniarb::{
+    calculate_tar_breakdown, calculate_tar_breakdown_batch, calculate_tar_score, QuoteInfo, QuoteProvider,
+    RouteColumns, TarBreakdown, TarWeights, TokenEntry,
+};
+use crate::py_errors::InvalidInputError;
+
+/// Python mirror of a token matrix row
+#[pyclass(name = "TokenEntry")]
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct PyTokenEntry {
+    #[pyo3(get, set)]
+    chain_origin: u64,
+    #[pyo3(get, set)]
+    chain_dest: u64,
+    #[pyo3(get, set)]
+    native_token: String,
+    #[pyo3(get, set)]
+    dex_origin: String,
+    #[pyo3(get, set)]
+    dex_dest: String,
+    #[pyo3(get, set)]
+    bridge_protocol: String,
+    #[pyo3(get, set)]
+    liquidity_score: f64,
+    #[pyo3(get, set)]
+    fee_tier: f64,
+    #[pyo3(get, set)]
+    token_address_origin: Option<String>,
+    #[pyo3(get, set)]
+    token_address_dest: Option<String>,
+    #[pyo3(get, set)]
+    pool_address_origin: Option<String>,
+    #[pyo3(get, set)]
+    pool_address_dest: Option<String>,
+}
+
+impl From<TokenEntry> for PyTokenEntry {
+    fn from(entry: TokenEntry) -> Self {
+        Self {
+            chain_origin: entry.chain_origin,
+            chain_dest: entry.chain_dest,
+            native_token: entry.native_token,
+            dex_origin: entry.dex_origin,
+            dex_dest: entry.dex_dest,
+            bridge_protocol: entry.bridge_protocol,
+            liquidity_score: entry.liquidity_score,
+            fee_tier: entry.fee_tier,
+            token_address_origin: entry.token_address_origin,
+            token_address_dest: entry.token_address_dest,
+            pool_address_origin: entry.pool_address_origin,
+            pool_address_dest: entry.pool_address_dest,
+        }
+    }
+}
+
+impl From<PyTokenEntry> for TokenEntry {
+    fn from(entry: PyTokenEntry) -> Self {
+        Self {
+            chain_origin: entry.chain_origin,
+            chain_dest: entry.chain_dest,
+            native_token: entry.native_token,
+            dex_origin: entry.dex_origin,
+            dex_dest: entry.dex_dest,
+            bridge_protocol: entry.bridge_protocol,
+            liquidity_score: entry.liquidity_score,
+            fee_tier: entry.fee_tier,
+            token_address_origin: entry.token_address_origin,
+            token_address_dest: entry.token_address_dest,
+            pool_address_origin: entry.pool_address_origin,
+            pool_address_dest: entry.pool_address_dest,
+        }
+    }
+}
+
+#[pymethods]
+impl PyTokenEntry {
+    #[new]
+    #[pyo3(signature = (
+        chain_origin, chain_dest, native_token, dex_origin, dex_dest, bridge_protocol, liquidity_score, fee_tier,
+        token_address_origin = None, token_address_dest = None, pool_address_origin = None, pool_address_dest = None
+    ))]
+    #[allow(clippy::too_many_arguments)]
+    fn new(
+        chain_origin: u64,
+        chain_dest: u64,
+        native_token: String,
+        dex_origin: String,
+        dex_dest: String,
+        bridge_protocol: String,
+        liquidity_score: f64,
+        fee_tier: f64,
+        token_address_origin: Option<String>,
+        token_address_dest: Option<String>,
+        pool_address_origin: Option<String>,
+        pool_address_dest: Option<String>,
+    ) -> Self {
+        Self {
+            chain_origin,
+            chain_dest,
+            native_token,
+            dex_origin,
+            dex_dest,
+            bridge_protocol,
+            liquidity_score,
+            fee_tier,
+            token_address_origin,
+            token_address_dest,
+            pool_address_origin,
+            pool_address_dest,
+        }
+    }
+
+    /// Build from a dict keyed like the constructor's arguments
+    #[classmethod]
+    fn from_dict<'py>(cls: &'py PyType, values: &'py PyDict) -> PyResult<&'py PyAny> {
+        cls.call((), Some(values))
+    }
+
+    fn to_dict<'py>(&self, py: Python<'py>) -> PyResult<&'py PyDict> {
+        let dict = PyDict::new(py);
+        dict.set_item("chain_origin", self.chain_origin)?;
+        dict.set_item("chain_dest", self.chain_dest)?;
+        dict.set_item("native_token", &self.native_token)?;
+        dict.set_item("dex_origin", &self.dex_origin)?;
+        dict.set_item("dex_dest", &self.dex_dest)?;
+        dict.set_item("bridge_protocol", &self.bridge_protocol)?;
+        dict.set_item("liquidity_score", self.liquidity_score)?;
+        dict.set_item("fee_tier", self.fee_tier)?;
+        dict.set_item("token_address_origin", &self.token_address_origin)?;
+        dict.set_item("token_address_dest", &self.token_address_dest)?;
+        dict.set_item("pool_address_origin", &self.pool_address_origin)?;
+        dict.set_item("pool_address_dest", &self.pool_address_dest)?;
+        Ok(dict)
+    }
+
+    fn __eq__(&self, other: &Self) -> bool {
+        self == other
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "TokenEntry({} {}->{} via {})",
+            self.native_token, self.chain_origin, self.chain_dest, self.bridge_protocol
+        )
+    }
+}
+
+/// Python mirror of a route quote
+#[pyclass(name = "QuoteInfo")]
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct PyQuoteInfo {
+    #[pyo3(get, set)]
+    spread_percentage: f64,
+    #[pyo3(get, set)]
+    slippage_estimate: f64,
+    #[pyo3(get, set)]
+    gas_cost_usd: f64,
+    #[pyo3(get, set)]
+    available_liquidity: f64,
+    fetched_at: DateTime<Utc>,
+    source: QuoteProvider,
+    #[pyo3(get, set)]
+    bridge_fee_bps: u32,
+}
+
+impl From<QuoteInfo> for PyQuoteInfo {
+    fn from(quote: QuoteInfo) -> Self {
+        Self {
+            spread_percentage: quote.spread_percentage,
+            slippage_estimate: quote.slippage_estimate,
+            gas_cost_usd: quote.gas_cost_usd,
+            available_liquidity: quote.available_liquidity,
+            fetched_at: quote.fetched_at,
+            source: quote.source,
+            bridge_fee_bps: quote.bridge_fee_bps,
+        }
+    }
+}
+
+impl From<PyQuoteInfo> for QuoteInfo {
+    fn from(quote: PyQuoteInfo) -> Self {
+        Self {
+            spread_percentage: quote.spread_percentage,
+            slippage_estimate: quote.slippage_estimate,
+            gas_cost_usd: quote.gas_cost_usd,
+            available_liquidity: quote.available_liquidity,
+            fetched_at: quote.fetched_at,
+            source: quote.source,
+            bridge_fee_bps: quote.bridge_fee_bps,
+        }
+    }
+}
+
+/// RFC 3339 timestamp, e.g. `2026-01-05T04:14:34Z`
+fn parse_fetched_at(value: &str) -> Result<DateTime<Utc>, String> {
+    DateTime::parse_from_rfc3339(value)
+        .map(|fetched_at| fetched_at.with_timezone(&Utc))
+        .map_err(|e| format!("Invalid fetched_at '{}': {}", value, e))
+}
+
+/// Quote source by its serialized name, e.g. `socket`
+fn parse_source(value: &str) -> Result<QuoteProvider, String> {
+    serde_json::from_value(serde_json::Value::String(value.to_string()))
+        .map_err(|_| format!("Unknown quote source '{}'", value))
+}
+
+fn source_name(source: QuoteProvider) -> String {
+    serde_json::to_value(source)
+        .ok()
+        .and_then(|value| value.as_str().map(str::to_string))
+        .unwrap_or_default()
+}
+
+#[pymethods]
+impl PyQuoteInfo {
+    /// `fetched_at` defaults to now, `source` to `simulated` and
+    /// `bridge_fee_bps` to 0
+    #[new]
+    #[pyo3(signature = (spread_percentage, slippage_estimate, gas_cost_usd, available_liquidity, fetched_at = None, source = None, bridge_fee_bps = 0))]
+    fn new(
+        spread_percentage: f64,
+        slippage_estimate: f64,
+        gas_cost_usd: f64,
+        available_liquidity: f64,
+        fetched_at: Option<&str>,
+        source: Option<&str>,
+        bridge_fee_bps: u32,
+    ) -> PyResult<Self> {
+        let fetched_at = fetched_at.map(parse_fetched_at).transpose().map_err(InvalidInputError::new_err)?;
+        let source = source.map(parse_source).transpose().map_err(InvalidInputError::new_err)?;
+        Ok(Self {
+            spread_percentage,
+            slippage_estimate,
+            gas_cost_usd,
+            available_liquidity,
+            fetched_at: fetched_at.unwrap_or_else(Utc::now),
+            source: source.unwrap_or_default(),
+            bridge_fee_bps,
+        })
+    }
+
+    /// Build from a dict keyed like the constructor's arguments
+    #[classmethod]
+    fn from_dict<'py>(cls: &'py PyType, values: &'py PyDict) -> PyResult<&'py PyAny> {
+        cls.call((), Some(values))
+    }
+
+    fn to_dict<'py>(&self, py: Python<'py>) -> PyResult<&'py PyDict> {
+        let dict = PyDict::new(py);
+        dict.set_item("spread_percentage", self.spread_percentage)?;
+        dict.set_item("slippage_estimate", self.slippage_estimate)?;
+        dict.set_item("gas_cost_usd", self.gas_cost_usd)?;
+        dict.set_item("available_liquidity", self.available_liquidity)?;
+        dict.set_item("fetched_at", self.get_fetched_at())?;
+        dict.set_item("source", self.get_source())?;
+        dict.set_item("bridge_fee_bps", self.bridge_fee_bps)?;
+        Ok(dict)
+    }
+
+    #[getter]
+    fn get_fetched_at(&self) -> String {
+        self.fetched_at.to_rfc3339()
+    }
+
+    #[setter]
+    fn set_fetched_at(&mut self, fetched_at: &str) -> PyResult<()> {
+        self.fetched_at = parse_fetched_at(fetched_at).map_err(InvalidInputError::new_err)?;
+        Ok(())
+    }
+
+    #[getter]
+    fn get_source(&self) -> String {
+        source_name(self.source)
+    }
+
+    #[setter]
+    fn set_source(&mut self, source: &str) -> PyResult<()> {
+        self.source = parse_source(source).map_err(InvalidInputError::new_err)?;
+        Ok(())
+    }
+
+    fn __eq__(&self, other: &Self) -> bool {
+        self == other
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "QuoteInfo(spread={}%, slippage={}%, gas=${})",
+            self.spread_percentage, self.slippage_estimate, self.gas_cost_usd
+        )
+    }
+}
+
+/// A `TokenEntry` or a dict of its fields
+pub(crate) fn entry_from_py(py: Python, value: &PyAny) -> PyResult<TokenEntry> {
+    let entry: PyTokenEntry = match value.downcast::<PyDict>() {
+        Ok(dict) => py.get_type::<PyTokenEntry>().call((), Some(dict))?.extract()?,
+        Err(_) => value.extract()?,
+    };
+    Ok(entry.into())
+}
+
+/// A `QuoteInfo` or a dict of its fields
+fn quote_from_py(py: Python, value: &PyAny) -> PyResult<QuoteInfo> {
+    let quote: PyQuoteInfo = match value.downcast::<PyDict>() {
+        Ok(dict) => py.get_type::<PyQuoteInfo>().call((), Some(dict))?.extract()?,
+        Err(_) => value.extract()?,
+    };
+    Ok(quote.into())
+}
+
+fn breakdown_to_py<'py>(py: Python<'py>, breakdown: &TarBreakdown) -> PyResult<&'py PyDict> {
+    let dict = PyDict::new(py);
+    dict.set_item("token_quality", breakdown.token_quality)?;
+    dict.set_item("arbitrage_efficiency", breakdown.arbitrage_efficiency)?;
+    dict.set_item("risk", breakdown.risk)?;
+    dict.set_item("total", breakdown.total)?;
+    dict.set_item("notes", PyList::new(py, &breakdown.notes))?;
+    Ok(dict)
+}
+
+/// TAR score (0-100) of `entry` against `quote`, with default weights
+#[pyfunction(name = "calculate_tar_score")]
+fn py_calculate_tar_score(py: Python, entry: &PyAny, quote: &PyAny) -> PyResult<f64> {
+    Ok(calculate_tar_score(&entry_from_py(py, entry)?, &quote_from_py(py, quote)?))
+}
+
+/// T, A and R components, total and per-input notes
+#[pyfunction(name = "calculate_tar_breakdown")]
+fn py_calculate_tar_breakdown<'py>(py: Python<'py>, entry: &PyAny, quote: &PyAny) -> PyResult<&'py PyDict> {
+    let breakdown = calculate_tar_breakdown(&entry_from_py(py, entry)?, &quote_from_py(py, quote)?);
+    breakdown_to_py(py, &breakdown)
+}
+
+/// Entries and quotes of equal-length lists, paired up by index
+fn routes_from_py(py: Python, entries: &PyList, quotes: &PyList) -> PyResult<(Vec<TokenEntry>, Vec<QuoteInfo>)> {
+    if entries.len() != quotes.len() {
+        return Err(InvalidInputError::new_err(format!(
+            "Got {} entries but {} quotes",
+            entries.len(),
+            quotes.len()
+        )));
+    }
+    let entries = entries.iter().map(|entry| entry_from_py(py, entry)).collect::<PyResult<Vec<_>>>()?;
+    let quotes = quotes.iter().map(|quote| quote_from_py(py, quote)).collect::<PyResult<Vec<_>>>()?;
+    Ok((entries, quotes))
+}
+
+/// Column name ⇒ numpy array (numeric columns) or list (string columns),
+/// ready for `pandas.DataFrame`
+///
+/// The arrays take over the columns' buffers rather than copying them.
+/// Raises `ImportError` when numpy isn't installed.
+pub(crate) fn columns_to_py<'py>(py: Python<'py>, columns: RouteColumns) -> PyResult<&'py PyDict> {
+    // The numpy crate panics rather than erroring without it
+    py.import("numpy")?;
+    let dict = PyDict::new(py);
+    dict.set_item("chain_origin", columns.chain_origin.into_pyarray(py))?;
+    dict.set_item("chain_dest", columns.chain_dest.into_pyarray(py))?;
+    dict.set_item("native_token", PyList::new(py, columns.native_token))?;
+    dict.set_item("dex_origin", PyList::new(py, columns.dex_origin))?;
+    dict.set_item("dex_dest", PyList::new(py, columns.dex_dest))?;
+    dict.set_item("bridge_protocol", PyList::new(py, columns.bridge_protocol))?;
+    dict.set_item("tar", columns.tar.into_pyarray(py))?;
+    dict.set_item("model_tar", columns.model_tar.into_pyarray(py))?;
+    dict.set_item("flanker", columns.flanker.into_pyarray(py))?;
+    dict.set_item("liquidity", columns.liquidity.into_pyarray(py))?;
+    Ok(dict)
+}
+
+/// Breakdown of `entries[i]` against `quotes[i]` for every route
+///
+/// Scoring runs across threads with the GIL released.
+#[pyfunction]
+fn score_matrix<'py>(py: Python<'py>, entries: &PyList, quotes: &PyList) -> PyResult<&'py PyList> {
+    let (entries, quotes) = routes_from_py(py, entries, quotes)?;
+    let breakdowns = py.allow_threads(|| calculate_tar_breakdown_batch(&entries, &quotes, &TarWeights::default()));
+    let dicts = breakdowns
+        .iter()
+        .map(|breakdown| breakdown_to_py(py, breakdown))
+        .collect::<PyResult<Vec<_>>>()?;
+    Ok(PyList::new(py, dicts))
+}
+
+/// Route fields, TAR score and model predictions of every route, by column
+///
+/// One array or list per column instead of a dict per route; `tar`
+/// matches `score_matrix`'s totals. Scoring runs with the GIL released.
+#[pyfunction]
+fn score_matrix_columnar<'py>(py: Python<'py>, entries: &PyList, quotes: &PyList) -> PyResult<&'py PyDict> {
+    let (entries, quotes) = routes_from_py(py, entries, quotes)?;
+    let columns = py.allow_threads(|| RouteColumns::score(entries, &quotes, &TarWeights::default()));
+    columns_to_py(py, columns)
+}
+
+/// Add the scoring classes and functions to the Python module
+pub(crate) fn register(m: &PyModule) -> PyResult<()> {
+    m.add_class::<PyTokenEntry>()?;
+    m.add_class::<PyQuoteInfo>()?;
+    m.add_function(wrap_pyfunction!(py_calculate_tar_score, m)?)?;
+    m.add_function(wrap_pyfunction!(py_calculate_tar_breakdown, m)?)?;
+    m.add_function(wrap_pyfunction!(score_matrix, m)?)?;
+    m.add_function(wrap_pyfunction!(score_matrix_columnar, m)?)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mirrors_round_trip() {
+        let entry = TokenEntry {
+            chain_origin: 1,
+            chain_dest: 137,
+            native_token: "USDC".to_string(),
+            bridge_protocol: "STARGATE".to_string(),
+            liquidity_score: 95.0,
+            fee_tier: 0.1,
+            token_address_origin: Some("0xA0b86991c6218b36c1d19D4a2e9Eb0cE3606eB48".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(TokenEntry::from(PyTokenEntry::from(entry.clone())), entry);
+
+        let quote = PyQuoteInfo::from(QuoteInfo {
+            spread_percentage: 1.5,
+            source: QuoteProvider::Socket,
+            ..Default::default()
+        });
+        assert_eq!(PyQuoteInfo::from(QuoteInfo::from(quote.clone())), quote);
+    }
+
+    #[test]
+    fn test_quote_fields_parse_their_serialized_form() {
+        assert_eq!(parse_source(&source_name(QuoteProvider::Socket)), Ok(QuoteProvider::Socket));
+        assert_eq!(source_name(QuoteProvider::Simulated), "simulated");
+        assert!(parse_source("carrier-pigeon").is_err());
+
+        let fetched_at = Utc::now();
+        assert_eq!(parse_fetched_at(&fetched_at.to_rfc3339()), Ok(fetched_at));
+        assert!(parse_fetched_at("yesterday").is_err());
+    }
+}
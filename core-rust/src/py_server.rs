@@ -0,0 +1,98 @@
+use std::time::Duration;
+
+use pyo3::prelude::*;
+use pyo3::types::PyType;
+
+use crate::config::Config;
+use crate::http_server::{ServerError, ServerHandle};
+use crate::py_errors::{ConfigError, TitanError};
+use crate::PyConfig;
+
+/// Seconds `stop()` and `with` blocks wait for a graceful shutdown
+const DEFAULT_STOP_TIMEOUT_SECS: f64 = 5.0;
+
+impl From<ServerError> for PyErr {
+    fn from(error: ServerError) -> Self {
+        match error {
+            ServerError::Bind { .. } | ServerError::Startup(_) => ConfigError::new_err(error.to_string()),
+            ServerError::Serve(_) | ServerError::StopTimeout(_) => TitanError::new_err(error.to_string()),
+        }
+    }
+}
+
+/// The HTTP API running on a background thread, from `start_server`
+///
+/// Usable as a context manager, which stops the server on exit.
+#[pyclass(name = "ServerHandle")]
+pub(crate) struct PyServerHandle {
+    inner: ServerHandle,
+}
+
+#[pymethods]
+impl PyServerHandle {
+    /// The port actually bound, also when 0 was asked for
+    #[getter]
+    fn port(&self) -> u16 {
+        self.inner.port()
+    }
+
+    fn is_running(&self) -> bool {
+        self.inner.is_running()
+    }
+
+    /// Shut down gracefully, waiting up to `timeout_secs` for in-flight
+    /// requests
+    #[pyo3(signature = (timeout_secs = DEFAULT_STOP_TIMEOUT_SECS))]
+    fn stop(&mut self, py: Python, timeout_secs: f64) -> PyResult<()> {
+        let timeout = Duration::try_from_secs_f64(timeout_secs)
+            .map_err(|e| TitanError::new_err(format!("Invalid timeout {}: {}", timeout_secs, e)))?;
+        let inner = &mut self.inner;
+        Ok(py.allow_threads(|| inner.stop(timeout))?)
+    }
+
+    fn __enter__(slf: PyRef<Self>) -> PyRef<Self> {
+        slf
+    }
+
+    fn __exit__(
+        &mut self,
+        py: Python,
+        _exc_type: Option<&PyType>,
+        _exc: Option<&PyAny>,
+        _traceback: Option<&PyAny>,
+    ) -> PyResult<bool> {
+        self.stop(py, DEFAULT_STOP_TIMEOUT_SECS)?;
+        Ok(false)
+    }
+
+    fn __repr__(&self) -> String {
+        let state = if self.inner.is_running() { "running" } else { "stopped" };
+        format!("ServerHandle(port={}, {})", self.inner.port(), state)
+    }
+}
+
+/// Serve the HTTP API on `port` (0 picks a free one) until stopped
+///
+/// Without `config` it's loaded from the environment, as the server binary
+/// does. Returns once the server accepts connections; a port in use or a
+/// failed startup raises `ConfigError`.
+#[pyfunction]
+#[pyo3(signature = (port, config = None))]
+fn start_server(py: Python, port: u16, config: Option<PyRef<PyConfig>>) -> PyResult<PyServerHandle> {
+    let config = config.map(|config| config.inner.clone());
+    let inner = py.allow_threads(|| {
+        let config = match config {
+            Some(config) => config,
+            None => Config::from_env().map_err(|e| ConfigError::new_err(format!("Failed to load config: {}", e)))?,
+        };
+        Ok::<_, PyErr>(ServerHandle::spawn(config, port)?)
+    })?;
+    Ok(PyServerHandle { inner })
+}
+
+/// Add the server handle class and `start_server` to the Python module
+pub(crate) fn register(m: &PyModule) -> PyResult<()> {
+    m.add_class::<PyServerHandle>()?;
+    m.add_function(wrap_pyfunction!(start_server, m)?)?;
+    Ok(())
+}
@@ -0,0 +1,357 @@
+use std::future::Future;
+use std::sync::{Arc, OnceLock};
+
+use ethers::prelude::*;
+use log::debug;
+use pyo3::prelude::*;
+use pyo3::sync::GILOnceCell;
+use pyo3::types::{PyDict, PyModule};
+
+use crate::aave::{get_aave_reserve_tokens_from, ReserveTokens};
+use crate::address::checksummed;
+use crate::chain_reader::ChainReader;
+use crate::py_commander::{parse_address, parse_amount, parse_rpc_url};
+use crate::py_errors::{supported_chain, CallError, TitanError};
+use crate::simulation_engine::TitanSimulationEngine;
+
+/// Completes an asyncio future from the loop's own thread, unless the
+/// awaiting task was cancelled in the meantime
+const COMPLETE_FUTURE_SRC: &str = r#"
+def complete(future, method, value):
+    if not future.done():
+        getattr(future, method)(value)
+"#;
+
+static COMPLETE_FUTURE: GILOnceCell<PyObject> = GILOnceCell::new();
+
+/// Runtime shared by every engine, so awaitables keep running between calls
+/// and are never torn down while a task waits on the GIL
+static RUNTIME: OnceLock<tokio::runtime::Runtime> = OnceLock::new();
+
+pub(crate) fn runtime() -> PyResult<&'static tokio::runtime::Runtime> {
+    if let Some(runtime) = RUNTIME.get() {
+        return Ok(runtime);
+    }
+    let runtime = tokio::runtime::Builder::new_multi_thread()
+        .worker_threads(2)
+        .thread_name("titan-py")
+        .enable_all()
+        .build()
+        .map_err(|e| TitanError::new_err(format!("Failed to start runtime: {}", e)))?;
+    Ok(RUNTIME.get_or_init(|| runtime))
+}
+
+/// Upper and lower 128 bits of `value`
+fn u256_halves(value: U256) -> (u128, u128) {
+    ((value >> 128).low_u128(), value.low_u128())
+}
+
+/// `value` as a Python int, exact across the full 256 bits
+fn u256_to_py(py: Python, value: U256) -> PyResult<PyObject> {
+    let (high, low) = u256_halves(value);
+    if high == 0 {
+        return Ok(low.into_py(py));
+    }
+    high.into_py(py)
+        .call_method1(py, "__lshift__", (128u32,))?
+        .call_method1(py, "__or__", (low,))
+}
+
+/// Raw amount from a Python int, or a decimal / `0x` hex string
+fn amount_from_py(value: &PyAny) -> PyResult<U256> {
+    Ok(parse_amount(value.str()?.to_str()?)?)
+}
+
+fn reserve_tokens_to_py(py: Python, tokens: ReserveTokens) -> PyResult<PyObject> {
+    let dict = PyDict::new(py);
+    dict.set_item("a_token", checksummed(&tokens.a_token))?;
+    dict.set_item("stable_debt_token", checksummed(&tokens.stable_debt_token))?;
+    dict.set_item("variable_debt_token", checksummed(&tokens.variable_debt_token))?;
+    Ok(dict.into())
+}
+
+/// Run `future` on the shared runtime and return an asyncio future for it
+///
+/// Must be called with an event loop running on this thread. The result is
+/// converted under the GIL once ready and handed back through
+/// `call_soon_threadsafe`, so the loop never blocks on the RPC.
+fn awaitable<'py, T, F>(
+    py: Python<'py>,
+    future: F,
+    convert: fn(Python, T) -> PyResult<PyObject>,
+) -> PyResult<&'py PyAny>
+where
+    T: Send + 'static,
+    F: Future<Output = Result<T, CallError>> + Send + 'static,
+{
+    let event_loop = py.import("asyncio")?.call_method0("get_running_loop")?;
+    let py_future = event_loop.call_method0("create_future")?;
+    let complete = COMPLETE_FUTURE
+        .get_or_try_init(py, || -> PyResult<PyObject> {
+            let module = PyModule::from_code(py, COMPLETE_FUTURE_SRC, "titan_core_async.py", "titan_core_async")?;
+            Ok(module.getattr("complete")?.into())
+        })?
+        .clone_ref(py);
+    let event_loop: PyObject = event_loop.into();
+    let target: PyObject = py_future.into();
+
+    runtime()?.spawn(async move {
+        let result = future.await;
+        Python::with_gil(|py| {
+            let (method, value) = match result.map_err(PyErr::from).and_then(|value| convert(py, value)) {
+                Ok(value) => ("set_result", value),
+                Err(err) => ("set_exception", err.into_value(py).into_py(py)),
+            };
+            // Fails only when the loop was closed before the call finished
+            if let Err(e) = event_loop.call_method1(py, "call_soon_threadsafe", (complete, target, method, value)) {
+                debug!("Dropped result for a closed event loop: {}", e);
+            }
+        });
+    });
+    Ok(py_future)
+}
+
+/// Python wrapper for TitanSimulationEngine
+///
+/// Each read has a blocking form, which releases the GIL while it waits, and
+/// an `_async` form returning an awaitable for asyncio code. Raw amounts come
+/// back as Python ints and addresses cross as hex strings.
+#[pyclass(name = "SimulationEngine")]
+pub(crate) struct PySimulationEngine {
+    inner: Arc<TitanSimulationEngine>,
+    reader: Arc<dyn ChainReader>,
+}
+
+impl PySimulationEngine {
+    fn block_on<T>(&self, py: Python, future: impl Future<Output = Result<T, CallError>> + Send) -> PyResult<T>
+    where
+        T: Send,
+    {
+        let runtime = runtime()?;
+        Ok(py.allow_threads(|| runtime.block_on(future))?)
+    }
+
+    fn lender_tvl(&self, token: &str, lender: &str) -> PyResult<impl Future<Output = Result<U256, CallError>>> {
+        let (token, lender) = (parse_address(token)?, parse_address(lender)?);
+        let engine = Arc::clone(&self.inner);
+        Ok(async move { engine.try_get_lender_tvl(token, lender).await.map_err(CallError::from_rpc) })
+    }
+
+    fn price_impact(
+        &self,
+        token_in: &str,
+        token_out: &str,
+        amount: &PyAny,
+        fee: u32,
+        quoter: &str,
+    ) -> PyResult<impl Future<Output = Result<U256, CallError>>> {
+        let (token_in, token_out, quoter) = (parse_address(token_in)?, parse_address(token_out)?, parse_address(quoter)?);
+        let amount = amount_from_py(amount)?;
+        let engine = Arc::clone(&self.inner);
+        Ok(async move {
+            engine
+                .try_get_price_impact(token_in, token_out, amount, fee, quoter)
+                .await
+                .map_err(CallError::from_rpc)
+        })
+    }
+
+    fn block_number(&self) -> impl Future<Output = Result<u64, CallError>> {
+        let engine = Arc::clone(&self.inner);
+        async move { engine.get_block_number().await.map_err(CallError::from_rpc) }
+    }
+
+    fn reserve_tokens(&self, token: &str, aave_pool: &str) -> PyResult<impl Future<Output = Result<ReserveTokens, CallError>>> {
+        let (token, aave_pool) = (parse_address(token)?, parse_address(aave_pool)?);
+        let reader = Arc::clone(&self.reader);
+        Ok(async move {
+            get_aave_reserve_tokens_from(reader.as_ref(), token, aave_pool)
+                .await
+                .map_err(CallError::from_rpc)
+        })
+    }
+}
+
+#[pymethods]
+impl PySimulationEngine {
+    #[new]
+    #[pyo3(signature = (chain_id, rpc_url, offline = false))]
+    fn new(chain_id: u64, rpc_url: &str, offline: bool) -> PyResult<Self> {
+        supported_chain(chain_id)?;
+        let provider = parse_rpc_url(rpc_url)?;
+        let reader: Arc<dyn ChainReader> = Arc::new(provider);
+        Ok(Self {
+            inner: Arc::new(TitanSimulationEngine::new(chain_id, Arc::clone(&reader)).with_offline(offline)),
+            reader,
+        })
+    }
+
+    #[getter]
+    fn chain_id(&self) -> u64 {
+        self.inner.chain_id()
+    }
+
+    /// Reuse identical price-impact quotes until the next cycle or block
+    fn start_cycle(&self, block: u64) {
+        self.inner.start_cycle(block);
+    }
+
+    fn clear_quote_cache(&self) {
+        self.inner.clear_quote_cache();
+    }
+
+    /// Balance of `token` held by `lender`, in raw units
+    fn get_lender_tvl(&self, py: Python, token: &str, lender: &str) -> PyResult<PyObject> {
+        let tvl = self.block_on(py, self.lender_tvl(token, lender)?)?;
+        u256_to_py(py, tvl)
+    }
+
+    fn get_lender_tvl_async<'py>(&self, py: Python<'py>, token: &str, lender: &str) -> PyResult<&'py PyAny> {
+        awaitable(py, self.lender_tvl(token, lender)?, u256_to_py)
+    }
+
+    /// Uniswap V3 quote for swapping `amount` of `token_in` through the `fee` tier
+    fn get_price_impact(
+        &self,
+        py: Python,
+        token_in: &str,
+        token_out: &str,
+        amount: &PyAny,
+        fee: u32,
+        quoter: &str,
+    ) -> PyResult<PyObject> {
+        let amount_out = self.block_on(py, self.price_impact(token_in, token_out, amount, fee, quoter)?)?;
+        u256_to_py(py, amount_out)
+    }
+
+    fn get_price_impact_async<'py>(
+        &self,
+        py: Python<'py>,
+        token_in: &str,
+        token_out: &str,
+        amount: &PyAny,
+        fee: u32,
+        quoter: &str,
+    ) -> PyResult<&'py PyAny> {
+        awaitable(py, self.price_impact(token_in, token_out, amount, fee, quoter)?, u256_to_py)
+    }
+
+    fn get_block_number(&self, py: Python) -> PyResult<u64> {
+        self.block_on(py, self.block_number())
+    }
+
+    fn get_block_number_async<'py>(&self, py: Python<'py>) -> PyResult<&'py PyAny> {
+        awaitable(py, self.block_number(), |py, block| Ok(block.into_py(py)))
+    }
+
+    /// aToken and debt token addresses of `token`'s reserve in an Aave V3 pool
+    fn get_aave_reserve_tokens(&self, py: Python, token: &str, aave_pool: &str) -> PyResult<PyObject> {
+        let tokens = self.block_on(py, self.reserve_tokens(token, aave_pool)?)?;
+        reserve_tokens_to_py(py, tokens)
+    }
+
+    fn get_aave_reserve_tokens_async<'py>(&self, py: Python<'py>, token: &str, aave_pool: &str) -> PyResult<&'py PyAny> {
+        awaitable(py, self.reserve_tokens(token, aave_pool)?, reserve_tokens_to_py)
+    }
+}
+
+/// Add the simulation engine class to the Python module
+pub(crate) fn register(m: &PyModule) -> PyResult<()> {
+    m.add_class::<PySimulationEngine>()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_u256_halves_above_u64() {
+        let above_u64 = U256::from(u64::MAX) + U256::from(2);
+        assert_eq!(u256_halves(above_u64), (0, u64::MAX as u128 + 2));
+
+        // 1M tokens at 18 decimals, the offline TVL
+        let tvl = crate::simulation_engine::simulated_tvl();
+        assert_eq!(u256_halves(tvl), (0, 10u128.pow(24)));
+
+        let above_u128 = (U256::from(3) << 128) + U256::from(5);
+        assert_eq!(u256_halves(above_u128), (3, 5));
+        assert_eq!(u256_halves(U256::MAX), (u128::MAX, u128::MAX));
+    }
+
+    /// JSON-RPC endpoint answering every request with block 42 after
+    /// `delay`; each request is signalled on arrival
+    #[cfg(not(feature = "extension-module"))]
+    fn slow_rpc(delay: std::time::Duration) -> (String, std::sync::mpsc::Receiver<()>) {
+        use std::io::{BufRead, BufReader, Read, Write};
+
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let (arrived, requests) = std::sync::mpsc::channel();
+        std::thread::spawn(move || {
+            for stream in listener.incoming() {
+                let mut reader = BufReader::new(stream.unwrap());
+                let mut content_length = 0;
+                loop {
+                    let mut line = String::new();
+                    reader.read_line(&mut line).unwrap();
+                    if line.trim().is_empty() {
+                        break;
+                    }
+                    if let Some((name, value)) = line.split_once(':') {
+                        if name.eq_ignore_ascii_case("content-length") {
+                            content_length = value.trim().parse().unwrap();
+                        }
+                    }
+                }
+                let mut body = vec![0; content_length];
+                reader.read_exact(&mut body).unwrap();
+                let request: serde_json::Value = serde_json::from_slice(&body).unwrap();
+                let _ = arrived.send(());
+
+                std::thread::sleep(delay);
+                let response = serde_json::json!({"jsonrpc": "2.0", "id": request["id"], "result": "0x2a"}).to_string();
+                let _ = write!(
+                    reader.get_mut(),
+                    "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    response.len(),
+                    response
+                );
+            }
+        });
+        (url, requests)
+    }
+
+    // Needs a linked libpython: cargo test --no-default-features
+    #[cfg(not(feature = "extension-module"))]
+    #[test]
+    fn test_blocking_calls_release_the_gil() {
+        use std::time::{Duration, Instant};
+
+        let delay = Duration::from_millis(800);
+        let (url, requests) = slow_rpc(delay);
+        pyo3::prepare_freethreaded_python();
+        let engine_class: PyObject = Python::with_gil(|py| {
+            let m = PyModule::new(py, "titan_core")?;
+            register(m)?;
+            Ok::<_, PyErr>(m.getattr("SimulationEngine")?.into())
+        })
+        .unwrap();
+        let engine = Python::with_gil(|py| engine_class.call1(py, (137, url))).unwrap();
+
+        let slow = std::thread::spawn(move || {
+            Python::with_gil(|py| engine.call_method0(py, "get_block_number")?.extract::<u64>(py))
+        });
+        // The slow call is now blocked on the RPC; a GIL it kept would
+        // stall everything below until the response arrives
+        requests.recv_timeout(Duration::from_secs(10)).unwrap();
+        let started = Instant::now();
+        let chain_id = Python::with_gil(|py| {
+            engine_class.call1(py, (1, "http://127.0.0.1:1"))?.getattr(py, "chain_id")?.extract::<u64>(py)
+        })
+        .unwrap();
+        assert_eq!(chain_id, 1);
+        assert!(started.elapsed() < delay / 2, "binding call waited {:?} for the GIL", started.elapsed());
+
+        assert_eq!(slow.join().unwrap().unwrap(), 42);
+    }
+}
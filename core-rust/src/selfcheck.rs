@@ -0,0 +1,222 @@
+use std::fmt;
+use std::future::Future;
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::{anyhow, Result};
+use ethers::prelude::*;
+
+use crate::config::{Config, TokenRegistry};
+use crate::dex_quoter::uniswap_v3_quoter;
+use crate::simulation_engine::{quote_params, UniswapV3QuoterV2, ERC20};
+
+/// Default limit for each individual check
+pub const DEFAULT_CHECK_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Pool fee tier used for the quoter probe
+const PROBE_FEE: u32 = 3000;
+
+/// Per-check time limits
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CheckTimeouts {
+    pub rpc: Duration,
+    pub token: Duration,
+    pub quoter: Duration,
+}
+
+impl Default for CheckTimeouts {
+    fn default() -> Self {
+        Self {
+            rpc: DEFAULT_CHECK_TIMEOUT,
+            token: DEFAULT_CHECK_TIMEOUT,
+            quoter: DEFAULT_CHECK_TIMEOUT,
+        }
+    }
+}
+
+/// Result of one check: detail on success, reason on failure
+pub type CheckResult = std::result::Result<String, String>;
+
+/// Connectivity results for one chain
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChainCheck {
+    pub chain_id: u64,
+    pub name: String,
+    pub rpc: CheckResult,
+    pub token: CheckResult,
+    pub quoter: CheckResult,
+}
+
+impl ChainCheck {
+    pub fn passed(&self) -> bool {
+        self.rpc.is_ok() && self.token.is_ok() && self.quoter.is_ok()
+    }
+}
+
+/// Token the decimals and quoter probes use, plus the wrapped native to quote into
+fn probe_tokens(chain_id: u64, registry: &TokenRegistry) -> Option<(Address, Address)> {
+    let wrapped = ["WETH", "WMATIC", "WBNB", "WAVAX"]
+        .iter()
+        .find_map(|symbol| registry.get(chain_id, symbol))?;
+    let usdc = registry.get(chain_id, "USDC")?;
+    Some((usdc.parse().ok()?, wrapped.parse().ok()?))
+}
+
+async fn timed<T>(limit: Duration, check: impl Future<Output = Result<T>>) -> Result<T> {
+    tokio::time::timeout(limit, check)
+        .await
+        .map_err(|_| anyhow!("timed out after {:?}", limit))?
+}
+
+/// Run the RPC, token and quoter checks against one chain
+///
+/// Later checks are skipped once the RPC check fails.
+pub async fn check_chain<P: JsonRpcClient + 'static>(
+    chain_id: u64,
+    name: &str,
+    provider: Arc<Provider<P>>,
+    registry: &TokenRegistry,
+    timeouts: CheckTimeouts,
+) -> ChainCheck {
+    let mut check = ChainCheck {
+        chain_id,
+        name: name.to_string(),
+        rpc: Err("skipped".to_string()),
+        token: Err("skipped".to_string()),
+        quoter: Err("skipped".to_string()),
+    };
+
+    check.rpc = timed(timeouts.rpc, async { Ok(provider.get_block_number().await?) })
+        .await
+        .map(|block| format!("block {}", block))
+        .map_err(|e| e.to_string());
+    if check.rpc.is_err() {
+        return check;
+    }
+
+    let Some((token, wrapped)) = probe_tokens(chain_id, registry) else {
+        let reason = "no USDC/wrapped native in token registry".to_string();
+        check.token = Err(reason.clone());
+        check.quoter = Err(reason);
+        return check;
+    };
+
+    let erc20 = ERC20::new(token, Arc::clone(&provider));
+    let decimals = timed(timeouts.token, async { Ok(erc20.decimals().call().await?) }).await;
+    check.token = decimals
+        .as_ref()
+        .map(|d| format!("USDC decimals {}", d))
+        .map_err(|e| e.to_string());
+
+    check.quoter = match uniswap_v3_quoter(chain_id) {
+        None => Err("no Uniswap V3 quoter known".to_string()),
+        Some(address) => {
+            let amount_in = U256::exp10(*decimals.as_ref().unwrap_or(&6) as usize);
+            let quoter = UniswapV3QuoterV2::new(address, Arc::clone(&provider));
+            timed(timeouts.quoter, async {
+                let call = quoter.quote_exact_input_single(quote_params(token, wrapped, amount_in, PROBE_FEE));
+                Ok(call.call().await?.0)
+            })
+            .await
+            .map(|out| format!("1 USDC -> {} wei", out))
+            .map_err(|e| e.to_string())
+        }
+    };
+    check
+}
+
+/// Check every configured chain concurrently, in chain ID order
+pub async fn run_selfcheck(config: &Config, timeouts: CheckTimeouts) -> Vec<ChainCheck> {
+    let mut chains: Vec<_> = config.chains.iter().collect();
+    chains.sort_by_key(|(id, _)| **id);
+
+    futures::future::join_all(chains.into_iter().map(|(chain_id, chain)| async move {
+        match Provider::<Http>::try_from(chain.rpc.as_str()) {
+            Ok(provider) => {
+                check_chain(*chain_id, &chain.name, Arc::new(provider), &config.token_registry, timeouts).await
+            }
+            Err(e) => ChainCheck {
+                chain_id: *chain_id,
+                name: chain.name.clone(),
+                rpc: Err(format!("invalid RPC URL: {}", e)),
+                token: Err("skipped".to_string()),
+                quoter: Err("skipped".to_string()),
+            },
+        }
+    }))
+    .await
+}
+
+/// Pass/fail matrix with failure reasons
+pub struct SelfCheckReport<'a>(pub &'a [ChainCheck]);
+
+impl fmt::Display for SelfCheckReport<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mark = |result: &CheckResult| if result.is_ok() { "PASS" } else { "FAIL" };
+        writeln!(f, "{:<8} {:<12} {:<6} {:<6} {:<6}", "CHAIN", "NAME", "RPC", "TOKEN", "QUOTER")?;
+        for check in self.0 {
+            writeln!(
+                f,
+                "{:<8} {:<12} {:<6} {:<6} {:<6}",
+                check.chain_id,
+                check.name,
+                mark(&check.rpc),
+                mark(&check.token),
+                mark(&check.quoter)
+            )?;
+        }
+        for check in self.0 {
+            for (label, result) in [("rpc", &check.rpc), ("token", &check.token), ("quoter", &check.quoter)] {
+                if let Err(reason) = result {
+                    writeln!(f, "{} {}: {}", check.name, label, reason)?;
+                }
+            }
+        }
+        let failed = self.0.iter().filter(|check| !check.passed()).count();
+        write!(f, "{} chains checked, {} failed", self.0.len(), failed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::abi_call::uint_words;
+
+    #[tokio::test]
+    async fn test_all_checks_pass() {
+        let (provider, mock) = Provider::mocked();
+        // Served last-in first-out: block number, decimals, quote
+        // (amountOut, sqrtPriceX96After, initializedTicksCrossed, gasEstimate)
+        let quote = uint_words(&[300_000_000_000_000, 0, 1, 80_000]);
+        mock.push::<Bytes, _>(quote).unwrap();
+        mock.push::<Bytes, _>(uint_words(&[6])).unwrap();
+        mock.push(U64::from(19_000_000)).unwrap();
+
+        let registry = TokenRegistry::with_defaults();
+        let check = check_chain(1, "ethereum", Arc::new(provider), &registry, CheckTimeouts::default()).await;
+        assert!(check.passed(), "{:?}", check);
+        assert_eq!(check.token.as_deref(), Ok("USDC decimals 6"));
+    }
+
+    #[tokio::test]
+    async fn test_rpc_failure_skips_remaining_checks() {
+        let (provider, _mock) = Provider::mocked();
+        let registry = TokenRegistry::with_defaults();
+        let check = check_chain(1, "ethereum", Arc::new(provider), &registry, CheckTimeouts::default()).await;
+
+        assert!(!check.passed());
+        assert_eq!(check.quoter, Err("skipped".to_string()));
+        let report = SelfCheckReport(&[check]).to_string();
+        assert!(report.contains("1        ethereum     FAIL   FAIL   FAIL"), "{}", report);
+        assert!(report.ends_with("1 chains checked, 1 failed"));
+    }
+
+    #[tokio::test]
+    async fn test_missing_registry_tokens() {
+        let (provider, mock) = Provider::mocked();
+        mock.push(U64::from(1)).unwrap();
+        let check = check_chain(250, "fantom", Arc::new(provider), &TokenRegistry::new(), CheckTimeouts::default()).await;
+        assert!(check.rpc.is_ok());
+        assert!(check.token.is_err() && check.quoter.is_err());
+    }
+}
@@ -1,51 +1,164 @@
+use ethers::abi::{AbiDecode, AbiEncode};
 use ethers::prelude::*;
-use std::sync::Arc;
-use anyhow::Result;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use anyhow::{anyhow, Result};
+use ethers::providers::{spoof, RawCall};
+use ethers::types::transaction::eip2718::TypedTransaction;
 use log::{warn, debug};
 
+use crate::chain_reader::ChainReader;
+use crate::chainlink::read_chainlink_price_from;
+
 abigen!(
     ERC20,
     r#"[
         function balanceOf(address owner) external view returns (uint256)
         function decimals() external view returns (uint8)
+        function totalSupply() external view returns (uint256)
     ]"#,
 );
 
 abigen!(
     UniswapV3QuoterV2,
     r#"[
-        function quoteExactInputSingle(address tokenIn, address tokenOut, uint256 amountIn, uint24 fee, uint160 sqrtPriceLimitX96) external returns (uint256 amountOut)
+        struct QuoteExactInputSingleParams { address tokenIn; address tokenOut; uint256 amountIn; uint24 fee; uint160 sqrtPriceLimitX96; }
+        function quoteExactInputSingle(QuoteExactInputSingleParams memory params) external returns (uint256 amountOut, uint160 sqrtPriceX96After, uint32 initializedTicksCrossed, uint256 gasEstimate)
     ]"#,
 );
 
+/// QuoterV2 params for swapping `amount_in` through the `fee` tier with no price limit
+pub fn quote_params(token_in: Address, token_out: Address, amount_in: U256, fee: u32) -> QuoteExactInputSingleParams {
+    QuoteExactInputSingleParams {
+        token_in,
+        token_out,
+        amount_in,
+        fee,
+        sqrt_price_limit_x96: U256::zero(),
+    }
+}
+
+/// Maximum block span per `eth_getLogs` request (most public RPCs cap at 2k-10k)
+const MAX_LOG_BLOCK_RANGE: u64 = 2_000;
+
+/// Uniswap V3 `Swap` event signature
+const UNISWAP_V3_SWAP_EVENT: &str = "Swap(address,address,int256,int256,uint160,uint128,int24)";
+
+/// Runtime code swapped onto the holder to measure a simulated transfer
+///
+/// Takes ABI-encoded `(token, recipient, amount)`, runs
+/// `token.transfer(recipient, amount)` and returns the recipient's balance
+/// delta. Reverts if any of the calls fail.
+const TRANSFER_PROBE_CODE: &str = "6370a0823160e01b60005260203560045260206080602460006000355afa15610080\
+    5763a9059cbb60e01b600052602035600452604035602452602060a06044600060006000355af115610080\
+    576370a0823160e01b600052602035600452602060c0602460006000355afa156100805760805160c05103\
+    60e052602060e0f35b600080fd";
+
+/// Fresh address receiving the simulated transfer
+const TRANSFER_PROBE_RECIPIENT: Address = H160([0x7e; 20]);
+
+/// Shortfall tolerated before a token counts as fee-on-transfer
+/// (rebasing tokens such as stETH lose 1-2 wei to share rounding)
+const TRANSFER_ROUNDING_TOLERANCE: u64 = 2;
+
+/// Lender balance reported for every token in offline mode (1M tokens at 18 decimals)
+pub const OFFLINE_SIMULATED_TVL: u128 = 1_000_000 * 10u128.pow(18);
+
+/// Deterministic TVL used instead of an RPC call in offline mode
+pub fn simulated_tvl() -> U256 {
+    U256::from(OFFLINE_SIMULATED_TVL)
+}
+
+/// Deterministic swap output used in offline mode: `amount` less the pool fee
+pub fn simulated_amount_out(amount: U256, fee: u32) -> U256 {
+    amount * U256::from(1_000_000u32.saturating_sub(fee)) / U256::from(1_000_000u32)
+}
+
+/// Identity of a quoter call within a block
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+struct QuoteKey {
+    chain_id: u64,
+    block: u64,
+    token_in: Address,
+    token_out: Address,
+    amount: U256,
+    fee: u32,
+    quoter: Address,
+}
+
+/// `amount_out` of the quoter calls made during the current scoring cycle
+///
+/// Routes sharing a pool issue the same quote many times per cycle. Nothing
+/// is cached until a cycle is started, since without a pinned block a cached
+/// quote could outlive the state it was read from.
+#[derive(Debug, Default)]
+struct QuoteCache {
+    block: Option<u64>,
+    amounts_out: HashMap<QuoteKey, U256>,
+}
+
 /// Titan Simulation Engine - Validates liquidity and simulates trades
 pub struct TitanSimulationEngine {
     chain_id: u64,
-    provider: Arc<Provider<Http>>,
+    reader: Arc<dyn ChainReader>,
+    offline: bool,
+    quote_cache: Mutex<QuoteCache>,
 }
 
 impl TitanSimulationEngine {
-    /// Create a new simulation engine
-    pub fn new(chain_id: u64, provider: Arc<Provider<Http>>) -> Self {
+    /// Create a new simulation engine over any chain reader (e.g. an ethers `Provider`)
+    pub fn new(chain_id: u64, reader: Arc<dyn ChainReader>) -> Self {
         Self {
             chain_id,
-            provider,
+            reader,
+            offline: false,
+            quote_cache: Mutex::default(),
         }
     }
 
-    /// Get total value locked (TVL) for a lender
+    /// Start a scoring cycle at `block`, dropping the previous cycle's quotes
+    ///
+    /// Until the next call, quotes are read at `block` and identical
+    /// `get_price_impact` calls are answered from memory instead of issuing
+    /// another `eth_call`.
+    pub fn start_cycle(&self, block: u64) {
+        let mut cache = self.quote_cache.lock().unwrap();
+        cache.block = Some(block);
+        cache.amounts_out.clear();
+    }
+
+    /// [`start_cycle`](Self::start_cycle) at `block` unless the current
+    /// cycle is already at or past it
+    ///
+    /// Lets concurrent callers, such as server requests landing in the same
+    /// block, share one cycle.
+    pub fn advance_cycle(&self, block: u64) {
+        let mut cache = self.quote_cache.lock().unwrap();
+        if cache.block.is_none_or(|current| current < block) {
+            cache.block = Some(block);
+            cache.amounts_out.clear();
+        }
+    }
+
+    /// Stop caching quotes until the next [`start_cycle`](Self::start_cycle)
+    pub fn clear_quote_cache(&self) {
+        *self.quote_cache.lock().unwrap() = QuoteCache::default();
+    }
+
+    /// Return simulated values instead of calling the provider
+    pub fn with_offline(mut self, offline: bool) -> Self {
+        self.offline = offline;
+        self
+    }
+
+    /// Get total value locked (TVL) for a lender; 0 when the read fails
     pub async fn get_lender_tvl(
         &self,
         token_address: Address,
         lender_address: Address,
     ) -> Result<U256> {
-        let token = ERC20::new(token_address, Arc::clone(&self.provider));
-        
-        match token.balance_of(lender_address).call().await {
-            Ok(balance) => {
-                debug!("TVL for token {:?} at lender {:?}: {}", token_address, lender_address, balance);
-                Ok(balance)
-            }
+        match self.try_get_lender_tvl(token_address, lender_address).await {
+            Ok(balance) => Ok(balance),
             Err(e) => {
                 warn!("Failed to get TVL: {}", e);
                 Ok(U256::zero())
@@ -53,7 +166,18 @@ impl TitanSimulationEngine {
         }
     }
 
-    /// Get price impact by simulating a swap on Uniswap V3
+    /// [`get_lender_tvl`](Self::get_lender_tvl), passing read failures on
+    pub async fn try_get_lender_tvl(&self, token_address: Address, lender_address: Address) -> Result<U256> {
+        if self.offline {
+            return Ok(simulated_tvl());
+        }
+
+        let balance = self.reader.balance_of(token_address, lender_address).await?;
+        debug!("TVL for token {:?} at lender {:?}: {}", token_address, lender_address, balance);
+        Ok(balance)
+    }
+
+    /// Get price impact by simulating a swap on Uniswap V3; 0 when the quote fails
     pub async fn get_price_impact(
         &self,
         token_in: Address,
@@ -62,13 +186,8 @@ impl TitanSimulationEngine {
         fee: u32,
         quoter_address: Address,
     ) -> Result<U256> {
-        let quoter = UniswapV3QuoterV2::new(quoter_address, Arc::clone(&self.provider));
-        
-        match quoter.quote_exact_input_single(token_in, token_out, amount, fee, U256::zero()).call().await {
-            Ok(amount_out) => {
-                debug!("Price impact simulation: {} in -> {} out", amount, amount_out);
-                Ok(amount_out)
-            }
+        match self.try_get_price_impact(token_in, token_out, amount, fee, quoter_address).await {
+            Ok(amount_out) => Ok(amount_out),
             Err(e) => {
                 warn!("Price impact simulation failed: {}", e);
                 Ok(U256::zero())
@@ -76,49 +195,530 @@ impl TitanSimulationEngine {
         }
     }
 
+    /// [`get_price_impact`](Self::get_price_impact), passing reverts and
+    /// RPC failures on
+    ///
+    /// Successful quotes are reused for the rest of the cycle, see
+    /// [`start_cycle`](Self::start_cycle).
+    pub async fn try_get_price_impact(
+        &self,
+        token_in: Address,
+        token_out: Address,
+        amount: U256,
+        fee: u32,
+        quoter_address: Address,
+    ) -> Result<U256> {
+        if self.offline {
+            return Ok(simulated_amount_out(amount, fee));
+        }
+
+        let key = self.quote_cache.lock().unwrap().block.map(|block| QuoteKey {
+            chain_id: self.chain_id,
+            block,
+            token_in,
+            token_out,
+            amount,
+            fee,
+            quoter: quoter_address,
+        });
+        if let Some(amount_out) = key.and_then(|key| self.quote_cache.lock().unwrap().amounts_out.get(&key).copied()) {
+            debug!("Price impact cache hit: {} in -> {} out", amount, amount_out);
+            return Ok(amount_out);
+        }
+
+        let calldata = QuoteExactInputSingleCall {
+            params: quote_params(token_in, token_out, amount, fee),
+        }
+        .encode();
+        let raw = match key {
+            Some(key) => self.reader.call_at(quoter_address, calldata.into(), key.block).await?,
+            None => self.reader.call(quoter_address, calldata.into()).await?,
+        };
+        let amount_out = QuoteExactInputSingleReturn::decode(raw)?.amount_out;
+        debug!("Price impact simulation: {} in -> {} out", amount, amount_out);
+        if let Some(key) = key {
+            let mut cache = self.quote_cache.lock().unwrap();
+            // A cycle started mid-call must not pick up the old block's quote
+            if cache.block == Some(key.block) {
+                cache.amounts_out.insert(key, amount_out);
+            }
+        }
+        Ok(amount_out)
+    }
+
     /// Check if provider is connected
     pub async fn is_connected(&self) -> bool {
-        self.provider.get_block_number().await.is_ok()
+        self.reader.block_number().await.is_ok()
     }
 
     /// Get current block number
     pub async fn get_block_number(&self) -> Result<u64> {
-        let block = self.provider.get_block_number().await?;
-        Ok(block.as_u64())
+        self.reader.block_number().await
+    }
+
+    /// Get native token balance (used to pay gas) of an address
+    pub async fn get_native_balance(&self, address: Address) -> Result<U256> {
+        let balance = self.reader.native_balance(address).await?;
+        debug!("Native balance of {:?}: {}", address, balance);
+        Ok(balance)
+    }
+
+    /// Current `(max_fee_per_gas, max_priority_fee_per_gas)`, see [`estimate_eip1559_fees`]
+    pub async fn estimate_eip1559_fees(&self) -> Result<(U256, U256)> {
+        let fees = self.reader.gas_fees().await?;
+        Ok((fees.max_fee, fees.priority_fee))
+    }
+
+    /// Expected USD cost of `gas_units` at the current base fee plus tip,
+    /// priced by a Chainlink native/USD feed
+    pub async fn estimate_gas_cost_usd(&self, gas_units: U256, native_usd_feed: Address) -> Result<f64> {
+        let fees = self.reader.gas_fees().await?;
+        let native_usd = read_chainlink_price_from(self.reader.as_ref(), native_usd_feed).await?;
+        gas_cost_usd(gas_cost_native(gas_units, fees.expected()), native_usd)
+    }
+
+    /// Worst-case USD cost of `gas_units` if the full max fee is charged
+    pub async fn estimate_worst_case_gas_cost_usd(&self, gas_units: U256, native_usd_feed: Address) -> Result<f64> {
+        let fees = self.reader.gas_fees().await?;
+        let native_usd = read_chainlink_price_from(self.reader.as_ref(), native_usd_feed).await?;
+        gas_cost_usd(gas_cost_native(gas_units, fees.max_fee), native_usd)
+    }
+
+    /// Get chain ID
+    pub fn chain_id(&self) -> u64 {
+        self.chain_id
+    }
+}
+
+/// Per-gas fees on a chain
+///
+/// A transaction pays the block's base fee plus its tip; `max_fee` is only
+/// the cap submitted with it so it stays valid if the base fee rises.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct GasFees {
+    /// Base fee of the latest block (the gas price on legacy chains)
+    pub base_fee: U256,
+    /// Median fee-history tip
+    pub priority_fee: U256,
+    /// Twice the base fee plus tip (the gas price on legacy chains)
+    pub max_fee: U256,
+}
+
+impl GasFees {
+    /// EIP-1559 fees for `base_fee` and `priority_fee`
+    pub fn eip1559(base_fee: U256, priority_fee: U256) -> Self {
+        Self {
+            base_fee,
+            priority_fee,
+            max_fee: base_fee * 2 + priority_fee,
+        }
+    }
+
+    /// Legacy chains pay exactly the quoted gas price
+    pub fn legacy(gas_price: U256) -> Self {
+        Self {
+            base_fee: gas_price,
+            priority_fee: U256::zero(),
+            max_fee: gas_price,
+        }
     }
+
+    /// Price per gas a transaction is expected to pay: base fee plus tip
+    pub fn expected(&self) -> U256 {
+        self.base_fee + self.priority_fee
+    }
+}
+
+/// Fee estimator: median fee-history tip, reported as `(base fee + tip, tip)`
+/// so [`fetch_gas_fees`] can recover the base fee
+fn eip1559_estimator(base_fee_per_gas: U256, rewards: Vec<Vec<U256>>) -> (U256, U256) {
+    let mut tips: Vec<U256> = rewards.iter().filter_map(|r| r.first().copied()).collect();
+    tips.sort();
+    let max_priority_fee = tips.get(tips.len() / 2).copied().unwrap_or_default();
+    (base_fee_per_gas + max_priority_fee, max_priority_fee)
+}
+
+/// Fetch the latest block's base fee and the median tip from fee history
+///
+/// Chains without EIP-1559 fall back to the legacy gas price, with no
+/// separate tip.
+pub async fn fetch_gas_fees<P: JsonRpcClient>(provider: &Provider<P>) -> Result<GasFees> {
+    match provider.estimate_eip1559_fees(Some(eip1559_estimator)).await {
+        Ok((expected, tip)) => Ok(GasFees::eip1559(expected - tip, tip)),
+        Err(e) => {
+            debug!("EIP-1559 fee estimation unavailable ({}), using legacy gas price", e);
+            Ok(GasFees::legacy(provider.get_gas_price().await?))
+        }
+    }
+}
+
+/// Estimate `(max_fee_per_gas, max_priority_fee_per_gas)` to submit a
+/// transaction with, see [`fetch_gas_fees`]
+pub async fn estimate_eip1559_fees<P: JsonRpcClient>(provider: &Provider<P>) -> Result<(U256, U256)> {
+    let fees = fetch_gas_fees(provider).await?;
+    Ok((fees.max_fee, fees.priority_fee))
+}
+
+/// Native cost in wei of `gas_units` at `gas_price` per gas
+///
+/// Pass [`GasFees::expected`] for the expected cost, or `max_fee` for the
+/// worst case.
+pub fn gas_cost_native(gas_units: U256, gas_price: U256) -> U256 {
+    gas_units.saturating_mul(gas_price)
+}
+
+/// Convert a wei amount to USD at `native_usd` per whole native token
+pub fn gas_cost_usd(cost_wei: U256, native_usd: f64) -> Result<f64> {
+    let native: f64 = ethers::utils::format_units(cost_wei, "ether")?.parse()?;
+    Ok(native * native_usd)
 }
 
 /// Standalone function for provider TVL checking (backward compatibility)
 pub async fn get_provider_tvl(
     token_address: Address,
     lender_address: Address,
-    provider: Arc<Provider<Http>>,
+    reader: Arc<dyn ChainReader>,
 ) -> Result<U256> {
-    let token = ERC20::new(token_address, provider);
-    
-    match token.balance_of(lender_address).call().await {
+    match reader.balance_of(token_address, lender_address).await {
         Ok(balance) => Ok(balance),
         Err(_) => Ok(U256::zero()),
     }
 }
 
+/// Default number of concurrent `balanceOf` calls in a TVL batch
+pub const DEFAULT_TVL_BATCH_CONCURRENCY: usize = 16;
+
+/// Run `fetch` for every token with at most `concurrency` calls in flight
+///
+/// Results come back in input order. Multicall3 is preferred where it's
+/// deployed since it's a single RPC call; this is the fallback path.
+pub async fn fetch_tvl_batch<F, Fut>(tokens: &[Address], concurrency: usize, fetch: F) -> Vec<Result<U256>>
+where
+    F: Fn(Address) -> Fut,
+    Fut: std::future::Future<Output = Result<U256>>,
+{
+    use futures::stream::{self, StreamExt};
+
+    let mut results: Vec<(usize, Result<U256>)> = stream::iter(tokens.iter().copied().enumerate())
+        .map(|(index, token)| {
+            let call = fetch(token);
+            async move { (index, call.await) }
+        })
+        .buffer_unordered(concurrency.max(1))
+        .collect()
+        .await;
+    results.sort_by_key(|(index, _)| *index);
+    results.into_iter().map(|(_, result)| result).collect()
+}
+
+/// Sum recent swap volume on a Uniswap V3 pool over the last `blocks` blocks
+///
+/// Reads `Swap` event logs via `eth_getLogs`, chunking the block range to stay
+/// within provider log limits. Volume is the sum of absolute `amount0` values,
+/// i.e. denominated in the pool's token0 raw units.
+pub async fn get_recent_swap_volume<P: JsonRpcClient>(
+    pool: Address,
+    blocks: u64,
+    provider: Arc<Provider<P>>,
+) -> Result<U256> {
+    if blocks == 0 {
+        return Ok(U256::zero());
+    }
+
+    let latest = provider.get_block_number().await?.as_u64();
+    let start = latest.saturating_sub(blocks - 1);
+    let swap_topic = H256::from(ethers::utils::keccak256(UNISWAP_V3_SWAP_EVENT));
+
+    let mut volume = U256::zero();
+    let mut from = start;
+    while from <= latest {
+        let to = (from + MAX_LOG_BLOCK_RANGE - 1).min(latest);
+        let filter = Filter::new()
+            .address(pool)
+            .topic0(swap_topic)
+            .from_block(from)
+            .to_block(to);
+
+        let logs = provider.get_logs(&filter).await?;
+        debug!("Swap logs for pool {:?} in blocks {}-{}: {}", pool, from, to, logs.len());
+
+        for log in logs {
+            // data layout: amount0 (int256), amount1 (int256), sqrtPriceX96, liquidity, tick
+            if log.data.len() < 32 {
+                continue;
+            }
+            let amount0 = I256::from_raw(U256::from_big_endian(&log.data[0..32]));
+            volume = volume.saturating_add(amount0.unsigned_abs());
+        }
+
+        from = to + 1;
+    }
+
+    Ok(volume)
+}
+
+/// Whether `received` falls short of `sent` beyond rounding noise
+fn is_transfer_shortfall(sent: U256, received: U256) -> bool {
+    received.saturating_add(U256::from(TRANSFER_ROUNDING_TOLERANCE)) < sent
+}
+
+/// Detect fee-on-transfer tokens by simulating a transfer from `holder`
+///
+/// Moves 1% of the holder's balance to a fresh address inside a single
+/// `eth_call` (using a state override, so the RPC must support it) and
+/// compares the amount received with the amount sent.
+pub async fn is_likely_fee_on_transfer<P: JsonRpcClient + 'static>(
+    token: Address,
+    holder: Address,
+    provider: Arc<Provider<P>>,
+) -> Result<bool> {
+    let erc20 = ERC20::new(token, Arc::clone(&provider));
+    let amount = erc20.balance_of(holder).call().await? / 100;
+    if amount.is_zero() {
+        return Err(anyhow!("Holder {:?} has too little of token {:?} to simulate a transfer", holder, token));
+    }
+
+    let calldata = ethers::abi::encode(&[
+        ethers::abi::Token::Address(token),
+        ethers::abi::Token::Address(TRANSFER_PROBE_RECIPIENT),
+        ethers::abi::Token::Uint(amount),
+    ]);
+    let tx: TypedTransaction = TransactionRequest::new().to(holder).data(calldata).into();
+    let probe_code: Bytes = TRANSFER_PROBE_CODE.parse()?;
+    let state = spoof::code(holder, probe_code);
+
+    let result = provider.call_raw(&tx).state(&state).await?;
+    if result.len() < 32 {
+        return Err(anyhow!("Transfer probe returned {} bytes", result.len()));
+    }
+    let received = U256::from_big_endian(&result[..32]);
+    debug!("Transfer probe for {:?}: sent {}, received {}", token, amount, received);
+
+    Ok(is_transfer_shortfall(amount, received))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::abi_call::uint_words;
 
     #[tokio::test]
-    async fn test_simulation_engine_creation() {
-        // This test requires a real RPC endpoint
-        // Skip in CI/CD environments
-        if std::env::var("RPC_POLYGON").is_err() {
-            return;
-        }
+    async fn test_tvl_batch_bounds_concurrency_and_keeps_order() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        let in_flight = AtomicUsize::new(0);
+        let peak = AtomicUsize::new(0);
+        let tokens: Vec<Address> = (1..=50u64).map(Address::from_low_u64_be).collect();
+
+        let results = fetch_tvl_batch(&tokens, 4, |token| {
+            let (in_flight, peak) = (&in_flight, &peak);
+            async move {
+                let now = in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+                peak.fetch_max(now, Ordering::SeqCst);
+                // Later tokens finish first, so completion order differs from input order
+                let delay = 60 - token.to_low_u64_be();
+                tokio::time::sleep(std::time::Duration::from_millis(delay)).await;
+                in_flight.fetch_sub(1, Ordering::SeqCst);
+                Ok(U256::from(token.to_low_u64_be()))
+            }
+        })
+        .await;
+
+        assert_eq!(peak.load(Ordering::SeqCst), 4);
+        let values: Vec<u64> = results.into_iter().map(|r| r.unwrap().as_u64()).collect();
+        assert_eq!(values, (1..=50).collect::<Vec<_>>());
+    }
+
+    #[tokio::test]
+    async fn test_simulation_engine_with_mock_reader() {
+        use crate::chain_reader::MockChainReader;
+
+        let token = Address::repeat_byte(1);
+        let lender = Address::repeat_byte(2);
+        let quoter = Address::repeat_byte(3);
+        // Quoter keeps 99.7% of the input
+        let reader = MockChainReader::new()
+            .with_block_number(50_000_000)
+            .with_balance(token, lender, U256::from(1_000_000u64))
+            .with_call_handler(move |to, data| {
+                assert_eq!(to, quoter);
+                let call = QuoteExactInputSingleCall::decode(data)?;
+                quoted(call.params.amount_in * 997 / 1000)
+            });
+        let engine = TitanSimulationEngine::new(137, Arc::new(reader));
+        assert_eq!(engine.chain_id(), 137);
+        assert_eq!(engine.get_block_number().await.unwrap(), 50_000_000);
+
+        assert_eq!(engine.get_lender_tvl(token, lender).await.unwrap(), U256::from(1_000_000u64));
+        assert_eq!(engine.get_lender_tvl(lender, token).await.unwrap(), U256::zero());
+        let reader: Arc<dyn ChainReader> = Arc::new(MockChainReader::new().with_balance(token, lender, U256::from(9)));
+        assert_eq!(get_provider_tvl(token, lender, reader).await.unwrap(), U256::from(9));
 
-        let rpc_url = std::env::var("RPC_POLYGON").unwrap();
-        let provider = Provider::<Http>::try_from(rpc_url).unwrap();
-        let provider = Arc::new(provider);
+        let out = engine.get_price_impact(token, lender, U256::from(1000), 3000, quoter).await.unwrap();
+        assert_eq!(out, U256::from(997));
+    }
+
+    #[tokio::test]
+    async fn test_quote_cache_reuses_calls_within_a_cycle() {
+        use crate::chain_reader::MockChainReader;
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        let (token_in, token_out, quoter) = (Address::repeat_byte(1), Address::repeat_byte(2), Address::repeat_byte(3));
+        let calls = Arc::new(AtomicUsize::new(0));
+        let counted = Arc::clone(&calls);
+        let reader = MockChainReader::new().with_call_handler(move |_, data| {
+            counted.fetch_add(1, Ordering::SeqCst);
+            let call = QuoteExactInputSingleCall::decode(data)?;
+            quoted(call.params.amount_in * 997 / 1000)
+        });
+        let engine = TitanSimulationEngine::new(137, Arc::new(reader));
+        let quote = |amount: u64| engine.get_price_impact(token_in, token_out, U256::from(amount), 3000, quoter);
+
+        // No cycle started: every call goes to the chain
+        quote(1000).await.unwrap();
+        quote(1000).await.unwrap();
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+
+        engine.start_cycle(100);
+        assert_eq!(quote(1000).await.unwrap(), U256::from(997));
+        assert_eq!(quote(1000).await.unwrap(), U256::from(997));
+        assert_eq!(calls.load(Ordering::SeqCst), 3);
+        // A different amount is a different call
+        quote(2000).await.unwrap();
+        assert_eq!(calls.load(Ordering::SeqCst), 4);
+
+        engine.start_cycle(101);
+        quote(1000).await.unwrap();
+        assert_eq!(calls.load(Ordering::SeqCst), 5);
+
+        // An older or equal block keeps the current cycle
+        engine.advance_cycle(100);
+        engine.advance_cycle(101);
+        quote(1000).await.unwrap();
+        assert_eq!(calls.load(Ordering::SeqCst), 5);
+        engine.advance_cycle(102);
+        quote(1000).await.unwrap();
+        assert_eq!(calls.load(Ordering::SeqCst), 6);
+
+        engine.clear_quote_cache();
+        quote(1000).await.unwrap();
+        assert_eq!(calls.load(Ordering::SeqCst), 7);
+    }
+
+    #[tokio::test]
+    async fn test_quote_cache_skips_failed_calls() {
+        use crate::chain_reader::MockChainReader;
+
+        // No call handler: every quote fails and reads as zero
+        let engine = TitanSimulationEngine::new(137, Arc::new(MockChainReader::new()));
+        engine.start_cycle(100);
+        let key = Address::repeat_byte(1);
+        assert_eq!(engine.get_price_impact(key, key, U256::from(1000), 3000, key).await.unwrap(), U256::zero());
+        assert!(engine.quote_cache.lock().unwrap().amounts_out.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_swap_volume_empty_range() {
+        let (provider, mock) = Provider::mocked();
+        // Mock responses are served last-in first-out
+        mock.push::<Vec<Log>, _>(Vec::new()).unwrap();
+        mock.push(U64::from(1_000)).unwrap();
+
+        let volume = get_recent_swap_volume(Address::zero(), 100, Arc::new(provider))
+            .await
+            .unwrap();
+        assert_eq!(volume, U256::zero());
+    }
+
+    #[tokio::test]
+    async fn test_eip1559_fees_from_base_fee_and_tip() {
+        let (provider, mock) = Provider::mocked();
+        let gwei = |n: u64| U256::from(n) * U256::exp10(9);
+        // Mock responses are served last-in first-out
+        mock.push(serde_json::json!({
+            "oldestBlock": "0x1",
+            "baseFeePerGas": [],
+            "gasUsedRatio": [],
+            "reward": [[gwei(1)], [gwei(2)], [gwei(5)]],
+        }))
+        .unwrap();
+        mock.push(serde_json::json!({ "number": "0x2", "baseFeePerGas": gwei(20) })).unwrap();
+
+        let fees = fetch_gas_fees(&provider).await.unwrap();
+        assert_eq!(fees, GasFees { base_fee: gwei(20), priority_fee: gwei(2), max_fee: gwei(42) });
+
+        // Expected cost pays base fee plus tip, not the max fee cap
+        let cost = gas_cost_native(U256::from(100_000u64), fees.expected());
+        assert_eq!(cost, gwei(2_200_000));
+        let usd = gas_cost_usd(cost, 2_000.0).unwrap();
+        assert!((usd - 4.4).abs() < 1e-9);
+        let worst = gas_cost_native(U256::from(100_000u64), fees.max_fee);
+        assert_eq!(worst, gwei(4_200_000));
+    }
+
+    #[tokio::test]
+    async fn test_legacy_gas_price_fallback() {
+        let (provider, mock) = Provider::mocked();
+        mock.push(U256::from(7_000_000_000u64)).unwrap();
+        mock.push(serde_json::json!({ "number": "0x2" })).unwrap();
+
+        let fees = estimate_eip1559_fees(&provider).await.unwrap();
+        assert_eq!(fees, (U256::from(7_000_000_000u64), U256::zero()));
+    }
+
+    /// ABI-encode a QuoterV2 result returning `amount_out`
+    fn quoted(amount_out: U256) -> Result<Bytes> {
+        let ret = QuoteExactInputSingleReturn {
+            amount_out,
+            sqrt_price_x96_after: U256::zero(),
+            initialized_ticks_crossed: 1,
+            gas_estimate: U256::from(80_000),
+        };
+        Ok(ret.encode().into())
+    }
+
+    #[test]
+    fn test_quoter_v2_selector() {
+        let call = QuoteExactInputSingleCall { params: quote_params(Address::zero(), Address::zero(), U256::one(), 3000) };
+        assert_eq!(call.encode()[..4], [0xc6, 0xa5, 0x02, 0x6a]);
+    }
+
+    #[test]
+    fn test_transfer_shortfall() {
+        assert!(!is_transfer_shortfall(U256::from(1_000), U256::from(1_000)));
+        assert!(!is_transfer_shortfall(U256::from(1_000), U256::from(999)));
+        assert!(is_transfer_shortfall(U256::from(1_000), U256::from(990)));
+    }
+
+    #[tokio::test]
+    async fn test_fee_on_transfer_mocked() {
+        let (provider, mock) = Provider::mocked();
+        // Probe result pushed first since mock responses are served LIFO
+        mock.push::<Bytes, _>(uint_words(&[9_900])).unwrap();
+        mock.push::<Bytes, _>(uint_words(&[1_000_000])).unwrap();
+
+        let flagged = is_likely_fee_on_transfer(Address::zero(), Address::zero(), Arc::new(provider))
+            .await
+            .unwrap();
+        assert!(flagged);
+    }
+
+    #[tokio::test]
+    async fn test_fee_on_transfer_fork() {
+        // Requires a node that supports eth_call state overrides (e.g. an
+        // anvil fork) plus a known fee-on-transfer token and one of its holders
+        let (Ok(rpc_url), Ok(token), Ok(holder)) = (
+            std::env::var("FORK_RPC_URL"),
+            std::env::var("FOT_TOKEN"),
+            std::env::var("FOT_HOLDER"),
+        ) else {
+            return;
+        };
 
-        let engine = TitanSimulationEngine::new(137, provider);
-        assert_eq!(engine.chain_id, 137);
+        let provider = Arc::new(Provider::<Http>::try_from(rpc_url).unwrap());
+        let flagged = is_likely_fee_on_transfer(token.parse().unwrap(), holder.parse().unwrap(), provider)
+            .await
+            .unwrap();
+        assert!(flagged);
     }
 }
@@ -0,0 +1,71 @@
+"""
+Tests for the titan_core columnar exports
+
+`score_matrix_columnar` and `matrix_to_records` return one numpy array or
+list per column for building dataframes. Skipped when the extension isn't
+built (`maturin develop` in core-rust) or numpy isn't installed.
+"""
+
+from pathlib import Path
+
+import pytest
+
+titan_core = pytest.importorskip("titan_core")
+np = pytest.importorskip("numpy")
+
+MATRIX = str(Path(__file__).resolve().parents[2] / "data" / "fixtures" / "omniarb_matrix_schema_v2.md")
+FLOAT_COLUMNS = ("tar", "model_tar", "flanker", "liquidity")
+STRING_COLUMNS = ("native_token", "dex_origin", "dex_dest", "bridge_protocol")
+
+
+def assert_matches_rows(columns, entries, quotes):
+    rows = titan_core.score_matrix(entries, quotes)
+    assert list(columns["tar"]) == [row["total"] for row in rows]
+    assert list(columns["liquidity"]) == [entry.liquidity_score for entry in entries]
+    assert list(columns["chain_origin"]) == [entry.chain_origin for entry in entries]
+    assert list(columns["chain_dest"]) == [entry.chain_dest for entry in entries]
+    for name in STRING_COLUMNS:
+        assert columns[name] == [getattr(entry, name) for entry in entries]
+
+
+class TestScoreMatrixColumnar:
+    """Column-major scoring of entry and quote lists"""
+
+    def test_matches_row_wise_scoring(self):
+        entries, _ = titan_core.load_token_matrix(MATRIX)
+        quotes = titan_core.fetch_live_quotes(entries)
+        columns = titan_core.score_matrix_columnar(entries, quotes)
+        assert_matches_rows(columns, entries, quotes)
+
+    def test_float_columns_are_ndarrays(self):
+        entries, _ = titan_core.load_token_matrix(MATRIX)
+        columns = titan_core.score_matrix_columnar(entries, titan_core.fetch_live_quotes(entries))
+        for name in FLOAT_COLUMNS:
+            assert isinstance(columns[name], np.ndarray)
+            assert columns[name].dtype == np.float64
+            assert columns[name].shape == (len(entries),)
+        for name in STRING_COLUMNS:
+            assert isinstance(columns[name], list)
+
+    def test_rejects_mismatched_lengths(self):
+        entries, _ = titan_core.load_token_matrix(MATRIX)
+        with pytest.raises(titan_core.InvalidInputError):
+            titan_core.score_matrix_columnar(entries, titan_core.fetch_live_quotes(entries[:1]))
+
+
+class TestMatrixToRecords:
+    """Loading a matrix file straight into columns"""
+
+    def test_matches_row_wise_scoring(self):
+        entries, _ = titan_core.load_token_matrix(MATRIX)
+        quotes = titan_core.fetch_live_quotes(entries)
+        columns = titan_core.matrix_to_records(MATRIX)
+        assert_matches_rows(columns, entries, quotes)
+        for name in ("model_tar", "flanker"):
+            np.testing.assert_array_equal(
+                columns[name], titan_core.score_matrix_columnar(entries, quotes)[name]
+            )
+
+    def test_missing_file_is_os_error(self):
+        with pytest.raises(OSError):
+            titan_core.matrix_to_records("/nonexistent/matrix.md")
@@ -0,0 +1,75 @@
+"""
+Tests for the titan_core exception hierarchy
+
+Each failure category raises its own TitanError subclass so callers can
+decide what to retry. Skipped when the extension isn't built
+(`maturin develop` in core-rust).
+"""
+
+import asyncio
+
+import pytest
+
+titan_core = pytest.importorskip("titan_core")
+
+USDC = "0x2791Bca1f2de4661ED88A30C99A7a9449Aa84174"
+UNREACHABLE_RPC = "http://127.0.0.1:1"
+
+
+class TestErrorHierarchy:
+    """Every category is a TitanError"""
+
+    def test_subclasses(self):
+        for name in [
+            "InvalidInputError",
+            "InvalidAddressError",
+            "ChainNotSupportedError",
+            "MatrixParseError",
+            "RpcError",
+            "QuoteRevertedError",
+            "ConfigError",
+        ]:
+            assert issubclass(getattr(titan_core, name), titan_core.TitanError)
+        assert issubclass(titan_core.InvalidAddressError, titan_core.InvalidInputError)
+        assert not issubclass(titan_core.QuoteRevertedError, titan_core.RpcError)
+
+    def test_invalid_address(self):
+        engine = titan_core.SimulationEngine(137, UNREACHABLE_RPC, offline=True)
+        with pytest.raises(titan_core.InvalidAddressError) as raised:
+            engine.get_lender_tvl("not-an-address", USDC)
+        assert isinstance(raised.value, titan_core.TitanError)
+
+    def test_chain_not_supported(self):
+        with pytest.raises(titan_core.ChainNotSupportedError) as raised:
+            titan_core.SimulationEngine(999999, UNREACHABLE_RPC)
+        assert isinstance(raised.value, titan_core.TitanError)
+        with pytest.raises(titan_core.ChainNotSupportedError):
+            titan_core.TitanCommander(999999, UNREACHABLE_RPC)
+        with pytest.raises(titan_core.ChainNotSupportedError):
+            titan_core.PyChainId.from_u64(999999)
+
+    def test_rpc_error(self):
+        engine = titan_core.SimulationEngine(137, UNREACHABLE_RPC)
+        with pytest.raises(titan_core.RpcError) as raised:
+            engine.get_lender_tvl(USDC, USDC)
+        assert isinstance(raised.value, titan_core.TitanError)
+        with pytest.raises(titan_core.RpcError):
+            engine.get_price_impact(USDC, USDC, 10**6, 500, USDC)
+
+        async def read():
+            return await engine.get_block_number_async()
+
+        with pytest.raises(titan_core.RpcError):
+            asyncio.run(read())
+
+    def test_config_error(self):
+        with pytest.raises(titan_core.ConfigError) as raised:
+            titan_core.SimulationEngine(137, "not a url")
+        assert isinstance(raised.value, titan_core.TitanError)
+
+    def test_matrix_parse_error(self, tmp_path):
+        path = tmp_path / "matrix.json"
+        path.write_text("{")
+        with pytest.raises(titan_core.MatrixParseError) as raised:
+            titan_core.load_token_matrix_json(str(path))
+        assert isinstance(raised.value, titan_core.InvalidInputError)
@@ -0,0 +1,91 @@
+"""
+Tests for the titan_core matrix loading and quote bindings
+
+Skipped when the extension isn't built (`maturin develop` in core-rust).
+"""
+
+from pathlib import Path
+
+import pytest
+
+titan_core = pytest.importorskip("titan_core")
+
+FIXTURES = Path(__file__).resolve().parents[2] / "data" / "fixtures"
+HEADER = "chain_origin,chain_dest,native_token,dex_origin,dex_dest,bridge_protocol,liquidity_score,fee_tier"
+
+
+def write_matrix(tmp_path, *rows):
+    path = tmp_path / "matrix.md"
+    path.write_text("## Data Entries\n\n" + "\n".join([HEADER, *rows]) + "\n")
+    return str(path)
+
+
+class TestLoadTokenMatrix:
+    """Loading the committed fixtures and reporting bad rows"""
+
+    def test_fixture_matrix(self):
+        entries, _ = titan_core.load_token_matrix(str(FIXTURES / "omniarb_matrix_schema_v2.md"))
+        assert len(entries) == 3
+        weth = entries[1]
+        assert isinstance(weth, titan_core.TokenEntry)
+        assert (weth.chain_origin, weth.chain_dest, weth.native_token) == (1, 42161, "WETH")
+        assert weth.bridge_protocol == "STARGATE"
+        assert weth.fee_tier == 0.05
+        assert weth.token_address_dest == "0x82aF49447D8a07e3bd95BD0d56f35241523fBab1"
+
+    def test_strict_mode_raises_with_diagnostics(self, tmp_path):
+        path = write_matrix(
+            tmp_path,
+            "1,137,USDC,UNISWAP_V3,QUICKSWAP,STARGATE,95.0,0.3",
+            "1,137,USDT,UNISWAP_V3,QUICKSWAP,STARGATE,lots,0.3",
+        )
+        with pytest.raises(titan_core.MatrixParseError) as raised:
+            titan_core.load_token_matrix(path)
+        [diagnostic] = raised.value.diagnostics
+        assert diagnostic["column"] == "liquidity_score"
+        assert diagnostic["raw_value"] == "lots"
+
+    def test_lenient_mode_returns_diagnostics(self, tmp_path):
+        path = write_matrix(
+            tmp_path,
+            "1,137,USDC,UNISWAP_V3,QUICKSWAP,STARGATE,95.0,0.3",
+            "1,137,USDT,UNISWAP_V3,QUICKSWAP,STARGATE,lots,0.3",
+        )
+        entries, diagnostics = titan_core.load_token_matrix(path, strict=False)
+        assert [entry.native_token for entry in entries] == ["USDC"]
+        assert len(diagnostics) == 1
+
+    def test_strict_mode_returns_no_diagnostics(self):
+        entries, diagnostics = titan_core.load_token_matrix(str(FIXTURES / "omniarb_matrix_schema_v2.md"))
+        assert len(entries) == 3
+        assert diagnostics == []
+
+    def test_missing_file_is_os_error(self):
+        with pytest.raises(OSError):
+            titan_core.load_token_matrix("/nonexistent/matrix.md")
+        with pytest.raises(OSError):
+            titan_core.load_token_matrix_json("/nonexistent/matrix.json")
+
+
+class TestFetchLiveQuotes:
+    """Simulated quotes line up with their entries"""
+
+    def test_simulated_quotes(self):
+        entries, _ = titan_core.load_token_matrix(str(FIXTURES / "omniarb_matrix_schema_v2.md"))
+        quotes = titan_core.fetch_live_quotes(entries + [entries[0].to_dict()])
+        assert len(quotes) == 4
+        assert all(quote.source == "simulated" for quote in quotes)
+        assert quotes[0].spread_percentage == quotes[3].spread_percentage
+
+    def test_simulated_quotes_carry_bridge_fee(self):
+        entries, _ = titan_core.load_token_matrix(str(FIXTURES / "omniarb_matrix_schema_v2.md"))
+        config = titan_core.PyConfig()
+        bridges = config.get_bridges()
+        expected = []
+        for entry in entries:
+            fee_range = bridges.get(entry.bridge_protocol.lower(), {}).get("fee_range_bps")
+            expected.append((min(fee_range) + max(fee_range)) // 2 if fee_range else 0)
+        assert any(expected)
+
+        for quotes in (titan_core.fetch_live_quotes(entries), titan_core.fetch_live_quotes(entries, config=config)):
+            assert [quote.bridge_fee_bps for quote in quotes] == expected
@@ -0,0 +1,109 @@
+"""
+Tests for the titan_core TAR scoring bindings
+
+Scores must match the Rust tar_scorer fixtures exactly, so Python never
+needs its own copy of the formula. Skipped when the extension isn't built
+(`maturin develop` in core-rust).
+"""
+
+import pytest
+
+titan_core = pytest.importorskip("titan_core")
+
+
+def route(token, bridge, liquidity, fee_tier, spread, slippage):
+    """Same shape as `scored_route` in tar_scorer.rs"""
+    entry = {
+        "chain_origin": 1,
+        "chain_dest": 137,
+        "native_token": token,
+        "dex_origin": "",
+        "dex_dest": "",
+        "bridge_protocol": bridge,
+        "liquidity_score": liquidity,
+        "fee_tier": fee_tier,
+    }
+    quote = {
+        "spread_percentage": spread,
+        "slippage_estimate": slippage,
+        "gas_cost_usd": 0.0,
+        "available_liquidity": 0.0,
+    }
+    return entry, quote
+
+
+# test_default_weights_reproduce_scores
+FIXTURES = [
+    (route("USDC", "STARGATE", 95.0, 0.1, 1.5, 0.3), 94.25),
+    (route("LINK", "HOP", 60.0, 0.4, 0.6, 1.2), 51.0),
+    (route("PEPE", "UNKNOWN", 10.0, 1.0, 0.1, 3.0), 11.5),
+]
+
+
+class TestTokenEntry:
+    """Conversions between dicts and the Rust-backed classes"""
+
+    def test_dict_round_trip_is_lossless(self):
+        values = {
+            "chain_origin": 1,
+            "chain_dest": 42161,
+            "native_token": "USDC",
+            "dex_origin": "UNISWAP_V3",
+            "dex_dest": "CAMELOT",
+            "bridge_protocol": "ACROSS",
+            "liquidity_score": 0.1 + 0.2,
+            "fee_tier": 0.05,
+            "token_address_origin": "0xA0b86991c6218b36c1d19D4a2e9Eb0cE3606eB48",
+            "token_address_dest": "0xaf88d065e77c8cC2239327C5EDb3A432268e5831",
+            "pool_address_origin": None,
+            "pool_address_dest": None,
+        }
+        entry = titan_core.TokenEntry.from_dict(values)
+        assert entry.to_dict() == values
+        assert entry == titan_core.TokenEntry(**values)
+
+    def test_quote_dict_round_trip_is_lossless(self):
+        values = {
+            "spread_percentage": 1.5,
+            "slippage_estimate": 0.3,
+            "gas_cost_usd": 5.0,
+            "available_liquidity": 1_000_000.0,
+            "fetched_at": "2026-01-05T04:14:34.123456789+00:00",
+            "source": "socket",
+            "bridge_fee_bps": 30,
+        }
+        assert titan_core.QuoteInfo.from_dict(values).to_dict() == values
+
+    def test_unknown_source_raises_typed_error(self):
+        with pytest.raises(titan_core.InvalidInputError):
+            titan_core.QuoteInfo(1.5, 0.3, 5.0, 1_000_000.0, source="carrier-pigeon")
+
+
+class TestScoring:
+    """Scores against the Rust unit-test fixtures"""
+
+    def test_scores_match_rust_fixtures(self):
+        for (entry, quote), expected in FIXTURES:
+            assert titan_core.calculate_tar_score(entry, quote) == expected
+            score = titan_core.calculate_tar_score(
+                titan_core.TokenEntry.from_dict(entry), titan_core.QuoteInfo.from_dict(quote)
+            )
+            assert score == expected
+
+    def test_breakdown_components(self):
+        entry, quote = FIXTURES[1][0]
+        breakdown = titan_core.calculate_tar_breakdown(entry, quote)
+        assert (breakdown["token_quality"], breakdown["arbitrage_efficiency"], breakdown["risk"]) == (21.0, 15.0, 15.0)
+        assert breakdown["total"] == 51.0
+        assert "bridge HOP tier 2 ⇒ 10/15" in breakdown["notes"]
+
+    def test_score_matrix_matches_single_scores(self):
+        entries = [entry for (entry, _), _ in FIXTURES] * 100
+        quotes = [quote for (_, quote), _ in FIXTURES] * 100
+        results = titan_core.score_matrix(entries, quotes)
+        assert [result["total"] for result in results] == [expected for _, expected in FIXTURES] * 100
+
+    def test_score_matrix_rejects_mismatched_lengths(self):
+        entry, quote = FIXTURES[0][0]
+        with pytest.raises(titan_core.InvalidInputError):
+            titan_core.score_matrix([entry, entry], [quote])
@@ -0,0 +1,52 @@
+"""
+Tests for running the Rust HTTP API from Python
+
+`start_server` serves on a background thread until the handle is stopped.
+Skipped when the extension isn't built (`maturin develop` in core-rust).
+"""
+
+import json
+import urllib.error
+import urllib.request
+
+import pytest
+
+titan_core = pytest.importorskip("titan_core")
+
+
+def get_json(port, path):
+    with urllib.request.urlopen(f"http://127.0.0.1:{port}{path}", timeout=5) as response:
+        return response.status, json.loads(response.read())
+
+
+class TestStartServer:
+    """Start, query and stop the server in-process"""
+
+    def test_serves_health_and_stops(self):
+        server = titan_core.start_server(0, titan_core.PyConfig())
+        assert server.port != 0
+        assert server.is_running()
+
+        status, health = get_json(server.port, "/health")
+        assert status == 200
+        assert health["status"] == "healthy"
+
+        server.stop(5.0)
+        assert not server.is_running()
+        with pytest.raises(urllib.error.URLError):
+            get_json(server.port, "/health")
+        # Stopping twice is harmless
+        server.stop()
+
+    def test_context_manager_stops_on_exit(self):
+        with titan_core.start_server(0) as server:
+            assert get_json(server.port, "/health")[0] == 200
+        assert not server.is_running()
+
+    def test_port_in_use_raises_config_error(self):
+        with titan_core.start_server(0) as server:
+            with pytest.raises(titan_core.ConfigError) as raised:
+                titan_core.start_server(server.port)
+            assert isinstance(raised.value, titan_core.TitanError)
+            assert str(server.port) in str(raised.value)
+            assert server.is_running()
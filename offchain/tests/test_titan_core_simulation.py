@@ -0,0 +1,63 @@
+"""
+Tests for the titan_core SimulationEngine bindings
+
+Runs the engine in offline mode, so no RPC is needed; skipped when the
+extension isn't built (`maturin develop` in core-rust).
+"""
+
+import asyncio
+
+import pytest
+
+titan_core = pytest.importorskip("titan_core")
+
+USDC = "0x2791Bca1f2de4661ED88A30C99A7a9449Aa84174"
+WETH = "0x7ceB23fD6bC0adD59E62ac25578270cFf1b9f619"
+QUOTER = "0x61fFE014bA17989E743c5F6cB21bF9697530B21e"
+OFFLINE_TVL = 1_000_000 * 10**18
+
+
+class TestSimulationEngine:
+    """Blocking and awaitable reads against the offline engine"""
+
+    def setup_method(self):
+        self.engine = titan_core.SimulationEngine(137, "http://127.0.0.1:1", offline=True)
+
+    def test_tvl_above_u64_is_a_python_int(self):
+        tvl = self.engine.get_lender_tvl(USDC, WETH)
+        assert isinstance(tvl, int)
+        assert tvl > 2**64
+        assert tvl == OFFLINE_TVL
+
+    def test_price_impact_keeps_full_precision(self):
+        amount = 2**200 + 1
+        amount_out = self.engine.get_price_impact(USDC, WETH, amount, 3000, QUOTER)
+        assert amount_out == amount * 997_000 // 1_000_000
+
+    def test_invalid_address_raises_typed_error(self):
+        with pytest.raises(titan_core.InvalidInputError):
+            self.engine.get_lender_tvl("not-an-address", WETH)
+        assert issubclass(titan_core.InvalidInputError, titan_core.TitanError)
+
+    def test_awaitable_under_running_loop(self):
+        async def read():
+            return await asyncio.gather(
+                self.engine.get_lender_tvl_async(USDC, WETH),
+                self.engine.get_price_impact_async(USDC, WETH, 10**24, 500, QUOTER),
+            )
+
+        tvl, amount_out = asyncio.run(read())
+        assert tvl == OFFLINE_TVL
+        assert amount_out == 10**24 * 999_500 // 1_000_000
+
+    def test_awaitable_raises_rpc_error(self):
+        # Block numbers aren't simulated, so this reaches the unreachable RPC
+        async def read():
+            return await self.engine.get_block_number_async()
+
+        with pytest.raises(titan_core.RpcError):
+            asyncio.run(read())
+
+    def test_awaitable_needs_running_loop(self):
+        with pytest.raises(RuntimeError):
+            self.engine.get_lender_tvl_async(USDC, WETH)